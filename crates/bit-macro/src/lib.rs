@@ -771,3 +771,161 @@ fn sys_expand_async_wasi_host_func_with_three_args(
         }
     )
 }
+
+// ================== derive macros ==================
+
+/// Derives `bitbang::marshal::WasmMarshal` for a struct, which lays out its fields in guest memory like `#[repr(C)]` on wasm32.
+///
+/// The struct must be declared with `#[repr(C)]`, optionally with `align(N)`, and every field must implement `WasmMarshal`; the type parameters of a generic struct are bound by `WasmMarshal`. Packed structs are rejected, since the fields are always placed at aligned offsets. The optional `#[wasm_marshal(version = N)]` attribute prefixes the layout with a `u32` version, which is checked when the struct is read from guest memory.
+#[proc_macro_derive(WasmMarshal, attributes(wasm_marshal))]
+pub fn derive_wasm_marshal(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    match expand_wasm_marshal(&input) {
+        Ok(token_stream) => token_stream.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_wasm_marshal(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let name_literal = name.to_string();
+
+    let fields = match &input.data {
+        syn::Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "WasmMarshal can only be derived for structs",
+            ))
+        }
+    };
+
+    // the layout must be explicit
+    let mut repr_c = false;
+    let mut repr_align: u32 = 1;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+                Ok(())
+            } else if meta.path.is_ident("align") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let lit: syn::LitInt = content.parse()?;
+                repr_align = lit.base10_parse()?;
+                Ok(())
+            } else if meta.path.is_ident("packed") {
+                Err(meta.error("WasmMarshal does not support packed structs"))
+            } else {
+                Err(meta.error("unsupported repr for WasmMarshal"))
+            }
+        })?;
+    }
+    if !repr_c {
+        return Err(syn::Error::new(
+            input.span(),
+            "WasmMarshal requires the struct to be declared with #[repr(C)]",
+        ));
+    }
+
+    let mut version: Option<u32> = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("wasm_marshal"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                version = Some(lit.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported wasm_marshal attribute"))
+            }
+        })?;
+    }
+
+    let members: Vec<syn::Member> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(idx)),
+        })
+        .collect();
+    let tys: Vec<&syn::Type> = fields.iter().map(|field| &field.ty).collect();
+    let vars: Vec<syn::Ident> = (0..members.len())
+        .map(|idx| syn::Ident::new(&format!("field_{idx}"), proc_macro2::Span::call_site()))
+        .collect();
+
+    let trait_path = quote!(::bitbang::marshal::WasmMarshal);
+    let (header_size, header_align) = match version {
+        Some(_) => (4u32, 4u32.max(repr_align)),
+        None => (0, repr_align),
+    };
+    let encode_version =
+        version.map(|version| quote!(buf[..4].copy_from_slice(&#version.to_le_bytes());));
+    let check_version = version
+        .map(|version| quote!(::bitbang::marshal::check_version(#name_literal, #version, buf)?;));
+    let construct = match fields {
+        syn::Fields::Named(_) => quote!(Self { #( #members: #vars ),* }),
+        syn::Fields::Unnamed(_) => quote!(Self( #( #vars ),* )),
+        syn::Fields::Unit => quote!(Self),
+    };
+
+    // the fields of a generic struct may hold its type parameters
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(#trait_path));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote!(
+        impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+            const ALIGN: u32 = {
+                #[allow(unused_mut)]
+                let mut align = #header_align;
+                #(
+                    if <#tys as #trait_path>::ALIGN > align {
+                        align = <#tys as #trait_path>::ALIGN;
+                    }
+                )*
+                align
+            };
+            const SIZE: u32 = {
+                #[allow(unused_mut)]
+                let mut offset = #header_size;
+                #(
+                    offset = ::bitbang::marshal::align_to(offset, <#tys as #trait_path>::ALIGN)
+                        + <#tys as #trait_path>::SIZE;
+                )*
+                ::bitbang::marshal::align_to(offset, <Self as #trait_path>::ALIGN)
+            };
+
+            #[allow(unused_mut, unused_assignments, unused_variables)]
+            fn encode(&self, buf: &mut [u8]) {
+                #encode_version
+                let mut offset = #header_size as usize;
+                #(
+                    offset = ::bitbang::marshal::align_to(offset as u32, <#tys as #trait_path>::ALIGN) as usize;
+                    let end = offset + <#tys as #trait_path>::SIZE as usize;
+                    <#tys as #trait_path>::encode(&self.#members, &mut buf[offset..end]);
+                    offset = end;
+                )*
+            }
+
+            #[allow(unused_mut, unused_assignments, unused_variables)]
+            fn decode(buf: &[u8]) -> ::bitbang::WasmEdgeResult<Self> {
+                #check_version
+                let mut offset = #header_size as usize;
+                #(
+                    offset = ::bitbang::marshal::align_to(offset as u32, <#tys as #trait_path>::ALIGN) as usize;
+                    let end = offset + <#tys as #trait_path>::SIZE as usize;
+                    let #vars = <#tys as #trait_path>::decode(&buf[offset..end])?;
+                    offset = end;
+                )*
+                Ok(#construct)
+            }
+        }
+    ))
+}
@@ -0,0 +1,150 @@
+//! Defines the cancellation of the wasm function calls running on a thread.
+//!
+//! A [CancelToken] is entered on the thread running a wasm function. Once the token is cancelled, the next host function invoked by the wasm function is not run, and the call fails with the `Interrupted` error instead. The host function running when the token is cancelled can abort its in-flight operation in a hook registered with [CallingFrame::on_cancel](crate::CallingFrame::on_cancel).
+//!
+//! A deadline pushed on the thread cancels the calls the same way once it passes, except that no hook is run, since no other thread observes the deadline.
+
+use bit_types::error::HostFuncError;
+use parking_lot::Mutex;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// The error code returned to the guest when a host call is made after the call is cancelled. It is the code of the `Interrupted` error.
+pub const CANCELLED_CODE: u32 = 0x07;
+
+type CancelHook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    hooks: Mutex<Vec<CancelHook>>,
+}
+
+/// Defines a token which cancels the wasm function calls running on the threads that entered it.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+impl CancelToken {
+    /// Creates a new token, which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, and runs on the current thread the hooks registered by the host functions in progress.
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let hooks = std::mem::take(&mut *self.state.hooks.lock());
+        for hook in hooks {
+            hook();
+        }
+    }
+
+    /// Checks if the token is cancelled or not.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Registers a hook to be run when the token is cancelled. If the token is already cancelled, the hook is run immediately.
+    fn on_cancel(&self, hook: CancelHook) {
+        let mut hooks = self.state.hooks.lock();
+        if self.is_cancelled() {
+            drop(hooks);
+            hook();
+        } else {
+            hooks.push(hook);
+        }
+    }
+
+    /// Returns the number of the registered hooks.
+    fn mark(&self) -> usize {
+        self.state.hooks.lock().len()
+    }
+
+    /// Drops the hooks registered after the given mark without running them.
+    fn release(&self, mark: usize) {
+        self.state.hooks.lock().truncate(mark);
+    }
+}
+impl std::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = RefCell::new(None);
+    static DEADLINES: RefCell<Vec<Instant>> = RefCell::new(Vec::new());
+}
+
+/// Enters the given token on the current thread, so that the wasm function calls made on the thread are cancelled with the token.
+pub fn enter(token: CancelToken) {
+    CURRENT.with(|current| *current.borrow_mut() = Some(token));
+}
+
+/// Leaves the token entered on the current thread, if any.
+pub fn leave() {
+    CURRENT.with(|current| *current.borrow_mut() = None);
+}
+
+/// Pushes a deadline on the current thread, after which the wasm function calls made on the thread are cancelled. The deadlines can be nested.
+pub fn push_deadline(deadline: Instant) {
+    DEADLINES.with(|deadlines| deadlines.borrow_mut().push(deadline));
+}
+
+/// Pops the innermost deadline pushed on the current thread.
+pub fn pop_deadline() {
+    DEADLINES.with(|deadlines| deadlines.borrow_mut().pop());
+}
+
+/// Checks if the wasm function calls made on the current thread are cancelled, either by the entered token or by a passed deadline.
+pub(crate) fn is_cancelled() -> bool {
+    let cancelled = CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    });
+    cancelled
+        || DEADLINES.with(|deadlines| {
+            let now = Instant::now();
+            deadlines.borrow().iter().any(|deadline| now >= *deadline)
+        })
+}
+
+/// Registers a hook on the token entered on the current thread. If no token is entered, the hook is dropped, since the call can not be cancelled.
+pub(crate) fn on_cancel(hook: CancelHook) {
+    if let Some(token) = CURRENT.with(|current| current.borrow().clone()) {
+        token.on_cancel(hook);
+    }
+}
+
+/// Runs a host function under the token and the deadlines of the current thread.
+///
+/// If the call is already cancelled, the host function is not run. The hooks registered by the host function are dropped when it returns, and if the call is cancelled while it runs, the call is interrupted even though the host function succeeds.
+pub(crate) fn guard<T>(f: impl FnOnce() -> Result<T, HostFuncError>) -> Result<T, HostFuncError> {
+    if is_cancelled() {
+        return Err(HostFuncError::Runtime(CANCELLED_CODE));
+    }
+
+    let token = CURRENT.with(|current| current.borrow().clone());
+    let mark = token.as_ref().map(CancelToken::mark);
+    let result = f();
+    if let (Some(token), Some(mark)) = (token, mark) {
+        token.release(mark);
+    }
+    match is_cancelled() {
+        true => Err(HostFuncError::Runtime(CANCELLED_CODE)),
+        false => result,
+    }
+}
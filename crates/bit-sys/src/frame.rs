@@ -1,6 +1,7 @@
 //! Defines WasmEdge CallingFrame.
 
 use crate::{
+    cancel,
     executor::InnerExecutor,
     ffi,
     instance::{memory::InnerMemory, module::InnerInstance},
@@ -86,6 +87,24 @@ impl CallingFrame {
         }
     }
 
+    /// Checks if the wasm function call this host function is invoked by is cancelled or not.
+    ///
+    /// A call is cancelled when the future returned by an asynchronous call, such as `run_func_async`, is dropped before completion, or when a deadline set on the call passes. A long-running host function can check it to stop early.
+    pub fn is_cancelled(&self) -> bool {
+        cancel::is_cancelled()
+    }
+
+    /// Registers a hook to be run when the wasm function call this host function is invoked by is cancelled while the host function is in progress.
+    ///
+    /// The hook runs on the thread cancelling the call, so it can abort the in-flight operation the host function is blocked on, for example by shutting down a socket. If the call is already cancelled, the hook is run immediately. The hook is dropped without being run when the host function returns, and it is never run if the call can not be cancelled.
+    ///
+    /// # Argument
+    ///
+    /// * `hook` - The hook to be run on cancellation.
+    pub fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) {
+        cancel::on_cancel(Box::new(hook))
+    }
+
     /// Provides a raw pointer to the inner CallingFrame context.
     #[cfg(feature = "ffi")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
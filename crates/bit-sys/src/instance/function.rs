@@ -1,16 +1,24 @@
 //! Defines WasmEdge Function and FuncType structs.
 
 use crate::{
-    ffi, BoxedFn, CallingFrame, Engine, WasmEdgeResult, WasmValue, HOST_FUNCS, HOST_FUNC_FOOTPRINTS,
+    cancel, ffi, instance::memory, replay, statistics::HostFuncStat, tally, utils, BoxedFn,
+    CallingFrame, Engine, HostFuncEntry, WasmEdgeResult, WasmValue, HOST_FUNCS,
+    HOST_FUNC_FOOTPRINTS,
 };
 use bit_types::{
-    error::{FuncError, HostFuncError, WasmEdgeError},
+    error::{FuncError, HostFuncError, Trap, WasmEdgeError},
     ValType,
 };
 use core::ffi::c_void;
 use parking_lot::Mutex;
 use rand::Rng;
-use std::{convert::TryInto, sync::Arc};
+use std::{
+    any::Any,
+    convert::TryInto,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Weak},
+    time::Instant,
+};
 
 pub type CustomFnWrapper = unsafe extern "C" fn(
     key_ptr: *mut c_void,
@@ -56,11 +64,41 @@ extern "C" fn wrap_fn(
     match map_host_func.get(&key) {
         None => unsafe { ffi::WasmEdge_ResultGen(ffi::WasmEdge_ErrCategory_WASM, 5) },
         Some(host_func) => {
-            let real_fn = Arc::clone(host_func);
-            let real_fn_locked = real_fn.lock();
+            let host_func = Arc::clone(host_func);
+            let real_fn_locked = host_func.real_fn.lock();
             drop(map_host_func);
 
-            match real_fn_locked(frame, input, data) {
+            let mem_ctx = unsafe { ffi::WasmEdge_CallingFrameGetMemoryInstance(call_frame_ctx, 0) };
+            tally::host_call(mem_ctx);
+
+            // observe the memory of the calling module instance, so that its grow hooks run before the host function
+            if !mem_ctx.is_null() {
+                memory::observe(mem_ctx);
+            }
+
+            // serve the call from the replay tape, if any
+            let import = || host_func.import.lock().clone().unwrap_or_default();
+            let result = match replay::intercept(import, &input) {
+                Some(result) => result,
+                None => {
+                    let params = replay::is_recording().then(|| input.clone());
+                    let start = Instant::now();
+                    // a panic must not unwind into the C library, so it traps the guest instead
+                    let result = cancel::guard(|| {
+                        panic::catch_unwind(AssertUnwindSafe(|| real_fn_locked(frame, input, data)))
+                            .unwrap_or_else(|payload| Err(panicked(payload)))
+                    });
+                    let elapsed = start.elapsed();
+                    tally::host_time(elapsed);
+                    host_func.stat.record(elapsed);
+                    if let Some(params) = params {
+                        replay::record(import(), params, &result);
+                    }
+                    result
+                }
+            };
+
+            match result {
                 Ok(returns) => {
                     assert!(returns.len() == return_len, "[wasmedge-sys] check the number of returns of host function. Expected: {}, actual: {}", return_len, returns.len());
                     for (idx, wasm_value) in returns.into_iter().enumerate() {
@@ -75,12 +113,31 @@ extern "C" fn wrap_fn(
                     HostFuncError::Runtime(code) => unsafe {
                         ffi::WasmEdge_ResultGen(ffi::WasmEdge_ErrCategory_WASM, code)
                     },
+                    HostFuncError::Trap(trap) => {
+                        let code = trap.code();
+                        utils::raise_trap(trap);
+                        unsafe {
+                            ffi::WasmEdge_ResultGen(ffi::WasmEdge_ErrCategory_UserLevelError, code)
+                        }
+                    }
                 },
             }
         }
     }
 }
 
+/// Returns the trap raised for a host function which panicked.
+fn panicked(payload: Box<dyn Any + Send>) -> HostFuncError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    };
+    HostFuncError::Trap(Trap::new(format!("host function panicked: {message}")))
+}
+
 /// Defines a host function.
 ///
 /// A WasmEdge [Function] defines a WebAssembly host function described by its [type](crate::FuncType). A host function is a closure of the original function defined in either the host or the WebAssembly module.
@@ -197,7 +254,7 @@ impl Function {
         while map_host_func.contains_key(&key) {
             key = rng.gen();
         }
-        map_host_func.insert(key, Arc::new(Mutex::new(real_fn)));
+        map_host_func.insert(key, Arc::new(HostFuncEntry::new(real_fn)));
         drop(map_host_func);
 
         let ctx = ffi::WasmEdge_FunctionInstanceCreateBinding(
@@ -290,6 +347,15 @@ impl Function {
         }
     }
 
+    /// Creates a [weak reference](crate::WeakFunction) to this function instance, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakFunction {
+        WeakFunction {
+            inner: Arc::downgrade(&self.inner),
+            registered: self.registered,
+            data_owner: self.data_owner,
+        }
+    }
+
     /// Runs this host function and returns the result.
     ///
     /// # Arguments
@@ -310,6 +376,40 @@ impl Function {
         engine.run_func(self, args)
     }
 
+    /// Returns the accumulated call count and time spent inside this host function.
+    ///
+    /// If this [Function] is not a host function, then `None` is returned.
+    pub fn call_stat(&self) -> Option<HostFuncStat> {
+        let footprint = self.inner.lock().0 as usize;
+        let key = *HOST_FUNC_FOOTPRINTS.lock().get(&footprint)?;
+        let stat = HOST_FUNCS
+            .read()
+            .get(&key)
+            .map(|host_func| host_func.stat.get())
+            .unwrap_or_default();
+        Some(stat)
+    }
+
+    /// Sets the module name and the name under which this host function is imported, so that its calls can be identified on the replay tape.
+    pub(crate) fn set_import(&self, mod_name: &str, name: &str) {
+        let footprint = self.inner.lock().0 as usize;
+        if let Some(key) = HOST_FUNC_FOOTPRINTS.lock().get(&footprint) {
+            if let Some(host_func) = HOST_FUNCS.read().get(key) {
+                *host_func.import.lock() = Some((mod_name.to_string(), name.to_string()));
+            }
+        }
+    }
+
+    /// Resets the accumulated call statistics of this host function.
+    pub fn reset_call_stat(&self) {
+        let footprint = self.inner.lock().0 as usize;
+        if let Some(key) = HOST_FUNC_FOOTPRINTS.lock().get(&footprint) {
+            if let Some(host_func) = HOST_FUNCS.read().get(key) {
+                host_func.stat.reset();
+            }
+        }
+    }
+
     /// Returns a reference to this [Function] instance.
     pub fn as_ref(&self) -> FuncRef {
         FuncRef {
@@ -351,10 +451,12 @@ impl Drop for Function {
             }
 
             // delete the function instance
-            if !self.inner.lock().0.is_null() {
+            let mut inner = self.inner.lock();
+            if !inner.0.is_null() {
                 unsafe {
-                    ffi::WasmEdge_FunctionInstanceDelete(self.inner.lock().0);
+                    ffi::WasmEdge_FunctionInstanceDelete(inner.0);
                 };
+                inner.0 = std::ptr::null_mut();
             }
         }
     }
@@ -369,6 +471,28 @@ impl Clone for Function {
     }
 }
 
+/// A weak reference to a [Function], which does not keep the function instance alive.
+#[derive(Debug, Clone)]
+pub struct WeakFunction {
+    inner: Weak<Mutex<InnerFunc>>,
+    registered: bool,
+    data_owner: bool,
+}
+impl WeakFunction {
+    /// Returns the [Function], if it is still alive.
+    pub fn upgrade(&self) -> Option<Function> {
+        let inner = self.inner.upgrade()?;
+        if inner.lock().0.is_null() {
+            return None;
+        }
+        Some(Function {
+            inner,
+            registered: self.registered,
+            data_owner: self.data_owner,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct InnerFunc(pub(crate) *mut ffi::WasmEdge_FunctionInstanceContext);
 unsafe impl Send for InnerFunc {}
@@ -574,6 +698,33 @@ impl FuncRef {
     ) -> WasmEdgeResult<Vec<WasmValue>> {
         engine.run_func_ref(self, args)
     }
+
+    /// Returns the [function instance](crate::Function) this [FuncRef] refers to. The returned function does not own the function instance, which lives as long as its owner.
+    pub fn as_func(&self) -> Function {
+        Function {
+            inner: Arc::new(Mutex::new(InnerFunc(self.inner.0 as *mut _))),
+            registered: true,
+            data_owner: false,
+        }
+    }
+
+    /// Checks if this [FuncRef] refers to the given [function instance](crate::Function).
+    ///
+    /// # Argument
+    ///
+    /// * `func` - The function instance to compare with.
+    pub fn refers_to(&self, func: &Function) -> bool {
+        std::ptr::eq(self.inner.0, func.inner.lock().0 as *const _)
+    }
+
+    /// Checks if this [FuncRef] and the given one refer to the same [function instance](crate::Function).
+    ///
+    /// # Argument
+    ///
+    /// * `other` - The function reference to compare with.
+    pub fn same_as(&self, other: &FuncRef) -> bool {
+        std::ptr::eq(self.inner.0, other.inner.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -5,7 +5,7 @@
 //! the limit range specifies min size (initial size) of that memory, while the end
 //! restricts the size to which the memory can grow later.
 
-use crate::{ffi, types::WasmEdgeLimit, utils::check, WasmEdgeResult};
+use crate::{ffi, types::WasmEdgeLimit, utils::check, WasmEdgeResult, MEM_GROW_HOOKS, MEM_STATS};
 use bit_types::error::{MemError, WasmEdgeError};
 use parking_lot::Mutex;
 use std::sync::Arc;
@@ -230,12 +230,35 @@ impl Memory {
     /// ```
     ///
     pub fn grow(&mut self, count: u32) -> WasmEdgeResult<()> {
-        unsafe {
-            check(ffi::WasmEdge_MemoryInstanceGrowPage(
-                self.inner.lock().0,
-                count,
-            ))
-        }
+        let ctx = self.inner.lock().0;
+        unsafe { check(ffi::WasmEdge_MemoryInstanceGrowPage(ctx, count))? };
+        observe(ctx);
+        Ok(())
+    }
+
+    /// Returns the usage statistics of this [Memory].
+    ///
+    /// The growth of a [Memory] is observed when it is grown by the host, when a host function is called from the module instance owning it, and when this method is called. Since a WebAssembly memory never shrinks, several `memory.grow` instructions executed by the guest between two observations are counted as a single grow event.
+    pub fn stat(&self) -> MemStat {
+        observe(self.inner.lock().0)
+    }
+
+    /// Registers a callback which is called with the old and the new size, in pages, each time the growth of this [Memory] is observed.
+    ///
+    /// The growth is observed as described in [stat](crate::Memory::stat). In particular, the growth caused by the guest is observed before the next host function called from the module instance owning the [Memory] runs, so the host function never sees a stale size. The callbacks are dropped with the last reference to the memory instance.
+    ///
+    /// # Argument
+    ///
+    /// * `callback` - The callback to call.
+    pub fn on_grow(&self, callback: impl Fn(u32, u32) + Send + Sync + 'static) {
+        let ctx = self.inner.lock().0;
+        // record the current size, so that the next growth is reported from it
+        observe(ctx);
+        MEM_GROW_HOOKS
+            .lock()
+            .entry(ctx as usize)
+            .or_default()
+            .push(Arc::new(callback));
     }
 
     /// Provides a raw pointer to the inner memory context.
@@ -250,6 +273,7 @@ impl Drop for Memory {
         if self.registered {
             self.inner.lock().0 = std::ptr::null_mut();
         } else if Arc::strong_count(&self.inner) == 1 && !self.inner.lock().0.is_null() {
+            forget(self.inner.lock().0);
             unsafe { ffi::WasmEdge_MemoryInstanceDelete(self.inner.lock().0) };
         }
     }
@@ -263,6 +287,60 @@ impl Clone for Memory {
     }
 }
 
+/// The usage statistics of a [Memory] observed by the host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemStat {
+    /// The size, in pages, of the memory when it was first observed.
+    pub initial_pages: u32,
+    /// The current size, in pages, of the memory.
+    pub current_pages: u32,
+    /// The largest size, in pages, the memory has been observed with.
+    pub peak_pages: u32,
+    /// The number of observed grow events.
+    pub grow_events: u64,
+}
+
+// Records the current size of the given memory context and returns its updated statistics.
+pub(crate) fn observe(ctx: *mut ffi::WasmEdge_MemoryInstanceContext) -> MemStat {
+    let pages = unsafe { ffi::WasmEdge_MemoryInstanceGetPageSize(ctx) };
+    let mut stats = MEM_STATS.lock();
+    let stat = stats.entry(ctx as usize).or_insert(MemStat {
+        initial_pages: pages,
+        current_pages: pages,
+        peak_pages: pages,
+        grow_events: 0,
+    });
+    let old_pages = stat.current_pages;
+    if pages > old_pages {
+        stat.grow_events += 1;
+    }
+    stat.current_pages = pages;
+    stat.peak_pages = stat.peak_pages.max(pages);
+    let stat = *stat;
+    drop(stats);
+
+    if pages > old_pages {
+        // the hooks run without the locks held, so they may use the memory
+        let hooks = MEM_GROW_HOOKS
+            .lock()
+            .get(&(ctx as usize))
+            .cloned()
+            .unwrap_or_default();
+        for hook in hooks {
+            hook(old_pages, pages);
+        }
+    }
+    stat
+}
+
+// Removes the statistics and the grow hooks of the given memory context, which is about to be deleted.
+pub(crate) fn forget(ctx: *mut ffi::WasmEdge_MemoryInstanceContext) {
+    MEM_STATS.lock().remove(&(ctx as usize));
+    MEM_GROW_HOOKS.lock().remove(&(ctx as usize));
+}
+
+pub(crate) type GrowHook = dyn Fn(u32, u32) + Send + Sync;
+
 #[derive(Debug)]
 pub(crate) struct InnerMemory(pub(crate) *mut ffi::WasmEdge_MemoryInstanceContext);
 unsafe impl Send for InnerMemory {}
@@ -2,13 +2,18 @@
 
 use crate::{
     ffi,
-    instance::{function::InnerFunc, global::InnerGlobal, memory::InnerMemory, table::InnerTable},
+    instance::{
+        function::InnerFunc,
+        global::InnerGlobal,
+        memory::{self, InnerMemory},
+        table::InnerTable,
+    },
     types::WasmEdgeString,
     Function, Global, Memory, Table, WasmEdgeResult, HOST_FUNCS, HOST_FUNC_FOOTPRINTS,
 };
 use bit_types::error::{InstanceError, WasmEdgeError};
 use parking_lot::Mutex;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 /// An [Instance] represents an instantiated module. In the instantiation process, An [Instance] is created from al[Module](crate::Module). From an [Instance] the exported [functions](crate::Function), [tables](crate::Table), [memories](crate::Memory), and [globals](crate::Global) can be fetched.
 #[derive(Debug)]
@@ -19,15 +24,34 @@ pub struct Instance {
 impl Drop for Instance {
     fn drop(&mut self) {
         if self.registered {
-            self.inner.lock().0 = std::ptr::null_mut();
+            if Arc::strong_count(&self.inner) == 1 {
+                self.inner.lock().0 = std::ptr::null_mut();
+            }
         } else if Arc::strong_count(&self.inner) == 1 && !self.inner.lock().0.is_null() {
+            // forget the statistics of the exported memories
+            for name in self.mem_names().unwrap_or_default() {
+                if let Ok(mem) = self.get_memory(name) {
+                    memory::forget(mem.inner.lock().0);
+                }
+            }
+
+            let mut inner = self.inner.lock();
             unsafe {
-                ffi::WasmEdge_ModuleInstanceDelete(self.inner.lock().0);
+                ffi::WasmEdge_ModuleInstanceDelete(inner.0);
             }
+            inner.0 = std::ptr::null_mut();
         }
     }
 }
 impl Instance {
+    /// Creates a [weak reference](crate::WeakInstance) to this module instance, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakInstance {
+        WeakInstance {
+            inner: Arc::downgrade(&self.inner),
+            registered: self.registered,
+        }
+    }
+
     /// Returns the name of this exported [module instance](crate::Instance).
     ///
     /// If this module instance is an active module instance, then None is returned.
@@ -305,6 +329,26 @@ impl Clone for Instance {
     }
 }
 
+/// A weak reference to an [Instance], which does not keep the module instance alive.
+#[derive(Debug, Clone)]
+pub struct WeakInstance {
+    inner: Weak<Mutex<InnerInstance>>,
+    registered: bool,
+}
+impl WeakInstance {
+    /// Returns the [Instance], if it is still alive.
+    pub fn upgrade(&self) -> Option<Instance> {
+        let inner = self.inner.upgrade()?;
+        if inner.lock().0.is_null() {
+            return None;
+        }
+        Some(Instance {
+            inner,
+            registered: self.registered,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct InnerInstance(pub(crate) *mut ffi::WasmEdge_ModuleInstanceContext);
 unsafe impl Send for InnerInstance {}
@@ -417,16 +461,6 @@ impl<T: ?Sized + Send + Sync + Clone> Drop for ImportModule<T> {
                                 "[wasmedge-sys] Failed to remove the host function from HOST_FUNCS_NEW container",
                             );
                     }
-
-                    #[cfg(all(feature = "async", target_os = "linux"))]
-                    {
-                        let mut map_host_func = ASYNC_HOST_FUNCS.write();
-                        if map_host_func.contains_key(&key) {
-                            map_host_func.remove(&key).expect(
-                                "[wasmedge-sys] Failed to remove the host function from ASYNC_HOST_FUNCS container",
-                            );
-                        }
-                    }
                 }
             }
 
@@ -492,6 +526,7 @@ impl<T: ?Sized + Send + Sync + Clone> AsImport for ImportModule<T> {
     }
 
     fn add_func(&mut self, name: impl AsRef<str>, func: Function) {
+        func.set_import(&self.name, name.as_ref());
         self.funcs.push(func);
         let f = self.funcs.last_mut().unwrap();
         f.registered = true;
@@ -792,26 +827,17 @@ pub trait AsImport {
     fn add_global(&mut self, name: impl AsRef<str>, global: Global);
 }
 
-/// Defines three types of module instances that can be imported into a WasmEdge [Store](crate::Store) instance.
+/// Defines the WASI module instances that can be imported into a WasmEdge [Store](crate::Store) instance.
 #[derive(Debug, Clone)]
 pub enum WasiInstance {
     /// Defines the import module instance of WasiModule type.
-    #[cfg(not(feature = "async"))]
-    #[cfg_attr(docsrs, doc(cfg(not(feature = "async"))))]
     Wasi(WasiModule),
-    /// Defines the import module instance of AsyncWasiModule type.
-    #[cfg(all(feature = "async", target_os = "linux"))]
-    #[cfg_attr(docsrs, doc(cfg(all(feature = "async", target_os = "linux"))))]
-    AsyncWasi(AsyncWasiModule),
 }
 impl WasiInstance {
     /// Returns the name of the import object.
     pub fn name(&self) -> &str {
         match self {
-            #[cfg(not(feature = "async"))]
             WasiInstance::Wasi(wasi) => wasi.name(),
-            #[cfg(all(feature = "async", target_os = "linux"))]
-            WasiInstance::AsyncWasi(async_wasi) => async_wasi.name(),
         }
     }
 
@@ -820,10 +846,7 @@ impl WasiInstance {
     #[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
     pub fn as_raw_ptr(&self) -> *const ffi::WasmEdge_ModuleInstanceContext {
         match self {
-            #[cfg(not(feature = "async"))]
             WasiInstance::Wasi(wasi) => wasi.inner.0,
-            #[cfg(all(feature = "async", target_os = "linux"))]
-            WasiInstance::AsyncWasi(async_wasi) => async_wasi.inner.0,
         }
     }
 }
@@ -837,12 +860,10 @@ mod tests {
     };
     use bit_macro::sys_host_function;
     use bit_types::{error::HostFuncError, Mutability, NeverType, RefType, ValType};
-    #[cfg(not(feature = "async"))]
     use std::sync::{Arc, Mutex};
     use std::thread;
 
     #[test]
-    // #[cfg(not(feature = "async"))]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_add_instance() {
         assert_eq!(HOST_FUNCS.read().len(), 0);
@@ -922,7 +943,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(feature = "async"))]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_import_module_sync() {
         let host_name = "extern";
@@ -1031,7 +1051,7 @@ mod tests {
         handle.join().unwrap();
     }
 
-    #[cfg(all(not(feature = "async"), target_family = "unix"))]
+    #[cfg(target_family = "unix")]
     #[test]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_wasi() {
@@ -1071,7 +1091,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(feature = "async"))]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_find_xxx() -> Result<(), Box<dyn std::error::Error>> {
         let module_name = "extern_module";
@@ -1193,7 +1212,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(feature = "async"))]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_find_names() -> Result<(), Box<dyn std::error::Error>> {
         let module_name = "extern_module";
@@ -1280,7 +1298,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(feature = "async"))]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_get() {
         let module_name = "extern_module";
@@ -1392,7 +1409,6 @@ mod tests {
         Ok(vec![WasmValue::from_i32(c)])
     }
 
-    #[cfg(not(feature = "async"))]
     #[test]
     #[allow(clippy::assertions_on_result_states)]
     fn test_instance_clone() {
@@ -155,6 +155,19 @@ impl Table {
         unsafe { check(ffi::WasmEdge_TableInstanceGrow(self.inner.lock().0, size)) }
     }
 
+    /// Returns a handle to the same table which does not own it, so it stays usable after this [Table] is moved into a module instance.
+    ///
+    /// # Safety
+    ///
+    /// The handle does not keep the table alive. The caller must keep the owner of the table, this [Table] or the module instance it is moved into, alive as long as the handle.
+    #[doc(hidden)]
+    pub unsafe fn alias(&self) -> Self {
+        Table {
+            inner: Arc::new(Mutex::new(InnerTable(self.inner.lock().0))),
+            registered: true,
+        }
+    }
+
     /// Provides a raw pointer to the inner table context.
     #[cfg(feature = "ffi")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
@@ -58,6 +58,7 @@
 extern crate lazy_static;
 
 use parking_lot::{Mutex, RwLock};
+use statistics::HostFuncCounter;
 use std::{collections::HashMap, sync::Arc};
 
 #[allow(warnings)]
@@ -68,6 +69,8 @@ pub mod ffi {
 #[doc(hidden)]
 pub mod ast_module;
 #[doc(hidden)]
+pub mod cancel;
+#[doc(hidden)]
 #[cfg(feature = "aot")]
 pub mod compiler;
 #[doc(hidden)]
@@ -82,9 +85,13 @@ pub mod io;
 pub mod loader;
 pub mod plugin;
 #[doc(hidden)]
+pub mod replay;
+#[doc(hidden)]
 pub mod statistics;
 #[doc(hidden)]
 pub mod store;
+#[doc(hidden)]
+pub mod tally;
 pub mod types;
 pub mod utils;
 #[doc(hidden)]
@@ -103,20 +110,21 @@ pub use config::Config;
 pub use executor::Executor;
 #[doc(inline)]
 pub use frame::CallingFrame;
+use instance::memory::GrowHook;
 #[doc(inline)]
 pub use instance::module::WasiModule;
 #[doc(inline)]
 pub use instance::{
-    function::{FuncRef, FuncType, Function},
+    function::{FuncRef, FuncType, Function, WeakFunction},
     global::{Global, GlobalType},
-    memory::{MemType, Memory},
-    module::{AsImport, AsInstance, ImportModule, Instance, WasiInstance},
+    memory::{MemStat, MemType, Memory},
+    module::{AsImport, AsInstance, ImportModule, Instance, WasiInstance, WeakInstance},
     table::{Table, TableType},
 };
 #[doc(inline)]
 pub use loader::Loader;
 #[doc(inline)]
-pub use statistics::Statistics;
+pub use statistics::{HostFuncStat, Statistics};
 #[doc(inline)]
 pub use store::Store;
 #[doc(inline)]
@@ -134,8 +142,24 @@ pub(crate) type BoxedFn = Box<
         + Sync,
 >;
 
+// A host function registered in `HOST_FUNCS`, together with its own call statistics and the import it is exported as.
+pub(crate) struct HostFuncEntry {
+    pub(crate) real_fn: Mutex<BoxedFn>,
+    pub(crate) stat: HostFuncCounter,
+    pub(crate) import: Mutex<Option<(String, String)>>,
+}
+impl HostFuncEntry {
+    pub(crate) fn new(real_fn: BoxedFn) -> Self {
+        Self {
+            real_fn: Mutex::new(real_fn),
+            stat: HostFuncCounter::default(),
+            import: Mutex::new(None),
+        }
+    }
+}
+
 lazy_static! {
-    pub(crate) static ref HOST_FUNCS: RwLock<HashMap<usize, Arc<Mutex<BoxedFn>>>> =
+    pub(crate) static ref HOST_FUNCS: RwLock<HashMap<usize, Arc<HostFuncEntry>>> =
         RwLock::new(HashMap::new());
 }
 
@@ -145,6 +169,17 @@ lazy_static! {
         Mutex::new(HashMap::new());
 }
 
+// Stores the usage statistics of each memory instance, keyed by the address of the memory instance context.
+lazy_static! {
+    pub(crate) static ref MEM_STATS: Mutex<HashMap<usize, MemStat>> = Mutex::new(HashMap::new());
+}
+
+// Stores the grow hooks of each memory instance, keyed by the address of the memory instance context.
+lazy_static! {
+    pub(crate) static ref MEM_GROW_HOOKS: Mutex<HashMap<usize, Vec<Arc<GrowHook>>>> =
+        Mutex::new(HashMap::new());
+}
+
 /// The object that is used to perform a [host function](crate::Function) is required to implement this trait.
 pub trait Engine {
     /// Runs a host function instance and returns the results.
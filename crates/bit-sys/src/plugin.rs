@@ -574,6 +574,7 @@ impl AsImport for PluginModule {
     }
 
     fn add_func(&mut self, name: impl AsRef<str>, func: Function) {
+        func.set_import(&self.name, name.as_ref());
         self.funcs.push(func);
         let f = self.funcs.last_mut().unwrap();
 
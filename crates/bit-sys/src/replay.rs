@@ -0,0 +1,133 @@
+//! Defines the tape used to record and replay the results of host function calls.
+//!
+//! The tape is thread-local: it only intercepts the host functions invoked by the wasm functions running on the current thread.
+
+use crate::WasmValue;
+use bit_types::error::HostFuncError;
+use std::{cell::RefCell, collections::VecDeque};
+
+/// The error code returned to the guest when a host call can not be served from the tape during replay. It is the code of the `HostFuncFailed` execution error, so that the executor reports it as a known runtime error.
+pub const REPLAY_DIVERGED_CODE: u32 = 0x8D;
+
+/// Defines a host function call recorded on the tape.
+#[derive(Debug, Clone)]
+pub struct HostCallRecord {
+    /// The name of the module instance that exports the host function, or an empty string if the host function is not imported by name.
+    pub mod_name: String,
+    /// The name of the host function in its module instance, or an empty string if the host function is not imported by name.
+    pub name: String,
+    /// The arguments passed to the host function.
+    pub params: Vec<WasmValue>,
+    /// The result returned by the host function.
+    pub result: Result<Vec<WasmValue>, HostFuncError>,
+}
+
+#[derive(Debug, Default)]
+enum Tape {
+    #[default]
+    Off,
+    Recording(Vec<HostCallRecord>),
+    Replaying {
+        calls: VecDeque<HostCallRecord>,
+        diverged: bool,
+    },
+}
+
+thread_local! {
+    static TAPE: RefCell<Tape> = RefCell::new(Tape::Off);
+}
+
+/// Starts recording the host function calls made on the current thread.
+pub fn start_recording() {
+    TAPE.with(|tape| *tape.borrow_mut() = Tape::Recording(Vec::new()));
+}
+
+/// Stops recording and returns the host function calls recorded on the current thread.
+pub fn finish_recording() -> Vec<HostCallRecord> {
+    TAPE.with(|tape| match std::mem::take(&mut *tape.borrow_mut()) {
+        Tape::Recording(calls) => calls,
+        _ => Vec::new(),
+    })
+}
+
+/// Starts serving the host function calls made on the current thread from the given records, instead of invoking the host functions.
+///
+/// # Argument
+///
+/// * `calls` - The recorded host function calls, in the order in which they were made.
+pub fn start_replay(calls: Vec<HostCallRecord>) {
+    TAPE.with(|tape| {
+        *tape.borrow_mut() = Tape::Replaying {
+            calls: calls.into(),
+            diverged: false,
+        }
+    });
+}
+
+/// Stops replaying. Returns `true` if the replayed execution made exactly the recorded host function calls, to the recorded imports with the recorded arguments.
+pub fn finish_replay() -> bool {
+    TAPE.with(|tape| match std::mem::take(&mut *tape.borrow_mut()) {
+        Tape::Replaying { calls, diverged } => !diverged && calls.is_empty(),
+        _ => true,
+    })
+}
+
+/// Returns the recorded result of the next host call if the tape is replaying.
+///
+/// The call is served only if the next record was made by the same import, given by `import` as the module name and the function name, with the same arguments.
+pub(crate) fn intercept(
+    import: impl FnOnce() -> (String, String),
+    params: &[WasmValue],
+) -> Option<Result<Vec<WasmValue>, HostFuncError>> {
+    TAPE.with(|tape| match &mut *tape.borrow_mut() {
+        Tape::Replaying { calls, diverged } => {
+            let (mod_name, name) = import();
+            let result = match calls.pop_front() {
+                Some(record)
+                    if !*diverged
+                        && record.mod_name == mod_name
+                        && record.name == name
+                        && same_values(&record.params, params) =>
+                {
+                    record.result
+                }
+                _ => {
+                    *diverged = true;
+                    Err(HostFuncError::Runtime(REPLAY_DIVERGED_CODE))
+                }
+            };
+            Some(result)
+        }
+        _ => None,
+    })
+}
+
+/// Checks if the tape is recording.
+pub(crate) fn is_recording() -> bool {
+    TAPE.with(|tape| matches!(&*tape.borrow(), Tape::Recording(_)))
+}
+
+/// Appends a host call to the tape if the tape is recording.
+pub(crate) fn record(
+    (mod_name, name): (String, String),
+    params: Vec<WasmValue>,
+    result: &Result<Vec<WasmValue>, HostFuncError>,
+) {
+    TAPE.with(|tape| {
+        if let Tape::Recording(calls) = &mut *tape.borrow_mut() {
+            calls.push(HostCallRecord {
+                mod_name,
+                name,
+                params,
+                result: result.clone(),
+            });
+        }
+    });
+}
+
+fn same_values(a: &[WasmValue], b: &[WasmValue]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(x, y)| x.ty() == y.ty() && x.as_raw().Value == y.as_raw().Value)
+}
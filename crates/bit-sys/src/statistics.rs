@@ -2,12 +2,20 @@
 
 use crate::{ffi, WasmEdgeResult};
 use bit_types::error::WasmEdgeError;
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 #[derive(Debug, Clone)]
 /// Struct of WasmEdge Statistics.
 pub struct Statistics {
     pub(crate) inner: Arc<InnerStat>,
+    // WasmEdge has no getter for the cost limit, so the last one set is kept here
+    cost_limit: Arc<AtomicU64>,
 }
 impl Statistics {
     /// Creates a new [Statistics].
@@ -21,6 +29,7 @@ impl Statistics {
             true => Err(Box::new(WasmEdgeError::StatisticsCreate)),
             false => Ok(Statistics {
                 inner: Arc::new(InnerStat(ctx)),
+                cost_limit: Arc::new(AtomicU64::new(u64::MAX)),
             }),
         }
     }
@@ -81,9 +90,15 @@ impl Statistics {
     ///
     /// * `limit` - The cost limit.
     pub fn set_cost_limit(&mut self, limit: u64) {
+        self.cost_limit.store(limit, Ordering::Relaxed);
         unsafe { ffi::WasmEdge_StatisticsSetCostLimit(self.inner.0, limit) }
     }
 
+    /// Returns the cost limit in execution, which is `u64::MAX` if it is never set.
+    pub fn cost_limit(&self) -> u64 {
+        self.cost_limit.load(Ordering::Relaxed)
+    }
+
     /// Clears the data in this statistics.
     pub fn clear(&mut self) {
         unsafe { ffi::WasmEdge_StatisticsClear(self.inner.0) }
@@ -104,6 +119,41 @@ impl Drop for Statistics {
     }
 }
 
+/// The accumulated call count and time spent inside a host function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostFuncStat {
+    /// The number of times the host function has been called.
+    pub calls: u64,
+    /// The total time spent inside the host function.
+    pub elapsed: Duration,
+}
+
+// The lock-free call counters kept alongside each host function.
+#[derive(Debug, Default)]
+pub(crate) struct HostFuncCounter {
+    calls: AtomicU64,
+    nanos: AtomicU64,
+}
+impl HostFuncCounter {
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn get(&self) -> HostFuncStat {
+        HostFuncStat {
+            calls: self.calls.load(Ordering::Relaxed),
+            elapsed: Duration::from_nanos(self.nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+        self.nanos.store(0, Ordering::Relaxed);
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct InnerStat(pub(crate) *mut ffi::WasmEdge_StatisticsContext);
 unsafe impl Send for InnerStat {}
@@ -0,0 +1,100 @@
+//! Defines the tally of the host function calls, the time spent in them, and the memory growth of the wasm function calls made on a thread.
+//!
+//! The tally is thread-local, so the calls running concurrently on other threads are not attributed to it. The memories are observed when they are given to [observe], when the wasm function calls a host function, and once more when the tally finishes.
+
+use crate::{ffi, Memory};
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+/// Defines the tally of a wasm function call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallTally {
+    /// The number of the host function calls.
+    pub host_calls: u64,
+    /// The time spent inside the host functions.
+    pub host_time: Duration,
+    /// The number of the pages the observed memories have grown by.
+    pub memory_grown: u32,
+}
+
+#[derive(Debug, Default)]
+struct Tally {
+    host_calls: u64,
+    host_time: Duration,
+    // the pages of the observed memories when they were first observed
+    memories: HashMap<usize, u32>,
+}
+
+thread_local! {
+    static TALLIES: RefCell<Vec<Tally>> = RefCell::new(Vec::new());
+}
+
+/// Starts a tally of the wasm function calls made on the current thread. The tallies can be nested.
+pub fn start() {
+    TALLIES.with(|tallies| tallies.borrow_mut().push(Tally::default()));
+}
+
+/// Finishes the innermost tally started on the current thread, and returns it.
+pub fn finish() -> CallTally {
+    let tally = TALLIES.with(|tallies| tallies.borrow_mut().pop().unwrap_or_default());
+    let memory_grown = tally
+        .memories
+        .iter()
+        .map(|(ctx, initial)| {
+            let pages = unsafe {
+                ffi::WasmEdge_MemoryInstanceGetPageSize(
+                    *ctx as *const ffi::WasmEdge_MemoryInstanceContext,
+                )
+            };
+            pages.saturating_sub(*initial)
+        })
+        .sum();
+    CallTally {
+        host_calls: tally.host_calls,
+        host_time: tally.host_time,
+        memory_grown,
+    }
+}
+
+/// Observes the given memory in the tallies of the current thread, so that its growth from its current size is counted.
+///
+/// # Argument
+///
+/// * `memory` - The memory to observe.
+pub fn observe(memory: &Memory) {
+    let mem_ctx = memory.inner.lock().0 as *const ffi::WasmEdge_MemoryInstanceContext;
+    let pages = unsafe { ffi::WasmEdge_MemoryInstanceGetPageSize(mem_ctx) };
+    TALLIES.with(|tallies| {
+        for tally in tallies.borrow_mut().iter_mut() {
+            tally.memories.entry(mem_ctx as usize).or_insert(pages);
+        }
+    })
+}
+
+/// Records a host function call made with the given memory of the calling module instance.
+pub(crate) fn host_call(mem_ctx: *const ffi::WasmEdge_MemoryInstanceContext) {
+    TALLIES.with(|tallies| {
+        let mut tallies = tallies.borrow_mut();
+        if tallies.is_empty() {
+            return;
+        }
+        let pages = match mem_ctx.is_null() {
+            true => None,
+            false => Some(unsafe { ffi::WasmEdge_MemoryInstanceGetPageSize(mem_ctx) }),
+        };
+        for tally in tallies.iter_mut() {
+            tally.host_calls += 1;
+            if let Some(pages) = pages {
+                tally.memories.entry(mem_ctx as usize).or_insert(pages);
+            }
+        }
+    })
+}
+
+/// Records the time spent inside a host function.
+pub(crate) fn host_time(elapsed: Duration) {
+    TALLIES.with(|tallies| {
+        for tally in tallies.borrow_mut().iter_mut() {
+            tally.host_time += elapsed;
+        }
+    })
+}
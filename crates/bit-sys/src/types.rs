@@ -284,6 +284,28 @@ impl WasmValue {
         }
     }
 
+    /// Creates a [WasmValue] from a raw pointer to an external object. The pointer is never dereferenced by WasmEdge.
+    ///
+    /// # Argument
+    ///
+    /// * `ptr` - The pointer to the external object.
+    pub fn from_extern_ptr(ptr: *const c_void) -> Self {
+        Self {
+            ctx: unsafe { ffi::WasmEdge_ValueGenExternRef(ptr as *mut c_void) },
+            ty: ValType::ExternRef,
+        }
+    }
+
+    /// Returns the raw pointer to the external object, or `None` if the [WasmValue] is a `NullRef`.
+    pub fn extern_ptr(&self) -> Option<*const c_void> {
+        unsafe {
+            match ffi::WasmEdge_ValueIsNullRef(self.ctx) {
+                true => None,
+                false => Some(ffi::WasmEdge_ValueGetExternRef(self.ctx) as *const c_void),
+            }
+        }
+    }
+
     /// Returns the reference to an external object.
     ///
     /// If the [WasmValue] is a `NullRef`, then `None` is returned.
@@ -6,9 +6,10 @@ use crate::{
 };
 use bit_types::error::{
     CoreCommonError, CoreError, CoreExecutionError, CoreInstantiationError, CoreLoadError,
-    CoreValidationError, WasmEdgeError,
+    CoreValidationError, Trap, WasmEdgeError,
 };
 use std::{
+    cell::RefCell,
     ffi::{CStr, CString},
     path::Path,
 };
@@ -45,6 +46,20 @@ pub fn log_off() {
     unsafe { ffi::WasmEdge_LogOff() }
 }
 
+thread_local! {
+    // the trap raised by the last host function which failed on the current thread
+    static LAST_TRAP: RefCell<Option<Trap>> = RefCell::new(None);
+}
+
+// Keeps the trap raised by a host function until the failed call is checked on the same thread.
+pub(crate) fn raise_trap(trap: Trap) {
+    LAST_TRAP.with(|last| *last.borrow_mut() = Some(trap));
+}
+
+fn take_trap() -> Option<Trap> {
+    LAST_TRAP.with(|last| last.borrow_mut().take())
+}
+
 // Checks the result of a `FFI` function.
 pub(crate) fn check(result: WasmEdge_Result) -> WasmEdgeResult<()> {
     let category = unsafe { ffi::WasmEdge_ResultGetCategory(result) };
@@ -57,7 +72,10 @@ pub(crate) fn check(result: WasmEdge_Result) -> WasmEdgeResult<()> {
     };
 
     match category {
-        ffi::WasmEdge_ErrCategory_UserLevelError => Err(Box::new(WasmEdgeError::User(code))),
+        ffi::WasmEdge_ErrCategory_UserLevelError => match take_trap() {
+            Some(trap) => Err(Box::new(WasmEdgeError::Trap(trap))),
+            None => Err(Box::new(WasmEdgeError::User(code))),
+        },
         ffi::WasmEdge_ErrCategory_WASM => gen_runtime_error(code),
         _ => panic!("Invalid category value: {category}"),
     }
@@ -1,6 +1,6 @@
 //! Defines WasmEdge error types.
 
-use crate::ExternalInstanceType;
+use crate::{ExternalInstanceType, FuncType, ValType};
 use thiserror::Error;
 
 /// The error types used by both wasmedge-sys and wasmedge crates.
@@ -66,6 +66,24 @@ pub enum WasmEdgeError {
     Instance(InstanceError),
     #[error("{0}")]
     Plugin(PluginError),
+    #[error("{0}")]
+    Replay(ReplayError),
+    #[error("{0}")]
+    Linker(LinkerError),
+    #[error("{0}")]
+    Tenant(TenantError),
+    #[error("{0}")]
+    Pool(PoolError),
+    #[error("{0}")]
+    Kv(KvError),
+    #[error("{0}")]
+    Wasi(WasiError),
+    #[error("{0}")]
+    Marshal(MarshalError),
+    #[error("{0}")]
+    Trap(Trap),
+    #[error("{0}")]
+    Aot(AotError),
 
     // std
     #[error("Found an internal 0 byte")]
@@ -82,6 +100,131 @@ pub enum WasmEdgeError {
     WindowsPathConversion(String),
 }
 
+/// The error types for the WASI host module.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum WasiError {
+    #[error("The guest path '{0}' of the preopen must not be empty or contain ':'")]
+    InvalidGuestPath(String),
+    #[error("The host path '{0}' of the preopen must not be empty or contain ':'")]
+    InvalidHostPath(String),
+}
+
+/// The error types for the verification of the AOT artifacts.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum AotError {
+    #[error("Not found the metadata embedded in the AOT artifact")]
+    MissingMetadata,
+    #[error("The metadata of the AOT artifact is malformed")]
+    InvalidMetadata,
+    #[error("The AOT artifact is compiled for {found}, but the current target is {expected}")]
+    TargetMismatch { expected: String, found: String },
+    #[error(
+        "The AOT artifact is compiled by WasmEdge {found}, but the loaded library is {expected}"
+    )]
+    RuntimeVersionMismatch { expected: String, found: String },
+    #[error("The AOT artifact is not compiled from the expected wasm")]
+    WasmHashMismatch,
+    #[error("The AOT artifact does not match the hash in its metadata")]
+    ArtifactHashMismatch,
+    #[error("The metadata of the AOT artifact is not signed")]
+    MissingSignature,
+    #[error("The signature of the AOT artifact is invalid")]
+    InvalidSignature,
+}
+
+/// The error types for marshalling values to and from guest memory.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MarshalError {
+    #[error("The layout version of {ty} in guest memory is {found}, but {expected} is expected")]
+    VersionMismatch {
+        ty: String,
+        expected: u32,
+        found: u32,
+    },
+    #[error("Found an invalid value of {0} in guest memory")]
+    InvalidValue(String),
+    #[error("Fail to encode or decode a value: {0}")]
+    Codec(String),
+}
+
+/// The error types for the multi-tenant execution manager.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TenantError {
+    #[error("The tenant named '{0}' is already added")]
+    Duplicate(String),
+    #[error("Not found the tenant named '{0}'")]
+    NotFound(String),
+    #[error("The tenant '{0}' has reached the limit of concurrent calls")]
+    ConcurrencyLimitExceeded(String),
+    #[error("The tenant '{0}' has used up its fuel for now")]
+    FuelExhausted(String),
+    #[error("The call panicked: {0}")]
+    Panicked(String),
+    #[error("The tenant manager is shut down")]
+    ShutDown,
+}
+
+/// The error types for the executor pool.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum PoolError {
+    #[error("The task panicked: {0}")]
+    Panicked(String),
+    #[error("The executor pool is shut down")]
+    ShutDown,
+}
+
+/// The error types for the key-value host module.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum KvError {
+    #[error("The key-value namespace '{0}' has reached its quota")]
+    QuotaExceeded(String),
+    #[error("Fail to access the key-value storage: {0}")]
+    Storage(String),
+}
+
+/// The error types for linking a graph of modules.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum LinkerError {
+    #[error("The module named '{0}' is added to the linker more than once")]
+    DuplicateModule(String),
+    #[error("The module '{module}' imports '{name}' from the module '{import_module}', which is neither in the linker nor registered in the store")]
+    UnresolvedImport {
+        module: String,
+        import_module: String,
+        name: String,
+    },
+    #[error("Found a cycle of module imports: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("Fail to instantiate the module '{module}': {error}")]
+    Instantiate {
+        module: String,
+        error: Box<WasmEdgeError>,
+    },
+}
+
+/// The error types for the replay bundles.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    #[error("The replay bundle is malformed: {0}")]
+    Malformed(String),
+    #[error("Unsupported replay bundle version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("The hash of the module in the replay bundle is {actual:#018x}, but the expected one is {expected:#018x}")]
+    ModuleHashMismatch { expected: u64, actual: u64 },
+    #[error("Fail to record an anonymous function. Only the functions exported by a module instance can be recorded.")]
+    AnonymousFunc,
+    #[error("The function ({0}) is not exported with the same type by the module to record")]
+    FuncNotInModule(String),
+    #[error("Fail to record the value of type {0}")]
+    UnsupportedValue(String),
+    #[error("Fail to stub the import ({0}) of the recorded module")]
+    UnsupportedImport(String),
+    #[error("The replayed execution diverged from the recorded host function calls")]
+    Diverged,
+    #[error("Fail to access the replay bundle file: {0}")]
+    Io(String),
+}
+
 /// The error types for WasmEdge Function.
 #[derive(Error, Clone, Debug, PartialEq, Eq)]
 pub enum FuncError {
@@ -91,6 +234,19 @@ pub enum FuncError {
     CreateBinding(String),
     #[error("Fail to get the function type")]
     Type,
+    #[error("The expected result types are {expected:?}, but found {actual:?}")]
+    ResultTypeMismatch {
+        expected: Vec<ValType>,
+        actual: Vec<ValType>,
+    },
+    #[error("The function ({name}) has the type {actual:?}, but {expected:?} is expected")]
+    SignatureMismatch {
+        name: String,
+        expected: FuncType,
+        actual: FuncType,
+    },
+    #[error("The call exceeded the limit of {0} instructions")]
+    InstructionLimitExceeded(u64),
 }
 
 /// The error types for WasmEdge Memory.
@@ -108,6 +264,15 @@ pub enum MemError {
     MutPtr,
     #[error("Fail to convert a raw pointer to a reference")]
     Ptr2Ref,
+    /// Raised by the host-side accesses to a memory. The accesses made by guest code trap with [CoreExecutionError::MemoryOutOfBounds] instead.
+    #[error("Out of bounds memory access: {len} bytes at offset {offset:#x}, but the memory size is {size:#x} bytes")]
+    OutOfBounds { offset: u32, len: u64, size: u64 },
+    #[error("The memory of {0} bytes is too large to be captured in a memory image")]
+    ImageTooLarge(u64),
+    #[error("The atomic wait and notify operations require a shared memory")]
+    NotShared,
+    #[error("The atomic access at offset {0:#x} is not aligned")]
+    UnalignedAtomic(u32),
 }
 
 /// The error types for WasmEdge Global.
@@ -130,6 +295,14 @@ pub enum TableError {
     Create,
     #[error("Fail to get the table type")]
     Type,
+    #[error("The table ({0}) does not hold function references")]
+    NotFuncRef(String),
+    #[error("The element at index {0} is an external reference which is not rooted by the table")]
+    UnknownExternRef(u32),
+    #[error("The table is already imported by a module")]
+    AlreadyImported,
+    #[error("The import object owning the table is dropped")]
+    OwnerDropped,
 }
 
 /// The error types for WasmEdge ImportType.
@@ -148,6 +321,8 @@ pub enum ImportError {
     MemType(String),
     #[error("{0}")]
     GlobalType(String),
+    #[error("The host API has no shared implementation of the function `{0}` to adapt")]
+    NotFoundSharedFunc(String),
 }
 
 /// The error types for WasmEdge ExportType.
@@ -189,6 +364,8 @@ pub enum InstanceError {
     NotFoundGlobal(String),
     #[error("Not found the given mapped Fd/handler")]
     NotFoundMappedFdHandler,
+    #[error("The module instance has no post-instantiation state to reset to")]
+    NoResetBaseline,
 }
 
 /// The error types for WasmEdge plugin.
@@ -231,6 +408,8 @@ pub enum StoreError {
     NotFoundModule(String),
     #[error("Not found the active module")]
     NotFoundActiveModule,
+    #[error("No executor is associated with the store, since no module is registered into it")]
+    NotFoundExecutor,
 }
 
 /// The error types for WasmEdge Vm.
@@ -460,6 +639,7 @@ pub enum CoreExecutionError {
     InvalidConvToInt,
     #[error("out of bounds table access")]
     TableOutOfBounds,
+    /// Raised when guest code accesses a memory out of bounds. The WasmEdge C API reports the trap as a bare error code, so the faulting address, the access size and the frame are not available.
     #[error("out of bounds memory access")]
     MemoryOutOfBounds,
     #[error("unreachable")]
@@ -487,4 +667,65 @@ pub enum HostFuncError {
     User(u32),
     #[error("Runtime error: {0}")]
     Runtime(u32),
+    #[error("{0}")]
+    Trap(Trap),
+}
+impl From<Trap> for HostFuncError {
+    fn from(trap: Trap) -> Self {
+        HostFuncError::Trap(trap)
+    }
+}
+
+/// A trap raised deliberately by a host function to abort the guest execution.
+///
+/// A host function returns it as [HostFuncError::Trap], and the call of the guest fails with [WasmEdgeError::Trap] carrying the same code and message.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("{message}")]
+pub struct Trap {
+    code: u32,
+    message: String,
+}
+impl Trap {
+    /// The user-level error code of a trap created with [Trap::new].
+    pub const DEFAULT_CODE: u32 = 1;
+
+    /// Creates a trap with the given message and [DEFAULT_CODE](Trap::DEFAULT_CODE).
+    ///
+    /// # Argument
+    ///
+    /// * `message` - The message describing why the execution is aborted.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            code: Self::DEFAULT_CODE,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the user-level error code of the trap.
+    ///
+    /// # Argument
+    ///
+    /// * `code` - The user-level error code reported to the caller of the guest function.
+    pub fn with_code(mut self, code: u32) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Returns the user-level error code of the trap.
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the message of the trap.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl From<u32> for Trap {
+    fn from(code: u32) -> Self {
+        Self {
+            code,
+            message: format!("host function trapped with code {code}"),
+        }
+    }
 }
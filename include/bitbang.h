@@ -0,0 +1,64 @@
+/*
+ * The C API of bitbang, built with the `capi` feature.
+ *
+ * A function which can fail returns 0 on success and -1 on failure, or a null
+ * handle on failure. The message of the last failure on the calling thread is
+ * returned by bitbang_last_error.
+ */
+
+#ifndef BITBANG_H
+#define BITBANG_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BITBANG_I32 0
+#define BITBANG_I64 1
+#define BITBANG_F32 2
+#define BITBANG_F64 3
+
+typedef struct BitbangExecutor BitbangExecutor;
+typedef struct BitbangModule BitbangModule;
+typedef struct BitbangInstance BitbangInstance;
+
+typedef union BitbangValueOf {
+  int32_t i32;
+  int64_t i64;
+  float f32;
+  double f64;
+} BitbangValueOf;
+
+typedef struct BitbangValue {
+  uint32_t kind;
+  BitbangValueOf of;
+} BitbangValue;
+
+const char *bitbang_last_error(void);
+
+BitbangExecutor *bitbang_executor_new(void);
+void bitbang_executor_delete(BitbangExecutor *executor);
+
+BitbangModule *bitbang_module_from_bytes(const uint8_t *bytes, size_t len);
+BitbangModule *bitbang_module_from_file(const char *path);
+void bitbang_module_delete(BitbangModule *module);
+
+BitbangInstance *bitbang_executor_instantiate(BitbangExecutor *executor,
+                                              const BitbangModule *module,
+                                              const char *name);
+void bitbang_instance_delete(BitbangInstance *instance);
+
+int32_t bitbang_call(const BitbangExecutor *executor,
+                     const BitbangInstance *instance, const char *func_name,
+                     const BitbangValue *params, size_t n_params,
+                     BitbangValue *results, size_t results_cap,
+                     size_t *n_results);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BITBANG_H */
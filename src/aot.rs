@@ -0,0 +1,541 @@
+//! Defines the metadata and the verification of the AOT artifacts generated by the [Compiler](crate::Compiler).
+//!
+//! The compiler embeds an [AotMetadata] in each artifact, recording the target and the WasmEdge version the artifact is compiled for, the hash of the source wasm and the hash of the artifact itself. If the compiler is given an [AotSigner], the metadata is also signed, typically with an Ed25519 key. Before loading an artifact from a cache, [Module::from_file_verified](crate::Module::from_file_verified) checks the metadata with an [AotVerifier], so an artifact which is stale, corrupted, or not signed by a trusted key is rejected instead of being loaded as native code:
+//!
+//! ```ignore
+//! let compiler = Compiler::new(Some(&config))?.with_signer(Ed25519Signer::new(signing_key));
+//! let artifact = compiler.compile_from_bytes(&wasm_bytes, "app", cache_dir)?;
+//!
+//! let verifier = AotVerifier::new()
+//!     .with_wasm(&wasm_bytes)
+//!     .with_signature_verifier(Ed25519Verifier::new(verifying_key));
+//! let module = Module::from_file_verified(Some(&config), &artifact, &verifier)?;
+//! ```
+//!
+//! The signature scheme is left to the [AotSigner] and [SignatureVerifier] implementations, so the crate does not depend on any specific cryptography library.
+//!
+//! The metadata is appended to the artifact as a wasm custom section named [METADATA_SECTION], which ends with the length of the metadata and a magic number, so it can be found from the end of the file. A universal wasm artifact stays a valid module, and the dynamic loaders ignore the trailing bytes of a shared library.
+
+use crate::{
+    binary::{self, SECTION_CUSTOM},
+    error::{AotError, WasmEdgeError},
+    utils::CoreVersion,
+    WasmEdgeResult,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the custom section carrying the metadata of an artifact.
+pub const METADATA_SECTION: &str = "bitbang.aotmeta";
+
+/// The magic number ending the metadata section.
+const METADATA_MAGIC: &[u8; 8] = b"BBAOTMD1";
+
+/// Signs the metadata of the AOT artifacts.
+pub trait AotSigner: Send + Sync + 'static {
+    /// Returns the signature of the given message.
+    ///
+    /// # Argument
+    ///
+    /// * `message` - The message to sign, which is returned by [AotMetadata::signed_message].
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+}
+
+/// Checks the signatures of the metadata of the AOT artifacts.
+pub trait SignatureVerifier: Send + Sync + 'static {
+    /// Checks if the given signature of the message is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The signed message, which is returned by [AotMetadata::signed_message].
+    ///
+    /// * `signature` - The signature to check.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Defines the metadata of an AOT artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AotMetadata {
+    /// The target the artifact is compiled for, in the `<arch>-<os>` format.
+    pub target: String,
+    /// The version of the WasmEdge library which compiled the artifact.
+    pub runtime_version: String,
+    /// The SHA-256 hash of the source wasm.
+    pub wasm_hash: [u8; 32],
+    /// The SHA-256 hash of the artifact.
+    pub artifact_hash: [u8; 32],
+    /// The signature of the [signed message](crate::aot::AotMetadata::signed_message), if the artifact is signed.
+    pub signature: Option<Vec<u8>>,
+}
+impl AotMetadata {
+    /// Creates the unsigned metadata of an artifact compiled from the given wasm for the current target by the loaded WasmEdge library.
+    ///
+    /// # Arguments
+    ///
+    /// * `wasm` - The source wasm.
+    ///
+    /// * `artifact` - The content of the artifact.
+    pub fn new(wasm: impl AsRef<[u8]>, artifact: impl AsRef<[u8]>) -> Self {
+        Self {
+            target: current_target(),
+            runtime_version: CoreVersion::version_string(),
+            wasm_hash: sha256(wasm.as_ref()),
+            artifact_hash: sha256(artifact.as_ref()),
+            signature: None,
+        }
+    }
+
+    /// Returns the message covered by the signature, which includes every field but the signature.
+    pub fn signed_message(&self) -> Vec<u8> {
+        let mut message = Vec::new();
+        for field in [self.target.as_bytes(), self.runtime_version.as_bytes()] {
+            message.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            message.extend_from_slice(field);
+        }
+        message.extend_from_slice(&self.wasm_hash);
+        message.extend_from_slice(&self.artifact_hash);
+        message
+    }
+
+    /// Signs the metadata with the given signer.
+    ///
+    /// # Argument
+    ///
+    /// * `signer` - The signer.
+    pub fn sign(&mut self, signer: &dyn AotSigner) {
+        self.signature = Some(signer.sign(&self.signed_message()));
+    }
+
+    /// Returns the given artifact with the metadata appended.
+    ///
+    /// # Argument
+    ///
+    /// * `artifact` - The content of the artifact, without metadata.
+    pub fn embed(&self, artifact: impl AsRef<[u8]>) -> Vec<u8> {
+        let content = self.encode();
+        let mut payload = Vec::new();
+        binary::write_name(&mut payload, METADATA_SECTION);
+        payload.extend_from_slice(content.as_bytes());
+        payload.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        payload.extend_from_slice(METADATA_MAGIC);
+
+        let mut out = artifact.as_ref().to_vec();
+        out.push(SECTION_CUSTOM);
+        binary::write_u32(&mut out, payload.len() as u32);
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    /// Splits the given content of an artifact into its metadata and the artifact without metadata.
+    ///
+    /// # Argument
+    ///
+    /// * `content` - The content of the artifact.
+    ///
+    /// # Error
+    ///
+    /// If the artifact carries no metadata, or the metadata is malformed, then an error is returned.
+    pub fn split(content: &[u8]) -> WasmEdgeResult<(Self, &[u8])> {
+        let err = |err: AotError| Box::new(WasmEdgeError::Aot(err));
+        if !content.ends_with(METADATA_MAGIC) {
+            return Err(err(AotError::MissingMetadata));
+        }
+
+        // the section is decoded backwards from the magic number
+        let (metadata, artifact) = (|| {
+            let end = content.len() - METADATA_MAGIC.len() - 4;
+            let len = u32::from_le_bytes(content.get(end..end + 4)?.try_into().ok()?) as usize;
+            let start = end.checked_sub(len)?;
+            let mut name = Vec::new();
+            binary::write_name(&mut name, METADATA_SECTION);
+            let payload_start = start.checked_sub(name.len())?;
+            if content[payload_start..start] != name[..] {
+                return None;
+            }
+            let mut header = vec![SECTION_CUSTOM];
+            binary::write_u32(&mut header, (content.len() - payload_start) as u32);
+            let section_start = payload_start.checked_sub(header.len())?;
+            if content[section_start..payload_start] != header[..] {
+                return None;
+            }
+            let metadata = Self::decode(std::str::from_utf8(&content[start..end]).ok()?)?;
+            Some((metadata, &content[..section_start]))
+        })()
+        .ok_or_else(|| err(AotError::InvalidMetadata))?;
+
+        Ok((metadata, artifact))
+    }
+
+    /// Reads the metadata embedded in the given artifact.
+    ///
+    /// # Argument
+    ///
+    /// * `artifact` - The path of the artifact.
+    ///
+    /// # Error
+    ///
+    /// If fail to read the artifact, or the artifact carries no metadata, or the metadata is malformed, then an error is returned.
+    pub fn read(artifact: impl AsRef<Path>) -> WasmEdgeResult<Self> {
+        let content = read_file(artifact.as_ref())?;
+        Self::split(&content).map(|(metadata, _)| metadata)
+    }
+
+    /// Embeds the metadata in the given artifact, replacing the metadata it carries, if any.
+    ///
+    /// # Argument
+    ///
+    /// * `artifact` - The path of the artifact.
+    ///
+    /// # Error
+    ///
+    /// If fail to rewrite the artifact, then an error is returned.
+    pub fn write(&self, artifact: impl AsRef<Path>) -> WasmEdgeResult<()> {
+        let content = read_file(artifact.as_ref())?;
+        let stripped = Self::split(&content).map_or(&content[..], |(_, stripped)| stripped);
+        std::fs::write(artifact.as_ref(), self.embed(stripped))
+            .map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))
+    }
+
+    fn encode(&self) -> String {
+        let mut content = format!(
+            "target={}\nruntime_version={}\nwasm_hash={}\nartifact_hash={}\n",
+            self.target,
+            self.runtime_version,
+            to_hex(&self.wasm_hash),
+            to_hex(&self.artifact_hash)
+        );
+        if let Some(signature) = &self.signature {
+            content.push_str(&format!("signature={}\n", to_hex(signature)));
+        }
+        content
+    }
+
+    fn decode(content: &str) -> Option<Self> {
+        let (mut target, mut runtime_version, mut wasm_hash, mut artifact_hash, mut signature) =
+            (None, None, None, None, None);
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=')?;
+            match key {
+                "target" => target = Some(value.to_string()),
+                "runtime_version" => runtime_version = Some(value.to_string()),
+                "wasm_hash" => wasm_hash = Some(from_hex(value)?.try_into().ok()?),
+                "artifact_hash" => artifact_hash = Some(from_hex(value)?.try_into().ok()?),
+                "signature" => signature = Some(from_hex(value)?),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            target: target?,
+            runtime_version: runtime_version?,
+            wasm_hash: wasm_hash?,
+            artifact_hash: artifact_hash?,
+            signature,
+        })
+    }
+}
+
+/// Defines the checks made on an AOT artifact before it is loaded.
+///
+/// The target, the WasmEdge version and the hash of the artifact are always checked. The hash of the source wasm and the signature are checked only if they are configured.
+#[derive(Clone, Default)]
+pub struct AotVerifier {
+    wasm_hash: Option<[u8; 32]>,
+    signature_verifier: Option<Arc<dyn SignatureVerifier>>,
+}
+impl AotVerifier {
+    /// Creates a new [AotVerifier].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the artifact to be compiled from the given wasm.
+    ///
+    /// # Argument
+    ///
+    /// * `wasm` - The expected source wasm.
+    pub fn with_wasm(self, wasm: impl AsRef<[u8]>) -> Self {
+        Self {
+            wasm_hash: Some(sha256(wasm.as_ref())),
+            ..self
+        }
+    }
+
+    /// Requires the metadata of the artifact to be signed, and checks the signature with the given verifier.
+    ///
+    /// # Argument
+    ///
+    /// * `verifier` - The signature verifier.
+    pub fn with_signature_verifier(self, verifier: impl SignatureVerifier) -> Self {
+        Self {
+            signature_verifier: Some(Arc::new(verifier)),
+            ..self
+        }
+    }
+
+    /// Checks the given artifact against its metadata, and returns the metadata.
+    ///
+    /// The file may be replaced once it is checked, so the checked content is to be loaded rather than the file, as [Module::from_file_verified](crate::Module::from_file_verified) does with [AotVerifier::verify_content].
+    ///
+    /// # Argument
+    ///
+    /// * `artifact` - The path of the artifact.
+    ///
+    /// # Error
+    ///
+    /// If fail to read the artifact, or the metadata is missing, or any check fails, then an error is returned.
+    pub fn verify(&self, artifact: impl AsRef<Path>) -> WasmEdgeResult<AotMetadata> {
+        self.verify_content(&read_file(artifact.as_ref())?)
+    }
+
+    /// Checks the given content of an artifact against its metadata, and returns the metadata.
+    ///
+    /// # Argument
+    ///
+    /// * `content` - The content of the artifact.
+    ///
+    /// # Error
+    ///
+    /// If the metadata is missing, or any check fails, then an error is returned.
+    pub fn verify_content(&self, content: &[u8]) -> WasmEdgeResult<AotMetadata> {
+        let (metadata, artifact) = AotMetadata::split(content)?;
+        let err = |err: AotError| Err(Box::new(WasmEdgeError::Aot(err)));
+
+        if let Some(verifier) = &self.signature_verifier {
+            match &metadata.signature {
+                Some(signature) if verifier.verify(&metadata.signed_message(), signature) => {}
+                Some(_) => return err(AotError::InvalidSignature),
+                None => return err(AotError::MissingSignature),
+            }
+        }
+        let target = current_target();
+        if metadata.target != target {
+            return err(AotError::TargetMismatch {
+                expected: target,
+                found: metadata.target,
+            });
+        }
+        let runtime_version = CoreVersion::version_string();
+        if metadata.runtime_version != runtime_version {
+            return err(AotError::RuntimeVersionMismatch {
+                expected: runtime_version,
+                found: metadata.runtime_version,
+            });
+        }
+        if self
+            .wasm_hash
+            .is_some_and(|wasm_hash| wasm_hash != metadata.wasm_hash)
+        {
+            return err(AotError::WasmHashMismatch);
+        }
+        if sha256(artifact) != metadata.artifact_hash {
+            return err(AotError::ArtifactHashMismatch);
+        }
+
+        Ok(metadata)
+    }
+}
+impl std::fmt::Debug for AotVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AotVerifier")
+            .field(
+                "wasm_hash",
+                &self.wasm_hash.as_ref().map(|hash| to_hex(hash)),
+            )
+            .field("signature_verifier", &self.signature_verifier.is_some())
+            .finish()
+    }
+}
+
+/// A directory under the temporary directory which only the current user can access, and which is removed with its content on drop.
+///
+/// The artifacts are written to and loaded from such directories, so that no other user can replace them between their creation or their verification and their loading.
+pub(crate) struct PrivateDir(PathBuf);
+impl PrivateDir {
+    /// Creates a new directory, never reusing an existing one.
+    pub(crate) fn new() -> WasmEdgeResult<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        loop {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.subsec_nanos());
+            let path = std::env::temp_dir().join(format!(
+                "bitbang-{}-{}-{nanos:08x}",
+                std::process::id(),
+                NEXT_ID.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            let mut builder = std::fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self(path)),
+                // the path is taken, possibly by another user, so another one is tried
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(Box::new(WasmEdgeError::Operation(err.to_string()))),
+            }
+        }
+    }
+
+    /// Returns the path of the directory.
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes a new file with the given content into the directory, and returns its path. The file must not exist.
+    pub(crate) fn write(&self, name: &str, content: &[u8]) -> WasmEdgeResult<PathBuf> {
+        let path = self.0.join(name);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(content))
+            .map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))?;
+        Ok(path)
+    }
+}
+impl Drop for PrivateDir {
+    fn drop(&mut self) {
+        // on Windows, a loaded artifact can not be removed, so it is left behind
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn read_file(path: &Path) -> WasmEdgeResult<Vec<u8>> {
+    std::fs::read(path).map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))
+}
+
+fn current_target() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a toy signature scheme, standing in for Ed25519 in the tests
+    struct XorKey(u8);
+    impl AotSigner for XorKey {
+        fn sign(&self, message: &[u8]) -> Vec<u8> {
+            sha256(message).iter().map(|byte| byte ^ self.0).collect()
+        }
+    }
+    impl SignatureVerifier for XorKey {
+        fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+            self.sign(message) == signature
+        }
+    }
+
+    #[test]
+    fn test_aot_verify() {
+        let dir = std::env::temp_dir().join(format!("bitbang-aot-{}", std::process::id()));
+        assert!(std::fs::create_dir_all(&dir).is_ok());
+        let artifact = dir.join("app.so");
+        let (wasm, native) = (b"\0asm\x01\0\0\0".as_slice(), b"native code".as_slice());
+        assert!(std::fs::write(&artifact, native).is_ok());
+
+        // no metadata
+        let result = AotVerifier::new().verify(&artifact);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::MissingMetadata)
+        );
+
+        let mut metadata = AotMetadata::new(wasm, native);
+        metadata.sign(&XorKey(0x5a));
+        assert!(metadata.write(&artifact).is_ok());
+        let result = AotMetadata::read(&artifact);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), metadata);
+
+        // the metadata is embedded as a custom section at the end of the artifact
+        let content = std::fs::read(&artifact).unwrap();
+        assert!(content.starts_with(native));
+        let result = AotMetadata::split(&content);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), (metadata.clone(), native));
+        let module = metadata.embed(wasm);
+        assert!(crate::binary::has_custom_section(&module, METADATA_SECTION));
+
+        // a section missing its id is malformed
+        let result = AotMetadata::split(&content[native.len() + 1..]);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::InvalidMetadata)
+        );
+
+        // the metadata matches the artifact
+        let verifier = AotVerifier::new()
+            .with_wasm(wasm)
+            .with_signature_verifier(XorKey(0x5a));
+        assert!(verifier.verify(&artifact).is_ok());
+
+        // signed by another key
+        let result = AotVerifier::new()
+            .with_signature_verifier(XorKey(0x33))
+            .verify(&artifact);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::InvalidSignature)
+        );
+
+        // compiled from another wasm
+        let result = AotVerifier::new().with_wasm(b"other").verify(&artifact);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::WasmHashMismatch)
+        );
+
+        // the artifact is replaced
+        assert!(std::fs::write(&artifact, metadata.embed(b"injected code")).is_ok());
+        let result = verifier.verify(&artifact);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::ArtifactHashMismatch)
+        );
+
+        // the signature is required
+        metadata.signature = None;
+        assert!(std::fs::write(&artifact, metadata.embed(native)).is_ok());
+        let result = verifier.verify(&artifact);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Aot(AotError::MissingSignature)
+        );
+
+        assert!(std::fs::remove_dir_all(&dir).is_ok());
+    }
+}
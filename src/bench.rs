@@ -0,0 +1,245 @@
+//! Defines the micro-benchmark mode of Executor.
+
+use crate::{Executor, Func, Statistics, WasmEdgeResult, WasmValue};
+use std::time::{Duration, Instant};
+
+/// Defines the options of [Executor::bench_func](crate::Executor::bench_func).
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    warmup_iterations: u32,
+    iterations: u32,
+    stat: Option<Statistics>,
+}
+impl BenchOptions {
+    /// Creates a new [BenchOptions] with 10 warmup iterations and 100 timed iterations.
+    pub fn new() -> Self {
+        Self {
+            warmup_iterations: 10,
+            iterations: 100,
+            stat: None,
+        }
+    }
+
+    /// Sets the number of untimed iterations run before the timed ones.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The number of warmup iterations.
+    pub fn with_warmup_iterations(self, count: u32) -> Self {
+        Self {
+            warmup_iterations: count,
+            ..self
+        }
+    }
+
+    /// Sets the number of timed iterations. At least one iteration is run.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The number of timed iterations.
+    pub fn with_iterations(self, count: u32) -> Self {
+        Self {
+            iterations: count.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the [statistics](crate::Statistics) the [executor](crate::Executor) is created with, so that the number of instructions per call is reported.
+    ///
+    /// Instruction counting must be enabled in the [config](crate::config::Config) of the executor with [StatisticsConfigOptions::count_instructions](crate::config::StatisticsConfigOptions::count_instructions).
+    ///
+    /// # Argument
+    ///
+    /// * `stat` - The statistics of the executor.
+    pub fn with_statistics(self, stat: &Statistics) -> Self {
+        Self {
+            stat: Some(stat.clone()),
+            ..self
+        }
+    }
+}
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The result of [Executor::bench_func](crate::Executor::bench_func).
+///
+/// The durations have the overhead of reading the clock subtracted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    /// The number of timed iterations.
+    pub iterations: u32,
+    /// The shortest call.
+    pub min: Duration,
+    /// The median call.
+    pub median: Duration,
+    /// The 99th percentile call.
+    pub p99: Duration,
+    /// The longest call.
+    pub max: Duration,
+    /// The average call.
+    pub mean: Duration,
+    /// The average number of instructions executed per call, or `None` if no [statistics](crate::Statistics) are given in the [options](crate::BenchOptions).
+    pub instructions_per_call: Option<f64>,
+    /// The measured overhead of reading the clock, which is subtracted from every sample.
+    pub timer_overhead: Duration,
+}
+
+impl Executor {
+    /// Benchmarks a function instance by running it repeatedly with the same arguments, and returns the statistics of the call time.
+    ///
+    /// The function is first run for the warmup iterations without being timed, and then each timed iteration is measured separately. Comparing the reports of executors created with different [configs](crate::config::Config), or of a module before and after the AOT compilation, tells the effect of the change without an external harness.
+    ///
+    /// ```ignore
+    /// let report = executor.bench_func(&fib, params!(20), BenchOptions::new().with_iterations(1_000))?;
+    /// println!("median: {:?}, p99: {:?}", report.median, report.p99);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to benchmark.
+    ///
+    /// * `params` - The arguments to pass to the function in every iteration.
+    ///
+    /// * `options` - The [options](crate::BenchOptions) of the benchmark.
+    ///
+    /// # Error
+    ///
+    /// If any iteration fails, then the error is returned.
+    pub fn bench_func(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+        options: BenchOptions,
+    ) -> WasmEdgeResult<BenchReport> {
+        let params: Vec<WasmValue> = params.into_iter().collect();
+
+        for _ in 0..options.warmup_iterations {
+            self.run_func(func, params.iter().copied())?;
+        }
+
+        let timer_overhead = timer_overhead();
+        let start_count = options.stat.as_ref().map(|stat| stat.count());
+        let mut samples = Vec::with_capacity(options.iterations as usize);
+        for _ in 0..options.iterations {
+            let started = Instant::now();
+            self.run_func(func, params.iter().copied())?;
+            samples.push(started.elapsed().saturating_sub(timer_overhead));
+        }
+        let instructions_per_call = options.stat.as_ref().zip(start_count).map(|(stat, start)| {
+            stat.count().saturating_sub(start) as f64 / options.iterations as f64
+        });
+
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        Ok(BenchReport {
+            iterations: options.iterations,
+            min: samples[0],
+            median: percentile(&samples, 50),
+            p99: percentile(&samples, 99),
+            max: samples[samples.len() - 1],
+            mean: total / options.iterations,
+            instructions_per_call,
+            timer_overhead,
+        })
+    }
+}
+
+/// Returns the smallest time measured between two consecutive reads of the clock.
+fn timer_overhead() -> Duration {
+    (0..100)
+        .map(|_| {
+            let started = Instant::now();
+            started.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Returns the given percentile of the sorted samples using the nearest-rank method.
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (sorted.len() as f64 * pct as f64 / 100.0).ceil() as usize;
+    sorted[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{CommonConfigOptions, ConfigBuilder, StatisticsConfigOptions},
+        params, wat2wasm, Module, Store,
+    };
+
+    #[test]
+    fn test_bench_func() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (func (export "add") (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                i32.add)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = ConfigBuilder::new(CommonConfigOptions::default())
+            .with_statistics_config(StatisticsConfigOptions::new().count_instructions(true))
+            .build();
+        assert!(result.is_ok());
+        let config = result.unwrap();
+
+        let result = Statistics::new();
+        assert!(result.is_ok());
+        let mut stat = result.unwrap();
+
+        let result = Executor::new(Some(&config), Some(&mut stat));
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let add = result.unwrap().func("add").unwrap();
+
+        let options = BenchOptions::new()
+            .with_warmup_iterations(5)
+            .with_iterations(50)
+            .with_statistics(&stat);
+        let result = executor.bench_func(&add, params!(1, 2), options);
+        assert!(result.is_ok());
+        let report = result.unwrap();
+        assert_eq!(report.iterations, 50);
+        assert!(report.min <= report.median);
+        assert!(report.median <= report.p99);
+        assert!(report.p99 <= report.max);
+        assert!(report.instructions_per_call.unwrap() > 0.0);
+
+        // without statistics
+        let result = executor.bench_func(&add, params!(1, 2), BenchOptions::new());
+        assert!(result.is_ok());
+        assert!(result.unwrap().instructions_per_call.is_none());
+
+        // a failing function
+        let result = executor.bench_func(&add, params!(1), BenchOptions::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bench_percentile() {
+        let samples: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&samples[..1], 99), Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,916 @@
+//! Defines a minimal reader and writer of the WebAssembly binary format, used to inspect and rewrite module binaries before they are loaded.
+
+use crate::{error::WasmEdgeError, FuncType, NameSection, ValType, WasmEdgeResult};
+use std::collections::HashMap;
+
+pub(crate) const MAGIC: &[u8; 4] = b"\0asm";
+pub(crate) const SECTION_CUSTOM: u8 = 0;
+pub(crate) const SECTION_TYPE: u8 = 1;
+pub(crate) const SECTION_IMPORT: u8 = 2;
+pub(crate) const SECTION_FUNCTION: u8 = 3;
+pub(crate) const SECTION_TABLE: u8 = 4;
+pub(crate) const SECTION_MEMORY: u8 = 5;
+pub(crate) const SECTION_GLOBAL: u8 = 6;
+pub(crate) const SECTION_EXPORT: u8 = 7;
+pub(crate) const SECTION_START: u8 = 8;
+pub(crate) const SECTION_ELEMENT: u8 = 9;
+pub(crate) const SECTION_CODE: u8 = 10;
+pub(crate) const EXTERNAL_FUNC: u8 = 0x00;
+pub(crate) const EXTERNAL_TABLE: u8 = 0x01;
+pub(crate) const EXTERNAL_MEMORY: u8 = 0x02;
+pub(crate) const EXTERNAL_GLOBAL: u8 = 0x03;
+
+/// The ids of the subsections of the name section.
+const NAME_SUBSECTION_MODULE: u8 = 0;
+const NAME_SUBSECTION_FUNC: u8 = 1;
+const NAME_SUBSECTION_LOCAL: u8 = 2;
+
+/// Defines a section of a module binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Section {
+    pub(crate) id: u8,
+    pub(crate) payload: Vec<u8>,
+}
+
+/// Defines the parsed sections of a module binary, which can be modified and encoded back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Binary {
+    pub(crate) version: [u8; 4],
+    pub(crate) sections: Vec<Section>,
+}
+impl Binary {
+    /// Parses the sections of the given module binary.
+    pub(crate) fn parse(bytes: &[u8]) -> WasmEdgeResult<Self> {
+        let mut r = Reader::new(bytes);
+        if r.bytes(4)? != MAGIC {
+            return Err(malformed("invalid magic number"));
+        }
+        let mut version = [0; 4];
+        version.copy_from_slice(r.bytes(4)?);
+
+        let mut sections = Vec::new();
+        while !r.is_empty() {
+            let id = r.u8()?;
+            let len = r.u32()? as usize;
+            let payload = r.bytes(len)?.to_vec();
+            sections.push(Section { id, payload });
+        }
+
+        Ok(Self { version, sections })
+    }
+
+    /// Encodes the sections into a module binary.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&self.version);
+        for section in self.sections.iter() {
+            out.push(section.id);
+            write_u32(&mut out, section.payload.len() as u32);
+            out.extend_from_slice(&section.payload);
+        }
+        out
+    }
+
+    /// Returns the position of the first section with the given id.
+    pub(crate) fn position(&self, id: u8) -> Option<usize> {
+        self.sections.iter().position(|s| s.id == id)
+    }
+
+    /// Returns the function index of the start section, if any.
+    pub(crate) fn start_func(&self) -> WasmEdgeResult<Option<u32>> {
+        match self.position(SECTION_START) {
+            Some(idx) => Ok(Some(Reader::new(&self.sections[idx].payload).u32()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the export entries as `(name, kind, index)` tuples.
+    pub(crate) fn exports(&self) -> WasmEdgeResult<Vec<(String, u8, u32)>> {
+        let mut exports = Vec::new();
+        if let Some(pos) = self.position(SECTION_EXPORT) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                let name = r.name()?;
+                let kind = r.u8()?;
+                let idx = r.u32()?;
+                exports.push((name, kind, idx));
+            }
+        }
+        Ok(exports)
+    }
+
+    /// Returns the import entries as `(module, name, kind)` tuples.
+    pub(crate) fn imports(&self) -> WasmEdgeResult<Vec<(String, String, u8)>> {
+        let mut imports = Vec::new();
+        if let Some(pos) = self.position(SECTION_IMPORT) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                let module = r.name()?;
+                let name = r.name()?;
+                let (kind, _) = r.import_desc()?;
+                imports.push((module, name, kind));
+            }
+        }
+        Ok(imports)
+    }
+
+    /// Returns the instructions of the bodies of the functions defined in the module, without their local declarations, in the order of their indices.
+    pub(crate) fn func_bodies(&self) -> WasmEdgeResult<Vec<&[u8]>> {
+        let mut bodies = Vec::new();
+        if let Some(pos) = self.position(SECTION_CODE) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                let len = r.u32()? as usize;
+                let mut body = Reader::new(r.bytes(len)?);
+                for _ in 0..body.u32()? {
+                    body.u32()?;
+                    body.u8()?;
+                }
+                bodies.push(body.rest());
+            }
+        }
+        Ok(bodies)
+    }
+
+    /// Returns the types of the functions in the function index space, in which the imported functions come first.
+    pub(crate) fn func_types(&self) -> WasmEdgeResult<Vec<FuncType>> {
+        let mut types = Vec::new();
+        if let Some(pos) = self.position(SECTION_TYPE) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                if r.u8()? != 0x60 {
+                    return Err(malformed("invalid function type"));
+                }
+                let args = r.val_types()?;
+                let returns = r.val_types()?;
+                types.push(FuncType::new(
+                    (!args.is_empty()).then_some(args),
+                    (!returns.is_empty()).then_some(returns),
+                ));
+            }
+        }
+        let ty = |idx: u32| {
+            types
+                .get(idx as usize)
+                .cloned()
+                .ok_or_else(|| malformed("invalid type index"))
+        };
+
+        let mut funcs = Vec::new();
+        if let Some(pos) = self.position(SECTION_IMPORT) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                r.name()?;
+                r.name()?;
+                if let (EXTERNAL_FUNC, type_idx) = r.import_desc()? {
+                    funcs.push(ty(type_idx)?);
+                }
+            }
+        }
+        if let Some(pos) = self.position(SECTION_FUNCTION) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                funcs.push(ty(r.u32()?)?);
+            }
+        }
+        Ok(funcs)
+    }
+
+    /// Returns the types of the tables, the memories, and the globals in their index spaces, in which the imported ones come first.
+    pub(crate) fn layout(&self) -> WasmEdgeResult<Layout> {
+        let mut layout = Layout::default();
+        if let Some(pos) = self.position(SECTION_IMPORT) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                r.name()?;
+                r.name()?;
+                match r.extern_desc()? {
+                    ExternDesc::Func(_) => {}
+                    ExternDesc::Table(elem_ty, limits) => layout.tables.push((elem_ty, limits)),
+                    ExternDesc::Memory(limits) => layout.memories.push(limits),
+                    ExternDesc::Global(val_ty, mutable) => layout.globals.push((val_ty, mutable)),
+                }
+            }
+        }
+        if let Some(pos) = self.position(SECTION_TABLE) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                let elem_ty = r.u8()?;
+                layout.tables.push((elem_ty, r.limits()?));
+            }
+        }
+        if let Some(pos) = self.position(SECTION_MEMORY) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                layout.memories.push(r.limits()?);
+            }
+        }
+        if let Some(pos) = self.position(SECTION_GLOBAL) {
+            let mut r = Reader::new(&self.sections[pos].payload);
+            for _ in 0..r.u32()? {
+                let val_ty = r.u8()?;
+                let mutable = r.u8()? == 0x01;
+                // skip the initializer
+                while r.instr()? != (0x0b, 0) {}
+                layout.globals.push((val_ty, mutable));
+            }
+        }
+        Ok(layout)
+    }
+
+    /// Returns the parsed name section. Returns an empty name section if there is none.
+    pub(crate) fn name_section(&self) -> WasmEdgeResult<NameSection> {
+        let mut names = NameSection::default();
+        for section in self.sections.iter().filter(|s| s.id == SECTION_CUSTOM) {
+            let mut r = Reader::new(&section.payload);
+            if r.name()? != "name" {
+                continue;
+            }
+            while !r.is_empty() {
+                let id = r.u8()?;
+                let len = r.u32()? as usize;
+                let mut sub = Reader::new(r.bytes(len)?);
+                match id {
+                    NAME_SUBSECTION_MODULE => names.module_name = Some(sub.name()?),
+                    NAME_SUBSECTION_FUNC => names.func_names = sub.name_map()?,
+                    NAME_SUBSECTION_LOCAL => {
+                        for _ in 0..sub.u32()? {
+                            let func_idx = sub.u32()?;
+                            names.local_names.insert(func_idx, sub.name_map()?);
+                        }
+                    }
+                    // other subsections, such as the label names, are not used
+                    _ => {}
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Returns the active element segments with constant offsets, which initialize the tables at the instantiation.
+    pub(crate) fn table_elements(&self) -> WasmEdgeResult<Vec<ElemSegment>> {
+        let mut segments = Vec::new();
+        let pos = match self.position(SECTION_ELEMENT) {
+            Some(pos) => pos,
+            None => return Ok(segments),
+        };
+
+        let mut r = Reader::new(&self.sections[pos].payload);
+        for _ in 0..r.u32()? {
+            let flags = r.u32()?;
+            if flags > 7 {
+                return Err(malformed("invalid element segment flags"));
+            }
+            let active = flags & 0b001 == 0;
+            let table = match flags & 0b011 == 0b010 {
+                true => r.u32()?,
+                false => 0,
+            };
+            let offset = match active {
+                true => r.const_expr()?,
+                false => ConstExpr::Other,
+            };
+            if flags & 0b011 != 0 {
+                // the element kind or the reference type
+                r.u8()?;
+            }
+            let mut funcs = Vec::new();
+            for _ in 0..r.u32()? {
+                let func = match flags & 0b100 == 0 {
+                    true => Some(r.u32()?),
+                    false => match r.const_expr()? {
+                        ConstExpr::RefFunc(idx) => Some(idx),
+                        _ => None,
+                    },
+                };
+                funcs.push(func);
+            }
+
+            if let ConstExpr::I32(offset) = offset {
+                segments.push(ElemSegment {
+                    table,
+                    offset: offset as u32,
+                    funcs,
+                });
+            }
+        }
+        Ok(segments)
+    }
+
+    /// Renames the imports for which the given function returns a new `(module, name)` pair, and returns the number of the renamed imports.
+    pub(crate) fn rename_imports(
+        &mut self,
+        rename: impl Fn(&str, &str) -> Option<(String, String)>,
+    ) -> WasmEdgeResult<usize> {
+        let pos = match self.position(SECTION_IMPORT) {
+            Some(pos) => pos,
+            None => return Ok(0),
+        };
+
+        let section = &mut self.sections[pos];
+        let mut r = Reader::new(&section.payload);
+        let count = r.u32()?;
+        let mut payload = Vec::new();
+        write_u32(&mut payload, count);
+        let mut renamed = 0;
+        for _ in 0..count {
+            let module = r.name()?;
+            let name = r.name()?;
+            let desc = r.rest();
+            r.import_desc()?;
+            let desc = &desc[..desc.len() - r.rest().len()];
+
+            let (module, name) = match rename(&module, &name) {
+                Some(new) => {
+                    renamed += 1;
+                    new
+                }
+                None => (module, name),
+            };
+            write_name(&mut payload, &module);
+            write_name(&mut payload, &name);
+            payload.extend_from_slice(desc);
+        }
+        section.payload = payload;
+        Ok(renamed)
+    }
+
+    /// Appends an export entry to the export section. If there is no export section, a new one is inserted at the given position.
+    pub(crate) fn add_export(
+        &mut self,
+        name: &str,
+        kind: u8,
+        idx: u32,
+        position: usize,
+    ) -> WasmEdgeResult<()> {
+        self.add_exports(&[(name.to_string(), kind, idx)], position)
+    }
+
+    /// Appends the given `(name, kind, index)` entries to the export section. If there is no export section, a new one is inserted at the given position.
+    pub(crate) fn add_exports(
+        &mut self,
+        exports: &[(String, u8, u32)],
+        position: usize,
+    ) -> WasmEdgeResult<()> {
+        let mut entries = Vec::new();
+        for (name, kind, idx) in exports.iter() {
+            write_name(&mut entries, name);
+            entries.push(*kind);
+            write_u32(&mut entries, *idx);
+        }
+
+        match self.position(SECTION_EXPORT) {
+            Some(pos) => {
+                let section = &mut self.sections[pos];
+                let mut r = Reader::new(&section.payload);
+                let count = r.u32()?;
+                let mut payload = Vec::new();
+                write_u32(&mut payload, count + exports.len() as u32);
+                payload.extend_from_slice(r.rest());
+                payload.extend_from_slice(&entries);
+                section.payload = payload;
+            }
+            None => {
+                let mut payload = Vec::new();
+                write_u32(&mut payload, exports.len() as u32);
+                payload.extend_from_slice(&entries);
+                self.sections.insert(
+                    position,
+                    Section {
+                        id: SECTION_EXPORT,
+                        payload,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the position at which a new section with the given id is inserted to keep the sections in the order the binary format requires.
+    pub(crate) fn insert_position(&self, id: u8) -> usize {
+        // the tag section (13) precedes the global section, and the data count section (12) precedes the code section
+        fn rank(id: u8) -> u8 {
+            match id {
+                13 => 6,
+                6..=9 => id + 1,
+                12 => 11,
+                10 | 11 => id + 2,
+                _ => id,
+            }
+        }
+        self.sections
+            .iter()
+            .position(|s| s.id != SECTION_CUSTOM && rank(s.id) > rank(id))
+            .unwrap_or(self.sections.len())
+    }
+}
+
+/// Defines the limits of a table or memory type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Limits {
+    pub(crate) min: u32,
+    pub(crate) max: Option<u32>,
+    pub(crate) shared: bool,
+}
+
+/// Defines the description of an imported entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExternDesc {
+    /// The type index of a function.
+    Func(u32),
+    /// The element type and the limits of a table.
+    Table(u8, Limits),
+    Memory(Limits),
+    /// The value type and the mutability of a global.
+    Global(u8, bool),
+}
+
+/// Defines the tables, the memories, and the globals of a module, in their index spaces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Layout {
+    /// The element types and the limits of the tables.
+    pub(crate) tables: Vec<(u8, Limits)>,
+    pub(crate) memories: Vec<Limits>,
+    /// The value types and the mutability of the globals.
+    pub(crate) globals: Vec<(u8, bool)>,
+}
+
+/// Defines an active element segment, which places functions into a table at the instantiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ElemSegment {
+    pub(crate) table: u32,
+    pub(crate) offset: u32,
+    /// The function indices of the elements. `None` stands for a null reference.
+    pub(crate) funcs: Vec<Option<u32>>,
+}
+
+/// Defines the value of a constant expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConstExpr {
+    I32(i32),
+    RefFunc(u32),
+    RefNull,
+    /// A value which is not known before the instantiation, such as the one of an imported global.
+    Other,
+}
+
+/// Defines a cursor over the bytes of a module binary.
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> WasmEdgeResult<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(malformed("unexpected end of the module binary"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> WasmEdgeResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Reads an unsigned LEB128 integer.
+    pub(crate) fn u32(&mut self) -> WasmEdgeResult<u32> {
+        let mut result = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift >= 32 {
+                return Err(malformed("integer too large"));
+            }
+            result |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+    }
+
+    /// Reads a signed LEB128 integer.
+    pub(crate) fn s32(&mut self) -> WasmEdgeResult<i32> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift >= 35 {
+                return Err(malformed("integer too large"));
+            }
+            result |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if byte & 0x40 != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Ok(result as i32);
+            }
+        }
+    }
+
+    /// Reads a UTF-8 name prefixed by its length.
+    pub(crate) fn name(&mut self) -> WasmEdgeResult<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| malformed("invalid UTF-8 name"))
+    }
+
+    /// Reads a vector of value types.
+    pub(crate) fn val_types(&mut self) -> WasmEdgeResult<Vec<ValType>> {
+        (0..self.u32()?)
+            .map(|_| match self.u8()? {
+                0x7f => Ok(ValType::I32),
+                0x7e => Ok(ValType::I64),
+                0x7d => Ok(ValType::F32),
+                0x7c => Ok(ValType::F64),
+                0x7b => Ok(ValType::V128),
+                0x70 => Ok(ValType::FuncRef),
+                0x6f => Ok(ValType::ExternRef),
+                _ => Err(malformed("invalid value type")),
+            })
+            .collect()
+    }
+
+    /// Reads the limits of a table or memory type.
+    pub(crate) fn limits(&mut self) -> WasmEdgeResult<Limits> {
+        let flags = self.u8()?;
+        let min = self.u32()?;
+        let max = match flags & 0x01 != 0 {
+            true => Some(self.u32()?),
+            false => None,
+        };
+        Ok(Limits {
+            min,
+            max,
+            shared: flags & 0x02 != 0,
+        })
+    }
+
+    /// Reads the description of an import.
+    pub(crate) fn extern_desc(&mut self) -> WasmEdgeResult<ExternDesc> {
+        let desc = match self.u8()? {
+            EXTERNAL_FUNC => ExternDesc::Func(self.u32()?),
+            EXTERNAL_TABLE => {
+                let elem_ty = self.u8()?;
+                ExternDesc::Table(elem_ty, self.limits()?)
+            }
+            EXTERNAL_MEMORY => ExternDesc::Memory(self.limits()?),
+            EXTERNAL_GLOBAL => {
+                let val_ty = self.u8()?;
+                ExternDesc::Global(val_ty, self.u8()? == 0x01)
+            }
+            _ => return Err(malformed("invalid import kind")),
+        };
+        Ok(desc)
+    }
+
+    /// Reads the description of an import, and returns its kind and, for a function, its type index.
+    pub(crate) fn import_desc(&mut self) -> WasmEdgeResult<(u8, u32)> {
+        let desc = match self.extern_desc()? {
+            ExternDesc::Func(type_idx) => (EXTERNAL_FUNC, type_idx),
+            ExternDesc::Table(..) => (EXTERNAL_TABLE, 0),
+            ExternDesc::Memory(_) => (EXTERNAL_MEMORY, 0),
+            ExternDesc::Global(..) => (EXTERNAL_GLOBAL, 0),
+        };
+        Ok(desc)
+    }
+
+    /// Skips a LEB128 integer of any width.
+    pub(crate) fn skip_leb(&mut self) -> WasmEdgeResult<()> {
+        for _ in 0..10 {
+            if self.u8()? & 0x80 == 0 {
+                return Ok(());
+            }
+        }
+        Err(malformed("integer too large"))
+    }
+
+    /// Skips the alignment, the memory index, and the offset of a memory instruction.
+    fn memarg(&mut self) -> WasmEdgeResult<()> {
+        if self.u32()? & 0x40 != 0 {
+            self.u32()?;
+        }
+        self.skip_leb()
+    }
+
+    /// Reads an instruction, skipping its immediates, and returns its opcode, and the sub-opcode of a prefixed instruction or zero.
+    pub(crate) fn instr(&mut self) -> WasmEdgeResult<(u8, u32)> {
+        let opcode = self.u8()?;
+        match opcode {
+            0x00 | 0x01 | 0x05 | 0x0b | 0x0f | 0x1a | 0x1b | 0x45..=0xc4 | 0xd1 => {}
+            // block, loop, if: an empty block type, a value type, or a type index
+            0x02..=0x04 => match self.rest().first() {
+                Some(0x40 | 0x6f | 0x70 | 0x7b..=0x7f) => {
+                    self.u8()?;
+                }
+                _ => self.skip_leb()?,
+            },
+            0x0c | 0x0d | 0x10 | 0x12 | 0x20..=0x26 | 0xd2 => {
+                self.u32()?;
+            }
+            0x0e => {
+                for _ in 0..=self.u32()? {
+                    self.u32()?;
+                }
+            }
+            0x11 | 0x13 => {
+                self.u32()?;
+                self.u32()?;
+            }
+            0x1c => {
+                let len = self.u32()? as usize;
+                self.bytes(len)?;
+            }
+            0x28..=0x3e => self.memarg()?,
+            0x3f | 0x40 | 0xd0 => {
+                self.u8()?;
+            }
+            0x41 | 0x42 => self.skip_leb()?,
+            0x43 => {
+                self.bytes(4)?;
+            }
+            0x44 => {
+                self.bytes(8)?;
+            }
+            0xfc => {
+                let sub = self.u32()?;
+                let immediates = match sub {
+                    0..=7 => 0,
+                    9 | 11 | 13 | 15..=17 => 1,
+                    8 | 10 | 12 | 14 => 2,
+                    _ => return Err(malformed("unsupported instruction")),
+                };
+                for _ in 0..immediates {
+                    self.u32()?;
+                }
+                return Ok((opcode, sub));
+            }
+            0xfd => {
+                let sub = self.u32()?;
+                match sub {
+                    0x00..=0x0b | 0x5c | 0x5d => self.memarg()?,
+                    0x0c | 0x0d => {
+                        self.bytes(16)?;
+                    }
+                    0x15..=0x22 => {
+                        self.u8()?;
+                    }
+                    0x54..=0x5b => {
+                        self.memarg()?;
+                        self.u8()?;
+                    }
+                    _ => {}
+                }
+                return Ok((opcode, sub));
+            }
+            0xfe => {
+                let sub = self.u32()?;
+                match sub {
+                    0x03 => {
+                        self.u8()?;
+                    }
+                    _ => self.memarg()?,
+                }
+                return Ok((opcode, sub));
+            }
+            _ => return Err(malformed("unsupported instruction")),
+        }
+        Ok((opcode, 0))
+    }
+
+    /// Reads a name map, a vector of indices and their names.
+    pub(crate) fn name_map(&mut self) -> WasmEdgeResult<HashMap<u32, String>> {
+        let mut map = HashMap::new();
+        for _ in 0..self.u32()? {
+            let idx = self.u32()?;
+            map.insert(idx, self.name()?);
+        }
+        Ok(map)
+    }
+
+    /// Reads a constant expression made of a single instruction.
+    pub(crate) fn const_expr(&mut self) -> WasmEdgeResult<ConstExpr> {
+        let value = match self.u8()? {
+            0x41 => ConstExpr::I32(self.s32()?),
+            0xd2 => ConstExpr::RefFunc(self.u32()?),
+            0xd0 => {
+                self.u8()?;
+                ConstExpr::RefNull
+            }
+            0x23 => {
+                self.u32()?;
+                ConstExpr::Other
+            }
+            _ => return Err(malformed("unsupported constant expression")),
+        };
+        if self.u8()? != 0x0b {
+            return Err(malformed("unsupported constant expression"));
+        }
+        Ok(value)
+    }
+}
+
+/// Writes an unsigned LEB128 integer.
+pub(crate) fn write_u32(out: &mut Vec<u8>, mut v: u32) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Checks if the given module binary has a custom section with the given name, without copying its sections.
+pub(crate) fn has_custom_section(bytes: &[u8], name: &str) -> bool {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).ok() != Some(&MAGIC[..]) || r.bytes(4).is_err() {
+        return false;
+    }
+    while !r.is_empty() {
+        let section = (|| {
+            let id = r.u8()?;
+            let len = r.u32()? as usize;
+            Ok::<_, Box<WasmEdgeError>>((id, r.bytes(len)?))
+        })();
+        match section {
+            Ok((SECTION_CUSTOM, payload)) => {
+                if Reader::new(payload).name().is_ok_and(|n| n == name) {
+                    return true;
+                }
+            }
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Writes a UTF-8 name prefixed by its length.
+pub(crate) fn write_name(out: &mut Vec<u8>, name: &str) {
+    write_u32(out, name.len() as u32);
+    out.extend_from_slice(name.as_bytes());
+}
+
+pub(crate) fn malformed(msg: &str) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Operation(format!(
+        "malformed module binary: {msg}"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wat2wasm, Module};
+
+    #[test]
+    fn test_binary_leb128() {
+        for v in [0u32, 1, 127, 128, 624485, u32::MAX] {
+            let mut out = Vec::new();
+            write_u32(&mut out, v);
+            assert_eq!(Reader::new(&out).u32().unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (func $init)
+              (start $init)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Binary::parse(&wasm_bytes);
+        assert!(result.is_ok());
+        let mut binary = result.unwrap();
+        assert_eq!(Binary::parse(&binary.encode()).unwrap(), binary);
+        assert_eq!(binary.start_func().unwrap(), Some(0));
+
+        // add an export section
+        let pos = binary.position(SECTION_START).unwrap();
+        let result = binary.add_export("init", EXTERNAL_FUNC, 0, pos);
+        assert!(result.is_ok());
+        assert!(binary.position(SECTION_EXPORT).is_some());
+
+        assert!(Binary::parse(&wasm_bytes[..wasm_bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_binary_rename_imports() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "wasi_unstable" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                (import "env" "memory" (memory 1 2))
+                (import "env" "old_fn" (func)))
+            "#,
+        )
+        .unwrap();
+        let mut binary = Binary::parse(&wasm_bytes).unwrap();
+        let result = binary.rename_imports(|module, name| match (module, name) {
+            ("wasi_unstable", _) => Some(("wasi_snapshot_preview1".into(), name.into())),
+            ("env", "old_fn") => Some(("host".into(), "new_fn".into())),
+            _ => None,
+        });
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 2);
+
+        let result = Module::from_bytes(None, binary.encode());
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        let imports = module
+            .imports()
+            .iter()
+            .map(|import| format!("{}.{}", import.module_name(), import.name()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            [
+                "wasi_snapshot_preview1.fd_write",
+                "env.memory",
+                "host.new_fn"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_table_elements() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "env" "log" (func $log (param i32)))
+              (global $base (import "env" "base") i32)
+              (table $callbacks (export "callbacks") 8 funcref)
+              (table $other 2 funcref)
+              (func $on_open)
+              (func $on_close)
+              (elem (table $callbacks) (i32.const 1) func $on_open $on_close)
+              (elem (table $other) (i32.const 0) funcref (ref.null func) (ref.func $log))
+              (elem (table $callbacks) (global.get $base) func $log)
+              (elem declare func $on_open)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Binary::parse(&wasm_bytes);
+        assert!(result.is_ok());
+        let binary = result.unwrap();
+
+        let result = binary.exports();
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            vec![("callbacks".to_string(), EXTERNAL_TABLE, 0)]
+        );
+
+        let result = binary.name_section();
+        assert!(result.is_ok());
+        let names = result.unwrap();
+        assert_eq!(names.func_name(0), Some("log"));
+        assert_eq!(names.func_name(2), Some("on_close"));
+
+        let result = binary.table_elements();
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                ElemSegment {
+                    table: 0,
+                    offset: 1,
+                    funcs: vec![Some(1), Some(2)],
+                },
+                ElemSegment {
+                    table: 1,
+                    offset: 0,
+                    funcs: vec![None, Some(0)],
+                },
+            ]
+        );
+
+        assert_eq!(Reader::new(&[0x7f]).s32().unwrap(), -1);
+        assert_eq!(Reader::new(&[0x80, 0x7f]).s32().unwrap(), -128);
+        assert_eq!(Reader::new(&[0x3f]).s32().unwrap(), 63);
+    }
+}
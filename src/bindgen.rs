@@ -0,0 +1,459 @@
+//! Defines the generator of typed Rust bindings for the exported functions of a wasm module.
+//!
+//! The generator is meant to be called from a build script. It reads the exports of a module and generates a wrapper struct with one method per exported function, so the functions are called with Rust types instead of being looked up by name:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     let out_file = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("guest.rs");
+//!     bitbang::bindgen::generate_to_file("guest.wasm", "Guest", out_file).unwrap();
+//!     println!("cargo:rerun-if-changed=guest.wasm");
+//! }
+//!
+//! // main.rs
+//! include!(concat!(env!("OUT_DIR"), "/guest.rs"));
+//!
+//! let instance = store.register_named_module(&mut executor, "guest", &module)?;
+//! let guest = Guest::new(executor, instance)?;
+//! let sum: i32 = guest.add(1, 2)?;
+//! ```
+
+use crate::{
+    binary::{Binary, EXTERNAL_FUNC},
+    error::{FuncError, WasmEdgeError},
+    wat2wasm, Func, FuncType, Instance, ValType, WasmEdgeResult,
+};
+use std::{collections::HashSet, fmt::Write, path::Path};
+
+/// The names which are used by the generated struct itself, or are keywords of Rust.
+const RESERVED: &[&str] = &[
+    "new", "instance", "executor", "as", "async", "await", "break", "const", "continue", "crate",
+    "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+    "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do",
+    "final", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// The most results a generated method returns as a tuple, which is the largest tuple [FromWasmValList](crate::FromWasmValList) is implemented for.
+const MAX_RESULTS: usize = 16;
+
+/// Generates the Rust source of a struct which wraps the exported functions of the given module.
+///
+/// The struct is created with `new(executor, instance)` from a [module instance](crate::Instance) of the module, which checks that every exported function exists with the expected type. Each exported function then becomes a method, named after the export in snake case, which takes and returns the Rust types of the parameters and results. Parameters are named after the local names of the name section, if any. Functions with reference types take and return [WasmValue](crate::WasmValue)s instead.
+///
+/// # Arguments
+///
+/// * `wasm` - The module, in the binary or the text format.
+///
+/// * `struct_name` - The name of the generated struct.
+///
+/// # Error
+///
+/// If the module cannot be parsed, two exports map to the same method name, or an exported function without reference types has more than 16 results, then an error is returned.
+pub fn generate(wasm: impl AsRef<[u8]>, struct_name: &str) -> WasmEdgeResult<String> {
+    let bytes =
+        wat2wasm(wasm.as_ref()).map_err(|e| Box::new(WasmEdgeError::Operation(e.to_string())))?;
+    let binary = Binary::parse(&bytes)?;
+    let func_types = binary.func_types()?;
+    let names = binary.name_section()?;
+
+    let mut methods = HashSet::new();
+    let mut funcs = Vec::new();
+    for (export_name, kind, idx) in binary.exports()? {
+        if kind != EXTERNAL_FUNC {
+            continue;
+        }
+        let method = ident(&export_name);
+        if !methods.insert(method.clone()) {
+            return Err(Box::new(WasmEdgeError::Operation(format!(
+                "the export ({export_name}) maps to the method name ({method}) of another export"
+            ))));
+        }
+        let ty = func_types.get(idx as usize).cloned().ok_or_else(|| {
+            Box::new(WasmEdgeError::Operation(format!(
+                "invalid function index ({idx})"
+            )))
+        })?;
+        let returns = ty.returns().unwrap_or_default();
+        if returns.len() > MAX_RESULTS && !returns.iter().any(|ty| is_ref(*ty)) {
+            return Err(Box::new(WasmEdgeError::Operation(format!(
+                "the export ({export_name}) has {} results, but at most {MAX_RESULTS} can be returned as a tuple",
+                returns.len()
+            ))));
+        }
+
+        let mut params: Vec<String> = (0..ty.args_len())
+            .map(|i| {
+                names
+                    .local_name(idx, i)
+                    .map(ident)
+                    .unwrap_or_else(|| format!("arg{i}"))
+            })
+            .collect();
+        if params.iter().collect::<HashSet<_>>().len() != params.len() {
+            params = (0..ty.args_len()).map(|i| format!("arg{i}")).collect();
+        }
+
+        funcs.push((export_name, method, params, ty));
+    }
+
+    let fields: String = funcs
+        .iter()
+        .map(|(_, method, _, _)| format!("    {method}: ::bitbang::Func,\n"))
+        .collect();
+    let bindings: String = funcs
+        .iter()
+        .map(|(export_name, method, _, ty)| {
+            format!(
+                "            {method}: ::bitbang::bindgen::bind_func(\n                \
+                 &instance,\n                {export_name:?},\n                \
+                 &[{}],\n                &[{}],\n            )?,\n",
+                val_types(ty.args()),
+                val_types(ty.returns())
+            )
+        })
+        .collect();
+
+    let mut out = format!(
+        r#"/// Typed bindings of the exported functions of a wasm module, generated by `bitbang::bindgen`.
+pub struct {struct_name} {{
+    executor: ::bitbang::Executor,
+    instance: ::bitbang::Instance,
+{fields}}}
+#[allow(clippy::too_many_arguments)]
+impl {struct_name} {{
+    /// Binds the exported functions of the given module instance, and checks their types.
+    pub fn new(
+        executor: ::bitbang::Executor,
+        instance: ::bitbang::Instance,
+    ) -> ::bitbang::WasmEdgeResult<Self> {{
+        Ok(Self {{
+{bindings}            executor,
+            instance,
+        }})
+    }}
+
+    /// Returns the module instance.
+    pub fn instance(&self) -> &::bitbang::Instance {{
+        &self.instance
+    }}
+"#
+    );
+    for (export_name, method, params, ty) in funcs.iter() {
+        let arg_tys = ty.args().unwrap_or_default();
+        let returns = ty.returns().unwrap_or_default();
+        let args = std::iter::once("&self".to_string())
+            .chain(
+                params
+                    .iter()
+                    .zip(arg_tys)
+                    .map(|(param, ty)| format!("{param}: {}", rust_type(*ty))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        let values = params
+            .iter()
+            .zip(arg_tys)
+            .map(|(param, ty)| wasm_value(param, *ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "    /// Calls the exported function `{}`.",
+            export_name.escape_default()
+        );
+        if returns.iter().any(|ty| is_ref(*ty)) {
+            let _ = writeln!(
+                out,
+                "    pub fn {method}({args}) -> ::bitbang::WasmEdgeResult<Vec<::bitbang::WasmValue>> {{"
+            );
+            let _ = writeln!(
+                out,
+                "        self.executor.run_func(&self.{method}, [{values}])"
+            );
+        } else {
+            let ret = match returns.len() {
+                1 => rust_type(returns[0]).to_string(),
+                _ => format!(
+                    "({})",
+                    returns
+                        .iter()
+                        .map(|ty| rust_type(*ty))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            let _ = writeln!(
+                out,
+                "    pub fn {method}({args}) -> ::bitbang::WasmEdgeResult<{ret}> {{"
+            );
+            let _ = writeln!(
+                out,
+                "        self.executor.run_func_typed::<{ret}>(&self.{method}, [{values}])"
+            );
+        }
+        let _ = writeln!(out, "    }}");
+    }
+    let _ = writeln!(out, "}}");
+
+    Ok(out)
+}
+
+/// Generates the bindings of a wasm file with [generate](crate::bindgen::generate), and writes them to the given file.
+///
+/// # Arguments
+///
+/// * `wasm_file` - The wasm file, in the binary or the text format.
+///
+/// * `struct_name` - The name of the generated struct.
+///
+/// * `out_file` - The file the Rust source is written to.
+///
+/// # Error
+///
+/// If fail to read the wasm file, to generate the bindings, or to write the output file, then an error is returned.
+pub fn generate_to_file(
+    wasm_file: impl AsRef<Path>,
+    struct_name: &str,
+    out_file: impl AsRef<Path>,
+) -> WasmEdgeResult<()> {
+    let io_error = |e: std::io::Error| Box::new(WasmEdgeError::Operation(e.to_string()));
+    let wasm = std::fs::read(wasm_file.as_ref()).map_err(io_error)?;
+    let source = generate(wasm, struct_name)?;
+    std::fs::write(out_file.as_ref(), source).map_err(io_error)
+}
+
+/// Returns the exported function of the given module instance, after checking its type. It is called by the generated bindings.
+///
+/// # Error
+///
+/// * If the function is not found, then an error is returned.
+///
+/// * If the function has another type, then [WasmEdgeError::Func(FuncError::SignatureMismatch)](crate::error::FuncError) is returned.
+#[doc(hidden)]
+pub fn bind_func(
+    instance: &Instance,
+    name: &str,
+    args: &[ValType],
+    returns: &[ValType],
+) -> WasmEdgeResult<Func> {
+    let func = instance.func(name)?;
+    let expected = FuncType::new(
+        (!args.is_empty()).then(|| args.to_vec()),
+        (!returns.is_empty()).then(|| returns.to_vec()),
+    );
+    if func.ty() != &expected {
+        return Err(Box::new(WasmEdgeError::Func(
+            FuncError::SignatureMismatch {
+                name: name.to_string(),
+                expected,
+                actual: func.ty().clone(),
+            },
+        )));
+    }
+    Ok(func)
+}
+
+/// Converts an export or local name into a snake case Rust identifier.
+fn ident(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+            prev_lower = true;
+        } else {
+            out.push('_');
+            prev_lower = false;
+        }
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    if RESERVED.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+fn is_ref(ty: ValType) -> bool {
+    matches!(ty, ValType::FuncRef | ValType::ExternRef)
+}
+
+fn rust_type(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "i128",
+        ValType::FuncRef | ValType::ExternRef => "::bitbang::WasmValue",
+    }
+}
+
+fn wasm_value(param: &str, ty: ValType) -> String {
+    match ty {
+        ValType::I32 => format!("::bitbang::WasmValue::from_i32({param})"),
+        ValType::I64 => format!("::bitbang::WasmValue::from_i64({param})"),
+        ValType::F32 => format!("::bitbang::WasmValue::from_f32({param})"),
+        ValType::F64 => format!("::bitbang::WasmValue::from_f64({param})"),
+        ValType::V128 => format!("::bitbang::WasmValue::from_v128({param})"),
+        ValType::FuncRef | ValType::ExternRef => param.to_string(),
+    }
+}
+
+fn val_types(tys: Option<&[ValType]>) -> String {
+    tys.unwrap_or_default()
+        .iter()
+        .map(|ty| format!("::bitbang::ValType::{ty:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Executor, Module, Store};
+
+    /// The bindings generated from [GUEST], which are compiled with the tests.
+    #[allow(dead_code)]
+    mod guest {
+        include!("testdata/bindgen_guest.rs");
+    }
+
+    const GUEST: &[u8] = br#"
+        (module
+          (func (export "add") (param $lhs i32) (param $rhs i32) (result i32)
+            local.get $lhs
+            local.get $rhs
+            i32.add)
+          (func (export "divRem") (param i64 i64) (result i64 i64)
+            local.get 0
+            local.get 1
+            i64.div_s
+            local.get 0
+            local.get 1
+            i64.rem_s)
+          (func (export "type") (param externref))
+          (func (export "reset"))
+          (memory (export "memory") 1)
+        )
+    "#;
+
+    #[test]
+    fn test_bindgen_generate() {
+        let result = generate(GUEST, "Guest");
+        assert!(result.is_ok());
+        let source = result.unwrap();
+
+        // the checked-in bindings must be regenerated when the generator changes
+        assert_eq!(source, include_str!("testdata/bindgen_guest.rs"));
+
+        // two exports with the same method name
+        let result = generate(
+            r#"(module (func (export "do-it")) (func (export "do_it")))"#,
+            "Guest",
+        );
+        assert!(result.is_err());
+
+        // too many results to return as a tuple
+        let results = "i32 ".repeat(MAX_RESULTS + 1);
+        let result = generate(
+            format!("(module (func (export \"many\") (result {results}) unreachable))"),
+            "Guest",
+        );
+        assert!(result.is_err());
+
+        assert_eq!(ident("processFrame"), "process_frame");
+        assert_eq!(ident("2d"), "_2d");
+        assert_eq!(ident("new"), "new_");
+    }
+
+    #[test]
+    fn test_bindgen_bind_func() {
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wat2wasm(GUEST).unwrap());
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let result = bind_func(
+            &instance,
+            "add",
+            &[ValType::I32, ValType::I32],
+            &[ValType::I32],
+        );
+        assert!(result.is_ok());
+        let add = result.unwrap();
+        let result = executor.run_func_typed::<i32>(&add, crate::params!(1, 2));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 3);
+
+        let result = bind_func(&instance, "reset", &[], &[]);
+        assert!(result.is_ok());
+
+        let result = bind_func(
+            &instance,
+            "add",
+            &[ValType::I64, ValType::I64],
+            &[ValType::I64],
+        );
+        assert!(result.is_err());
+        assert!(matches!(
+            *result.unwrap_err(),
+            WasmEdgeError::Func(FuncError::SignatureMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bindgen_generated() {
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wat2wasm(GUEST).unwrap());
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let result = guest::Guest::new(executor, instance);
+        assert!(result.is_ok());
+        let guest = result.unwrap();
+
+        let result = guest.add(1, 2);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 3);
+
+        let result = guest.div_rem(7, 2);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), (3, 1));
+
+        let result = guest.reset();
+        assert!(result.is_ok());
+    }
+}
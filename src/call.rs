@@ -0,0 +1,495 @@
+//! Defines the per-call overrides passed to [Executor::run_func_with](crate::Executor::run_func_with).
+//!
+//! The overrides carry request-scoped metadata to a single call without rebuilding the WASI context or the module instance:
+//!
+//! * environment variables, which are layered over the base environment of the [import object](crate::call::import_object) serving `environ_get` and `environ_sizes_get`;
+//!
+//! * a key-value context map, which the guest reads with the `get` function of the same import object, and the host functions read with [value](crate::call::value);
+//!
+//! * a deadline, after which the call is cancelled like under a [Timeout](crate::middleware::Timeout) layer.
+//!
+//! The WASI functions are implemented by the WasmEdge library, so a guest reads the overridden environment only if its WASI environment imports are redirected to the import object, for example with [env_aliases](crate::call::env_aliases):
+//!
+//! ```ignore
+//! let bytes = call::env_aliases().apply(&wasm_bytes)?;
+//! let module = Module::from_bytes(None, bytes)?;
+//! store.register_import_module(&mut executor, &call::import_object([("LANG", "C")])?)?;
+//! let instance = store.register_active_module(&mut executor, &module)?;
+//!
+//! let options = CallOptions::new()
+//!     .with_env("REQUEST_ID", request.id())
+//!     .with_value("tenant", request.tenant())
+//!     .with_timeout(Duration::from_millis(50));
+//! let returns = executor.run_func_with(&instance.func("handle")?, params!(), &options)?;
+//! ```
+
+use crate::{
+    error::{HostFuncError, Trap},
+    wasi::WASI_MODULE_NAMES,
+    Caller, CallingFrame, ImportAliases, ImportObject, ImportObjectBuilder, Memory, NeverType,
+    WasmEdgeResult, WasmValue,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The name of the import object serving the per-call overrides to the guests.
+pub const CALL_MODULE_NAME: &str = "bitbang_call";
+
+/// The WASI error code returned when the guest memory can not be accessed.
+const ERRNO_FAULT: i32 = 21;
+
+#[derive(Debug, Clone, Default)]
+struct Overrides {
+    env: Vec<(String, String)>,
+    values: HashMap<String, Vec<u8>>,
+    deadline: Option<Instant>,
+}
+
+/// Defines the overrides of a single call. Cloning the options is cheap, since the clones share the overrides.
+#[derive(Debug, Clone, Default)]
+pub struct CallOptions {
+    inner: Arc<Overrides>,
+}
+impl CallOptions {
+    /// Creates empty options, which override nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets an environment variable for the call, which replaces the variable of the same name in the base environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the variable.
+    ///
+    /// * `value` - The value of the variable.
+    pub fn with_env(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.env.retain(|(k, _)| k != key.as_ref());
+        inner
+            .env
+            .push((key.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// Sets an entry of the context map of the call.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the entry.
+    ///
+    /// * `value` - The value of the entry.
+    pub fn with_value(mut self, key: impl AsRef<str>, value: impl Into<Vec<u8>>) -> Self {
+        Arc::make_mut(&mut self.inner)
+            .values
+            .insert(key.as_ref().to_string(), value.into());
+        self
+    }
+
+    /// Sets the deadline of the call. See [Timeout](crate::middleware::Timeout) for how a call is cancelled.
+    ///
+    /// # Argument
+    ///
+    /// * `deadline` - The instant after which the call is cancelled.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        Arc::make_mut(&mut self.inner).deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline of the call to the given duration from now.
+    ///
+    /// # Argument
+    ///
+    /// * `timeout` - The maximum duration of the call.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Returns the deadline of the call, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.inner.deadline
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Vec<CallOptions>> = RefCell::new(Vec::new());
+}
+
+/// Leaves the options entered on the current thread when dropped, even if the call unwinds.
+pub(crate) struct Entered(());
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.borrow_mut().pop());
+    }
+}
+
+/// Enters the given options on the current thread for the duration of a call. The options can be nested; the inner ones take precedence.
+pub(crate) fn enter(options: &CallOptions) -> Entered {
+    CURRENT.with(|current| current.borrow_mut().push(options.clone()));
+    Entered(())
+}
+
+/// Returns the value of the given key in the context maps of the calls running on the current thread, or `None` if no call sets it.
+///
+/// # Argument
+///
+/// * `key` - The key of the entry.
+pub fn value(key: impl AsRef<str>) -> Option<Vec<u8>> {
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|options| options.inner.values.get(key.as_ref()).cloned())
+    })
+}
+
+/// Returns the overridden value of the given environment variable in the calls running on the current thread, or `None` if no call overrides it.
+///
+/// # Argument
+///
+/// * `key` - The name of the variable.
+pub fn env_var(key: impl AsRef<str>) -> Option<String> {
+    CURRENT.with(|current| {
+        current.borrow().iter().rev().find_map(|options| {
+            options
+                .inner
+                .env
+                .iter()
+                .find(|(k, _)| k == key.as_ref())
+                .map(|(_, v)| v.clone())
+        })
+    })
+}
+
+/// Returns the given base environment with the overrides of the calls running on the current thread applied.
+fn environ(base: &[(String, String)]) -> Vec<(String, String)> {
+    let mut env = base.to_vec();
+    CURRENT.with(|current| {
+        for options in current.borrow().iter() {
+            for (key, value) in options.inner.env.iter() {
+                match env.iter_mut().find(|(k, _)| k == key) {
+                    Some(entry) => entry.1 = value.clone(),
+                    None => env.push((key.clone(), value.clone())),
+                }
+            }
+        }
+    });
+    env
+}
+
+/// Creates the import object named [CALL_MODULE_NAME] serving the per-call overrides to the guests. It exports the following functions:
+///
+/// * `get(key_ptr: i32, key_len: i32, buf_ptr: i32, buf_len: i32) -> i32` copies at most `buf_len` bytes of the value of the key in the context map to the buffer, and returns the length of the value, or `-1` if the key is not set.
+///
+/// * `environ_get` and `environ_sizes_get`, which have the signatures and the semantics of the WASI functions of the same names, and return the base environment with the overrides applied.
+///
+/// # Argument
+///
+/// * `base_env` - The environment variables of the guests when no call overrides them.
+///
+/// # Error
+///
+/// If fail to create the import object, then an error is returned.
+pub fn import_object<K, V>(
+    base_env: impl IntoIterator<Item = (K, V)>,
+) -> WasmEdgeResult<ImportObject<NeverType>>
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let base_env: Arc<Vec<(String, String)>> = Arc::new(
+        base_env
+            .into_iter()
+            .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+            .collect(),
+    );
+    let sizes_env = Arc::clone(&base_env);
+
+    ImportObjectBuilder::new()
+        .with_func::<(i32, i32, i32, i32), i32, NeverType>("get", get, None)?
+        .with_func::<(i32, i32), i32, NeverType>(
+            "environ_sizes_get",
+            move |frame, inputs, _data| {
+                let env = environ(&sizes_env);
+                let size: usize = env.iter().map(|(k, v)| k.len() + v.len() + 2).sum();
+                let errno = match memory(frame) {
+                    Some(mut memory) => {
+                        let count = (env.len() as u32).to_le_bytes();
+                        let size = (size as u32).to_le_bytes();
+                        match memory
+                            .write(count, inputs[0].to_i32() as u32)
+                            .and_then(|_| memory.write(size, inputs[1].to_i32() as u32))
+                        {
+                            Ok(()) => 0,
+                            Err(_) => ERRNO_FAULT,
+                        }
+                    }
+                    None => ERRNO_FAULT,
+                };
+                Ok(vec![WasmValue::from_i32(errno)])
+            },
+            None,
+        )?
+        .with_func::<(i32, i32), i32, NeverType>(
+            "environ_get",
+            move |frame, inputs, _data| {
+                let env = environ(&base_env);
+                let mut pointers = Vec::with_capacity(env.len() * 4);
+                let mut buf = Vec::new();
+                let buf_ptr = inputs[1].to_i32() as u32;
+                for (key, value) in env.iter() {
+                    // a buffer at the end of the address space can not hold the strings
+                    match u32::try_from(buf.len())
+                        .ok()
+                        .and_then(|len| buf_ptr.checked_add(len))
+                    {
+                        Some(ptr) => pointers.extend_from_slice(&ptr.to_le_bytes()),
+                        None => return Ok(vec![WasmValue::from_i32(ERRNO_FAULT)]),
+                    }
+                    buf.extend_from_slice(format!("{key}={value}\0").as_bytes());
+                }
+                let errno = match memory(frame) {
+                    Some(mut memory) => match memory
+                        .write(pointers, inputs[0].to_i32() as u32)
+                        .and_then(|_| memory.write(buf, buf_ptr))
+                    {
+                        Ok(()) => 0,
+                        Err(_) => ERRNO_FAULT,
+                    },
+                    None => ERRNO_FAULT,
+                };
+                Ok(vec![WasmValue::from_i32(errno)])
+            },
+            None,
+        )?
+        .build::<NeverType>(CALL_MODULE_NAME, None)
+}
+
+/// Returns the [import aliases](crate::ImportAliases) which redirect the WASI environment imports of a module to the import object created by [import_object](crate::call::import_object).
+pub fn env_aliases() -> ImportAliases {
+    let mut aliases = ImportAliases::new();
+    for module in WASI_MODULE_NAMES {
+        for name in ["environ_get", "environ_sizes_get"] {
+            aliases = aliases.with_import((module, name), (CALL_MODULE_NAME, name));
+        }
+    }
+    aliases
+}
+
+/// Implements the `get` function of the import object.
+fn get(
+    frame: CallingFrame,
+    inputs: Vec<WasmValue>,
+    _data: *mut std::os::raw::c_void,
+) -> Result<Vec<WasmValue>, HostFuncError> {
+    let mut memory = match memory(frame) {
+        Some(memory) => memory,
+        None => return Err(Trap::new("the caller of `get` has no memory").into()),
+    };
+    let key = memory
+        .read(inputs[0].to_i32() as u32, inputs[1].to_i32() as u32)
+        .map_err(|_| Trap::new("the key passed to `get` is out of bounds"))?;
+    let key = String::from_utf8_lossy(&key);
+
+    let len = match value(key) {
+        Some(value) => {
+            let copied = value.len().min(inputs[3].to_i32().max(0) as usize);
+            memory
+                .write(&value[..copied], inputs[2].to_i32() as u32)
+                .map_err(|_| Trap::new("the buffer passed to `get` is out of bounds"))?;
+            value.len() as i32
+        }
+        None => -1,
+    };
+    Ok(vec![WasmValue::from_i32(len)])
+}
+
+/// Returns the default memory of the module instance calling a host function.
+fn memory(frame: CallingFrame) -> Option<Memory> {
+    Caller::new(frame).memory(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::WasmEdgeError, params, wat2wasm, Executor, Module, Store};
+
+    #[test]
+    fn test_call_options() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "wasi_snapshot_preview1" "environ_sizes_get" (func $sizes (param i32 i32) (result i32)))
+              (import "wasi_snapshot_preview1" "environ_get" (func $environ (param i32 i32) (result i32)))
+              (import "bitbang_call" "get" (func $get (param i32 i32 i32 i32) (result i32)))
+              (import "host" "spin" (func $spin))
+              (memory (export "memory") 1)
+              (data (i32.const 0) "tenant")
+              ;; returns the length of the value of "tenant", which is copied to offset 100
+              (func (export "tenant") (result i32)
+                i32.const 0
+                i32.const 6
+                i32.const 100
+                i32.const 64
+                call $get)
+              ;; returns the number of the environment variables, and copies them to offset 300
+              (func (export "env") (result i32)
+                i32.const 200
+                i32.const 204
+                call $sizes
+                drop
+                i32.const 208
+                i32.const 300
+                call $environ
+                drop
+                i32.const 200
+                i32.load)
+              ;; copies the environment variables to the given offset, and returns the errno
+              (func (export "env_at") (param i32) (result i32)
+                i32.const 208
+                local.get 0
+                call $environ)
+              (func (export "spin")
+                (loop $again
+                  call $spin
+                  br $again))
+            )
+            "#,
+        )
+        .unwrap();
+        let result = env_aliases().apply(&wasm_bytes);
+        assert!(result.is_ok());
+        let bytes = result.unwrap().into_owned();
+        let result = Module::from_bytes(None, bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = import_object([("LANG", "C"), ("MODE", "base")]);
+        assert!(result.is_ok());
+        assert!(store
+            .register_import_module(&mut executor, &result.unwrap())
+            .is_ok());
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), (), NeverType>(
+                "spin",
+                |_frame, _inputs, _data| {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Ok(vec![])
+                },
+                None,
+            )
+            .unwrap()
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        assert!(store
+            .register_import_module(&mut executor, &result.unwrap())
+            .is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let memory = instance.memory("memory").unwrap();
+
+        // without overrides
+        let tenant = instance.func("tenant").unwrap();
+        let result = executor.run_func(&tenant, params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), -1);
+
+        // the context map and the environment are overridden for one call
+        let options = CallOptions::new()
+            .with_value("tenant", "acme")
+            .with_env("MODE", "request")
+            .with_env("REQUEST_ID", "42");
+        let result = executor.run_func_with(&tenant, params!(), &options);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 4);
+        assert_eq!(memory.read(100, 4).unwrap(), b"acme");
+
+        let env = instance.func("env").unwrap();
+        let result = executor.run_func_with(&env, params!(), &options);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 3);
+        let size = u32::from_le_bytes(memory.read(204, 4).unwrap().try_into().unwrap());
+        assert_eq!(
+            memory.read(300, size).unwrap(),
+            b"LANG=C\0MODE=request\0REQUEST_ID=42\0"
+        );
+        let result = executor.run_func(&env, params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 2);
+
+        // the strings would wrap around the address space
+        let env_at = instance.func("env_at").unwrap();
+        let result = executor.run_func(&env_at, params!(-4));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), ERRNO_FAULT);
+
+        // the deadline cancels the call
+        let spin = instance.func("spin").unwrap();
+        let options = CallOptions::new().with_timeout(Duration::from_millis(20));
+        let result = executor.run_func_with(&spin, params!(), &options);
+        assert!(result.is_err());
+        assert_eq!(*result.unwrap_err(), WasmEdgeError::ExecuteTimeout);
+
+        // the overrides are visible to the host functions only during the call
+        assert!(value("tenant").is_none());
+        assert!(env_var("MODE").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_call_options_async() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "host" "lookup" (func $lookup (result i32)))
+              (func (export "run") (result i32)
+                call $lookup)
+            )
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // the host function reads the context map of the call
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), i32, NeverType>(
+                "lookup",
+                |_frame, _inputs, _data| {
+                    let len = value("user").map(|v| v.len() as i32).unwrap_or(-1);
+                    Ok(vec![WasmValue::from_i32(len)])
+                },
+                None,
+            )
+            .unwrap()
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let instance = store
+            .register_active_module(&mut executor, &module)
+            .unwrap();
+        let run = instance.func("run").unwrap();
+
+        let options = CallOptions::new().with_value("user", "alice");
+        let result = executor.run_func_async_with(&run, params!(), options).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 5);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{Executor, Instance, Memory};
+use crate::{
+    diagnostics::{HandleGuard, HandleKind},
+    middleware::Layers,
+    Executor, Instance, Memory,
+};
 use bit_sys::CallingFrame;
 use bit_types::MemoryType;
 
@@ -20,8 +24,19 @@ impl Caller {
     /// `CallingFrame` is a low-level type defined in `wasmedge-sys` crate, while `Caller` is a high-level type. For developers using the APIs in `wasmedge-sdk`, they should create a `Caller` instance with the given `CallingFrame` instance, as `Caller` provides APIs to access high-level instances, such as executor and memory, related to the current calling frame.
     ///
     pub fn new(frame: CallingFrame) -> Self {
-        let executor = frame.executor_mut().map(|inner| Executor { inner });
-        let instance = frame.module_instance().map(|inner| Instance { inner });
+        let executor = frame.executor_mut().map(|inner| Executor {
+            inner,
+            config: None,
+            stat: None,
+            middleware: Layers::default(),
+        });
+        let instance = frame.module_instance().map(|inner| Instance {
+            inner,
+            baseline: None,
+            module_info: None,
+            segment_funcs: None,
+            _guard: HandleGuard::new(HandleKind::Instance),
+        });
 
         Self {
             inner: Some(frame),
@@ -66,6 +81,7 @@ impl Caller {
                     name: None,
                     mod_name: None,
                     ty,
+                    _guard: HandleGuard::new(HandleKind::Memory),
                 }
             }),
             None => None,
@@ -0,0 +1,466 @@
+//! Defines a C API around the [Executor](crate::Executor), [Store](crate::Store), [Module](crate::Module) and [Instance](crate::Instance), so that the hosts written in other languages can load modules and call their functions. The declarations are in `include/bitbang.h`.
+//!
+//! The objects are opaque handles created and deleted through the API. A function which can fail returns `0` on success and `-1` on failure, and the message of the failure is read with [bitbang_last_error] on the same thread:
+//!
+//! ```c
+//! BitbangExecutor *executor = bitbang_executor_new();
+//! BitbangModule *module = bitbang_module_from_file("add.wasm");
+//! BitbangInstance *instance = bitbang_executor_instantiate(executor, module, NULL);
+//!
+//! BitbangValue params[2] = {{BITBANG_I32, {.i32 = 1}}, {BITBANG_I32, {.i32 = 2}}};
+//! BitbangValue results[1];
+//! size_t n_results = 0;
+//! if (bitbang_call(executor, instance, "add", params, 2, results, 1, &n_results) != 0) {
+//!     fprintf(stderr, "%s\n", bitbang_last_error());
+//! }
+//!
+//! bitbang_instance_delete(instance);
+//! bitbang_module_delete(module);
+//! bitbang_executor_delete(executor);
+//! ```
+//!
+//! The API does not unwind across the language boundary: a panic inside a call is reported as a failure.
+//!
+//! A shared or static library exporting the API is built with `cargo rustc --release --features capi --crate-type cdylib` or `--crate-type staticlib`.
+
+use crate::{
+    error::WasmEdgeError, Executor, Instance, Module, Store, ValType, WasmEdgeResult, WasmValue,
+};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+/// The kind of a [BitbangValue] holding an `i32`.
+pub const BITBANG_I32: u32 = 0;
+/// The kind of a [BitbangValue] holding an `i64`.
+pub const BITBANG_I64: u32 = 1;
+/// The kind of a [BitbangValue] holding an `f32`.
+pub const BITBANG_F32: u32 = 2;
+/// The kind of a [BitbangValue] holding an `f64`.
+pub const BITBANG_F64: u32 = 3;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// An executor and the store the module instances are registered into.
+#[derive(Debug)]
+pub struct BitbangExecutor {
+    executor: Executor,
+    store: Store,
+}
+
+/// A loaded and validated module.
+#[derive(Debug)]
+pub struct BitbangModule(Module);
+
+/// A module instance.
+#[derive(Debug)]
+pub struct BitbangInstance(Instance);
+
+/// The payload of a [BitbangValue].
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union BitbangValueOf {
+    /// The value of a `BITBANG_I32`.
+    pub i32: i32,
+    /// The value of a `BITBANG_I64`.
+    pub i64: i64,
+    /// The value of a `BITBANG_F32`.
+    pub f32: f32,
+    /// The value of a `BITBANG_F64`.
+    pub f64: f64,
+}
+
+/// A number passed to or returned from a wasm function, whose `kind` is one of `BITBANG_I32`, `BITBANG_I64`, `BITBANG_F32` and `BITBANG_F64`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct BitbangValue {
+    /// The kind of the value.
+    pub kind: u32,
+    /// The payload, whose field is selected by the kind.
+    pub of: BitbangValueOf,
+}
+impl BitbangValue {
+    fn to_wasm_value(self) -> WasmEdgeResult<WasmValue> {
+        // the field read matches the kind
+        unsafe {
+            match self.kind {
+                BITBANG_I32 => Ok(WasmValue::from_i32(self.of.i32)),
+                BITBANG_I64 => Ok(WasmValue::from_i64(self.of.i64)),
+                BITBANG_F32 => Ok(WasmValue::from_f32(self.of.f32)),
+                BITBANG_F64 => Ok(WasmValue::from_f64(self.of.f64)),
+                kind => Err(operation(format!("unknown value kind {kind}"))),
+            }
+        }
+    }
+
+    fn from_wasm_value(value: &WasmValue) -> WasmEdgeResult<Self> {
+        let (kind, of) = match value.ty() {
+            ValType::I32 => (
+                BITBANG_I32,
+                BitbangValueOf {
+                    i32: value.to_i32(),
+                },
+            ),
+            ValType::I64 => (
+                BITBANG_I64,
+                BitbangValueOf {
+                    i64: value.to_i64(),
+                },
+            ),
+            ValType::F32 => (
+                BITBANG_F32,
+                BitbangValueOf {
+                    f32: value.to_f32(),
+                },
+            ),
+            ValType::F64 => (
+                BITBANG_F64,
+                BitbangValueOf {
+                    f64: value.to_f64(),
+                },
+            ),
+            ty => return Err(operation(format!("unsupported value type {ty:?}"))),
+        };
+        Ok(Self { kind, of })
+    }
+}
+impl std::fmt::Debug for BitbangValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_wasm_value() {
+            Ok(value) => f.debug_tuple("BitbangValue").field(&value).finish(),
+            Err(_) => f
+                .debug_struct("BitbangValue")
+                .field("kind", &self.kind)
+                .finish(),
+        }
+    }
+}
+
+/// Returns the message of the last failure on the current thread, or null if none. The message is valid until the next failure on the thread.
+#[no_mangle]
+pub extern "C" fn bitbang_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Creates an executor with the default configuration and an empty store. Returns null on failure.
+#[no_mangle]
+pub extern "C" fn bitbang_executor_new() -> *mut BitbangExecutor {
+    guard_ptr(|| {
+        Ok(BitbangExecutor {
+            executor: Executor::new(None, None)?,
+            store: Store::new()?,
+        })
+    })
+}
+
+/// Deletes an executor created with [bitbang_executor_new]. The module instances created with it stay usable.
+///
+/// # Safety
+///
+/// `executor` is null, or a handle returned by [bitbang_executor_new] which is not deleted yet.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_executor_delete(executor: *mut BitbangExecutor) {
+    if !executor.is_null() {
+        drop(Box::from_raw(executor));
+    }
+}
+
+/// Loads and validates a module from in-memory bytes in the binary or the text format. Returns null on failure.
+///
+/// # Safety
+///
+/// `bytes` points to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_module_from_bytes(
+    bytes: *const u8,
+    len: usize,
+) -> *mut BitbangModule {
+    guard_ptr(|| {
+        if bytes.is_null() {
+            return Err(operation("the module bytes are null"));
+        }
+        let bytes = std::slice::from_raw_parts(bytes, len);
+        let bytes = crate::wat2wasm(bytes).map_err(|err| operation(err.to_string()))?;
+        Ok(BitbangModule(Module::from_bytes(None, bytes)?))
+    })
+}
+
+/// Loads and validates a module from a file. Returns null on failure.
+///
+/// # Safety
+///
+/// `path` is a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_module_from_file(path: *const c_char) -> *mut BitbangModule {
+    guard_ptr(|| {
+        let path = to_str(path)?;
+        Ok(BitbangModule(Module::from_file(None, path)?))
+    })
+}
+
+/// Deletes a module.
+///
+/// # Safety
+///
+/// `module` is null, or a handle returned by the API which is not deleted yet.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_module_delete(module: *mut BitbangModule) {
+    if !module.is_null() {
+        drop(Box::from_raw(module));
+    }
+}
+
+/// Instantiates a module in the store of the executor. Returns null on failure.
+///
+/// If `name` is not null, the module instance is registered under the name, so the modules instantiated later can import from it.
+///
+/// # Safety
+///
+/// `executor` and `module` are valid handles, and `name` is null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_executor_instantiate(
+    executor: *mut BitbangExecutor,
+    module: *const BitbangModule,
+    name: *const c_char,
+) -> *mut BitbangInstance {
+    guard_ptr(|| {
+        let executor = executor
+            .as_mut()
+            .ok_or_else(|| operation("the executor is null"))?;
+        let module = module
+            .as_ref()
+            .ok_or_else(|| operation("the module is null"))?;
+        let instance = match name.is_null() {
+            true => executor
+                .store
+                .register_active_module(&mut executor.executor, &module.0)?,
+            false => executor.store.register_named_module(
+                &mut executor.executor,
+                to_str(name)?,
+                &module.0,
+            )?,
+        };
+        Ok(BitbangInstance(instance))
+    })
+}
+
+/// Deletes a module instance.
+///
+/// # Safety
+///
+/// `instance` is null, or a handle returned by the API which is not deleted yet.
+#[no_mangle]
+pub unsafe extern "C" fn bitbang_instance_delete(instance: *mut BitbangInstance) {
+    if !instance.is_null() {
+        drop(Box::from_raw(instance));
+    }
+}
+
+/// Calls an exported function of a module instance. Returns `0` on success and `-1` on failure.
+///
+/// On success, the results are written to `results`, and their number to `n_results`. The call fails if the function returns more than `results_cap` results.
+///
+/// # Safety
+///
+/// `executor` and `instance` are valid handles, `func_name` is a nul-terminated string, `params` points to `n_params` values, `results` points to room for `results_cap` values, and `n_results` is writable.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn bitbang_call(
+    executor: *const BitbangExecutor,
+    instance: *const BitbangInstance,
+    func_name: *const c_char,
+    params: *const BitbangValue,
+    n_params: usize,
+    results: *mut BitbangValue,
+    results_cap: usize,
+    n_results: *mut usize,
+) -> i32 {
+    guard(|| {
+        let executor = executor
+            .as_ref()
+            .ok_or_else(|| operation("the executor is null"))?;
+        let instance = instance
+            .as_ref()
+            .ok_or_else(|| operation("the instance is null"))?;
+        let params = match n_params {
+            0 => &[],
+            _ => std::slice::from_raw_parts(params, n_params),
+        };
+        let params = params
+            .iter()
+            .map(|param| param.to_wasm_value())
+            .collect::<WasmEdgeResult<Vec<_>>>()?;
+
+        let func = instance.0.func(to_str(func_name)?)?;
+        let returns = executor.executor.run_func(&func, params)?;
+        if returns.len() > results_cap {
+            return Err(operation(format!(
+                "the function returns {} results, but there is room for {}",
+                returns.len(),
+                results_cap
+            )));
+        }
+        for (i, value) in returns.iter().enumerate() {
+            *results.add(i) = BitbangValue::from_wasm_value(value)?;
+        }
+        if !n_results.is_null() {
+            *n_results = returns.len();
+        }
+        Ok(())
+    })
+}
+
+/// Runs an entry point of the API, and records its failure or panic.
+fn guard(f: impl FnOnce() -> WasmEdgeResult<()>) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(err)) => {
+            set_last_error(err.to_string());
+            -1
+        }
+        Err(_) => {
+            set_last_error("panicked".to_string());
+            -1
+        }
+    }
+}
+
+/// Runs an entry point of the API creating an object, and returns the handle of the object, or null on failure.
+fn guard_ptr<T>(f: impl FnOnce() -> WasmEdgeResult<T>) -> *mut T {
+    let mut object = None;
+    match guard(|| {
+        object = Some(f()?);
+        Ok(())
+    }) {
+        0 => object.map_or(ptr::null_mut(), |object| Box::into_raw(Box::new(object))),
+        _ => ptr::null_mut(),
+    }
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> WasmEdgeResult<&'a str> {
+    if s.is_null() {
+        return Err(operation("the string is null"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|err| Box::new(WasmEdgeError::Utf8(err)))
+}
+
+fn operation(message: impl Into<String>) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Operation(message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capi_call() {
+        let executor = bitbang_executor_new();
+        assert!(!executor.is_null());
+
+        let wat = br#"
+            (module
+              (func (export "add") (param i32 i64) (result i64 f64)
+                local.get 1
+                local.get 0
+                i64.extend_i32_s
+                i64.add
+                f64.const 0.5))
+            "#;
+        let module = unsafe { bitbang_module_from_bytes(wat.as_ptr(), wat.len()) };
+        assert!(!module.is_null());
+        let instance = unsafe { bitbang_executor_instantiate(executor, module, ptr::null()) };
+        assert!(!instance.is_null());
+
+        let params = [
+            BitbangValue {
+                kind: BITBANG_I32,
+                of: BitbangValueOf { i32: 1 },
+            },
+            BitbangValue {
+                kind: BITBANG_I64,
+                of: BitbangValueOf { i64: 2 },
+            },
+        ];
+        let mut results = [BitbangValue {
+            kind: BITBANG_I32,
+            of: BitbangValueOf { i64: 0 },
+        }; 2];
+        let mut n_results = 0;
+        let name = CString::new("add").unwrap();
+        let status = unsafe {
+            bitbang_call(
+                executor,
+                instance,
+                name.as_ptr(),
+                params.as_ptr(),
+                params.len(),
+                results.as_mut_ptr(),
+                results.len(),
+                &mut n_results,
+            )
+        };
+        assert_eq!(status, 0);
+        assert_eq!(n_results, 2);
+        assert_eq!(results[0].kind, BITBANG_I64);
+        assert_eq!(unsafe { results[0].of.i64 }, 3);
+        assert_eq!(results[1].kind, BITBANG_F64);
+        assert_eq!(unsafe { results[1].of.f64 }, 0.5);
+
+        // the failures are reported through the last error
+        let status = unsafe {
+            bitbang_call(
+                executor,
+                instance,
+                name.as_ptr(),
+                params.as_ptr(),
+                params.len(),
+                results.as_mut_ptr(),
+                1,
+                &mut n_results,
+            )
+        };
+        assert_eq!(status, -1);
+        let message = unsafe { CStr::from_ptr(bitbang_last_error()) };
+        assert!(message.to_str().unwrap().contains("2 results"));
+
+        let missing = CString::new("missing").unwrap();
+        let status = unsafe {
+            bitbang_call(
+                executor,
+                instance,
+                missing.as_ptr(),
+                ptr::null(),
+                0,
+                results.as_mut_ptr(),
+                results.len(),
+                &mut n_results,
+            )
+        };
+        assert_eq!(status, -1);
+
+        let junk = unsafe { bitbang_module_from_bytes(b"junk".as_ptr(), 4) };
+        assert!(junk.is_null());
+        assert!(!bitbang_last_error().is_null());
+
+        unsafe {
+            bitbang_instance_delete(instance);
+            bitbang_module_delete(module);
+            bitbang_executor_delete(executor);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Defines the passing of serde values to and from guest functions through guest memory.
+//!
+//! A value is serialized in the selected [format](crate::codec::Format), written to memory allocated by the guest, and passed to the guest function as a `(ptr, len)` pair. The guest follows the same allocator protocol as [VmDock](crate::dock::VmDock), exporting:
+//!
+//! * `memory` - The linear memory.
+//!
+//! * `allocate(size: i32) -> i32` - Allocates `size` bytes and returns the address.
+//!
+//! * `deallocate(ptr: i32, size: i32)` - Frees the memory returned by `allocate`.
+//!
+//! A function called with [call](crate::codec::call) takes `(ptr: i32, len: i32)` and returns an `i64` holding the address of the serialized result in the high 32 bits and its length in the low 32 bits. The result must be allocated with `allocate`, since the host frees both the argument and the result after the call.
+//!
+//! ```ignore
+//! #[derive(Serialize)]
+//! struct Request { path: String, headers: Vec<(String, String)> }
+//!
+//! #[derive(Deserialize)]
+//! struct Response { status: u16, body: Vec<u8> }
+//!
+//! let response: Response = codec::call(&executor, &instance, "handle", &request, Format::Json)?;
+//! ```
+
+use crate::{
+    error::{MarshalError, WasmEdgeError},
+    params, Executor, Func, Instance, Memory, WasmEdgeResult, WasmVal,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Defines the serialization formats of the values passed to guest functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON, with [serde_json](https://docs.rs/serde_json).
+    Json,
+    /// CBOR, with [ciborium](https://docs.rs/ciborium).
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    Cbor,
+    /// MessagePack with named fields, with [rmp-serde](https://docs.rs/rmp-serde).
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    MessagePack,
+}
+impl Format {
+    /// Serializes a value into bytes.
+    ///
+    /// # Argument
+    ///
+    /// * `value` - The value to serialize.
+    ///
+    /// # Error
+    ///
+    /// If fail to serialize the value, then [WasmEdgeError::Marshal(MarshalError::Codec)](crate::error::MarshalError) is returned.
+    pub fn serialize<T: Serialize + ?Sized>(self, value: &T) -> WasmEdgeResult<Vec<u8>> {
+        match self {
+            Format::Json => serde_json::to_vec(value).map_err(codec_error),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(value, &mut bytes).map_err(codec_error)?;
+                Ok(bytes)
+            }
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::to_vec_named(value).map_err(codec_error),
+        }
+    }
+
+    /// Deserializes a value from bytes.
+    ///
+    /// # Argument
+    ///
+    /// * `bytes` - The bytes to deserialize.
+    ///
+    /// # Error
+    ///
+    /// If fail to deserialize the value, then [WasmEdgeError::Marshal(MarshalError::Codec)](crate::error::MarshalError) is returned.
+    pub fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> WasmEdgeResult<T> {
+        match self {
+            Format::Json => serde_json::from_slice(bytes).map_err(codec_error),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::de::from_reader(bytes).map_err(codec_error),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(codec_error),
+        }
+    }
+}
+
+fn codec_error(err: impl std::fmt::Display) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Marshal(MarshalError::Codec(err.to_string())))
+}
+
+/// Defines the allocator exported by a guest, which follows the protocol described in the [module documentation](crate::codec).
+#[derive(Debug, Clone)]
+pub struct GuestAllocator {
+    executor: Executor,
+    allocate: Func,
+    deallocate: Func,
+}
+impl GuestAllocator {
+    /// Creates a new [GuestAllocator] from the `allocate` and `deallocate` functions exported by the given module instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) running the allocator functions.
+    ///
+    /// * `instance` - The module instance exporting the allocator functions.
+    ///
+    /// # Error
+    ///
+    /// If the allocator functions are not found, then an error is returned.
+    pub fn new(executor: &Executor, instance: &Instance) -> WasmEdgeResult<Self> {
+        Self::with_names(executor, instance, "allocate", "deallocate")
+    }
+
+    /// Creates a new [GuestAllocator] from allocator functions exported under other names.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) running the allocator functions.
+    ///
+    /// * `instance` - The module instance exporting the allocator functions.
+    ///
+    /// * `allocate` - The name of the allocation function.
+    ///
+    /// * `deallocate` - The name of the deallocation function.
+    ///
+    /// # Error
+    ///
+    /// If the allocator functions are not found, then an error is returned.
+    pub fn with_names(
+        executor: &Executor,
+        instance: &Instance,
+        allocate: impl AsRef<str>,
+        deallocate: impl AsRef<str>,
+    ) -> WasmEdgeResult<Self> {
+        Ok(Self {
+            executor: executor.clone(),
+            allocate: instance.func(allocate)?,
+            deallocate: instance.func(deallocate)?,
+        })
+    }
+
+    /// Allocates guest memory and returns the address.
+    ///
+    /// # Argument
+    ///
+    /// * `size` - The size in bytes.
+    ///
+    /// # Error
+    ///
+    /// If the allocation function fails, then an error is returned.
+    pub fn alloc(&self, size: u32) -> WasmEdgeResult<u32> {
+        let ptr = self
+            .executor
+            .run_func_typed::<i32>(&self.allocate, params!(size as i32))?;
+        Ok(ptr as u32)
+    }
+
+    /// Frees guest memory returned by [alloc](crate::codec::GuestAllocator::alloc).
+    ///
+    /// # Arguments
+    ///
+    /// * `ptr` - The address of the memory.
+    ///
+    /// * `size` - The size in bytes.
+    ///
+    /// # Error
+    ///
+    /// If the deallocation function fails, then an error is returned.
+    pub fn dealloc(&self, ptr: u32, size: u32) -> WasmEdgeResult<()> {
+        self.executor
+            .run_func_typed::<()>(&self.deallocate, params!(ptr as i32, size as i32))
+    }
+}
+
+/// Serializes a value into memory allocated by the guest, and returns the address and the length of the bytes.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize.
+///
+/// * `format` - The serialization format.
+///
+/// * `memory` - The memory of the guest.
+///
+/// * `allocator` - The allocator of the guest.
+///
+/// # Error
+///
+/// If fail to serialize the value, to allocate the memory or to write to it, then an error is returned.
+pub fn to_guest<T: Serialize + ?Sized>(
+    value: &T,
+    format: Format,
+    memory: &mut Memory,
+    allocator: &GuestAllocator,
+) -> WasmEdgeResult<(u32, u32)> {
+    let bytes = format.serialize(value)?;
+    let len = bytes.len() as u32;
+    let ptr = allocator.alloc(len)?;
+    memory.write(bytes, ptr)?;
+    Ok((ptr, len))
+}
+
+/// Deserializes a value from guest memory.
+///
+/// # Arguments
+///
+/// * `memory` - The memory of the guest.
+///
+/// * `ptr` - The address of the bytes.
+///
+/// * `len` - The length of the bytes.
+///
+/// * `format` - The serialization format.
+///
+/// # Error
+///
+/// If fail to read the memory or to deserialize the value, then an error is returned.
+pub fn from_guest<T: DeserializeOwned>(
+    memory: &Memory,
+    ptr: u32,
+    len: u32,
+    format: Format,
+) -> WasmEdgeResult<T> {
+    let bytes = memory.read(ptr, len)?;
+    format.deserialize(&bytes)
+}
+
+/// Calls a guest function with a serialized argument, and deserializes its result. See the [module documentation](crate::codec) for the calling convention.
+///
+/// # Arguments
+///
+/// * `executor` - The [executor](crate::Executor) running the function.
+///
+/// * `instance` - The module instance exporting the function, the memory and the allocator.
+///
+/// * `func_name` - The name of the function.
+///
+/// * `value` - The argument of the function.
+///
+/// * `format` - The serialization format of both the argument and the result.
+///
+/// # Error
+///
+/// If fail to pass the argument, to run the function or to read its result, then an error is returned.
+pub fn call<P: Serialize + ?Sized, R: DeserializeOwned>(
+    executor: &Executor,
+    instance: &Instance,
+    func_name: impl AsRef<str>,
+    value: &P,
+    format: Format,
+) -> WasmEdgeResult<R> {
+    let func = instance.func(func_name)?;
+    let mut memory = instance.memory("memory")?;
+    let allocator = GuestAllocator::new(executor, instance)?;
+
+    let (ptr, len) = to_guest(value, format, &mut memory, &allocator)?;
+    let result = executor.run_func_typed::<i64>(&func, params!(ptr as i32, len as i32));
+    allocator.dealloc(ptr, len)?;
+
+    let packed = result? as u64;
+    let (result_ptr, result_len) = ((packed >> 32) as u32, packed as u32);
+    let result = from_guest(&memory, result_ptr, result_len, format);
+    allocator.dealloc(result_ptr, result_len)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Val, wat2wasm, Module, Store};
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Request {
+        path: String,
+        headers: Vec<(String, String)>,
+        retries: Option<u8>,
+    }
+
+    /// A guest with a bump allocator, whose `echo` function returns a copy of its argument.
+    fn guest() -> (Executor, Instance) {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (memory (export "memory") 1)
+              (global $next (mut i32) (i32.const 1024))
+              (global $live (export "live") (mut i32) (i32.const 0))
+              (func $allocate (export "allocate") (param $size i32) (result i32)
+                (local $ptr i32)
+                global.get $next
+                local.set $ptr
+                global.get $next
+                local.get $size
+                i32.add
+                global.set $next
+                global.get $live
+                i32.const 1
+                i32.add
+                global.set $live
+                local.get $ptr)
+              (func (export "deallocate") (param i32 i32)
+                global.get $live
+                i32.const 1
+                i32.sub
+                global.set $live)
+              (func (export "echo") (param $ptr i32) (param $len i32) (result i64)
+                (local $copy i32)
+                local.get $len
+                call $allocate
+                local.set $copy
+                local.get $copy
+                local.get $ptr
+                local.get $len
+                memory.copy
+                local.get $copy
+                i64.extend_i32_u
+                i64.const 32
+                i64.shl
+                local.get $len
+                i64.extend_i32_u
+                i64.or)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        (executor, result.unwrap())
+    }
+
+    #[test]
+    fn test_codec_call() {
+        let (executor, instance) = guest();
+        let request = Request {
+            path: "/index.html".to_string(),
+            headers: vec![("accept".to_string(), "text/html".to_string())],
+            retries: None,
+        };
+
+        let mut formats = vec![Format::Json];
+        #[cfg(feature = "cbor")]
+        formats.push(Format::Cbor);
+        #[cfg(feature = "msgpack")]
+        formats.push(Format::MessagePack);
+
+        for format in formats {
+            let result = call::<_, Request>(&executor, &instance, "echo", &request, format);
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap(), request);
+        }
+
+        // both the argument and the result are freed
+        let live = instance.global("live").unwrap().get_value();
+        assert!(matches!(live, Val::I32(0)));
+
+        // the result does not match the expected type
+        let result = call::<_, u32>(&executor, &instance, "echo", &request, Format::Json);
+        assert!(result.is_err());
+        assert!(matches!(
+            *result.unwrap_err(),
+            WasmEdgeError::Marshal(MarshalError::Codec(_))
+        ));
+    }
+
+    #[test]
+    fn test_codec_to_guest() {
+        let (executor, instance) = guest();
+        let mut memory = instance.memory("memory").unwrap();
+        let result = GuestAllocator::new(&executor, &instance);
+        assert!(result.is_ok());
+        let allocator = result.unwrap();
+
+        let result = to_guest(&[1u32, 2, 3], Format::Json, &mut memory, &allocator);
+        assert!(result.is_ok());
+        let (ptr, len) = result.unwrap();
+        assert_eq!(ptr, 1024);
+        assert_eq!(memory.read_string(ptr, len).unwrap(), "[1,2,3]");
+
+        let result = from_guest::<Vec<u32>>(&memory, ptr, len, Format::Json);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![1, 2, 3]);
+
+        // no allocator exported
+        let result = GuestAllocator::with_names(&executor, &instance, "malloc", "free");
+        assert!(result.is_err());
+    }
+}
@@ -1,13 +1,23 @@
 //! Defines WasmEdge ahead-of-time compiler.
 
-use crate::{config::Config, WasmEdgeResult};
+use crate::{
+    aot::{AotMetadata, AotSigner},
+    config::Config,
+    error::WasmEdgeError,
+    WasmEdgeResult,
+};
 use bit_sys as sys;
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// Defines WasmEdge ahead-of-time(AOT) compiler and the relevant APIs.
-#[derive(Debug)]
+///
+/// Each generated artifact embeds an [AotMetadata](crate::aot::AotMetadata), which is used to [verify](crate::aot::AotVerifier) the artifact before loading it.
 pub struct Compiler {
     pub(crate) inner: sys::Compiler,
+    signer: Option<Arc<dyn AotSigner>>,
 }
 impl Compiler {
     /// Creates a new AOT compiler.
@@ -21,11 +31,28 @@ impl Compiler {
             None => sys::Compiler::create(None)?,
         };
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            signer: None,
+        })
+    }
+
+    /// Signs the metadata of the generated artifacts with the given signer.
+    ///
+    /// # Argument
+    ///
+    /// * `signer` - The signer.
+    pub fn with_signer(self, signer: impl AotSigner) -> Self {
+        Self {
+            signer: Some(Arc::new(signer)),
+            ..self
+        }
     }
 
     /// Compiles the given wasm file into a shared library file (*.so in Linux, *.dylib in macOS, or *.dll in Windows). The file path of the generated shared library file will be returned if the method works successfully.
     ///
+    /// The [metadata](crate::aot::AotMetadata) of the shared library file is embedded in it.
+    ///
     /// # Arguments
     ///
     /// * `wasm_file` - The target wasm file.
@@ -52,13 +79,20 @@ impl Compiler {
         let aot_file = out_dir
             .as_ref()
             .join(format!("{}.{}", filename.as_ref(), extension));
-        self.inner.compile_from_file(wasm_file, &aot_file)?;
+        self.inner
+            .compile_from_file(wasm_file.as_ref(), &aot_file)?;
+
+        let wasm = std::fs::read(wasm_file.as_ref())
+            .map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))?;
+        self.write_metadata(wasm, &aot_file)?;
 
         Ok(aot_file)
     }
 
     /// Compiles the given wasm bytes into a shared library file (*.so in Linux, *.dylib in macOS, or *.dll in Windows). The file path of the generated shared library file will be returned if the method works successfully.
     ///
+    /// The [metadata](crate::aot::AotMetadata) of the shared library file is embedded in it.
+    ///
     /// # Argument
     ///
     /// * `bytes` - A in-memory WASM bytes.
@@ -85,18 +119,38 @@ impl Compiler {
         let aot_file = out_dir
             .as_ref()
             .join(format!("{}.{}", filename.as_ref(), extension));
-        self.inner.compile_from_bytes(bytes, &aot_file)?;
+        self.inner.compile_from_bytes(bytes.as_ref(), &aot_file)?;
+        self.write_metadata(bytes, &aot_file)?;
 
         Ok(aot_file)
     }
+
+    fn write_metadata(&self, wasm: impl AsRef<[u8]>, aot_file: &Path) -> WasmEdgeResult<()> {
+        let artifact = std::fs::read(aot_file)
+            .map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))?;
+        let mut metadata = AotMetadata::new(wasm, artifact);
+        if let Some(signer) = &self.signer {
+            metadata.sign(signer.as_ref());
+        }
+        metadata.write(aot_file)
+    }
+}
+impl std::fmt::Debug for Compiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Compiler")
+            .field("inner", &self.inner)
+            .field("signer", &self.signer.is_some())
+            .finish()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
+        aot::AotVerifier,
         config::{CompilerConfigOptions, ConfigBuilder},
-        params, wat2wasm, CompilerOutputFormat, VmBuilder, WasmVal,
+        params, wat2wasm, CompilerOutputFormat, Module, VmBuilder, WasmVal,
     };
     use std::io::Read;
 
@@ -116,7 +170,7 @@ mod tests {
                 .join("examples/wasmedge-sys/data/fibonacci.wat");
             let out_dir = std::env::current_dir()?;
             let aot_filename = "aot_fibonacci_1";
-            let aot_file_path = compiler.compile_from_file(wasm_file, aot_filename, out_dir)?;
+            let aot_file_path = compiler.compile_from_file(&wasm_file, aot_filename, out_dir)?;
             assert!(aot_file_path.exists());
             #[cfg(target_os = "macos")]
             assert!(aot_file_path.ends_with("aot_fibonacci_1.dylib"));
@@ -132,6 +186,11 @@ mod tests {
             let wasm_magic: [u8; 4] = [0x00, 0x61, 0x73, 0x6D];
             assert_ne!(buffer, wasm_magic);
 
+            // the artifact matches its metadata
+            let verifier = AotVerifier::new().with_wasm(std::fs::read(&wasm_file)?);
+            let result = Module::from_file_verified(Some(&config), &aot_file_path, &verifier);
+            assert!(result.is_ok());
+
             let res =
                 VmBuilder::new()
                     .build()?
@@ -111,14 +111,23 @@ impl ConfigBuilder {
             inner.generic_binary(compiler_config.generic_binary);
             inner.interruptible(compiler_config.interruptible);
         }
+        let mut max_instructions = None;
         if let Some(runtim_config) = self.runtime_config {
             inner.set_max_memory_pages(runtim_config.max_memory_pages);
+            if let Some(limit) = runtim_config.max_instructions {
+                // the limit is enforced as a cost limit with the default cost of 1 per instruction
+                inner.measure_cost(true);
+                max_instructions = Some(limit);
+            }
         }
         if let Some(host_config) = self.host_config {
             inner.wasi(host_config.wasi);
         }
 
-        Ok(Config { inner })
+        Ok(Config {
+            inner,
+            max_instructions,
+        })
     }
 }
 
@@ -163,6 +172,7 @@ impl ConfigBuilder {
 #[derive(Debug, Clone)]
 pub struct Config {
     pub(crate) inner: sys::Config,
+    pub(crate) max_instructions: Option<u64>,
 }
 impl Config {
     /// Checks if the host registration wasi option turns on or not.
@@ -175,6 +185,11 @@ impl Config {
         self.inner.get_max_memory_pages()
     }
 
+    /// Returns the maximum number of the instructions a single function call can execute, if any.
+    pub fn max_instructions(&self) -> Option<u64> {
+        self.max_instructions
+    }
+
     /// Checks if the ImportExportMutGlobals option turns on or not.
     pub fn mutable_globals_enabled(&self) -> bool {
         self.inner.mutable_globals_enabled()
@@ -653,15 +668,19 @@ impl Default for CompilerConfigOptions {
 ///
 /// - `maximum_memory_page` limits the page size of [Memory](crate::Memory). This option is only effective to
 ///       [Executor](crate::Executor).
+///
+/// - `max_instructions` limits the number of the instructions a single function call run by an [Executor](crate::Executor) can execute.
 #[derive(Debug, Clone, Copy)]
 pub struct RuntimeConfigOptions {
     max_memory_pages: u32,
+    max_instructions: Option<u64>,
 }
 impl RuntimeConfigOptions {
     /// Creates a new instance of [RuntimeConfigOptions].
     pub fn new() -> Self {
         Self {
             max_memory_pages: 65536,
+            max_instructions: None,
         }
     }
 
@@ -673,6 +692,25 @@ impl RuntimeConfigOptions {
     pub fn max_memory_pages(self, count: u32) -> Self {
         Self {
             max_memory_pages: count,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of the instructions a single function call can execute.
+    ///
+    /// A call which crosses the limit traps with [FuncError::InstructionLimitExceeded](crate::error::FuncError). The limit is enforced as a cost limit on the [Statistics](crate::Statistics) the executor is created with, or on a statistics the executor creates if none is given, counting from its cost at the start of each call. The cost limit set by the caller on the statistics, if any, is still enforced during the call, and restored once the call returns. Calls running concurrently on the same statistics share the budget of the call started last. It is a cheap backstop against infinite loops, which needs no metering by the guest or the host.
+    ///
+    /// WasmEdge only limits the cost of the instructions, so the limit counts instructions as long as each of them costs 1, which is the case unless a [cost table](crate::Statistics::set_cost_table) is set on the statistics. With a custom cost table, the limit counts the weights of the table instead, the same as the cost limit does.
+    ///
+    /// Setting a limit turns on cost measuring for every executor created with the config.
+    ///
+    /// # Argument
+    ///
+    /// - `limit` specifies the maximum instruction count.
+    pub fn max_instructions(self, limit: u64) -> Self {
+        Self {
+            max_instructions: Some(limit),
+            ..self
         }
     }
 }
@@ -0,0 +1,398 @@
+//! Defines the collection of the guest code coverage, which records the functions, and optionally the blocks, executed by the calls into a module instance.
+//!
+//! The coverage is opt-in: the module is loaded through the [Coverage] transform, which adds a counter to every function defined in the module, and to every block if enabled. The counters are exported globals, so they cost a few instructions per function entry and nothing on the host side. After the calls, [CoverageReport::collect] reads the counters of a module instance:
+//!
+//! ```ignore
+//! let module = Module::from_bytes_with(None, &plugin_bytes, &[&Coverage::new().with_blocks()])?;
+//! let instance = store.register_active_module(&mut executor, &module)?;
+//! // run the test suite of the plugin
+//! let report = CoverageReport::collect(&instance)?;
+//! println!("{}/{} functions covered", report.covered(), report.functions().len());
+//! std::fs::write("plugin.cov", report.to_text())?;
+//! ```
+
+use crate::{
+    binary::{
+        malformed, write_u32, Binary, Reader, Section, EXTERNAL_FUNC, EXTERNAL_GLOBAL,
+        SECTION_CODE, SECTION_EXPORT, SECTION_GLOBAL,
+    },
+    error::WasmEdgeError,
+    transform::ModuleTransform,
+    types::Val,
+    Instance, WasmEdgeResult,
+};
+use std::collections::BTreeMap;
+
+/// The prefix of the names of the exported counters.
+const COUNTER_PREFIX: &str = "__bitbang_cov:";
+
+/// Instruments a module to count the executions of its functions, and optionally of its blocks. See [coverage](crate::coverage).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Coverage {
+    blocks: bool,
+}
+impl Coverage {
+    /// Creates a transform which counts the executions of the functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also counts the executions of the blocks, which are the bodies of the `block`, `loop`, `if` and `else` instructions.
+    pub fn with_blocks(self) -> Self {
+        Self { blocks: true }
+    }
+
+    /// Adds the counters to a function body, which starts with its local declarations.
+    fn instrument(
+        &self,
+        body: &[u8],
+        func_idx: u32,
+        counters: &mut Counters,
+    ) -> WasmEdgeResult<Vec<u8>> {
+        let mut r = Reader::new(body);
+        for _ in 0..r.u32()? {
+            r.u32()?;
+            r.u8()?;
+        }
+        let mut out = body[..body.len() - r.rest().len()].to_vec();
+        counters.add(&mut out, format!("{COUNTER_PREFIX}{func_idx}"));
+
+        let mut block = 0;
+        while !r.is_empty() {
+            let instr = r.rest();
+            let (opcode, _) = r.instr()?;
+            out.extend_from_slice(&instr[..instr.len() - r.rest().len()]);
+            // block, loop, if, else
+            if self.blocks && (0x02..=0x05).contains(&opcode) {
+                counters.add(&mut out, format!("{COUNTER_PREFIX}{func_idx}:{block}"));
+                block += 1;
+            }
+        }
+        Ok(out)
+    }
+}
+impl ModuleTransform for Coverage {
+    fn id(&self) -> String {
+        match self.blocks {
+            true => "bitbang-coverage/1?blocks".to_string(),
+            false => "bitbang-coverage/1".to_string(),
+        }
+    }
+
+    fn transform(&self, bytes: &[u8]) -> WasmEdgeResult<Vec<u8>> {
+        let mut binary = Binary::parse(bytes)?;
+        let imports = binary.imports()?;
+        let imported_funcs = imports
+            .iter()
+            .filter(|(_, _, kind)| *kind == EXTERNAL_FUNC)
+            .count() as u32;
+        let imported_globals = imports
+            .iter()
+            .filter(|(_, _, kind)| *kind == EXTERNAL_GLOBAL)
+            .count() as u32;
+        let defined_globals = match binary.position(SECTION_GLOBAL) {
+            Some(pos) => Reader::new(&binary.sections[pos].payload).u32()?,
+            None => 0,
+        };
+
+        // the counters are appended to the global index space, so the existing indices are kept
+        let mut counters = Counters {
+            next: imported_globals + defined_globals,
+            exports: Vec::new(),
+        };
+        if let Some(pos) = binary.position(SECTION_CODE) {
+            let mut r = Reader::new(&binary.sections[pos].payload);
+            let count = r.u32()?;
+            let mut payload = Vec::new();
+            write_u32(&mut payload, count);
+            for i in 0..count {
+                let len = r.u32()? as usize;
+                let body = self.instrument(r.bytes(len)?, imported_funcs + i, &mut counters)?;
+                write_u32(&mut payload, body.len() as u32);
+                payload.extend_from_slice(&body);
+            }
+            binary.sections[pos].payload = payload;
+        }
+        if counters.exports.is_empty() {
+            return Ok(bytes.to_vec());
+        }
+
+        // mutable i64 globals initialized to zero
+        let added = counters.exports.len() as u32;
+        let mut globals = Vec::new();
+        for _ in 0..added {
+            globals.extend_from_slice(&[0x7e, 0x01, 0x42, 0x00, 0x0b]);
+        }
+        match binary.position(SECTION_GLOBAL) {
+            Some(pos) => {
+                let section = &mut binary.sections[pos];
+                let mut r = Reader::new(&section.payload);
+                let count = r.u32()?;
+                let mut payload = Vec::new();
+                write_u32(&mut payload, count + added);
+                payload.extend_from_slice(r.rest());
+                payload.extend_from_slice(&globals);
+                section.payload = payload;
+            }
+            None => {
+                let mut payload = Vec::new();
+                write_u32(&mut payload, added);
+                payload.extend_from_slice(&globals);
+                let pos = binary.insert_position(SECTION_GLOBAL);
+                binary.sections.insert(
+                    pos,
+                    Section {
+                        id: SECTION_GLOBAL,
+                        payload,
+                    },
+                );
+            }
+        }
+        let pos = binary.insert_position(SECTION_EXPORT);
+        binary.add_exports(&counters.exports, pos)?;
+
+        Ok(binary.encode())
+    }
+}
+
+#[derive(Debug)]
+struct Counters {
+    next: u32,
+    exports: Vec<(String, u8, u32)>,
+}
+impl Counters {
+    /// Allocates a counter exported under the given name, and writes the instructions incrementing it.
+    fn add(&mut self, out: &mut Vec<u8>, name: String) {
+        let idx = self.next;
+        self.next += 1;
+        self.exports.push((name, EXTERNAL_GLOBAL, idx));
+
+        // global.get idx; i64.const 1; i64.add; global.set idx
+        out.push(0x23);
+        write_u32(out, idx);
+        out.extend_from_slice(&[0x42, 0x01, 0x7c, 0x24]);
+        write_u32(out, idx);
+    }
+}
+
+/// The coverage of a function.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuncCoverage {
+    /// The index of the function.
+    pub index: u32,
+    /// The [symbol](crate::NameSection::symbol) of the function.
+    pub symbol: String,
+    /// The number of the calls of the function.
+    pub hits: u64,
+    /// The number of the executions of each block of the function, in the order the blocks appear in the code. It is empty unless the blocks are counted.
+    pub blocks: Vec<u64>,
+}
+
+/// The coverage of the functions of a module instance, collected from the counters added by the [Coverage] transform.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    functions: Vec<FuncCoverage>,
+}
+impl CoverageReport {
+    /// Reads the counters of a module instance, which count the executions since the instantiation.
+    ///
+    /// # Argument
+    ///
+    /// * `instance` - The module instance created from a module loaded through the [Coverage] transform.
+    ///
+    /// # Error
+    ///
+    /// If the module instance has no counters, then an error is returned.
+    pub fn collect(instance: &Instance) -> WasmEdgeResult<Self> {
+        let mut functions: BTreeMap<u32, FuncCoverage> = BTreeMap::new();
+        for name in instance.global_names().unwrap_or_default() {
+            let counter = match name.strip_prefix(COUNTER_PREFIX) {
+                Some(counter) => counter,
+                None => continue,
+            };
+            let hits = match instance.global(&name)?.get_value() {
+                Val::I64(hits) => hits as u64,
+                _ => return Err(malformed("coverage counter of an unexpected type")),
+            };
+            let mut parts = counter.split(':').map(str::parse::<u32>);
+            let (index, block) = match (parts.next(), parts.next()) {
+                (Some(Ok(index)), None) => (index, None),
+                (Some(Ok(index)), Some(Ok(block))) => (index, Some(block as usize)),
+                _ => return Err(malformed("coverage counter of an unexpected name")),
+            };
+
+            let func = functions.entry(index).or_insert_with(|| FuncCoverage {
+                index,
+                symbol: match instance.module_info.as_ref() {
+                    Some(info) => info.names.symbol(index),
+                    None => format!("func[{index}]"),
+                },
+                ..Default::default()
+            });
+            match block {
+                Some(block) => {
+                    if func.blocks.len() <= block {
+                        func.blocks.resize(block + 1, 0);
+                    }
+                    func.blocks[block] = hits;
+                }
+                None => func.hits = hits,
+            }
+        }
+
+        if functions.is_empty() {
+            return Err(Box::new(WasmEdgeError::Operation(
+                "the module instance is not instrumented for coverage".to_string(),
+            )));
+        }
+        Ok(Self {
+            functions: functions.into_values().collect(),
+        })
+    }
+
+    /// Returns the coverage of the functions, in the order of their indices.
+    pub fn functions(&self) -> &[FuncCoverage] {
+        &self.functions
+    }
+
+    /// Returns the number of the functions called at least once.
+    pub fn covered(&self) -> usize {
+        self.functions.iter().filter(|func| func.hits > 0).count()
+    }
+
+    /// Adds the counts of another report of the same module, such as one collected from another module instance.
+    ///
+    /// # Argument
+    ///
+    /// * `other` - The report to merge.
+    pub fn merge(&mut self, other: &CoverageReport) {
+        for func in other.functions.iter() {
+            match self.functions.iter_mut().find(|f| f.index == func.index) {
+                Some(f) => {
+                    f.hits += func.hits;
+                    if f.blocks.len() < func.blocks.len() {
+                        f.blocks.resize(func.blocks.len(), 0);
+                    }
+                    for (hits, other) in f.blocks.iter_mut().zip(func.blocks.iter()) {
+                        *hits += other;
+                    }
+                }
+                None => self.functions.push(func.clone()),
+            }
+        }
+        self.functions.sort_by_key(|func| func.index);
+    }
+
+    /// Exports the report as text, with a line per function and a line per block:
+    ///
+    /// ```text
+    /// func <index> <hits> <symbol>
+    /// block <index> <block> <hits>
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for func in self.functions.iter() {
+            text.push_str(&format!(
+                "func {} {} {}\n",
+                func.index, func.hits, func.symbol
+            ));
+            for (block, hits) in func.blocks.iter().enumerate() {
+                text.push_str(&format!("block {} {} {}\n", func.index, block, hits));
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        params, wat2wasm, Executor, Global, GlobalType, ImportObjectBuilder, Module, Mutability,
+        NeverType, Store, ValType, WasmVal,
+    };
+
+    #[test]
+    fn test_coverage() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "env" "g" (global i32))
+              (global $limit i32 (i32.const 10))
+              (func $abs (export "abs") (param i32) (result i32)
+                local.get 0
+                i32.const 0
+                i32.lt_s
+                (if (result i32)
+                  (then
+                    i32.const 0
+                    local.get 0
+                    i32.sub)
+                  (else
+                    local.get 0)))
+              (func $unused (export "unused") (result i32)
+                global.get $limit)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let global = Global::new(
+            GlobalType::new(ValType::I32, Mutability::Const),
+            Val::I32(0),
+        );
+        let import = ImportObjectBuilder::new()
+            .with_global("g", global.unwrap())
+            .build::<NeverType>("env", None)
+            .unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+
+        let result = Module::from_bytes_with(None, &wasm_bytes, &[&Coverage::new().with_blocks()]);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let abs = instance.func("abs").unwrap();
+        for x in [-3, 4, 5] {
+            let result = executor.run_func(&abs, params!(x));
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap()[0].to_i32(), i32::abs(x));
+        }
+        // the existing globals keep their indices
+        let result = executor.run_func(&instance.func("unused").unwrap(), params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 10);
+
+        let result = CoverageReport::collect(&instance);
+        assert!(result.is_ok());
+        let mut report = result.unwrap();
+        assert_eq!(report.functions().len(), 2);
+        assert_eq!(report.covered(), 2);
+        let abs = &report.functions()[0];
+        assert_eq!(abs.index, 0);
+        assert_eq!(abs.symbol, "abs");
+        assert_eq!(abs.hits, 3);
+        // the then and else blocks
+        assert_eq!(abs.blocks, vec![1, 2]);
+        assert_eq!(
+            report.to_text(),
+            "func 0 3 abs\nblock 0 0 1\nblock 0 1 2\nfunc 1 1 unused\n"
+        );
+
+        let other = report.clone();
+        report.merge(&other);
+        assert_eq!(report.functions()[0].hits, 6);
+        assert_eq!(report.functions()[0].blocks, vec![2, 4]);
+
+        // a module instance which is not instrumented has no coverage
+        let module = Module::from_bytes(None, &wasm_bytes).unwrap();
+        let instance = store
+            .register_active_module(&mut executor, &module)
+            .unwrap();
+        assert!(CoverageReport::collect(&instance).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Defines the analysis of the sources of non-determinism in a module, returned by [Module::check_determinism](crate::Module::check_determinism).
+//!
+//! A module running on several hosts, such as a smart contract replicated on the nodes of a chain, must compute the same results everywhere. The analysis reports the instructions and the imports which may break it, so operators can vet a module before accepting it:
+//!
+//! ```ignore
+//! let module = Module::from_bytes(None, wasm_bytes)?;
+//! let report = module.check_determinism()?;
+//! if !report.is_deterministic() {
+//!     for issue in report.issues() {
+//!         eprintln!("{issue}");
+//!     }
+//!     return Err("the module is rejected".into());
+//! }
+//! ```
+
+use crate::{
+    binary::{Binary, Reader, EXTERNAL_FUNC},
+    wasi::WASI_MODULE_NAMES,
+    NameSection, WasmEdgeResult,
+};
+use std::collections::BTreeMap;
+
+/// The WASI functions which read a clock. `poll_oneoff` waits on clock subscriptions.
+const WASI_CLOCK_FUNCS: [&str; 3] = ["clock_time_get", "clock_res_get", "poll_oneoff"];
+
+/// The WASI functions which read a source of randomness.
+const WASI_RANDOM_FUNCS: [&str; 1] = ["random_get"];
+
+/// The kinds of the sources of non-determinism.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NonDeterminism {
+    /// A floating-point instruction which may produce a NaN, whose payload and sign may differ between platforms.
+    NanPayload,
+    /// A reinterpretation of the bits of a float as an integer, such as `i32.reinterpret_f32`, which exposes the NaN payloads to the integer code. The reinterpretations of integers as floats are deterministic.
+    Reinterpret,
+    /// A relaxed SIMD instruction, whose results are implementation-defined.
+    RelaxedSimd,
+    /// An import of a WASI clock, whose readings differ between runs unless the host provides a virtual clock under the same name, for example with [ImportAliases](crate::ImportAliases).
+    Clock,
+    /// An import of the WASI source of randomness.
+    Random,
+}
+impl std::fmt::Display for NonDeterminism {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonDeterminism::NanPayload => write!(f, "NaN payload"),
+            NonDeterminism::Reinterpret => write!(f, "float reinterpretation"),
+            NonDeterminism::RelaxedSimd => write!(f, "relaxed SIMD"),
+            NonDeterminism::Clock => write!(f, "clock"),
+            NonDeterminism::Random => write!(f, "randomness"),
+        }
+    }
+}
+
+/// Describes a source of non-determinism found in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeterminismIssue {
+    /// The kind of the source.
+    pub kind: NonDeterminism,
+    /// The index of the imported function, or of the function containing the instructions.
+    pub func_index: u32,
+    /// The symbol of the function; see [NameSection::symbol](crate::NameSection::symbol).
+    pub symbol: String,
+    /// The import as `module::name`, or the name of the instruction.
+    pub item: String,
+    /// The number of the occurrences of the instruction in the function, or 1 for an import.
+    pub count: u32,
+}
+impl std::fmt::Display for DeterminismIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} in {}: {} x {}",
+            self.kind, self.symbol, self.count, self.item
+        )
+    }
+}
+
+/// The sources of non-determinism found in a module, ordered by function index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeterminismReport {
+    issues: Vec<DeterminismIssue>,
+}
+impl DeterminismReport {
+    /// Checks if no source of non-determinism is found.
+    pub fn is_deterministic(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the sources of non-determinism.
+    pub fn issues(&self) -> &[DeterminismIssue] {
+        &self.issues
+    }
+
+    /// Returns the sources of non-determinism of the given kind.
+    ///
+    /// # Argument
+    ///
+    /// * `kind` - The kind of the sources to return.
+    pub fn issues_of(&self, kind: NonDeterminism) -> impl Iterator<Item = &DeterminismIssue> {
+        self.issues.iter().filter(move |issue| issue.kind == kind)
+    }
+}
+
+/// Finds the sources of non-determinism in the given module binary.
+pub(crate) fn analyze(binary: &Binary, names: &NameSection) -> WasmEdgeResult<DeterminismReport> {
+    let mut issues = Vec::new();
+
+    let mut func_index = 0;
+    for (module, name, kind) in binary.imports()? {
+        if kind != EXTERNAL_FUNC {
+            continue;
+        }
+        if WASI_MODULE_NAMES.contains(&module.as_str()) {
+            let kind = match name.as_str() {
+                name if WASI_CLOCK_FUNCS.contains(&name) => Some(NonDeterminism::Clock),
+                name if WASI_RANDOM_FUNCS.contains(&name) => Some(NonDeterminism::Random),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                issues.push(DeterminismIssue {
+                    kind,
+                    func_index,
+                    symbol: names.symbol(func_index),
+                    item: format!("{module}::{name}"),
+                    count: 1,
+                });
+            }
+        }
+        func_index += 1;
+    }
+
+    for body in binary.func_bodies()? {
+        // the occurrences of each flagged instruction, in the order of the opcodes
+        let mut found: BTreeMap<(u8, u32), (NonDeterminism, u32)> = BTreeMap::new();
+        let mut r = Reader::new(body);
+        while !r.is_empty() {
+            let instr = r.instr()?;
+            if let Some(kind) = classify(instr) {
+                found.entry(instr).or_insert((kind, 0)).1 += 1;
+            }
+        }
+        for (instr, (kind, count)) in found {
+            issues.push(DeterminismIssue {
+                kind,
+                func_index,
+                symbol: names.symbol(func_index),
+                item: mnemonic(instr).to_string(),
+                count,
+            });
+        }
+        func_index += 1;
+    }
+
+    Ok(DeterminismReport { issues })
+}
+
+/// Returns the kind of non-determinism the given instruction introduces, if any.
+fn classify(instr: (u8, u32)) -> Option<NonDeterminism> {
+    match instr {
+        (0x8d..=0x97 | 0x9b..=0xa5 | 0xb6 | 0xbb, _) => Some(NonDeterminism::NanPayload),
+        (0xbc | 0xbd, _) => Some(NonDeterminism::Reinterpret),
+        (
+            0xfd,
+            0x5e | 0x5f | 0x67..=0x6a | 0x74 | 0x75 | 0x7a | 0x94 | 0xe3..=0xe9 | 0xef..=0xf5,
+        ) => Some(NonDeterminism::NanPayload),
+        (0xfd, 0x100..=0x113) => Some(NonDeterminism::RelaxedSimd),
+        _ => None,
+    }
+}
+
+/// Returns the name of an instruction classified by [classify].
+fn mnemonic(instr: (u8, u32)) -> &'static str {
+    match instr {
+        (0x8d, _) => "f32.ceil",
+        (0x8e, _) => "f32.floor",
+        (0x8f, _) => "f32.trunc",
+        (0x90, _) => "f32.nearest",
+        (0x91, _) => "f32.sqrt",
+        (0x92, _) => "f32.add",
+        (0x93, _) => "f32.sub",
+        (0x94, _) => "f32.mul",
+        (0x95, _) => "f32.div",
+        (0x96, _) => "f32.min",
+        (0x97, _) => "f32.max",
+        (0x9b, _) => "f64.ceil",
+        (0x9c, _) => "f64.floor",
+        (0x9d, _) => "f64.trunc",
+        (0x9e, _) => "f64.nearest",
+        (0x9f, _) => "f64.sqrt",
+        (0xa0, _) => "f64.add",
+        (0xa1, _) => "f64.sub",
+        (0xa2, _) => "f64.mul",
+        (0xa3, _) => "f64.div",
+        (0xa4, _) => "f64.min",
+        (0xa5, _) => "f64.max",
+        (0xb6, _) => "f32.demote_f64",
+        (0xbb, _) => "f64.promote_f32",
+        (0xbc, _) => "i32.reinterpret_f32",
+        (0xbd, _) => "i64.reinterpret_f64",
+        (0xfd, 0x5e) => "f32x4.demote_f64x2_zero",
+        (0xfd, 0x5f) => "f64x2.promote_low_f32x4",
+        (0xfd, 0x67) => "f32x4.ceil",
+        (0xfd, 0x68) => "f32x4.floor",
+        (0xfd, 0x69) => "f32x4.trunc",
+        (0xfd, 0x6a) => "f32x4.nearest",
+        (0xfd, 0x74) => "f64x2.ceil",
+        (0xfd, 0x75) => "f64x2.floor",
+        (0xfd, 0x7a) => "f64x2.trunc",
+        (0xfd, 0x94) => "f64x2.nearest",
+        (0xfd, 0xe3) => "f32x4.sqrt",
+        (0xfd, 0xe4) => "f32x4.add",
+        (0xfd, 0xe5) => "f32x4.sub",
+        (0xfd, 0xe6) => "f32x4.mul",
+        (0xfd, 0xe7) => "f32x4.div",
+        (0xfd, 0xe8) => "f32x4.min",
+        (0xfd, 0xe9) => "f32x4.max",
+        (0xfd, 0xef) => "f64x2.sqrt",
+        (0xfd, 0xf0) => "f64x2.add",
+        (0xfd, 0xf1) => "f64x2.sub",
+        (0xfd, 0xf2) => "f64x2.mul",
+        (0xfd, 0xf3) => "f64x2.div",
+        (0xfd, 0xf4) => "f64x2.min",
+        (0xfd, 0xf5) => "f64x2.max",
+        (0xfd, _) => "relaxed SIMD instruction",
+        _ => "instruction",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wat2wasm, Module};
+
+    #[test]
+    fn test_determinism_check() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+              (import "wasi_snapshot_preview1" "clock_time_get" (func (param i32 i64 i32) (result i32)))
+              (import "wasi_snapshot_preview1" "random_get" (func (param i32 i32) (result i32)))
+              (func $int (export "int") (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                i32.add)
+              (func $float (export "float") (param f32 f32) (result i32)
+                local.get 0
+                local.get 1
+                f32.add
+                local.get 1
+                f32.add
+                i32.reinterpret_f32)
+              (func $bits (export "bits") (param i32) (result f32)
+                block (result f32)
+                  local.get 0
+                  f32.reinterpret_i32
+                  f32.abs
+                  f32.neg
+                end)
+            )
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = module.check_determinism();
+        assert!(result.is_ok());
+        let report = result.unwrap();
+        assert!(!report.is_deterministic());
+
+        let found: Vec<(NonDeterminism, u32, &str, u32)> = report
+            .issues()
+            .iter()
+            .map(|issue| {
+                (
+                    issue.kind,
+                    issue.func_index,
+                    issue.item.as_str(),
+                    issue.count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    NonDeterminism::Clock,
+                    1,
+                    "wasi_snapshot_preview1::clock_time_get",
+                    1
+                ),
+                (
+                    NonDeterminism::Random,
+                    2,
+                    "wasi_snapshot_preview1::random_get",
+                    1
+                ),
+                (NonDeterminism::NanPayload, 4, "f32.add", 2),
+                (NonDeterminism::Reinterpret, 4, "i32.reinterpret_f32", 1),
+            ]
+        );
+        assert_eq!(report.issues_of(NonDeterminism::NanPayload).count(), 1);
+        assert_eq!(
+            report.issues()[2].to_string(),
+            "NaN payload in float: 2 x f32.add"
+        );
+
+        // the integer code is deterministic
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (memory 1)
+              (func (export "sum") (param i32 i32) (result i64)
+                (local i64)
+                block
+                  local.get 1
+                  br_table 0 0
+                end
+                local.get 0
+                i32.load offset=8
+                i64.extend_i32_u)
+            )
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let report = result.unwrap().check_determinism().unwrap();
+        assert!(report.is_deterministic());
+    }
+}
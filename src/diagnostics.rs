@@ -0,0 +1,214 @@
+//! Defines the registry of live runtime handles used to diagnose resource leaks.
+//!
+//! When the `leak_diagnostics` feature is enabled, every [Module](crate::Module), [Instance](crate::Instance), [Func](crate::Func), and [Memory](crate::Memory) handle is recorded in a process-wide registry when it is created or cloned, and removed from the registry when it is dropped. The registry can be queried at any time to find out which handles are still alive. In debug builds, the backtrace of the creation site is captured as well, if backtraces are enabled with the `RUST_BACKTRACE` environment variable.
+//!
+//! When the feature is disabled, the registry is compiled out and the handles carry no overhead.
+//!
+//! ```ignore
+//! use bitbang::diagnostics::{self, HandleKind};
+//!
+//! // ... run the server for a while ...
+//!
+//! println!("live instances: {}", diagnostics::live_count(HandleKind::Instance));
+//! eprintln!("{}", diagnostics::dump_live_handles());
+//! ```
+
+#[cfg(feature = "leak_diagnostics")]
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+/// The kinds of runtime handles tracked by the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    /// A [Module](crate::Module) handle.
+    Module,
+    /// An [Instance](crate::Instance) handle.
+    Instance,
+    /// A [Func](crate::Func) handle.
+    Func,
+    /// A [Memory](crate::Memory) handle.
+    Memory,
+}
+impl std::fmt::Display for HandleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandleKind::Module => write!(f, "Module"),
+            HandleKind::Instance => write!(f, "Instance"),
+            HandleKind::Func => write!(f, "Func"),
+            HandleKind::Memory => write!(f, "Memory"),
+        }
+    }
+}
+
+/// Describes a handle which is still alive.
+#[cfg(feature = "leak_diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "leak_diagnostics")))]
+#[derive(Debug, Clone)]
+pub struct LiveHandle {
+    /// The unique id of the handle.
+    pub id: u64,
+    /// The kind of the handle.
+    pub kind: HandleKind,
+    /// The instant at which the handle was created.
+    pub created_at: Instant,
+    /// The backtrace of the creation site. It is only captured in debug builds.
+    pub backtrace: Option<Arc<std::backtrace::Backtrace>>,
+}
+
+#[cfg(feature = "leak_diagnostics")]
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[cfg(feature = "leak_diagnostics")]
+fn registry() -> &'static Mutex<HashMap<u64, LiveHandle>> {
+    static REGISTRY: std::sync::OnceLock<Mutex<HashMap<u64, LiveHandle>>> =
+        std::sync::OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns all handles which are still alive, ordered by their creation.
+#[cfg(feature = "leak_diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "leak_diagnostics")))]
+pub fn live_handles() -> Vec<LiveHandle> {
+    let mut handles = registry()
+        .lock()
+        .expect("[bitbang] the handle registry is poisoned")
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    handles.sort_by_key(|h| h.id);
+    handles
+}
+
+/// Returns the number of the live handles of the given kind.
+///
+/// # Argument
+///
+/// * `kind` - The kind of the handles to count.
+#[cfg(feature = "leak_diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "leak_diagnostics")))]
+pub fn live_count(kind: HandleKind) -> usize {
+    registry()
+        .lock()
+        .expect("[bitbang] the handle registry is poisoned")
+        .values()
+        .filter(|h| h.kind == kind)
+        .count()
+}
+
+/// Returns a human-readable dump of all handles which are still alive, including the creation backtraces if they are captured.
+#[cfg(feature = "leak_diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "leak_diagnostics")))]
+pub fn dump_live_handles() -> String {
+    use std::fmt::Write;
+
+    let handles = live_handles();
+    let mut out = String::new();
+    let _ = writeln!(out, "{} live handle(s)", handles.len());
+    for handle in handles {
+        let _ = writeln!(
+            out,
+            "#{} {} (alive for {:?})",
+            handle.id,
+            handle.kind,
+            handle.created_at.elapsed()
+        );
+        if let Some(backtrace) = handle.backtrace {
+            let _ = writeln!(out, "{backtrace}");
+        }
+    }
+    out
+}
+
+/// Registers a handle in the registry for as long as it is alive.
+#[derive(Debug)]
+pub(crate) struct HandleGuard {
+    #[cfg(feature = "leak_diagnostics")]
+    id: u64,
+    #[cfg(feature = "leak_diagnostics")]
+    kind: HandleKind,
+}
+impl HandleGuard {
+    /// Creates a new guard for a handle of the given kind.
+    #[cfg(feature = "leak_diagnostics")]
+    pub(crate) fn new(kind: HandleKind) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(debug_assertions)]
+        let backtrace = Some(Arc::new(std::backtrace::Backtrace::capture()));
+        #[cfg(not(debug_assertions))]
+        let backtrace = None;
+
+        registry()
+            .lock()
+            .expect("[bitbang] the handle registry is poisoned")
+            .insert(
+                id,
+                LiveHandle {
+                    id,
+                    kind,
+                    created_at: Instant::now(),
+                    backtrace,
+                },
+            );
+
+        Self { id, kind }
+    }
+
+    /// Creates a new guard for a handle of the given kind.
+    #[cfg(not(feature = "leak_diagnostics"))]
+    pub(crate) fn new(_kind: HandleKind) -> Self {
+        Self {}
+    }
+}
+impl Clone for HandleGuard {
+    #[cfg(feature = "leak_diagnostics")]
+    fn clone(&self) -> Self {
+        Self::new(self.kind)
+    }
+
+    #[cfg(not(feature = "leak_diagnostics"))]
+    fn clone(&self) -> Self {
+        Self {}
+    }
+}
+#[cfg(feature = "leak_diagnostics")]
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = registry().lock() {
+            registry.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "leak_diagnostics")]
+mod tests {
+    use super::*;
+    use crate::{Memory, MemoryType};
+
+    #[test]
+    fn test_diagnostics_live_handles() {
+        let before = live_count(HandleKind::Memory);
+
+        // create a memory and a clone of it
+        let result = MemoryType::new(1, None, false);
+        assert!(result.is_ok());
+        let result = Memory::new(result.unwrap());
+        assert!(result.is_ok());
+        let memory = result.unwrap();
+        let memory_clone = memory.clone();
+        assert_eq!(live_count(HandleKind::Memory), before + 2);
+        assert!(dump_live_handles().contains("Memory"));
+
+        // drop the handles
+        drop(memory);
+        drop(memory_clone);
+        assert_eq!(live_count(HandleKind::Memory), before);
+    }
+}
@@ -1,12 +1,26 @@
 //! Defines Executor struct.
 
-use crate::{config::Config, Func, FuncRef, Statistics, WasmEdgeResult, WasmValue};
+use crate::{
+    call::{self, CallOptions},
+    config::Config,
+    error::{CoreCommonError, CoreError, FuncError, ReplayError, WasmEdgeError},
+    io::FromWasmValList,
+    middleware::{self, Layers, Middleware, Next},
+    replay::{self, ReplayBundle},
+    task, ExecutionReport, ExternalInstanceType, Func, FuncRef, Module, Statistics, Store,
+    WasmEdgeResult, WasmValue,
+};
 use bit_sys as sys;
+use std::{cell::Cell, sync::Arc, time::Instant};
 
 /// Defines an execution environment for both pure WASM and compiled WASM.
 #[derive(Debug, Clone)]
 pub struct Executor {
     pub(crate) inner: sys::Executor,
+    pub(crate) config: Option<Config>,
+    // the statistics the executor counts with, kept alive as long as the executor and used to enforce the instruction limit
+    pub(crate) stat: Option<Statistics>,
+    pub(crate) middleware: Layers,
 }
 impl Executor {
     /// Creates a new [executor](crate::Executor) to be associated with the given [config](crate::config::Config) and [statistics](crate::Statistics).
@@ -15,12 +29,20 @@ impl Executor {
     ///
     /// - `config` specifies the configuration of the new [executor](crate::Executor).
     ///
-    /// - `stat` specifies the [statistics](crate::Statistics) needed by the new [executor](crate::Executor).
+    /// - `stat` specifies the [statistics](crate::Statistics) needed by the new [executor](crate::Executor). If the config sets an [instruction limit](crate::config::RuntimeConfigOptions::max_instructions) and no statistics is given, the executor creates its own.
     ///
     /// # Error
     ///
     /// If fail to create a [executor](crate::Executor), then an error is returned.
     pub fn new(config: Option<&Config>, stat: Option<&mut Statistics>) -> WasmEdgeResult<Self> {
+        // the instruction limit is enforced on the statistics of the executor
+        let mut own_stat = match (&stat, config.and_then(|config| config.max_instructions)) {
+            (None, Some(_)) => Some(Statistics::new()?),
+            _ => None,
+        };
+        let stat = stat.or(own_stat.as_mut());
+        let kept_stat = stat.as_deref().cloned();
+
         let inner_executor = match config {
             Some(config) => match stat {
                 Some(stat) => sys::Executor::create(Some(&config.inner), Some(&mut stat.inner))?,
@@ -34,6 +56,9 @@ impl Executor {
 
         Ok(Self {
             inner: inner_executor,
+            config: config.cloned(),
+            stat: kept_stat,
+            middleware: Layers::default(),
         })
     }
 
@@ -53,11 +78,134 @@ impl Executor {
         func: &Func,
         params: impl IntoIterator<Item = WasmValue>,
     ) -> WasmEdgeResult<Vec<WasmValue>> {
-        self.inner.call_func(&func.inner, params)
+        Next::new(self, func, &self.middleware.0).run(params.into_iter().collect())
+    }
+
+    /// Runs a function instance with the given [per-call overrides](crate::call::CallOptions), and returns the results.
+    ///
+    /// The environment variables and the context map of the options are visible to the guest through the [import object](crate::call::import_object) serving them, and to the host functions through [call::value](crate::call::value) and [call::env_var](crate::call::env_var), for the duration of the call only. If the options set a deadline, the call is cancelled once it passes, as described in [Timeout](crate::middleware::Timeout).
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// * `options` - The overrides of the call.
+    ///
+    /// # Errors
+    ///
+    /// * If the deadline passes before the call returns, then [WasmEdgeError::ExecuteTimeout](crate::error::WasmEdgeError) is returned.
+    ///
+    /// * If fail to run the function, then an error is returned.
+    pub fn run_func_with(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+        options: &CallOptions,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        let _entered = call::enter(options);
+        match options.deadline() {
+            Some(deadline) => middleware::run_before(deadline, || self.run_func(func, params)),
+            None => self.run_func(func, params),
+        }
+    }
+
+    /// Runs a function instance and returns the results converted to the given Rust types.
+    ///
+    /// ```ignore
+    /// let (quotient, remainder) = executor.run_func_typed::<(i32, i32)>(&div_rem, params!(7, 2))?;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// * If fail to run the function, then an error is returned.
+    ///
+    /// * If the results do not match the given Rust types, then [WasmEdgeError::Func(FuncError::ResultTypeMismatch)](crate::error::FuncError) is returned.
+    pub fn run_func_typed<R>(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<R>
+    where
+        R: FromWasmValList,
+    {
+        let returns = self.run_func(func, params)?;
+        R::from_wasm_values(&returns).ok_or_else(|| {
+            Box::new(WasmEdgeError::Func(FuncError::ResultTypeMismatch {
+                expected: R::wasm_types().to_vec(),
+                actual: returns.iter().map(|v| v.ty()).collect(),
+            }))
+        })
+    }
+
+    /// Runs a function instance and returns the results along with an [execution report](crate::ExecutionReport) of the call.
+    ///
+    /// The call runs through the [middleware](crate::middleware) layers of the executor, which the report covers as well. The function itself runs on a call-scoped executor with the same [config](crate::config::Config) and its own [statistics](crate::Statistics), which enforces the [instruction limit](crate::config::RuntimeConfigOptions::max_instructions) of the config if any, so the report only covers this call even if other calls run concurrently, and the call is not counted in the statistics this executor is created with. The host function calls, the time spent in them, and the memory growth are tallied on the current thread, so the report tells the time spent executing wasm from the time spent inside host functions. The exported memories of the module instance exporting the function are observed from the start of the call, so their growth is reported even if the function calls no host function.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function, then an error is returned.
+    pub fn run_func_with_report(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<(Vec<WasmValue>, ExecutionReport)> {
+        let stat = Cell::new(None);
+        let call = |params: Vec<WasmValue>| {
+            let (returns, call_stat) =
+                self.run_scoped(|executor| executor.call_func(&func.inner, params))?;
+            stat.set(Some(call_stat));
+            Ok(returns)
+        };
+
+        sys::tally::start();
+        if let Some(instance) = func.instance.as_ref().and_then(sys::WeakInstance::upgrade) {
+            for name in instance.mem_names().unwrap_or_default() {
+                if let Ok(memory) = instance.get_memory(name) {
+                    sys::tally::observe(&memory);
+                }
+            }
+        }
+        let start = Instant::now();
+        let result = Next::with_call(self, func, &self.middleware.0, &call)
+            .run(params.into_iter().collect());
+        let duration = start.elapsed();
+        let tally = sys::tally::finish();
+
+        let returns = result?;
+        // a layer may answer the call without running the function
+        let stat = stat.take();
+        Ok((
+            returns,
+            ExecutionReport {
+                duration,
+                instructions: stat.as_ref().map_or(0, Statistics::count),
+                fuel_used: stat.as_ref().map_or(0, Statistics::cost),
+                memory_grown: tally.memory_grown,
+                host_calls: tally.host_calls,
+                host_time: tally.host_time,
+                guest_time: duration.saturating_sub(tally.host_time),
+            },
+        ))
     }
 
     /// Runs a host function reference instance and returns the results.
     ///
+    /// The call runs through the [middleware](crate::middleware) layers of the executor, which are given an anonymous [function](crate::Func).
+    ///
     /// # Arguments
     ///
     /// * `func_ref` - The function reference instance to run.
@@ -72,7 +220,312 @@ impl Executor {
         func_ref: &FuncRef,
         params: impl IntoIterator<Item = WasmValue>,
     ) -> WasmEdgeResult<Vec<WasmValue>> {
-        self.inner.call_func_ref(&func_ref.inner, params)
+        self.run_func(&func_ref.as_func(), params)
+    }
+
+    /// Asynchronously runs a function instance and returns the results.
+    ///
+    /// The function runs on a dedicated thread, so it does not block the thread polling the returned future, and the host functions it calls may block, such as the ones which wait on I/O. The future does not depend on any specific async runtime.
+    ///
+    /// If the future is dropped before completion, for example when it is wrapped in a timeout which elapses, the call is cancelled: the host function in progress is notified through the hooks it registered with [CallingFrame::on_cancel](crate::CallingFrame::on_cancel), and the next host function call fails with the `Interrupted` error, which aborts the call. A wasm function which does not call any host function runs to the end.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function, then an error is returned.
+    pub async fn run_func_async(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        let executor = self.clone();
+        let func = func.clone();
+        let params: Vec<WasmValue> = params.into_iter().collect();
+
+        task::spawn_blocking(move || executor.run_func(&func, params)).await
+    }
+
+    /// Asynchronously runs a function instance with the given [per-call overrides](crate::call::CallOptions), and returns the results. See [Executor::run_func_with](crate::Executor::run_func_with) for the overrides, and [Executor::run_func_async](crate::Executor::run_func_async) for how the function runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// * `options` - The overrides of the call.
+    ///
+    /// # Errors
+    ///
+    /// * If the deadline passes before the call returns, then [WasmEdgeError::ExecuteTimeout](crate::error::WasmEdgeError) is returned.
+    ///
+    /// * If fail to run the function, then an error is returned.
+    pub async fn run_func_async_with(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+        options: CallOptions,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        let executor = self.clone();
+        let func = func.clone();
+        let params: Vec<WasmValue> = params.into_iter().collect();
+
+        task::spawn_blocking(move || executor.run_func_with(&func, params, &options)).await
+    }
+
+    /// Asynchronously runs a function reference instance and returns the results. See [Executor::run_func_async](crate::Executor::run_func_async) for how the function runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `func_ref` - The function reference instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function reference instance, then an error is returned.
+    pub async fn run_func_ref_async(
+        &self,
+        func_ref: &FuncRef,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        let executor = self.clone();
+        let func_ref = func_ref.clone();
+        let params: Vec<WasmValue> = params.into_iter().collect();
+
+        task::spawn_blocking(move || executor.run_func_ref(&func_ref, params)).await
+    }
+
+    /// Runs a wasm function instance and records the execution into a [replay bundle](crate::replay::ReplayBundle).
+    ///
+    /// The results of the host function calls made by the function are recorded, and [random_u64](crate::replay::random_u64) is seeded with the given seed while the function runs. The outcome of the function, either the returns or the error message, is stored in the returned bundle. The call runs through the [middleware](crate::middleware) layers of the executor, as the replayed call does.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The binary of the [module](crate::Module) from which the function is instantiated.
+    ///
+    /// * `config` - The [config](crate::config::Config) the module is loaded with.
+    ///
+    /// * `func` - The function instance to run. It must be obtained by name from a [module instance](crate::Instance) of the given `module`.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// * `seed` - The seed of the host-side randomness.
+    ///
+    /// # Error
+    ///
+    /// * If the function is anonymous, then [WasmEdgeError::Replay(ReplayError::AnonymousFunc)](crate::error::ReplayError) is returned.
+    ///
+    /// * If the given module does not export a function with the same name and type, then [WasmEdgeError::Replay(ReplayError::FuncNotInModule)](crate::error::ReplayError) is returned.
+    ///
+    /// * If fail to load the given module, then an error is returned.
+    pub fn run_func_recorded(
+        &self,
+        module: impl AsRef<[u8]>,
+        config: Option<&Config>,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+        seed: u64,
+    ) -> WasmEdgeResult<ReplayBundle> {
+        let func_name = match func.name() {
+            Some(name) => name.to_string(),
+            None => return Err(Box::new(WasmEdgeError::Replay(ReplayError::AnonymousFunc))),
+        };
+        let params = params.into_iter().collect::<Vec<_>>();
+
+        // the bundle is useless if the function does not come from the recorded module
+        let exported = Module::from_bytes(config, module.as_ref())?
+            .exports()
+            .iter()
+            .any(|export| {
+                export.name() == func_name
+                    && matches!(export.ty(), Ok(ExternalInstanceType::Func(ty)) if ty == *func.ty())
+            });
+        if !exported {
+            return Err(Box::new(WasmEdgeError::Replay(
+                ReplayError::FuncNotInModule(func_name),
+            )));
+        }
+
+        replay::seed(seed);
+        sys::replay::start_recording();
+        let result = self.run_func(func, params.clone());
+        let host_calls = sys::replay::finish_recording();
+
+        Ok(ReplayBundle::new(
+            module.as_ref().to_vec(),
+            config,
+            func_name,
+            params,
+            seed,
+            host_calls,
+            result.map_err(|e| e.to_string()),
+        ))
+    }
+
+    /// Replays the execution recorded in the given [replay bundle](crate::replay::ReplayBundle), and returns the results.
+    ///
+    /// The recorded module is instantiated in a new [store](crate::Store) with the recorded configuration. Its imported functions are stubbed: every host function call is served from the recorded results instead. The call runs through the [middleware](crate::middleware) layers of the executor.
+    ///
+    /// # Argument
+    ///
+    /// * `bundle` - The bundle to replay.
+    ///
+    /// # Error
+    ///
+    /// * If the module binary does not match the recorded hash, then [WasmEdgeError::Replay(ReplayError::ModuleHashMismatch)](crate::error::ReplayError) is returned.
+    ///
+    /// * If the replayed execution does not make exactly the recorded host function calls, then [WasmEdgeError::Replay(ReplayError::Diverged)](crate::error::ReplayError) is returned.
+    ///
+    /// * If fail to instantiate the module or to run the function, then an error is returned.
+    pub fn replay(&mut self, bundle: &ReplayBundle) -> WasmEdgeResult<Vec<WasmValue>> {
+        bundle.check_module_hash()?;
+
+        // instantiate the module with stubbed imports
+        let config = bundle.config()?;
+        let module = Module::from_bytes(config.as_ref(), bundle.module())?;
+        let mut store = Store::new()?;
+        for import in replay::stub_imports(&module)? {
+            store.register_import_module(self, &import)?;
+        }
+        let instance = store.register_active_module(self, &module)?;
+        let func = instance.func(bundle.func_name())?;
+
+        replay::seed(bundle.seed());
+        sys::replay::start_replay(bundle.host_calls.clone());
+        let result = self.run_func(&func, bundle.params().to_vec());
+        if !sys::replay::finish_replay() {
+            return Err(Box::new(WasmEdgeError::Replay(ReplayError::Diverged)));
+        }
+
+        result
+    }
+
+    /// Runs a function instance after the middleware layers.
+    pub(crate) fn call_func(
+        &self,
+        func: &Func,
+        params: Vec<WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        match (self.max_instructions(), &self.stat) {
+            (Some(limit), Some(stat)) => {
+                // the limit counts from the cost of the statistics at the start of the call, and
+                // the cost limit set by the caller is restored after the call
+                let mut stat = stat.clone();
+                let caller_limit = stat.cost_limit();
+                let call_limit = stat.cost().saturating_add(limit);
+                stat.set_cost_limit(call_limit.min(caller_limit));
+                let result = self.inner.call_func(&func.inner, params);
+                stat.set_cost_limit(caller_limit);
+
+                match call_limit < caller_limit {
+                    true => result.map_err(|err| self.limit_exceeded(err)),
+                    // the cost limit of the caller is reached first, so its error is kept
+                    false => result,
+                }
+            }
+            _ => self.inner.call_func(&func.inner, params),
+        }
+    }
+
+    /// Returns the maximum number of the instructions a single call can execute, if it is set in the config.
+    fn max_instructions(&self) -> Option<u64> {
+        self.config
+            .as_ref()
+            .and_then(|config| config.max_instructions)
+    }
+
+    /// Runs a call on a call-scoped executor with the same config and its own statistics, which enforces the instruction limit, and returns the result with the statistics of the call.
+    ///
+    /// It is used by the reports only, as it creates the statistics and the executor on every call.
+    fn run_scoped<T>(
+        &self,
+        call: impl FnOnce(&sys::Executor) -> WasmEdgeResult<T>,
+    ) -> WasmEdgeResult<(T, Statistics)> {
+        let mut stat = Statistics::new()?;
+        if let Some(limit) = self.max_instructions() {
+            stat.set_cost_limit(limit);
+        }
+        let executor = Executor::new(self.config.as_ref(), Some(&mut stat))?;
+
+        call(&executor.inner)
+            .map(|returns| (returns, stat))
+            .map_err(|err| self.limit_exceeded(err))
+    }
+
+    /// Converts the cost limit error of a call under the instruction limit into [FuncError::InstructionLimitExceeded](crate::error::FuncError).
+    fn limit_exceeded(&self, err: Box<WasmEdgeError>) -> Box<WasmEdgeError> {
+        match (*err, self.max_instructions()) {
+            (
+                WasmEdgeError::Core(CoreError::Common(CoreCommonError::CostLimitExceeded)),
+                Some(limit),
+            ) => Box::new(WasmEdgeError::Func(FuncError::InstructionLimitExceeded(
+                limit,
+            ))),
+            (err, _) => Box::new(err),
+        }
+    }
+}
+
+/// Constructs an [Executor] with middleware layers.
+///
+/// The [middleware](crate::middleware) layers are wrapped around the function calls in the order they are added, the first one being the outermost.
+#[derive(Debug, Default)]
+pub struct ExecutorBuilder {
+    config: Option<Config>,
+    stat: Option<Statistics>,
+    middleware: Layers,
+}
+impl ExecutorBuilder {
+    /// Creates a new [ExecutorBuilder].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [config](crate::config::Config) for the [Executor] to build.
+    ///
+    /// # Argument
+    ///
+    /// * `config` - The configuration of the [Executor].
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Sets the [statistics](crate::Statistics) for the [Executor] to build.
+    ///
+    /// # Argument
+    ///
+    /// * `stat` - The [statistics](crate::Statistics) of the [Executor]. A clone of it observes the same data.
+    pub fn with_statistics(mut self, stat: Statistics) -> Self {
+        self.stat = Some(stat);
+        self
+    }
+
+    /// Adds a [middleware](crate::middleware::Middleware) layer inside the layers added before.
+    ///
+    /// # Argument
+    ///
+    /// * `middleware` - The middleware layer to add.
+    pub fn with_middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.0.push(Arc::new(middleware));
+        self
+    }
+
+    /// Creates a new [Executor].
+    ///
+    /// # Error
+    ///
+    /// If fail to create the [Executor], then an error is returned.
+    pub fn build(mut self) -> WasmEdgeResult<Executor> {
+        let mut executor = Executor::new(self.config.as_ref(), self.stat.as_mut())?;
+        executor.middleware = self.middleware;
+        Ok(executor)
     }
 }
 
@@ -80,11 +533,19 @@ impl Executor {
 mod tests {
     use super::*;
     use crate::{
-        config::{CommonConfigOptions, ConfigBuilder},
-        params, wat2wasm, Module, Statistics, Store, WasmVal,
+        config::{
+            CommonConfigOptions, ConfigBuilder, RuntimeConfigOptions, StatisticsConfigOptions,
+        },
+        error::{HostFuncError, Trap},
+        params,
+        task::block_on,
+        wat2wasm, CallingFrame, ImportObjectBuilder, Module, NeverType, Statistics, Store, ValType,
+        WasmVal,
+    };
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
     };
-    #[cfg(all(feature = "async", target_os = "linux"))]
-    use crate::{error::HostFuncError, CallingFrame};
 
     #[test]
     #[allow(clippy::assertions_on_result_states)]
@@ -289,68 +750,368 @@ mod tests {
         let returns = result.unwrap();
         assert_eq!(returns.len(), 1);
         assert_eq!(returns[0].to_i32(), 8);
+
+        // run the exported host function and extract the typed result
+        let result = executor.run_func_typed::<i32>(&fib, params!(5));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 8);
+
+        let result = executor.run_func_typed::<i64>(&fib, params!(5));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Func(FuncError::ResultTypeMismatch {
+                expected: vec![ValType::I64],
+                actual: vec![ValType::I32],
+            })
+        );
     }
 
-    #[cfg(all(feature = "async", target_os = "linux"))]
     #[tokio::test]
-    async fn test_executor_run_async_func() -> Result<(), Box<dyn std::error::Error>> {
-        fn async_hello(
-            _frame: CallingFrame,
-            _inputs: Vec<WasmValue>,
-            _data: *mut std::os::raw::c_void,
-        ) -> Box<(dyn std::future::Future<Output = Result<Vec<WasmValue>, HostFuncError>> + Send)>
-        {
-            Box::new(async move {
-                for _ in 0..10 {
-                    println!("[async hello] say hello");
-                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                }
+    async fn test_executor_run_func_async_cancel() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hooked = Arc::new(AtomicBool::new(false));
 
-                println!("[async hello] Done!");
+        // a host function waiting until the call is cancelled
+        let host_calls = Arc::clone(&calls);
+        let host_hooked = Arc::clone(&hooked);
+        let wait = move |frame: CallingFrame,
+                         _inputs: Vec<WasmValue>,
+                         _data: *mut std::os::raw::c_void|
+              -> Result<Vec<WasmValue>, HostFuncError> {
+            host_calls.fetch_add(1, Ordering::SeqCst);
+            let hooked = Arc::clone(&host_hooked);
+            frame.on_cancel(move || hooked.store(true, Ordering::SeqCst));
+            while !frame.is_cancelled() {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            Ok(vec![])
+        };
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), (), NeverType>("wait", wait, None)
+            .expect("failed to add host func")
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
 
-                Ok(vec![])
-            })
-        }
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "wait" (func $wait))
+                (func (export "run") (result i32)
+                    call $wait
+                    call $wait
+                    i32.const 1)
+                (func (export "answer") (result i32)
+                    i32.const 42))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
 
-        #[derive(Debug)]
-        struct Data<T, S> {
-            _x: i32,
-            _y: String,
-            _v: Vec<T>,
-            _s: Vec<S>,
-        }
-        let data: Data<i32, &str> = Data {
-            _x: 12,
-            _y: "hello".to_string(),
-            _v: vec![1, 2, 3],
-            _s: vec!["macos", "linux", "windows"],
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        // a call which is not cancelled
+        let answer = instance.func("answer").unwrap();
+        let result = executor.run_func_async(&answer, params!()).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 42);
+
+        // a call which times out
+        let run = instance.func("run").unwrap();
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            executor.run_func_async(&run, params!()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(hooked.load(Ordering::SeqCst));
+
+        // the second host call is not made
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_executor_run_func_with_report() {
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), (), NeverType>(
+                "ping",
+                |_frame: CallingFrame,
+                 _inputs: Vec<WasmValue>,
+                 _data: *mut std::os::raw::c_void|
+                 -> Result<Vec<WasmValue>, HostFuncError> {
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                    Ok(vec![])
+                },
+                None,
+            )
+            .expect("failed to add host func")
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "ping" (func $ping))
+                (memory (export "memory") 1)
+                (func (export "run") (result i32)
+                    call $ping
+                    i32.const 2
+                    memory.grow
+                    drop
+                    call $ping
+                    i32.const 7)
+                (func (export "grow")
+                    i32.const 3
+                    memory.grow
+                    drop))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // enable instruction counting and cost measuring
+        let result = ConfigBuilder::new(CommonConfigOptions::default())
+            .with_statistics_config(
+                StatisticsConfigOptions::new()
+                    .count_instructions(true)
+                    .measure_cost(true),
+            )
+            .build();
+        assert!(result.is_ok());
+        let config = result.unwrap();
+
+        let mut executor = Executor::new(Some(&config), None).unwrap();
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let run = instance.func("run").unwrap();
+
+        let result = executor.run_func_with_report(&run, params!());
+        assert!(result.is_ok());
+        let (returns, report) = result.unwrap();
+        assert_eq!(returns[0].to_i32(), 7);
+        assert_eq!(report.host_calls, 2);
+        assert_eq!(report.memory_grown, 2);
+        assert!(report.host_time >= std::time::Duration::from_millis(10));
+        assert_eq!(report.host_time + report.guest_time, report.duration);
+        assert!(report.instructions > 0);
+        assert!(report.fuel_used > 0);
+
+        // each call is reported separately
+        let result = executor.run_func_with_report(&run, params!());
+        assert!(result.is_ok());
+        let (_, second) = result.unwrap();
+        assert_eq!(second.host_calls, 2);
+        assert_eq!(second.instructions, report.instructions);
+
+        // the growth of a call without host function calls is reported
+        let result = executor.run_func_with_report(&instance.func("grow").unwrap(), params!());
+        assert!(result.is_ok());
+        let (_, grow) = result.unwrap();
+        assert_eq!(grow.host_calls, 0);
+        assert_eq!(grow.memory_grown, 3);
+    }
+
+    #[test]
+    fn test_executor_host_func_trap() {
+        // a host function aborting the guest when the input is negative
+        let check = |_frame: CallingFrame,
+                     inputs: Vec<WasmValue>,
+                     _data: *mut std::os::raw::c_void|
+         -> Result<Vec<WasmValue>, HostFuncError> {
+            let value = inputs[0].to_i32();
+            if value < 0 {
+                crate::bail!("negative input: {}", value);
+            }
+            Ok(vec![])
         };
+        let result = ImportObjectBuilder::new()
+            .with_func::<i32, (), NeverType>("check", check, None)
+            .expect("failed to add host func")
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
 
-        // create an async host function
-        let result =
-            Func::wrap_async_func::<(), (), Data<i32, &str>>(async_hello, Some(Box::new(data)));
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "check" (func $check (param i32)))
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $check
+                    local.get 0))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
         assert!(result.is_ok());
-        let func = result.unwrap();
+        let module = result.unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let run = result.unwrap().func("run").unwrap();
+
+        let result = executor.run_func(&run, params!(3));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 3);
+
+        // the trap carries the message to the caller
+        let result = executor.run_func(&run, params!(-1));
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(*err, WasmEdgeError::Trap(Trap::new("negative input: -1")));
+        assert_eq!(err.to_string(), "negative input: -1");
+
+        // the next call is not affected by the previous trap
+        let result = executor.run_func(&run, params!(5));
+        assert!(result.is_ok());
+
+        let trap = Trap::from(7);
+        assert_eq!(trap.code(), 7);
+    }
+
+    #[test]
+    fn test_executor_host_func_panic() {
+        // a panic of a host function traps the guest instead of unwinding into the C library
+        let result = Func::wrap::<i32, (), NeverType>(
+            |_frame: CallingFrame, inputs: Vec<WasmValue>, _data: *mut std::os::raw::c_void| {
+                if inputs[0].to_i32() < 0 {
+                    panic!("negative input");
+                }
+                Ok(vec![])
+            },
+            None,
+        );
+        assert!(result.is_ok());
+        let check = result.unwrap();
 
-        // create an executor
         let executor = Executor::new(None, None).unwrap();
+        let result = executor.run_func(&check, params!(-1));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Trap(Trap::new("host function panicked: negative input"))
+        );
 
-        // create an async state
-        let async_state = AsyncState::new();
+        let result = executor.run_func(&check, params!(1));
+        assert!(result.is_ok());
+    }
 
-        async fn tick() {
-            let mut i = 0;
-            loop {
-                println!("[tick] i={i}");
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                i += 1;
-            }
+    #[test]
+    fn test_executor_instruction_limit() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (func (export "spin")
+                    (loop $again
+                        br $again))
+                (func (export "answer") (result i32)
+                    i32.const 42))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = ConfigBuilder::new(CommonConfigOptions::default())
+            .with_runtime_config(RuntimeConfigOptions::default().max_instructions(10_000))
+            .build();
+        assert!(result.is_ok());
+        let config = result.unwrap();
+        assert_eq!(config.max_instructions(), Some(10_000));
+
+        let mut executor = Executor::new(Some(&config), None).unwrap();
+        let mut store = Store::new().unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        // an infinite loop traps
+        let spin = instance.func("spin").unwrap();
+        let result = executor.run_func(&spin, params!());
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Func(FuncError::InstructionLimitExceeded(10_000))
+        );
+
+        // the limit applies to each call separately
+        let answer = instance.func("answer").unwrap();
+        for _ in 0..3 {
+            let result = executor.run_func_typed::<i32>(&answer, params!());
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap(), 42);
         }
-        tokio::spawn(tick());
 
-        // call the async host function
-        let _ = executor.run_func_async(&async_state, &func, []).await?;
+        // the calls under the limit are counted in the statistics of the executor
+        let mut stat = Statistics::new().unwrap();
+        let mut executor = Executor::new(Some(&config), Some(&mut stat)).unwrap();
+        let mut store = Store::new().unwrap();
+        let instance = store
+            .register_active_module(&mut executor, &module)
+            .unwrap();
+
+        let result = executor.run_func(&instance.func("spin").unwrap(), params!());
+        assert!(result.is_err());
+        let cost = stat.cost();
+        assert!(cost >= 10_000);
+
+        let result = executor.run_func_typed::<i32>(&instance.func("answer").unwrap(), params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 42);
+        assert!(stat.cost() > cost);
+
+        // the cost limit of the caller is restored after the call, and its error is kept if it is reached first
+        let mut stat = Statistics::new().unwrap();
+        stat.set_cost_limit(100);
+        let mut executor = Executor::new(Some(&config), Some(&mut stat)).unwrap();
+        let mut store = Store::new().unwrap();
+        let instance = store
+            .register_active_module(&mut executor, &module)
+            .unwrap();
+
+        let result = executor.run_func(&instance.func("spin").unwrap(), params!());
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Core(CoreError::Common(CoreCommonError::CostLimitExceeded))
+        );
+        assert_eq!(stat.cost_limit(), 100);
+    }
+
+    #[test]
+    fn test_executor_run_async_func() {
+        // a host function run by reference from a thread which is not driven by any async runtime
+        let result = Func::wrap_fn(|a: i32, b: i32| a + b);
+        assert!(result.is_ok());
+        let add = result.unwrap();
+        let add_ref = add.as_ref();
 
-        Ok(())
+        let executor = Executor::new(None, None).unwrap();
+        let result = block_on(add_ref.run_async(&executor, params!(2, 3)));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 5);
+
+        let result = block_on(executor.run_func_async(&add, params!(4, 5)));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 9);
     }
 }
@@ -1,10 +1,14 @@
 //! Defines Func, SignatureBuilder, and Signature structs.
 
 use crate::{
-    error::HostFuncError, io::WasmValTypeList, CallingFrame, Executor, FuncType, ValType,
-    WasmEdgeResult, WasmValue,
+    diagnostics::{HandleGuard, HandleKind},
+    error::{HostFuncError, StoreError, WasmEdgeError},
+    io::{FromWasmVal, FromWasmValList, HostFuncReturn, IntoWasmValList, WasmValTypeList},
+    runtime::{self, AsyncRuntime, BoxFuture},
+    CallingFrame, Executor, FuncType, NeverType, Store, ValType, WasmEdgeResult, WasmValue,
 };
 use bit_sys as sys;
+use std::sync::Arc;
 
 /// Defines a host function instance.
 ///
@@ -16,6 +20,9 @@ pub struct Func {
     pub(crate) name: Option<String>,
     pub(crate) mod_name: Option<String>,
     pub(crate) ty: FuncType,
+    // the module instance exporting the function, whose memories are observed by Executor::run_func_with_report
+    pub(crate) instance: Option<sys::WeakInstance>,
+    pub(crate) _guard: HandleGuard,
 }
 impl Func {
     /// Creates a host function by wrapping a native function.
@@ -56,6 +63,8 @@ impl Func {
             name: None,
             mod_name: None,
             ty,
+            instance: None,
+            _guard: HandleGuard::new(HandleKind::Func),
         })
     }
 
@@ -93,6 +102,84 @@ impl Func {
             name: None,
             mod_name: None,
             ty,
+            instance: None,
+            _guard: HandleGuard::new(HandleKind::Func),
+        })
+    }
+
+    /// Creates a host function by wrapping a typed Rust closure.
+    ///
+    /// The [function type](crate::FuncType) is derived from the types of the arguments and the returns of the closure, which take up to 16 arguments. The closure returns either a Rust value or a tuple of Rust values, or a `Result` of them; an error traps the guest.
+    ///
+    /// ```ignore
+    /// let add = Func::wrap_fn(|a: i32, b: i32| a + b)?;
+    /// let div = Func::wrap_fn(|a: i64, b: i64| -> Result<i64, HostFuncError> {
+    ///     a.checked_div(b).ok_or(HostFuncError::User(1))
+    /// })?;
+    /// ```
+    ///
+    /// # Argument
+    ///
+    /// * `real_func` - The Rust closure to be wrapped.
+    ///
+    /// # Error
+    ///
+    /// * If fail to create a Func instance, then [WasmEdgeError::Func(FuncError::Create)](crate::error::FuncError) is returned.
+    pub fn wrap_fn<Params, Rets>(
+        real_func: impl IntoHostFunc<Params, Rets>,
+    ) -> WasmEdgeResult<Self> {
+        let (ty, boxed_func) = real_func.into_host_func();
+        let inner = sys::Function::create::<NeverType>(&ty.clone().into(), boxed_func, None, 0)?;
+        Ok(Self {
+            inner,
+            name: None,
+            mod_name: None,
+            ty,
+            instance: None,
+            _guard: HandleGuard::new(HandleKind::Func),
+        })
+    }
+
+    /// Creates an async host function, whose future is driven by the given [async runtime](crate::runtime::AsyncRuntime).
+    ///
+    /// The thread running the guest blocks on the future until it completes, so the function is usually called by a guest running with [Executor::run_func_async](crate::Executor::run_func_async). If the guest call is cancelled, the future is dropped at once, and the host call fails with the `Interrupted` error.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The async runtime which drives the future.
+    ///
+    /// * `real_func` - The native function returning the future.
+    ///
+    /// # Error
+    ///
+    /// * If fail to create a Func instance, then [WasmEdgeError::Func(FuncError::Create)](crate::error::FuncError) is returned.
+    pub fn wrap_async<Args, Rets>(
+        runtime: Arc<dyn AsyncRuntime>,
+        real_func: impl Fn(
+                CallingFrame,
+                Vec<WasmValue>,
+            ) -> BoxFuture<'static, Result<Vec<WasmValue>, HostFuncError>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> WasmEdgeResult<Self>
+    where
+        Args: WasmValTypeList,
+        Rets: WasmValTypeList,
+    {
+        let boxed_func = runtime::async_host_func(runtime, real_func);
+        let ty = FuncType::new(
+            Some(Args::wasm_types().to_vec()),
+            Some(Rets::wasm_types().to_vec()),
+        );
+        let inner = sys::Function::create::<NeverType>(&ty.clone().into(), boxed_func, None, 0)?;
+        Ok(Self {
+            inner,
+            name: None,
+            mod_name: None,
+            ty,
+            instance: None,
+            _guard: HandleGuard::new(HandleKind::Func),
         })
     }
 
@@ -148,6 +235,109 @@ impl Func {
     ) -> WasmEdgeResult<Vec<WasmValue>> {
         executor.run_func(self, args)
     }
+
+    /// Runs this function in the given context, and returns the result.
+    ///
+    /// The context is either an [Executor](crate::Executor), or a [Store](crate::Store), in which case the function is run with the executor associated with the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The [Executor](crate::Executor) or the [Store](crate::Store) to run the function with.
+    ///
+    /// * `args` - The arguments passed to the function.
+    ///
+    /// # Error
+    ///
+    /// If no executor is associated with the store, or fail to run the function, then an error is returned.
+    pub fn call(
+        &self,
+        context: &impl CallContext,
+        args: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        context.executor()?.run_func(self, args)
+    }
+
+    /// Asynchronously runs this function in the given context. See [Func::call](crate::Func::call) for the context, and [Executor::run_func_async](crate::Executor::run_func_async) for how the function runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The [Executor](crate::Executor) or the [Store](crate::Store) to run the function with.
+    ///
+    /// * `args` - The arguments passed to the function.
+    ///
+    /// # Error
+    ///
+    /// If no executor is associated with the store, or fail to run the function, then an error is returned.
+    pub async fn call_async(
+        &self,
+        context: &impl CallContext,
+        args: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        context.executor()?.run_func_async(self, args).await
+    }
+
+    /// Creates a [weak handle](crate::WeakFunc) to this function, which does not keep it alive.
+    ///
+    /// A host function is deleted once its last handle is dropped, while a function exported by a [module instance](crate::Instance) lives as long as the module instance.
+    pub fn downgrade(&self) -> WeakFunc {
+        WeakFunc {
+            inner: self.inner.downgrade(),
+            name: self.name.clone(),
+            mod_name: self.mod_name.clone(),
+            ty: self.ty.clone(),
+            instance: self.instance.clone(),
+        }
+    }
+}
+
+/// A weak handle to a [function](crate::Func), which does not keep the function alive. It is created with [Func::downgrade](crate::Func::downgrade).
+#[derive(Debug, Clone)]
+pub struct WeakFunc {
+    inner: sys::WeakFunction,
+    name: Option<String>,
+    mod_name: Option<String>,
+    ty: FuncType,
+    instance: Option<sys::WeakInstance>,
+}
+impl WeakFunc {
+    /// Returns the [function](crate::Func), if it is still alive.
+    pub fn upgrade(&self) -> Option<Func> {
+        Some(Func {
+            inner: self.inner.upgrade()?,
+            name: self.name.clone(),
+            mod_name: self.mod_name.clone(),
+            ty: self.ty.clone(),
+            instance: self.instance.clone(),
+            _guard: HandleGuard::new(HandleKind::Func),
+        })
+    }
+
+    /// Checks if the [function](crate::Func) is still alive.
+    pub fn is_alive(&self) -> bool {
+        self.inner.upgrade().is_some()
+    }
+}
+
+/// Defines the context a [function](crate::Func) is called in with [Func::call](crate::Func::call).
+pub trait CallContext {
+    /// Returns the [Executor](crate::Executor) to run the function with.
+    ///
+    /// # Error
+    ///
+    /// If no executor is available in the context, then an error is returned.
+    fn executor(&self) -> WasmEdgeResult<&Executor>;
+}
+impl CallContext for Executor {
+    fn executor(&self) -> WasmEdgeResult<&Executor> {
+        Ok(self)
+    }
+}
+impl CallContext for Store {
+    fn executor(&self) -> WasmEdgeResult<&Executor> {
+        self.executor
+            .as_ref()
+            .ok_or_else(|| Box::new(WasmEdgeError::Store(StoreError::NotFoundExecutor)))
+    }
 }
 
 /// Defines a type builder for creating a [FuncType](https://wasmedge.github.io/WasmEdge/wasmedge_types/struct.FuncType.html) instance.
@@ -213,6 +403,92 @@ impl FuncTypeBuilder {
     }
 }
 
+/// The error code returned when the arguments of a typed host function do not match its signature. It is the code of the `FuncTypeMismatch` execution error.
+const FUNC_TYPE_MISMATCH: u32 = 0x83;
+
+/// The boxed native function wrapped by a [host function](crate::Func).
+pub(crate) type BoxedHostFn = Box<
+    dyn Fn(
+            CallingFrame,
+            Vec<WasmValue>,
+            *mut std::os::raw::c_void,
+        ) -> Result<Vec<WasmValue>, HostFuncError>
+        + Send
+        + Sync,
+>;
+
+/// Defines the Rust closures which can be wrapped as typed host functions by [Func::wrap_fn](crate::Func::wrap_fn).
+///
+/// It is implemented for the closures which take up to 16 arguments of the types implementing [FromWasmVal](crate::FromWasmVal), and return a value implementing [HostFuncReturn](crate::HostFuncReturn).
+pub trait IntoHostFunc<Params, Rets>: Send + Sync + 'static {
+    /// Returns the derived function type and the native function wrapping the closure.
+    #[doc(hidden)]
+    fn into_host_func(self) -> (FuncType, BoxedHostFn);
+}
+
+macro_rules! impl_into_host_func {
+    ( $( $a:ident $v:ident ),* ) => {
+        #[allow(unused_parens)]
+        impl<F, R, $( $a ),*> IntoHostFunc<( $( $a, )* ), R> for F
+        where
+            F: Fn( $( $a ),* ) -> R + Send + Sync + 'static,
+            $( $a: FromWasmVal, )*
+            R: HostFuncReturn,
+        {
+            fn into_host_func(self) -> (FuncType, BoxedHostFn) {
+                let ty = FuncType::new(
+                    Some(<( $( $a ),* )>::wasm_types().to_vec()),
+                    Some(R::Rets::wasm_types().to_vec()),
+                );
+                let real_func = move |_frame: CallingFrame,
+                                      inputs: Vec<WasmValue>,
+                                      _data: *mut std::os::raw::c_void|
+                      -> Result<Vec<WasmValue>, HostFuncError> {
+                    let ( $( $v ),* ) = <( $( $a ),* )>::from_wasm_values(&inputs)
+                        .ok_or(HostFuncError::Runtime(FUNC_TYPE_MISMATCH))?;
+                    let rets = self( $( $v ),* ).into_host_result()?;
+                    Ok(rets.into_wasm_values())
+                };
+                (ty, Box::new(real_func))
+            }
+        }
+    };
+}
+
+impl_into_host_func!();
+impl_into_host_func!(A1 a1);
+impl_into_host_func!(A1 a1, A2 a2);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9);
+impl_into_host_func!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11
+);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11, A12 a12
+);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11, A12 a12,
+    A13 a13
+);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11, A12 a12,
+    A13 a13, A14 a14
+);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11, A12 a12,
+    A13 a13, A14 a14, A15 a15
+);
+impl_into_host_func!(
+    A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6, A7 a7, A8 a8, A9 a9, A10 a10, A11 a11, A12 a12,
+    A13 a13, A14 a14, A15 a15, A16 a16
+);
+
 /// Defines a reference to a [host function](crate::Func).
 ///
 /// The [table_and_funcref](https://github.com/WasmEdge/WasmEdge/tree/master/bindings/rust/wasmedge-sdk/examples/table_and_funcref.rs) example presents how to obtain and use [FuncRef].
@@ -246,7 +522,19 @@ impl FuncRef {
         executor.run_func_ref(self, args)
     }
 
-    /// Asynchronously runs this host function the reference refers to.
+    /// Returns the anonymous [function](crate::Func) the reference refers to, which is run through the [middleware](crate::middleware) layers.
+    pub(crate) fn as_func(&self) -> Func {
+        Func {
+            inner: self.inner.as_func(),
+            name: None,
+            mod_name: None,
+            ty: self.ty.clone(),
+            instance: None,
+            _guard: HandleGuard::new(HandleKind::Func),
+        }
+    }
+
+    /// Asynchronously runs this host function the reference refers to. See [Executor::run_func_async](crate::Executor::run_func_async) for how the function runs.
     ///
     /// # Arguments
     ///
@@ -257,15 +545,12 @@ impl FuncRef {
     /// # Error
     ///
     /// If fail to run the host function, then an error is returned.
-    #[cfg(feature = "async")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
-    pub async fn run_async<T>(
+    pub async fn run_async(
         &self,
-        async_state: &AsyncState,
         executor: &Executor,
-        args: impl IntoIterator<Item = WasmValue> + Send,
+        args: impl IntoIterator<Item = WasmValue>,
     ) -> WasmEdgeResult<Vec<WasmValue>> {
-        executor.run_func_ref_async(async_state, self, args).await
+        executor.run_func_ref_async(self, args).await
     }
 }
 
@@ -274,7 +559,7 @@ mod tests {
     use super::*;
     use crate::{
         config::{CommonConfigOptions, ConfigBuilder},
-        error::HostFuncError,
+        error::{HostFuncError, StoreError, WasmEdgeError},
         params, CallingFrame, Executor, ImportObjectBuilder, NeverType, Statistics, Store,
         VmBuilder, WasmVal, WasmValue,
     };
@@ -386,6 +671,25 @@ mod tests {
         let returns = result.unwrap();
         assert_eq!(returns.len(), 1);
         assert_eq!(returns[0].to_i32(), 5);
+
+        // call the host function with the executor associated with the store
+        let result = host_func.call(&store, params!(4, 5));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 9);
+
+        // call the host function with the executor directly
+        let result = host_func.call(&executor, params!(1, 1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 2);
+
+        // no executor is associated with an empty store
+        let store = Store::new().unwrap();
+        let result = host_func.call(&store, params!(1, 1));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Store(StoreError::NotFoundExecutor)
+        );
     }
 
     #[test]
@@ -405,6 +709,51 @@ mod tests {
         assert_eq!(returns[0].to_i32(), 5);
     }
 
+    #[test]
+    fn test_func_wrap_fn() {
+        // create an executor
+        let mut executor = Executor::new(None, None).unwrap();
+
+        // wrap a closure returning a single value
+        let result = Func::wrap_fn(|a: i32, b: f64| a as i64 + b as i64);
+        assert!(result.is_ok());
+        let func = result.unwrap();
+        assert_eq!(func.ty().args(), Some(&[ValType::I32, ValType::F64][..]));
+        assert_eq!(func.ty().returns(), Some(&[ValType::I64][..]));
+
+        let result = func.run(&mut executor, params!(2, 3.5));
+        assert!(result.is_ok());
+        let returns = result.unwrap();
+        assert_eq!(returns[0].to_i64(), 5);
+
+        // wrap a closure returning multiple values or an error
+        let result = Func::wrap_fn(|a: i32, b: i32| -> Result<(i32, i32), HostFuncError> {
+            match b {
+                0 => Err(HostFuncError::User(7)),
+                _ => Ok((a / b, a % b)),
+            }
+        });
+        assert!(result.is_ok());
+        let func = result.unwrap();
+
+        let result = func.run(&mut executor, params!(7, 2));
+        assert!(result.is_ok());
+        let returns = result.unwrap();
+        assert_eq!(returns[0].to_i32(), 3);
+        assert_eq!(returns[1].to_i32(), 1);
+
+        let result = func.run(&mut executor, params!(7, 0));
+        assert!(result.is_err());
+        assert_eq!(*result.unwrap_err(), WasmEdgeError::User(7));
+
+        // wrap a closure without arguments and returns
+        let result = Func::wrap_fn(|| {});
+        assert!(result.is_ok());
+        let func = result.unwrap();
+        assert_eq!(func.ty().args_len(), 0);
+        assert_eq!(func.ty().returns_len(), 0);
+    }
+
     fn real_add(
         _frame: CallingFrame,
         inputs: Vec<WasmValue>,
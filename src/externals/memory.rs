@@ -1,6 +1,18 @@
-use crate::{error::WasmEdgeError, WasmEdgeResult};
+use crate::{
+    diagnostics::{HandleGuard, HandleKind},
+    error::{MemError, WasmEdgeError},
+    Instance, MemoryStats, WasmEdgeResult,
+};
 use bit_sys as sys;
 use bit_types::MemoryType;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Condvar, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
 
 /// Defines a linear memory.
 #[derive(Debug, Clone)]
@@ -9,6 +21,7 @@ pub struct Memory {
     pub(crate) name: Option<String>,
     pub(crate) mod_name: Option<String>,
     pub(crate) ty: MemoryType,
+    pub(crate) _guard: HandleGuard,
 }
 impl Memory {
     /// Creates a new wasm memory instance with the given type.
@@ -27,6 +40,7 @@ impl Memory {
             name: None,
             mod_name: None,
             ty,
+            _guard: HandleGuard::new(HandleKind::Memory),
         })
     }
 
@@ -75,8 +89,11 @@ impl Memory {
     ///
     /// # Error
     ///
-    /// If fail to read the memory, then an error is returned.
+    /// * If the range is out of the bounds of this memory, then [WasmEdgeError::Mem(MemError::OutOfBounds)](crate::error::MemError) is returned with the faulting range and the memory size.
+    ///
+    /// * If fail to read the memory, then an error is returned.
     pub fn read(&self, offset: u32, len: u32) -> WasmEdgeResult<Vec<u8>> {
+        self.check_bounds(offset, len as u64)?;
         let data = self.inner.get_data(offset, len)?;
         Ok(data)
     }
@@ -109,8 +126,11 @@ impl Memory {
     ///
     /// # Error
     ///
-    /// If fail to write to the memory, then an error is returned.
+    /// * If the range is out of the bounds of this memory, then [WasmEdgeError::Mem(MemError::OutOfBounds)](crate::error::MemError) is returned with the faulting range and the memory size.
+    ///
+    /// * If fail to write to the memory, then an error is returned.
     pub fn write(&mut self, data: impl AsRef<[u8]>, offset: u32) -> WasmEdgeResult<()> {
+        self.check_bounds(offset, data.as_ref().len() as u64)?;
         self.inner.set_data(data, offset)?;
         Ok(())
     }
@@ -129,6 +149,24 @@ impl Memory {
         Ok(())
     }
 
+    /// Returns the usage statistics of this memory, including the peak size and the number of grow events.
+    ///
+    /// The growth of a memory is observed when it is grown by the host, when a host function is called from the module instance owning it, and when this method is called. Since a wasm memory never shrinks, several `memory.grow` instructions executed by the guest between two observations are counted as a single grow event.
+    pub fn stats(&self) -> MemoryStats {
+        self.inner.stat()
+    }
+
+    /// Subscribes to the growth of this memory. The callback is called with the old and the new size, in pages, each time the growth is observed.
+    ///
+    /// The growth caused by the guest is observed before the next host function called from the module instance owning the memory runs, so a host function can invalidate the raw pointers into the memory it caches before using them. See [stats](crate::Memory::stats) for when else the growth is observed. The callbacks are dropped with the memory instance.
+    ///
+    /// # Argument
+    ///
+    /// * `callback` - The callback, which is given the old and the new size of the memory in pages.
+    pub fn on_grow(&self, callback: impl Fn(u32, u32) + Send + Sync + 'static) {
+        self.inner.on_grow(callback)
+    }
+
     /// Returns the const data pointer to this memory.
     ///
     /// # Arguments
@@ -144,6 +182,7 @@ impl Memory {
     /// If fail to get the data pointer, then an error is returned.
     ///
     pub fn data_pointer(&self, offset: u32, len: u32) -> WasmEdgeResult<*const u8> {
+        self.check_bounds(offset, len as u64)?;
         self.inner.data_pointer(offset, len)
     }
 
@@ -160,8 +199,252 @@ impl Memory {
     /// If fail to get the data pointer, then an error is returned.
     ///
     pub fn data_pointer_mut(&mut self, offset: u32, len: u32) -> WasmEdgeResult<*mut u8> {
+        self.check_bounds(offset, len as u64)?;
         self.inner.data_pointer_mut(offset, len)
     }
+
+    /// Blocks the current thread until it is woken by [atomic_notify](crate::Memory::atomic_notify) or the timeout elapses, if the 32-bit integer at the given offset holds the expected value. It is the host counterpart of the `memory.atomic.wait32` instruction.
+    ///
+    /// Only the host threads take part in this synchronization: the engine does not expose the waiters of the `memory.atomic.wait32` and `memory.atomic.notify` instructions, so the thread is not woken by the guest threads notifying the same address.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset of the integer, which must be aligned to 4 bytes.
+    ///
+    /// * `expected` - The value the integer is expected to hold.
+    ///
+    /// * `timeout` - The maximum duration to wait. `None` means waiting without a timeout.
+    ///
+    /// # Error
+    ///
+    /// If the memory is not shared, or the offset is unaligned or out of bounds, then an error is returned.
+    pub fn atomic_wait(
+        &self,
+        offset: u32,
+        expected: i32,
+        timeout: Option<Duration>,
+    ) -> WasmEdgeResult<AtomicWaitResult> {
+        let cell = self.atomic_i32(offset)?;
+        let addr = cell as *const AtomicI32 as usize;
+        let waiter = Arc::new(Waiter::default());
+        {
+            let mut waiters = waiters()
+                .lock()
+                .expect("[bitbang] the atomic waiters are poisoned");
+            if cell.load(Ordering::SeqCst) != expected {
+                return Ok(AtomicWaitResult::NotEqual);
+            }
+            waiters.entry(addr).or_default().push(Arc::clone(&waiter));
+        }
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut woken = waiter
+            .woken
+            .lock()
+            .expect("[bitbang] the atomic waiter is poisoned");
+        let result = loop {
+            if *woken {
+                break AtomicWaitResult::Woken;
+            }
+            woken = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break AtomicWaitResult::TimedOut;
+                    }
+                    waiter
+                        .condvar
+                        .wait_timeout(woken, deadline - now)
+                        .expect("[bitbang] the atomic waiter is poisoned")
+                        .0
+                }
+                None => waiter
+                    .condvar
+                    .wait(woken)
+                    .expect("[bitbang] the atomic waiter is poisoned"),
+            };
+        };
+        drop(woken);
+
+        let mut waiters = waiters()
+            .lock()
+            .expect("[bitbang] the atomic waiters are poisoned");
+        if let Some(queue) = waiters.get_mut(&addr) {
+            queue.retain(|other| !Arc::ptr_eq(other, &waiter));
+            if queue.is_empty() {
+                waiters.remove(&addr);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Wakes up to `count` host threads blocked in [atomic_wait](crate::Memory::atomic_wait) on the given offset, and returns the number of the woken threads. It is the host counterpart of the `memory.atomic.notify` instruction.
+    ///
+    /// Only the host threads are woken: the guest threads blocked in the `memory.atomic.wait32` or `memory.atomic.wait64` instructions are waiting inside the engine, which does not expose them. To hand data to a guest worker, store it in the memory, and let the guest wait with a timeout and check the memory again.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset of the integer, which must be aligned to 4 bytes.
+    ///
+    /// * `count` - The maximum number of the threads to wake.
+    ///
+    /// # Error
+    ///
+    /// If the memory is not shared, or the offset is unaligned or out of bounds, then an error is returned.
+    pub fn atomic_notify(&self, offset: u32, count: u32) -> WasmEdgeResult<u32> {
+        let addr = self.atomic_i32(offset)? as *const AtomicI32 as usize;
+        let mut waiters = waiters()
+            .lock()
+            .expect("[bitbang] the atomic waiters are poisoned");
+        let mut woken = 0;
+        if let Some(queue) = waiters.get_mut(&addr) {
+            let count = queue.len().min(count as usize);
+            for waiter in queue.drain(..count) {
+                *waiter
+                    .woken
+                    .lock()
+                    .expect("[bitbang] the atomic waiter is poisoned") = true;
+                waiter.condvar.notify_one();
+                woken += 1;
+            }
+            if queue.is_empty() {
+                waiters.remove(&addr);
+            }
+        }
+        Ok(woken)
+    }
+
+    /// Returns the 32-bit integer at the given offset of this shared memory as an atomic.
+    fn atomic_i32(&self, offset: u32) -> WasmEdgeResult<&AtomicI32> {
+        if !self.ty.shared() {
+            return Err(Box::new(WasmEdgeError::Mem(MemError::NotShared)));
+        }
+        if offset % 4 != 0 {
+            return Err(Box::new(WasmEdgeError::Mem(MemError::UnalignedAtomic(
+                offset,
+            ))));
+        }
+        let ptr = self.data_pointer(offset, 4)?;
+        // a shared memory is never moved when it grows, and the pointer is aligned and in bounds
+        Ok(unsafe { &*(ptr as *const AtomicI32) })
+    }
+
+    /// Checks if the given range lies inside this memory.
+    fn check_bounds(&self, offset: u32, len: u64) -> WasmEdgeResult<()> {
+        let size = self.size();
+        if offset as u64 + len > size {
+            return Err(Box::new(WasmEdgeError::Mem(MemError::OutOfBounds {
+                offset,
+                len,
+                size,
+            })));
+        }
+        Ok(())
+    }
+}
+
+/// The result of [Memory::atomic_wait](crate::Memory::atomic_wait).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomicWaitResult {
+    /// The thread is woken by a notification.
+    Woken,
+    /// The value is not the expected one, so the thread did not wait.
+    NotEqual,
+    /// The timeout elapsed.
+    TimedOut,
+}
+
+#[derive(Debug, Default)]
+struct Waiter {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+/// Returns the host threads waiting on each address of the shared memories.
+fn waiters() -> &'static Mutex<HashMap<usize, Vec<Arc<Waiter>>>> {
+    static WAITERS: OnceLock<Mutex<HashMap<usize, Vec<Arc<Waiter>>>>> = OnceLock::new();
+    WAITERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The size of a page of a [memory](crate::Memory) in bytes.
+const PAGE_SIZE: usize = 65536;
+
+/// Defines an image of the contents of an exported [memory](crate::Memory), which is written to the memory when a [module](crate::Module) is instantiated with [Store::register_named_module_with_images](crate::Store::register_named_module_with_images) or [Store::register_active_module_with_images](crate::Store::register_active_module_with_images).
+///
+/// The image replaces the contents the data segments initialized, so a heap captured from a warmed-up instance, or built by tooling, can be injected without running the initialization code of the guest again. The bytes past the end of the image are zeroed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryImage {
+    pub(crate) memory_name: String,
+    pub(crate) data: Vec<u8>,
+}
+impl MemoryImage {
+    /// Creates a new [MemoryImage].
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_name` - The exported name of the memory the image is written to.
+    ///
+    /// * `data` - The contents of the memory, starting at offset 0.
+    pub fn new(memory_name: impl AsRef<str>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            memory_name: memory_name.as_ref().to_string(),
+            data: data.into(),
+        }
+    }
+
+    /// Captures the current contents of an exported memory of the given [module instance](crate::Instance). The trailing zero bytes are omitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance` - The module instance which exports the memory.
+    ///
+    /// * `memory_name` - The exported name of the memory.
+    ///
+    /// # Error
+    ///
+    /// * If the memory holds 4GiB, which does not fit in a single read, then [WasmEdgeError::Mem(MemError::ImageTooLarge)](crate::error::MemError) is returned.
+    ///
+    /// * If fail to find or read the memory, then an error is returned.
+    pub fn capture(instance: &Instance, memory_name: impl AsRef<str>) -> WasmEdgeResult<Self> {
+        let memory = instance.memory(memory_name.as_ref())?;
+        let size = memory.size();
+        let len = u32::try_from(size)
+            .map_err(|_| Box::new(WasmEdgeError::Mem(MemError::ImageTooLarge(size))))?;
+        let mut data = memory.read(0, len)?;
+        let len = data.iter().rposition(|&b| b != 0).map_or(0, |idx| idx + 1);
+        data.truncate(len);
+        Ok(Self::new(memory_name, data))
+    }
+
+    /// Returns the exported name of the memory the image is written to.
+    pub fn memory_name(&self) -> &str {
+        &self.memory_name
+    }
+
+    /// Returns the contents of the image.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Writes the image to the memory of the given module instance, growing the memory if it is smaller than the image.
+    pub(crate) fn apply(&self, instance: &Instance) -> WasmEdgeResult<()> {
+        let mut memory = instance.memory(&self.memory_name)?;
+        let pages = ((self.data.len() + PAGE_SIZE - 1) / PAGE_SIZE) as u32;
+        if memory.page() < pages {
+            memory.grow(pages - memory.page())?;
+        }
+        memory.write(&self.data, 0)?;
+
+        let zero_page = vec![0u8; PAGE_SIZE];
+        let size = memory.size() as usize;
+        let mut offset = self.data.len();
+        while offset < size {
+            let len = (size - offset).min(PAGE_SIZE);
+            memory.write(&zero_page[..len], offset as u32)?;
+            offset += len;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -188,6 +471,128 @@ mod tests {
         assert_eq!(ty.maximum(), Some(20));
     }
 
+    #[test]
+    #[allow(clippy::assertions_on_result_states)]
+    fn test_memory_stats() {
+        // create a memory instance
+        let result = MemoryType::new(1, Some(10), false);
+        assert!(result.is_ok());
+        let memory_type = result.unwrap();
+        let result = Memory::new(memory_type);
+        assert!(result.is_ok());
+        let mut memory = result.unwrap();
+
+        let stats = memory.stats();
+        assert_eq!(stats.initial_pages, 1);
+        assert_eq!(stats.current_pages, 1);
+        assert_eq!(stats.peak_pages, 1);
+        assert_eq!(stats.grow_events, 0);
+
+        // grow the memory twice
+        assert!(memory.grow(2).is_ok());
+        assert!(memory.grow(3).is_ok());
+
+        let stats = memory.stats();
+        assert_eq!(stats.initial_pages, 1);
+        assert_eq!(stats.current_pages, 6);
+        assert_eq!(stats.peak_pages, 6);
+        assert_eq!(stats.grow_events, 2);
+
+        // a failed grow is not counted
+        assert!(memory.grow(10).is_err());
+        assert_eq!(memory.stats().grow_events, 2);
+    }
+
+    #[test]
+    fn test_memory_atomic_wait_notify() {
+        let result = MemoryType::new(1, Some(1), true);
+        assert!(result.is_ok());
+        let result = Memory::new(result.unwrap());
+        assert!(result.is_ok());
+        let mut memory = result.unwrap();
+
+        // the value is not the expected one
+        let result = memory.atomic_wait(0, 1, None);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), AtomicWaitResult::NotEqual);
+
+        let result = memory.atomic_wait(0, 0, Some(Duration::from_millis(10)));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), AtomicWaitResult::TimedOut);
+
+        // woken by a notification
+        let waiting = memory.clone();
+        let handle = std::thread::spawn(move || waiting.atomic_wait(0, 0, None));
+        loop {
+            let result = memory.atomic_notify(0, 1);
+            assert!(result.is_ok());
+            if result.unwrap() == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let result = handle.join().unwrap();
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), AtomicWaitResult::Woken);
+
+        // the value is stored before notifying, so the thread either does not wait or is woken
+        let waiting = memory.clone();
+        let handle = std::thread::spawn(move || waiting.atomic_wait(8, 0, None));
+        assert!(memory.write(1i32.to_le_bytes(), 8).is_ok());
+        while !handle.is_finished() {
+            let result = memory.atomic_notify(8, 1);
+            assert!(result.is_ok());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let result = handle.join().unwrap();
+        assert!(result.is_ok());
+        assert_ne!(result.unwrap(), AtomicWaitResult::TimedOut);
+
+        // unaligned
+        let result = memory.atomic_notify(2, 1);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Mem(MemError::UnalignedAtomic(2))
+        );
+
+        // not shared
+        let result = Memory::new(MemoryType::new(1, None, false).unwrap());
+        assert!(result.is_ok());
+        let result = result.unwrap().atomic_notify(0, 1);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Mem(MemError::NotShared)
+        );
+    }
+
+    #[test]
+    fn test_memory_on_grow() {
+        let result = MemoryType::new(1, Some(10), false);
+        assert!(result.is_ok());
+        let result = Memory::new(result.unwrap());
+        assert!(result.is_ok());
+        let mut memory = result.unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let grow_events = Arc::clone(&events);
+        memory.on_grow(move |old_pages, new_pages| {
+            grow_events.lock().unwrap().push((old_pages, new_pages))
+        });
+
+        assert!(memory.grow(2).is_ok());
+        assert!(memory.grow(3).is_ok());
+        // a failed grow is not reported
+        assert!(memory.grow(10).is_err());
+        assert_eq!(*events.lock().unwrap(), [(1, 3), (3, 6)]);
+
+        // the clones share the subscription
+        let mut clone = memory.clone();
+        assert!(clone.grow(1).is_ok());
+        assert_eq!(events.lock().unwrap().last(), Some(&(6, 7)));
+    }
+
     #[test]
     #[allow(clippy::assertions_on_result_states)]
     fn test_memory() {
@@ -312,6 +717,48 @@ mod tests {
         assert_eq!(data, s);
     }
 
+    #[test]
+    fn test_memory_out_of_bounds() {
+        let result = MemoryType::new(1, Some(2), false);
+        assert!(result.is_ok());
+        let memory_type = result.unwrap();
+        let result = Memory::new(memory_type);
+        assert!(result.is_ok());
+        let mut memory = result.unwrap();
+
+        // the last byte is in bounds
+        let result = memory.read(65535, 1);
+        assert!(result.is_ok());
+
+        let result = memory.read(65530, 10);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Mem(MemError::OutOfBounds {
+                offset: 65530,
+                len: 10,
+                size: 65536
+            })
+        );
+
+        let result = memory.write([1; 4], u32::MAX);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Mem(MemError::OutOfBounds {
+                offset: u32::MAX,
+                len: 4,
+                size: 65536
+            })
+        );
+
+        // the range is in bounds after growing the memory
+        let result = memory.grow(1);
+        assert!(result.is_ok());
+        let result = memory.data_pointer(65530, 10);
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_memory_clone() {
         #[derive(Debug, Clone)]
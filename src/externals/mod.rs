@@ -3,7 +3,9 @@ mod global;
 mod memory;
 mod table;
 
-pub use function::{Func, FuncRef, FuncTypeBuilder};
+pub(crate) use function::BoxedHostFn;
+pub use function::{CallContext, Func, FuncRef, FuncTypeBuilder, IntoHostFunc, WeakFunc};
 pub use global::Global;
-pub use memory::Memory;
-pub use table::Table;
+pub use memory::{AtomicWaitResult, Memory, MemoryImage};
+pub(crate) use table::TableOwner;
+pub use table::{ExternTable, Table};
@@ -1,5 +1,17 @@
-use crate::{types::Val, TableType, WasmEdgeResult};
+use crate::{
+    error::{TableError, WasmEdgeError},
+    types::{ExternRef, Val},
+    RefType, TableType, ValType, WasmEdgeResult, WasmValue,
+};
 use bit_sys as sys;
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
 
 /// Defines a table storing the references to host functions or external objects.
 #[derive(Debug, Clone)]
@@ -116,15 +128,317 @@ impl Table {
     }
 }
 
+/// The owner of the table of an [extern table](crate::ExternTable), which the extern table shares with the [import object builder](crate::ImportObjectBuilder) importing it.
+#[derive(Clone)]
+pub(crate) struct TableOwner(Arc<Mutex<Owner>>);
+impl TableOwner {
+    /// Keeps the given owner of the table alive as long as the extern table.
+    pub(crate) fn hold(&self, owner: Arc<dyn Send + Sync>) {
+        *self.lock() = Owner::Held(owner);
+    }
+
+    /// Refers to the import object owning the table, without keeping it alive.
+    pub(crate) fn refer(&self, owner: &Arc<dyn Send + Sync>) {
+        *self.lock() = Owner::Import(Arc::downgrade(owner));
+    }
+
+    /// Returns a handle which keeps the owner of the table alive while the table is used, or an error if the owner is dropped.
+    fn upgrade(&self) -> WasmEdgeResult<Option<Arc<dyn Send + Sync>>> {
+        match &*self.lock() {
+            Owner::Itself => Ok(None),
+            Owner::Held(owner) => Ok(Some(Arc::clone(owner))),
+            Owner::Import(owner) => owner
+                .upgrade()
+                .map(Some)
+                .ok_or_else(|| Box::new(WasmEdgeError::Table(TableError::OwnerDropped))),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Owner> {
+        self.0
+            .lock()
+            .expect("[bitbang] the owner of the extern table is poisoned")
+    }
+}
+impl Default for TableOwner {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Owner::Itself)))
+    }
+}
+impl std::fmt::Debug for TableOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableOwner").finish_non_exhaustive()
+    }
+}
+
+enum Owner {
+    /// The extern table owns its table.
+    Itself,
+    /// The table is taken for an import object which is not built yet, and the extern table keeps it alive.
+    Held(Arc<dyn Send + Sync>),
+    /// The table is owned by the import object, which is not kept alive, since its host functions commonly hold clones of the extern table.
+    Import(Weak<dyn Send + Sync>),
+}
+
+/// Defines a table of external references to host objects of type `T`, which the host shares with the guests as a handle table backed by real reference types.
+///
+/// The host creates the table, pre-populates it, and imports it with [ImportObjectBuilder::with_extern_table](crate::ImportObjectBuilder::with_extern_table). The guests read and write the table with `table.get` and `table.set`, and pass the references to the host functions, which resolve them back to the host objects with [resolve](crate::ExternTable::resolve). The host keeps mutating the table during execution through a clone of it, for example from a host function.
+///
+/// The table roots every object it hands out to the guests, so the objects stay alive while the guests may refer to them. The objects are released only by [gc](crate::ExternTable::gc):
+///
+/// ```ignore
+/// let handles = ExternTable::<Connection>::new(0, None)?;
+/// handles.push(Connection::open("db-a")?)?;
+/// handles.push(Connection::open("db-b")?)?;
+///
+/// let resolver = handles.clone();
+/// let import = ImportObjectBuilder::new()
+///     .with_extern_table("handles", &handles)?
+///     .with_func::<ExternRef, i32, NeverType>(
+///         "query",
+///         move |_frame, args, _data| {
+///             let conn = resolver.resolve(&args[0]).ok_or(HostFuncError::User(1))?;
+///             Ok(vec![WasmValue::from_i32(conn.query())])
+///         },
+///         None,
+///     )?
+///     .build::<NeverType>("host", None)?;
+/// ```
+///
+/// Once imported, the table is owned by the [import object](crate::ImportObject). The extern table does not keep the import object alive, since its host functions commonly hold clones of the extern table, so the table is not usable anymore once the import object is dropped.
+pub struct ExternTable<T> {
+    table: Arc<Mutex<Table>>,
+    roots: Arc<Mutex<HashMap<usize, Arc<T>>>>,
+    imported: Arc<AtomicBool>,
+    owner: TableOwner,
+}
+impl<T: Send + Sync + 'static> ExternTable<T> {
+    /// Creates an empty table of external references.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The initial size of the table, whose elements are null references.
+    ///
+    /// * `max` - The maximum size of the table, or `None` if unlimited.
+    ///
+    /// # Error
+    ///
+    /// If fail to create the table, then [WasmEdgeError::Table(TableError::Create)](crate::error::TableError) is returned.
+    pub fn new(min: u32, max: Option<u32>) -> WasmEdgeResult<Self> {
+        let table = Table::new(TableType::new(RefType::ExternRef, min, max))?;
+        Ok(Self {
+            table: Arc::new(Mutex::new(table)),
+            roots: Arc::new(Mutex::new(HashMap::new())),
+            imported: Arc::new(AtomicBool::new(false)),
+            owner: TableOwner::default(),
+        })
+    }
+
+    /// Returns the size of the table, or 0 once the [import object](crate::ImportObject) owning the table is dropped.
+    pub fn size(&self) -> u32 {
+        match self.lock_table() {
+            Ok((_owner, table)) => table.size(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Returns the number of the host objects rooted by the table.
+    pub fn rooted(&self) -> usize {
+        self.lock_roots().len()
+    }
+
+    /// Appends a host object to the table, and returns its index.
+    ///
+    /// # Argument
+    ///
+    /// * `obj` - The host object to append.
+    ///
+    /// # Error
+    ///
+    /// * If the [import object](crate::ImportObject) owning the table is dropped, then [WasmEdgeError::Table(TableError::OwnerDropped)](crate::error::TableError) is returned.
+    ///
+    /// * If fail to grow the table, for example beyond its maximum size, then an error is returned.
+    pub fn push(&self, obj: T) -> WasmEdgeResult<u32> {
+        let (_owner, mut table) = self.lock_table()?;
+        let value = self.root(obj);
+        table.grow(1, Some(Val::ExternRef(Some(ExternRef { inner: value }))))
+    }
+
+    /// Stores a host object at the given index of the table, or a null reference if `obj` is `None`.
+    ///
+    /// The object previously stored at the index stays rooted until the next [gc](crate::ExternTable::gc), since the guests may still refer to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the element to store.
+    ///
+    /// * `obj` - The host object to store.
+    ///
+    /// # Error
+    ///
+    /// * If the [import object](crate::ImportObject) owning the table is dropped, then [WasmEdgeError::Table(TableError::OwnerDropped)](crate::error::TableError) is returned.
+    ///
+    /// * If the index is out of bounds, then an error is returned.
+    pub fn set(&self, index: u32, obj: Option<T>) -> WasmEdgeResult<()> {
+        let (_owner, mut table) = self.lock_table()?;
+        let value = obj.map(|obj| ExternRef {
+            inner: self.root(obj),
+        });
+        table.set(index, Val::ExternRef(value))
+    }
+
+    /// Returns the host object at the given index of the table, or `None` if the element is a null reference.
+    ///
+    /// # Argument
+    ///
+    /// * `index` - The index of the element to get.
+    ///
+    /// # Error
+    ///
+    /// * If the [import object](crate::ImportObject) owning the table is dropped, then [WasmEdgeError::Table(TableError::OwnerDropped)](crate::error::TableError) is returned.
+    ///
+    /// * If the index is out of bounds, then an error is returned.
+    ///
+    /// * If the element is an external reference which the table does not root, for example one created by another table, then [WasmEdgeError::Table(TableError::UnknownExternRef)](crate::error::TableError) is returned.
+    pub fn get(&self, index: u32) -> WasmEdgeResult<Option<Arc<T>>> {
+        let value: WasmValue = {
+            let (_owner, table) = self.lock_table()?;
+            table.get(index)?.into()
+        };
+        match value.extern_ptr() {
+            Some(ptr) => match self.lock_roots().get(&(ptr as usize)) {
+                Some(obj) => Ok(Some(Arc::clone(obj))),
+                None => Err(Box::new(WasmEdgeError::Table(
+                    TableError::UnknownExternRef(index),
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Roots a host object without storing it in the table, and returns an external reference to it, for example to return from a host function.
+    ///
+    /// The object stays rooted until the next [gc](crate::ExternTable::gc) which does not find it in the table.
+    ///
+    /// # Argument
+    ///
+    /// * `obj` - The host object to root.
+    pub fn root(&self, obj: T) -> WasmValue {
+        let obj = Arc::new(obj);
+        let ptr = Arc::as_ptr(&obj);
+        self.lock_roots().insert(ptr as usize, obj);
+        WasmValue::from_extern_ptr(ptr as *const c_void)
+    }
+
+    /// Resolves an external reference, such as an argument passed to a host function, to the host object it refers to.
+    ///
+    /// Returns `None` if the value is a null reference, not an external reference, or refers to an object the table does not root.
+    ///
+    /// # Argument
+    ///
+    /// * `value` - The external reference to resolve.
+    pub fn resolve(&self, value: &WasmValue) -> Option<Arc<T>> {
+        if value.ty() != ValType::ExternRef {
+            return None;
+        }
+        let ptr = value.extern_ptr()?;
+        self.lock_roots().get(&(ptr as usize)).cloned()
+    }
+
+    /// Releases the rooted host objects which are not stored in the table anymore, and returns the number of the released objects.
+    ///
+    /// The references held by the guests outside of the table, such as in locals and globals, are not visible to the host, so `gc` must only be called when no guest function which may hold such references is running.
+    ///
+    /// # Error
+    ///
+    /// If the [import object](crate::ImportObject) owning the table is dropped, or fail to read the table, then an error is returned.
+    pub fn gc(&self) -> WasmEdgeResult<usize> {
+        let mut reachable = HashSet::new();
+        {
+            let (_owner, table) = self.lock_table()?;
+            for index in 0..table.size() {
+                let value: WasmValue = table.get(index)?.into();
+                if let Some(ptr) = value.extern_ptr() {
+                    reachable.insert(ptr as usize);
+                }
+            }
+        }
+
+        let mut roots = self.lock_roots();
+        let count = roots.len();
+        roots.retain(|ptr, _| reachable.contains(ptr));
+        Ok(count - roots.len())
+    }
+
+    /// Takes the table out for an import object, and keeps a handle which stays usable as long as the import object owning the table.
+    ///
+    /// The extern table keeps the table alive until the import object is built, then the builder makes the returned owner refer to the import object.
+    pub(crate) fn take_for_import(&self) -> WasmEdgeResult<(sys::Table, TableOwner)> {
+        if self.imported.swap(true, Ordering::SeqCst) {
+            return Err(Box::new(WasmEdgeError::Table(TableError::AlreadyImported)));
+        }
+        let mut table = self
+            .table
+            .lock()
+            .expect("[bitbang] the extern table is poisoned");
+        // the handle is only used while the owner of the table is alive, which the owner checks
+        let alias = unsafe { table.inner.alias() };
+        let owned = std::mem::replace(&mut table.inner, alias);
+        self.owner.hold(Arc::new(owned.clone()));
+        Ok((owned, self.owner.clone()))
+    }
+
+    /// Locks the table, along with a handle keeping its owner alive while the table is used.
+    #[allow(clippy::type_complexity)]
+    fn lock_table(
+        &self,
+    ) -> WasmEdgeResult<(
+        Option<Arc<dyn Send + Sync>>,
+        std::sync::MutexGuard<'_, Table>,
+    )> {
+        let owner = self.owner.upgrade()?;
+        let table = self
+            .table
+            .lock()
+            .expect("[bitbang] the extern table is poisoned");
+        Ok((owner, table))
+    }
+
+    fn lock_roots(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Arc<T>>> {
+        self.roots
+            .lock()
+            .expect("[bitbang] the roots of the extern table are poisoned")
+    }
+}
+impl<T> Clone for ExternTable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: Arc::clone(&self.table),
+            roots: Arc::clone(&self.roots),
+            imported: Arc::clone(&self.imported),
+            owner: self.owner.clone(),
+        }
+    }
+}
+impl<T> std::fmt::Debug for ExternTable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternTable")
+            .field("table", &self.table)
+            .field("imported", &self.imported.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         config::{CommonConfigOptions, ConfigBuilder},
         error::HostFuncError,
+        params,
         types::Val,
-        CallingFrame, Executor, ImportObjectBuilder, NeverType, RefType, Statistics, Store,
-        ValType, WasmValue,
+        wat2wasm, CallingFrame, Executor, ImportObjectBuilder, Module, NeverType, RefType,
+        Statistics, Store, ValType, WasmVal, WasmValue,
     };
 
     #[test]
@@ -253,6 +567,182 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extern_table() {
+        let result = ExternTable::<String>::new(0, None);
+        assert!(result.is_ok());
+        let handles = result.unwrap();
+
+        // pre-populate the table
+        let result = handles.push("alpha".to_string());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 0);
+        let result = handles.push("be".to_string());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(handles.size(), 2);
+
+        let resolver = handles.clone();
+        let opener = handles.clone();
+        let result = ImportObjectBuilder::new()
+            .with_extern_table("handles", &handles)
+            .unwrap()
+            .with_func::<ExternRef, i32, NeverType>(
+                "name_len",
+                move |_frame, args, _data| {
+                    let len = match resolver.resolve(&args[0]) {
+                        Some(name) => name.len() as i32,
+                        None => -1,
+                    };
+                    Ok(vec![WasmValue::from_i32(len)])
+                },
+                None,
+            )
+            .unwrap()
+            .with_func::<(), ExternRef, NeverType>(
+                "open",
+                move |_frame, _args, _data| Ok(vec![opener.root("opened".to_string())]),
+                None,
+            )
+            .unwrap()
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        // the table can only be imported once
+        let result = ImportObjectBuilder::new().with_extern_table("handles", &handles);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Table(TableError::AlreadyImported)
+        );
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "host" "handles" (table $h 0 externref))
+              (import "host" "name_len" (func $name_len (param externref) (result i32)))
+              (import "host" "open" (func $open (result externref)))
+              (func (export "len_at") (param i32) (result i32)
+                local.get 0
+                table.get $h
+                call $name_len)
+              (func (export "swap")
+                (local externref)
+                i32.const 0
+                table.get $h
+                local.set 0
+                i32.const 0
+                i32.const 1
+                table.get $h
+                table.set $h
+                i32.const 1
+                local.get 0
+                table.set $h)
+              (func (export "open_at") (param i32)
+                local.get 0
+                call $open
+                table.set $h)
+            )
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        // the guest passes the references to the host
+        let len_at = instance.func("len_at").unwrap();
+        let result = executor.run_func(&len_at, params!(0));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 5);
+
+        // the guest mutates the table
+        let result = executor.run_func(&instance.func("swap").unwrap(), params!());
+        assert!(result.is_ok());
+        let result = handles.get(0);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().unwrap().as_str(), "be");
+
+        // the guest stores a reference created by the host during execution
+        let result = executor.run_func(&instance.func("open_at").unwrap(), params!(1));
+        assert!(result.is_ok());
+        assert_eq!(handles.get(1).unwrap().unwrap().as_str(), "opened");
+        assert_eq!(handles.rooted(), 3);
+
+        // "alpha" is not in the table anymore
+        let result = handles.gc();
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(handles.rooted(), 2);
+
+        // the host mutates the imported table
+        let result = handles.set(0, None);
+        assert!(result.is_ok());
+        let result = executor.run_func(&len_at, params!(0));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), -1);
+        assert_eq!(handles.gc().unwrap(), 1);
+        assert!(handles.get(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_extern_table_owner() {
+        // the extern table keeps the table alive if the import object is never built
+        let handles = ExternTable::<String>::new(1, None).unwrap();
+        let result = ImportObjectBuilder::new().with_extern_table("handles", &handles);
+        assert!(result.is_ok());
+        drop(result.unwrap());
+        let result = handles.set(0, Some("alpha".to_string()));
+        assert!(result.is_ok());
+        assert_eq!(handles.get(0).unwrap().unwrap().as_str(), "alpha");
+
+        // the extern table does not keep the import object alive, which its host functions may hold
+        let handles = ExternTable::<String>::new(1, None).unwrap();
+        let resolver = handles.clone();
+        let result = ImportObjectBuilder::new()
+            .with_extern_table("handles", &handles)
+            .unwrap()
+            .with_func::<ExternRef, i32, NeverType>(
+                "is_null",
+                move |_frame, args, _data| {
+                    Ok(vec![WasmValue::from_i32(
+                        resolver.resolve(&args[0]).is_none() as i32,
+                    )])
+                },
+                None,
+            )
+            .unwrap()
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+        let result = handles.set(0, Some("alpha".to_string()));
+        assert!(result.is_ok());
+        assert_eq!(handles.size(), 1);
+
+        // the table is not usable once the import object is dropped
+        drop(import);
+        assert_eq!(handles.size(), 0);
+        let result = handles.set(0, None);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Table(TableError::OwnerDropped)
+        );
+        let result = handles.get(0);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Table(TableError::OwnerDropped)
+        );
+    }
+
     fn real_add(
         _frame: CallingFrame,
         inputs: Vec<WasmValue>,
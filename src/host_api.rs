@@ -0,0 +1,363 @@
+//! Defines HostApi, which registers the same host functions under several versioned namespaces.
+
+use crate::{
+    error::{HostFuncError, ImportError, WasmEdgeError},
+    io::WasmValTypeList,
+    CallingFrame, FuncType, ImportObject, ImportObjectBuilder, NeverType, WasmEdgeResult,
+    WasmValue,
+};
+use std::{collections::HashSet, os::raw::c_void, sync::Arc};
+
+type HostFn = dyn Fn(CallingFrame, Vec<WasmValue>, *mut c_void) -> Result<Vec<WasmValue>, HostFuncError>
+    + Send
+    + Sync;
+
+type AdapterFn = dyn Fn(CallingFrame, Vec<WasmValue>, &SharedFunc) -> Result<Vec<WasmValue>, HostFuncError>
+    + Send
+    + Sync;
+
+/// Defines a host function implementation shared by the versions of a [host API](crate::HostApi).
+#[derive(Clone)]
+pub struct SharedFunc {
+    ty: FuncType,
+    func: Arc<HostFn>,
+}
+impl SharedFunc {
+    /// Returns the type of the shared implementation.
+    pub fn ty(&self) -> &FuncType {
+        &self.ty
+    }
+
+    /// Calls the shared implementation.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The calling frame of the adapter.
+    ///
+    /// * `args` - The arguments to pass to the shared implementation, which must match its type.
+    ///
+    /// # Error
+    ///
+    /// If the shared implementation fails, then its error is returned.
+    pub fn call(
+        &self,
+        frame: CallingFrame,
+        args: Vec<WasmValue>,
+    ) -> Result<Vec<WasmValue>, HostFuncError> {
+        (self.func)(frame, args, std::ptr::null_mut())
+    }
+}
+impl std::fmt::Debug for SharedFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedFunc").field("ty", &self.ty).finish()
+    }
+}
+
+/// Defines a version of a [host API](crate::HostApi), which exports the shared implementations as they are, except for the functions it adapts or removes.
+pub struct ApiVersion {
+    version: String,
+    adapters: Vec<(String, FuncType, Arc<AdapterFn>)>,
+    removed: HashSet<String>,
+}
+impl ApiVersion {
+    /// Creates a version which exports all the shared implementations as they are.
+    ///
+    /// # Argument
+    ///
+    /// * `version` - The name of the version, such as `v1`.
+    pub fn new(version: impl AsRef<str>) -> Self {
+        Self {
+            version: version.as_ref().to_string(),
+            adapters: Vec::new(),
+            removed: HashSet::new(),
+        }
+    }
+
+    /// Exports the shared implementation of the given name through an adapter, which has the signature the guests of this version expect and converts the arguments and the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the function, which must be a shared implementation of the API.
+    ///
+    /// * `adapter` - The adapter, which is given the calling frame, the arguments, and the shared implementation.
+    pub fn with_adapter<Args, Rets>(
+        mut self,
+        name: impl AsRef<str>,
+        adapter: impl Fn(CallingFrame, Vec<WasmValue>, &SharedFunc) -> Result<Vec<WasmValue>, HostFuncError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self
+    where
+        Args: WasmValTypeList,
+        Rets: WasmValTypeList,
+    {
+        let ty = FuncType::new(
+            Some(Args::wasm_types().to_vec()),
+            Some(Rets::wasm_types().to_vec()),
+        );
+        self.adapters
+            .push((name.as_ref().to_string(), ty, Arc::new(adapter)));
+        self
+    }
+
+    /// Removes the shared implementation of the given name from this version.
+    ///
+    /// # Argument
+    ///
+    /// * `name` - The name of the function.
+    pub fn without(mut self, name: impl AsRef<str>) -> Self {
+        self.removed.insert(name.as_ref().to_string());
+        self
+    }
+}
+impl std::fmt::Debug for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiVersion")
+            .field("version", &self.version)
+            .field(
+                "adapters",
+                &self
+                    .adapters
+                    .iter()
+                    .map(|(name, _, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("removed", &self.removed)
+            .finish()
+    }
+}
+
+/// Defines a host API registered under a versioned namespace per version, such as `myhost/v1` and `myhost/v2`.
+///
+/// The functions are implemented once and shared by all versions. A version evolving the ABI adapts the functions whose signatures or semantics changed, so the guests built against older versions keep working:
+///
+/// ```ignore
+/// let api = HostApi::new("myhost")
+///     // the current ABI: log(ptr, len, level)
+///     .with_func::<(i32, i32, i32), ()>("log", log)
+///     .with_version(ApiVersion::new("v2"))
+///     // the first ABI had no level
+///     .with_version(ApiVersion::new("v1").with_adapter::<(i32, i32), ()>(
+///         "log",
+///         |frame, args, log| log.call(frame, vec![args[0], args[1], WasmValue::from_i32(INFO)]),
+///     ));
+/// for import in api.build()? {
+///     store.register_import_module(&mut executor, &import)?;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct HostApi {
+    name: String,
+    funcs: Vec<(String, SharedFunc)>,
+    versions: Vec<ApiVersion>,
+}
+impl HostApi {
+    /// Creates a host API without functions and versions.
+    ///
+    /// # Argument
+    ///
+    /// * `name` - The name of the API, which prefixes the namespaces of its versions.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            funcs: Vec::new(),
+            versions: Vec::new(),
+        }
+    }
+
+    /// Adds a shared implementation, which is exported by all versions unless they adapt or remove it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the function.
+    ///
+    /// * `real_func` - The native function.
+    pub fn with_func<Args, Rets>(
+        mut self,
+        name: impl AsRef<str>,
+        real_func: impl Fn(CallingFrame, Vec<WasmValue>, *mut c_void) -> Result<Vec<WasmValue>, HostFuncError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self
+    where
+        Args: WasmValTypeList,
+        Rets: WasmValTypeList,
+    {
+        let ty = FuncType::new(
+            Some(Args::wasm_types().to_vec()),
+            Some(Rets::wasm_types().to_vec()),
+        );
+        self.funcs.push((
+            name.as_ref().to_string(),
+            SharedFunc {
+                ty,
+                func: Arc::new(real_func),
+            },
+        ));
+        self
+    }
+
+    /// Adds a version of the API.
+    ///
+    /// # Argument
+    ///
+    /// * `version` - The version to add.
+    pub fn with_version(mut self, version: ApiVersion) -> Self {
+        self.versions.push(version);
+        self
+    }
+
+    /// Returns the namespace of the given version, which is `name/version`.
+    ///
+    /// # Argument
+    ///
+    /// * `version` - The name of the version.
+    pub fn namespace(&self, version: impl AsRef<str>) -> String {
+        format!("{}/{}", self.name, version.as_ref())
+    }
+
+    /// Creates an [import object](crate::ImportObject) per version, named after the [namespace](crate::HostApi::namespace) of the version.
+    ///
+    /// # Error
+    ///
+    /// * If a version adapts a function which is not a shared implementation, then [WasmEdgeError::Import(ImportError::NotFoundSharedFunc)](crate::error::ImportError) is returned.
+    ///
+    /// * If fail to create the import objects, then an error is returned.
+    pub fn build(&self) -> WasmEdgeResult<Vec<ImportObject<NeverType>>> {
+        let mut imports = Vec::with_capacity(self.versions.len());
+        for version in self.versions.iter() {
+            let mut builder = ImportObjectBuilder::new();
+            for (name, shared) in self.funcs.iter() {
+                let adapted = version.adapters.iter().any(|(n, _, _)| n == name);
+                if adapted || version.removed.contains(name) {
+                    continue;
+                }
+                let func = Arc::clone(&shared.func);
+                builder = builder.with_func_by_type::<NeverType>(
+                    name,
+                    shared.ty.clone(),
+                    move |frame, args, data| func(frame, args, data),
+                    None,
+                )?;
+            }
+            for (name, ty, adapter) in version.adapters.iter() {
+                let shared = match self.funcs.iter().find(|(n, _)| n == name) {
+                    Some((_, shared)) => shared.clone(),
+                    None => {
+                        return Err(Box::new(WasmEdgeError::Import(
+                            ImportError::NotFoundSharedFunc(name.clone()),
+                        )))
+                    }
+                };
+                let adapter = Arc::clone(adapter);
+                builder = builder.with_func_by_type::<NeverType>(
+                    name,
+                    ty.clone(),
+                    move |frame, args, _data| adapter(frame, args, &shared),
+                    None,
+                )?;
+            }
+            imports.push(builder.build::<NeverType>(self.namespace(&version.version), None)?);
+        }
+        Ok(imports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, wat2wasm, Executor, Module, Store, WasmVal};
+
+    #[test]
+    fn test_host_api_versions() {
+        // v1 guests pass one operand, which is added to 100; v2 guests pass both operands
+        let api = HostApi::new("myhost")
+            .with_func::<(i32, i32), i32>("add", |_frame, args, _data| {
+                Ok(vec![WasmValue::from_i32(
+                    args[0].to_i32() + args[1].to_i32(),
+                )])
+            })
+            .with_func::<(), i32>("legacy", |_frame, _args, _data| {
+                Ok(vec![WasmValue::from_i32(7)])
+            })
+            .with_version(
+                ApiVersion::new("v1").with_adapter::<i32, i32>("add", |frame, args, add| {
+                    add.call(frame, vec![args[0], WasmValue::from_i32(100)])
+                }),
+            )
+            .with_version(ApiVersion::new("v2").without("legacy"));
+        assert_eq!(api.namespace("v2"), "myhost/v2");
+
+        let result = api.build();
+        assert!(result.is_ok());
+        let imports = result.unwrap();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].name(), "myhost/v1");
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        for import in imports.iter() {
+            assert!(store.register_import_module(&mut executor, import).is_ok());
+        }
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "myhost/v1" "add" (func $add_v1 (param i32) (result i32)))
+              (import "myhost/v1" "legacy" (func $legacy (result i32)))
+              (import "myhost/v2" "add" (func $add_v2 (param i32 i32) (result i32)))
+              (func (export "v1") (param i32) (result i32)
+                local.get 0
+                call $add_v1
+                call $legacy
+                i32.add)
+              (func (export "v2") (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                call $add_v2)
+            )
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let result = executor.run_func(&instance.func("v1").unwrap(), params!(1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 108);
+        let result = executor.run_func(&instance.func("v2").unwrap(), params!(1, 2));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 3);
+
+        // v2 does not export the removed function
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "myhost/v2" "legacy" (func (result i32))))
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+        assert!(store
+            .register_active_module(&mut executor, &module)
+            .is_err());
+
+        // an adapter needs a shared implementation
+        let result = HostApi::new("myhost")
+            .with_version(
+                ApiVersion::new("v1").with_adapter::<(), ()>("missing", |frame, args, missing| {
+                    missing.call(frame, args)
+                }),
+            )
+            .build();
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Import(ImportError::NotFoundSharedFunc("missing".to_string()))
+        );
+    }
+}
@@ -1,8 +1,12 @@
 use crate::{
-    error::HostFuncError, io::WasmValTypeList, CallingFrame, FuncType, Global, Memory, Table,
-    WasmEdgeResult,
+    error::HostFuncError,
+    externals::TableOwner,
+    io::WasmValTypeList,
+    runtime::{self, AsyncRuntime, BoxFuture},
+    CallingFrame, ExternTable, FuncType, Global, Memory, NeverType, Table, WasmEdgeResult,
 };
 use bit_sys::{self as sys, AsImport, WasmValue};
+use std::sync::Arc;
 
 /// Creates a normal or wasi [import object](crate::ImportObject).
 ///
@@ -16,6 +20,7 @@ pub struct ImportObjectBuilder {
     globals: Vec<(String, sys::Global)>,
     memories: Vec<(String, sys::Memory)>,
     tables: Vec<(String, sys::Table)>,
+    table_owners: Vec<TableOwner>,
 }
 impl ImportObjectBuilder {
     /// Creates a new [ImportObjectBuilder].
@@ -25,6 +30,7 @@ impl ImportObjectBuilder {
             globals: Vec::new(),
             memories: Vec::new(),
             tables: Vec::new(),
+            table_owners: Vec::new(),
         }
     }
 
@@ -106,6 +112,45 @@ impl ImportObjectBuilder {
         Ok(self)
     }
 
+    /// Adds an async [host function](crate::Func) to the [ImportObject] to create. See [Func::wrap_async](crate::Func::wrap_async) for how the future is run.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The exported name of the [host function](crate::Func) to add.
+    ///
+    /// * `runtime` - The async runtime which drives the future.
+    ///
+    /// * `real_func` - The native function returning the future.
+    ///
+    /// # error
+    ///
+    /// If fail to create or add the [host function](crate::Func), then an error is returned.
+    pub fn with_async_func<Args, Rets>(
+        mut self,
+        name: impl AsRef<str>,
+        runtime: Arc<dyn AsyncRuntime>,
+        real_func: impl Fn(
+                CallingFrame,
+                Vec<WasmValue>,
+            ) -> BoxFuture<'static, Result<Vec<WasmValue>, HostFuncError>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> WasmEdgeResult<Self>
+    where
+        Args: WasmValTypeList,
+        Rets: WasmValTypeList,
+    {
+        let boxed_func = runtime::async_host_func(runtime, real_func);
+        let ty = FuncType::new(
+            Some(Args::wasm_types().to_vec()),
+            Some(Rets::wasm_types().to_vec()),
+        );
+        let inner_func = sys::Function::create::<NeverType>(&ty.into(), boxed_func, None, 0)?;
+        self.funcs.push((name.as_ref().to_owned(), inner_func));
+        Ok(self)
+    }
+
     /// Adds a [global](crate::Global) to the [ImportObject] to create.
     ///
     /// # Arguments
@@ -145,6 +190,28 @@ impl ImportObjectBuilder {
         self
     }
 
+    /// Adds an [extern table](crate::ExternTable) to the [ImportObject] to create. The extern table keeps working on the imported table, so the host can mutate it while the guests run.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The exported name of the table to add.
+    ///
+    /// * `table` - The extern table to add.
+    ///
+    /// # Error
+    ///
+    /// If the extern table is already added to an import object, then [WasmEdgeError::Table(TableError::AlreadyImported)](crate::error::TableError) is returned.
+    pub fn with_extern_table<T: Send + Sync + 'static>(
+        mut self,
+        name: impl AsRef<str>,
+        table: &ExternTable<T>,
+    ) -> WasmEdgeResult<Self> {
+        let (table, owner) = table.take_for_import()?;
+        self.tables.push((name.as_ref().to_owned(), table));
+        self.table_owners.push(owner);
+        Ok(self)
+    }
+
     /// Creates a new [ImportObject].
     ///
     /// # Argument
@@ -162,7 +229,7 @@ impl ImportObjectBuilder {
         host_data: Option<Box<T>>,
     ) -> WasmEdgeResult<ImportObject<T>>
     where
-        T: ?Sized + Send + Sync + Clone,
+        T: ?Sized + Send + Sync + Clone + 'static,
     {
         let mut inner = sys::ImportModule::create(name.as_ref(), host_data)?;
 
@@ -186,6 +253,13 @@ impl ImportObjectBuilder {
             inner.add_table(name, table);
         }
 
+        // the extern tables check that the import object owning their tables is alive before using them
+        let inner = Arc::new(inner);
+        let owner = Arc::clone(&inner) as Arc<dyn Send + Sync>;
+        for table_owner in self.table_owners.into_iter() {
+            table_owner.refer(&owner);
+        }
+
         Ok(ImportObject(inner))
     }
 }
@@ -194,7 +268,7 @@ impl ImportObjectBuilder {
 ///
 /// An [ImportObject] instance is created with [ImportObjectBuilder](crate::ImportObjectBuilder).
 #[derive(Debug, Clone)]
-pub struct ImportObject<T: ?Sized + Send + Sync + Clone>(pub(crate) sys::ImportModule<T>);
+pub struct ImportObject<T: ?Sized + Send + Sync + Clone>(pub(crate) Arc<sys::ImportModule<T>>);
 impl<T: ?Sized + Send + Sync + Clone> ImportObject<T> {
     /// Returns the name of the import object.
     pub fn name(&self) -> &str {
@@ -1,9 +1,28 @@
 //! Defines WasmEdge Instance.
 
 use crate::{
-    Func, FuncType, Global, GlobalType, Memory, MemoryType, Table, TableType, WasmEdgeResult,
+    diagnostics::{HandleGuard, HandleKind},
+    error::{InstanceError, TableError, WasmEdgeError},
+    module::{ModuleInfo, DEFERRED_START_EXPORT},
+    types::Val,
+    wasi::WasiKind,
+    Executor, Func, FuncRef, FuncType, Global, GlobalType, Memory, MemoryStats, MemoryType,
+    Mutability, RefType, Table, TableType, WasmEdgeResult,
 };
 use bit_sys as sys;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+/// The size of a wasm page in bytes.
+const PAGE_SIZE: usize = 65536;
+
+/// The entry point of a WASI command.
+const WASI_START: &str = "_start";
+
+/// The initialization function of a WASI reactor.
+pub(crate) const WASI_INITIALIZE: &str = "_initialize";
 
 /// Represents an instantiated module.
 ///
@@ -11,6 +30,10 @@ use bit_sys as sys;
 #[derive(Debug, Clone)]
 pub struct Instance {
     pub(crate) inner: sys::Instance,
+    pub(crate) baseline: Option<Arc<Baseline>>,
+    pub(crate) module_info: Option<Arc<ModuleInfo>>,
+    pub(crate) segment_funcs: Option<Arc<SegmentFuncs>>,
+    pub(crate) _guard: HandleGuard,
 }
 impl Instance {
     /// Returns the name of this exported [module instance](crate::Instance).
@@ -44,6 +67,8 @@ impl Instance {
             name: Some(name.as_ref().into()),
             mod_name: self.inner.name(),
             ty,
+            instance: Some(self.inner.downgrade()),
+            _guard: HandleGuard::new(HandleKind::Func),
         })
     }
 
@@ -98,9 +123,23 @@ impl Instance {
             name: Some(name.as_ref().into()),
             mod_name: self.inner.name(),
             ty,
+            _guard: HandleGuard::new(HandleKind::Memory),
         })
     }
 
+    /// Returns the usage statistics of the exported [memory instances](crate::Memory) in this [module instance](crate::Instance), keyed by the exported names of the memories.
+    ///
+    /// See [Memory::stats](crate::Memory::stats) for when the growth of a memory is observed.
+    pub fn memory_stats(&self) -> HashMap<String, MemoryStats> {
+        let mut stats = HashMap::new();
+        for name in self.memory_names().unwrap_or_default() {
+            if let Ok(memory) = self.inner.get_memory(&name) {
+                stats.insert(name, memory.stat());
+            }
+        }
+        stats
+    }
+
     /// Returns the count of the exported [table instances](crate::Table) in this [module instance](crate::Instance).
     pub fn table_count(&self) -> usize {
         self.inner.table_len() as usize
@@ -128,10 +167,300 @@ impl Instance {
         })
     }
 
+    /// Returns the entries of an exported funcref [table](crate::Table) of this [module instance](crate::Instance), with the names of the functions they refer to.
+    ///
+    /// A function is named after its export name if this module instance exports it. Otherwise, the name is taken from the name section of the module, if the slot still holds the function the element segments of the module placed into it at the instantiation; once the guest or the host stores another function into the slot, the entry is unnamed. The types of the entries always reflect the current contents, so the table can be checked before its functions are used as callbacks:
+    ///
+    /// ```ignore
+    /// for entry in instance.table_funcs("callbacks")? {
+    ///     if let Some(func) = entry.func.as_ref() {
+    ///         if func.ty() != &expected {
+    ///             panic!("callback {} has the wrong signature", entry.name.as_deref().unwrap_or("?"));
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Argument
+    ///
+    /// * `name` - the name of the target exported [table instance](crate::Table).
+    ///
+    /// # Error
+    ///
+    /// * If the table is not found, then an error is returned.
+    ///
+    /// * If the table does not hold function references, then [WasmEdgeError::Table(TableError::NotFuncRef)](crate::error::TableError) is returned.
+    pub fn table_funcs(&self, name: impl AsRef<str>) -> WasmEdgeResult<Vec<TableFunc>> {
+        let table = self.table(name.as_ref())?;
+        if table.ty().elem_ty() != RefType::FuncRef {
+            return Err(Box::new(WasmEdgeError::Table(TableError::NotFuncRef(
+                name.as_ref().to_string(),
+            ))));
+        }
+
+        let exported_funcs = self
+            .func_names()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|func_name| Ok((self.func(&func_name)?, func_name)))
+            .collect::<WasmEdgeResult<Vec<_>>>()?;
+
+        let mut entries = Vec::with_capacity(table.size() as usize);
+        for slot in 0..table.size() {
+            let func = match table.get(slot)? {
+                Val::FuncRef(func) => func,
+                _ => None,
+            };
+            let name = func.as_ref().and_then(|func_ref| {
+                exported_funcs
+                    .iter()
+                    .find(|(func, _)| func_ref.inner.refers_to(&func.inner))
+                    .map(|(_, func_name)| func_name.clone())
+                    .or_else(|| {
+                        // the name section only names the function placed by the segments
+                        let placed = self
+                            .segment_funcs
+                            .as_ref()?
+                            .get(&(name.as_ref().to_string(), slot))?;
+                        if !placed.same_as(&func_ref.inner) {
+                            return None;
+                        }
+                        let info = self.module_info.as_ref()?;
+                        let idx = info.table_func(name.as_ref(), slot)?;
+                        info.names.func_name(idx).map(str::to_string)
+                    })
+            });
+            entries.push(TableFunc { slot, func, name });
+        }
+        Ok(entries)
+    }
+
+    /// Runs the start function deferred by [Module::from_bytes_deferred_start](crate::Module::from_bytes_deferred_start).
+    ///
+    /// If the module has no deferred start function, this method does nothing. Notice that the start function is run again each time this method is called.
+    ///
+    /// # Argument
+    ///
+    /// * `executor` - The [executor](crate::Executor) running the start function.
+    ///
+    /// # Error
+    ///
+    /// If the start function fails, then an error is returned.
+    pub fn run_start(&self, executor: &Executor) -> WasmEdgeResult<()> {
+        match self.func(DEFERRED_START_EXPORT) {
+            Ok(start) => executor.run_func(&start, []).map(|_| ()),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Restores the exported [memories](crate::Memory), mutable [globals](crate::Global), and [tables](crate::Table) of this [module instance](crate::Instance) to their state right after the instantiation, so that the instance can be reused instead of instantiating the module again.
+    ///
+    /// The post-instantiation state is captured only when the module is registered with [RegisterOptions::with_reset](crate::RegisterOptions::with_reset) enabled, or with memory images. Only the pages of the memories which differ from that state are written back, and the pages which were zero are not kept in the captured state.
+    ///
+    /// Notice that a memory which has grown since the instantiation keeps its size, with the grown pages zeroed, and a grown table keeps its size, with the grown elements set to null. The state which is not exported from the module, as well as the state held by the host functions, is not restored.
+    ///
+    /// # Error
+    ///
+    /// * If the post-instantiation state of the instance is not captured, then [WasmEdgeError::Instance(InstanceError::NoResetBaseline)](crate::error::InstanceError) is returned.
+    ///
+    /// * If fail to restore a memory, a global, or a table, then an error is returned.
+    pub fn reset(&self) -> WasmEdgeResult<()> {
+        match &self.baseline {
+            Some(baseline) => baseline.restore(self),
+            None => Err(Box::new(WasmEdgeError::Instance(
+                InstanceError::NoResetBaseline,
+            ))),
+        }
+    }
+
+    /// Returns the WASI execution model of this [module instance](crate::Instance), which is detected from its exports.
+    ///
+    /// Returns [WasiKind::Command](crate::wasi::WasiKind) if the instance exports `_start`, [WasiKind::Reactor](crate::wasi::WasiKind) if it exports `_initialize`, or `None` otherwise. The `_initialize` function of a reactor importing the WASI functions is run when the module is registered into a [store](crate::Store) with [RegisterOptions::with_initialize](crate::RegisterOptions::with_initialize) enabled, after which only the other exported functions of the reactor should be called. The `_start` function of a command is never run automatically.
+    pub fn wasi_kind(&self) -> Option<WasiKind> {
+        let names = self.func_names().unwrap_or_default();
+        if names.iter().any(|name| name == WASI_START) {
+            Some(WasiKind::Command)
+        } else if names.iter().any(|name| name == WASI_INITIALIZE) {
+            Some(WasiKind::Reactor)
+        } else {
+            None
+        }
+    }
+
     /// Returns the host data held by the module instance.
     pub fn host_data<T: Send + Sync + Clone>(&mut self) -> Option<&mut T> {
         self.inner.host_data()
     }
+
+    /// Creates a [weak handle](crate::WeakInstance) to this [module instance](crate::Instance), which does not keep it alive.
+    ///
+    /// A module instance registered into a [store](crate::Store) is deleted, and unregistered from the store, once its last handle is dropped.
+    pub fn downgrade(&self) -> WeakInstance {
+        WeakInstance {
+            inner: self.inner.downgrade(),
+            name: self.name(),
+            baseline: self.baseline.as_ref().map(Arc::downgrade),
+            module_info: self.module_info.clone(),
+            segment_funcs: self.segment_funcs.clone(),
+        }
+    }
+}
+
+/// A weak handle to a [module instance](crate::Instance), which does not keep the module instance alive. It is created with [Instance::downgrade](crate::Instance::downgrade).
+#[derive(Debug, Clone)]
+pub struct WeakInstance {
+    inner: sys::WeakInstance,
+    name: Option<String>,
+    baseline: Option<Weak<Baseline>>,
+    module_info: Option<Arc<ModuleInfo>>,
+    segment_funcs: Option<Arc<SegmentFuncs>>,
+}
+impl WeakInstance {
+    /// Returns the name of the [module instance](crate::Instance), or `None` if it is an active module instance.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the [module instance](crate::Instance), if it is still alive.
+    pub fn upgrade(&self) -> Option<Instance> {
+        Some(Instance {
+            inner: self.inner.upgrade()?,
+            baseline: self.baseline.as_ref().and_then(Weak::upgrade),
+            module_info: self.module_info.clone(),
+            segment_funcs: self.segment_funcs.clone(),
+            _guard: HandleGuard::new(HandleKind::Instance),
+        })
+    }
+
+    /// Checks if the [module instance](crate::Instance) is still alive.
+    pub fn is_alive(&self) -> bool {
+        self.inner.upgrade().is_some()
+    }
+}
+
+/// Defines an entry of a funcref [table](crate::Table), returned by [Instance::table_funcs](crate::Instance::table_funcs).
+#[derive(Debug, Clone)]
+pub struct TableFunc {
+    /// The index of the entry in the table.
+    pub slot: u32,
+    /// The function the entry refers to, or `None` if the entry is a null reference.
+    pub func: Option<FuncRef>,
+    /// The name of the function, if known.
+    pub name: Option<String>,
+}
+
+/// The function references which the element segments of a module placed into the exported tables at the instantiation, keyed by the table name and the slot.
+pub(crate) type SegmentFuncs = HashMap<(String, u32), sys::FuncRef>;
+
+/// Captures the function references at the slots whose functions are named by the name section of the module, so that [Instance::table_funcs](crate::Instance::table_funcs) only uses the names while the slots are unchanged.
+pub(crate) fn capture_segment_funcs(
+    instance: &Instance,
+    info: &ModuleInfo,
+) -> WasmEdgeResult<SegmentFuncs> {
+    let mut funcs = HashMap::new();
+    for (table_name, slot) in info.named_segment_slots() {
+        if let Val::FuncRef(Some(func_ref)) = instance.table(table_name)?.get(slot)? {
+            funcs.insert((table_name.to_string(), slot), func_ref.inner);
+        }
+    }
+    Ok(funcs)
+}
+
+/// Defines the state of the exported instances of a [module instance](crate::Instance) right after the instantiation.
+#[derive(Debug, Default)]
+pub(crate) struct Baseline {
+    memories: Vec<(String, MemoryBaseline)>,
+    globals: Vec<(String, Val)>,
+    tables: Vec<(String, Vec<Val>)>,
+}
+impl Baseline {
+    /// Captures the current state of the exported instances of the given module instance.
+    pub(crate) fn capture(instance: &Instance) -> WasmEdgeResult<Self> {
+        let mut baseline = Self::default();
+
+        for name in instance.memory_names().unwrap_or_default() {
+            let memory = instance.memory(&name)?;
+            let mut pages = HashMap::new();
+            for page in 0..memory.page() {
+                let data = page_data(&memory, page)?;
+                if data.iter().any(|&b| b != 0) {
+                    pages.insert(page, data.to_vec().into_boxed_slice());
+                }
+            }
+            baseline.memories.push((name, MemoryBaseline { pages }));
+        }
+
+        for name in instance.global_names().unwrap_or_default() {
+            let global = instance.global(&name)?;
+            if global.ty().mutability() == Mutability::Var {
+                baseline.globals.push((name, global.get_value()));
+            }
+        }
+
+        for name in instance.table_names().unwrap_or_default() {
+            let table = instance.table(&name)?;
+            let elems = (0..table.size())
+                .map(|idx| table.get(idx))
+                .collect::<WasmEdgeResult<Vec<_>>>()?;
+            baseline.tables.push((name, elems));
+        }
+
+        Ok(baseline)
+    }
+
+    /// Restores the captured state to the exported instances of the given module instance.
+    fn restore(&self, instance: &Instance) -> WasmEdgeResult<()> {
+        let zero_page = vec![0u8; PAGE_SIZE];
+        for (name, baseline) in self.memories.iter() {
+            let mut memory = instance.memory(name)?;
+            for page in 0..memory.page() {
+                let expected = baseline.pages.get(&page).map_or(&zero_page[..], |d| &d[..]);
+                if page_data(&memory, page)? != expected {
+                    memory.write(expected, page * PAGE_SIZE as u32)?;
+                }
+            }
+        }
+
+        for (name, value) in self.globals.iter() {
+            instance.global(name)?.set_value(value.clone())?;
+        }
+
+        for (name, elems) in self.tables.iter() {
+            let mut table = instance.table(name)?;
+            let null = match table.ty().elem_ty() {
+                RefType::FuncRef => Val::FuncRef(None),
+                RefType::ExternRef => Val::ExternRef(None),
+            };
+            for idx in 0..table.size() {
+                let elem = elems
+                    .get(idx as usize)
+                    .cloned()
+                    .unwrap_or_else(|| null.clone());
+                table.set(idx, elem)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Defines the pages of a memory right after the instantiation. The pages which are all zero are omitted.
+struct MemoryBaseline {
+    pages: HashMap<u32, Box<[u8]>>,
+}
+impl std::fmt::Debug for MemoryBaseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryBaseline")
+            .field("pages", &self.pages.len())
+            .finish()
+    }
+}
+
+/// Returns the bytes of the given page of a memory without copying them.
+fn page_data(memory: &Memory, page: u32) -> WasmEdgeResult<&[u8]> {
+    let ptr = memory.data_pointer(page * PAGE_SIZE as u32, PAGE_SIZE as u32)?;
+    // SAFETY: the pointer refers to a whole page inside the memory, which is not resized while the returned slice borrows the memory.
+    Ok(unsafe { std::slice::from_raw_parts(ptr, PAGE_SIZE) })
 }
 
 /// The object used as an module instance is required to implement this trait.
@@ -196,12 +525,13 @@ pub trait AsInstance {
 #[cfg(target_os = "linux")]
 mod tests {
     use crate::{
-        config::{CommonConfigOptions, ConfigBuilder},
-        error::HostFuncError,
+        config::{CommonConfigOptions, ConfigBuilder, HostRegistrationConfigOptions},
+        error::{HostFuncError, InstanceError, TableError, WasmEdgeError},
         types::Val,
-        CallingFrame, Executor, FuncTypeBuilder, Global, GlobalType, ImportObjectBuilder, Memory,
-        MemoryType, Module, Mutability, NeverType, RefType, Statistics, Store, Table, TableType,
-        ValType, WasmValue,
+        wasi::WasiKind,
+        wat2wasm, CallingFrame, Executor, FuncTypeBuilder, Global, GlobalType, ImportObjectBuilder,
+        Memory, MemoryType, Module, Mutability, NeverType, RefType, RegisterOptions, Statistics,
+        Store, Table, TableType, ValType, VmBuilder, WasmValue,
     };
 
     #[test]
@@ -354,6 +684,13 @@ mod tests {
             assert_eq!(instance.global_count(), 0);
             assert_eq!(instance.memory_count(), 1);
 
+            // check the memory statistics
+            let stats = instance.memory_stats();
+            assert_eq!(stats.len(), 1);
+            let stat = stats.values().next().unwrap();
+            assert_eq!(stat.current_pages, stat.peak_pages);
+            assert_eq!(stat.grow_events, 0);
+
             // check the exported host function
             let result = instance.func("fib");
             assert!(result.is_ok());
@@ -368,6 +705,350 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_instance_run_start() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (global (export "counter") (mut i32) (i32.const 0))
+              (func $init
+                global.get 0
+                i32.const 1
+                i32.add
+                global.set 0)
+              (start $init)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        // the start function runs at instantiation by default
+        let result = Module::from_bytes(None, &wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        let result = store.register_named_module(&mut executor, "eager", &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(1)
+        ));
+
+        // the deferred start function runs explicitly
+        let result = Module::from_bytes_deferred_start(None, &wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        let result = store.register_named_module(&mut executor, "deferred", &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(0)
+        ));
+
+        let result = instance.run_start(&executor);
+        assert!(result.is_ok());
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(1)
+        ));
+
+        // a module without a start function is loaded as is
+        let result = Module::from_bytes_deferred_start(None, wat2wasm(b"(module)").unwrap());
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert_eq!(instance.func_count(), 0);
+        assert!(instance.run_start(&executor).is_ok());
+    }
+
+    #[test]
+    fn test_instance_reset() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (memory (export "memory") 1)
+              (data (i32.const 16) "hello")
+              (global (export "counter") (mut i32) (i32.const 7))
+              (table (export "table") 2 funcref)
+              (func $f)
+              (elem (i32.const 0) $f)
+              (func (export "mutate")
+                i32.const 16
+                i32.const 0x6c6c6579
+                i32.store
+                i32.const 1
+                memory.grow
+                drop
+                i32.const 70000
+                i32.const 1
+                i32.store8
+                global.get 0
+                i32.const 1
+                i32.add
+                global.set 0
+                i32.const 1
+                ref.func $f
+                table.set 0)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // the post-instantiation state is not captured by default
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let result = result.unwrap().reset();
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Instance(InstanceError::NoResetBaseline)
+        );
+
+        let options = RegisterOptions::new().with_reset(true);
+        let result =
+            store.register_named_module_with_options(&mut executor, "extern", &module, &options);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let mutate = instance.func("mutate").unwrap();
+        for round in 0..2 {
+            let result = executor.run_func(&mutate, []);
+            assert!(result.is_ok());
+            let memory = instance.memory("memory").unwrap();
+            assert_eq!(memory.read(16, 5).unwrap(), b"yello");
+            assert!(matches!(
+                instance.global("counter").unwrap().get_value(),
+                Val::I32(8)
+            ));
+
+            // restore the post-instantiation state
+            let result = instance.reset();
+            assert!(result.is_ok());
+
+            let memory = instance.memory("memory").unwrap();
+            assert_eq!(memory.read(16, 5).unwrap(), b"hello");
+            assert_eq!(memory.page(), 2 + round);
+            assert_eq!(memory.read(70000, 1).unwrap(), [0]);
+            assert!(matches!(
+                instance.global("counter").unwrap().get_value(),
+                Val::I32(7)
+            ));
+            let table = instance.table("table").unwrap();
+            assert!(matches!(table.get(0).unwrap(), Val::FuncRef(Some(_))));
+            assert!(matches!(table.get(1).unwrap(), Val::FuncRef(None)));
+        }
+
+        // the instances which are looked up by name have no post-instantiation state
+        let result = store.named_instance("extern");
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let result = instance.reset();
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Instance(InstanceError::NoResetBaseline)
+        );
+    }
+
+    #[test]
+    fn test_instance_table_funcs() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (table $callbacks (export "callbacks") 4 funcref)
+              (table $values (export "values") 1 externref)
+              (func $on_open (export "open") (param i32))
+              (func $on_close (param i32))
+              (func $on_error (param i64))
+              (elem (table $callbacks) (i32.const 0) func $on_open $on_close)
+              (elem (table $callbacks) (i32.const 3) func $on_error)
+              (func (export "replace")
+                (table.set $callbacks (i32.const 1) (ref.func $on_error)))
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_named_module(&mut executor, "guest", &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+
+        let result = instance.table_funcs("callbacks");
+        assert!(result.is_ok());
+        let entries = result.unwrap();
+        assert_eq!(entries.len(), 4);
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_deref()).collect();
+        assert_eq!(
+            names,
+            vec![Some("open"), Some("on_close"), None, Some("on_error")]
+        );
+        assert!(entries[2].func.is_none());
+
+        // find the entries with an unexpected signature
+        let expected = FuncTypeBuilder::new().with_arg(ValType::I32).build();
+        let mismatched: Vec<_> = entries
+            .iter()
+            .filter(|e| e.func.as_ref().map_or(false, |f| f.ty() != &expected))
+            .map(|e| e.slot)
+            .collect();
+        assert_eq!(mismatched, vec![3]);
+
+        // the name of a replaced slot is not taken from the segments
+        let result = executor.run_func(&instance.func("replace").unwrap(), []);
+        assert!(result.is_ok());
+        let result = instance.table_funcs("callbacks");
+        assert!(result.is_ok());
+        let names: Vec<_> = result.unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("open".to_string()),
+                None,
+                None,
+                Some("on_error".to_string())
+            ]
+        );
+
+        // not a funcref table
+        let result = instance.table_funcs("values");
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Table(TableError::NotFuncRef("values".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_instance_wasi_kind() {
+        let result = ConfigBuilder::new(CommonConfigOptions::default())
+            .with_host_registration_config(HostRegistrationConfigOptions::default().wasi(true))
+            .build();
+        assert!(result.is_ok());
+        let config = result.unwrap();
+
+        let result = VmBuilder::new().with_config(config).build();
+        assert!(result.is_ok());
+        let vm = result.unwrap();
+
+        let module = |entry: &str| {
+            let wat = format!(
+                r#"
+                (module
+                  (import "wasi_snapshot_preview1" "proc_exit" (func (param i32)))
+                  (global (export "counter") (mut i32) (i32.const 0))
+                  (func (export "{entry}")
+                    global.get 0
+                    i32.const 1
+                    i32.add
+                    global.set 0)
+                )
+                "#
+            );
+            Module::from_bytes(None, wat2wasm(wat.as_bytes()).unwrap()).unwrap()
+        };
+
+        // the reactor is not initialized by default
+        let result = vm.register_module(Some("reactor"), module("_initialize"));
+        assert!(result.is_ok());
+        let mut vm = result.unwrap();
+        let instance = vm.named_module("reactor").unwrap();
+        assert_eq!(instance.wasi_kind(), Some(WasiKind::Reactor));
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(0)
+        ));
+
+        // the reactor is initialized at instantiation on request
+        let mut executor = vm.executor().clone();
+        let options = RegisterOptions::new().with_initialize(true);
+        let result = vm.store_mut().register_named_module_with_options(
+            &mut executor,
+            "initialized",
+            &module("_initialize"),
+            &options,
+        );
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(1)
+        ));
+
+        // the command is not started at instantiation
+        let result = vm.register_module(Some("command"), module("_start"));
+        assert!(result.is_ok());
+        let vm = result.unwrap();
+        let instance = vm.named_module("command").unwrap();
+        assert_eq!(instance.wasi_kind(), Some(WasiKind::Command));
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(0)
+        ));
+
+        // a module not importing WASI is left as is
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (global (export "counter") (mut i32) (i32.const 0))
+              (func (export "_initialize")
+                i32.const 1
+                global.set 0)
+            )
+            "#,
+        )
+        .unwrap();
+        let result = vm.register_module_from_bytes("plain", wasm_bytes);
+        assert!(result.is_ok());
+        let vm = result.unwrap();
+        let instance = vm.named_module("plain").unwrap();
+        assert!(matches!(
+            instance.global("counter").unwrap().get_value(),
+            Val::I32(0)
+        ));
+
+        let result = vm.register_module_from_bytes("none", wat2wasm(b"(module)").unwrap());
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().named_module("none").unwrap().wasi_kind(),
+            None
+        );
+    }
+
     fn real_add(
         _frame: CallingFrame,
         inputs: Vec<WasmValue>,
@@ -1,4 +1,4 @@
-use crate::{types::ExternRef, FuncRef, ValType, WasmValue};
+use crate::{error::HostFuncError, types::ExternRef, FuncRef, ValType, WasmValue};
 
 /// Describes the mapping of Rust type to Wasm type.
 ///
@@ -291,6 +291,245 @@ impl WasmVal for ExternRef {
     }
 }
 
+/// Defines the function converting a value of Wasm type to the one of Rust type.
+///
+/// ```rust
+/// use bitbang::{FromWasmVal, WasmValue};
+///
+/// assert_eq!(i32::from_wasm_value(&WasmValue::from_i32(1)), Some(1));
+/// assert_eq!(i32::from_wasm_value(&WasmValue::from_i64(1)), None);
+/// ```
+pub trait FromWasmVal: WasmValType + Sized {
+    /// Returns the Rust value, or `None` if the type of the given value is not [WASM_TYPE](crate::WasmValType::WASM_TYPE).
+    fn from_wasm_value(value: &WasmValue) -> Option<Self>;
+}
+
+/// The `impl_from_wasm_val` macro is used to generate the following struct
+///
+/// ```ignore
+/// impl FromWasmVal for i32 {
+///     fn from_wasm_value(value: &WasmValue) -> Option<Self> {
+///         match value.ty() == Self::WASM_TYPE {
+///             true => Some(value.to_i32() as i32),
+///             false => None,
+///         }
+///     }
+/// }
+/// ```
+macro_rules! impl_from_wasm_val {
+    ($t:ty, $to:ident) => {
+        impl FromWasmVal for $t {
+            #[allow(clippy::unnecessary_cast)]
+            fn from_wasm_value(value: &WasmValue) -> Option<Self> {
+                match value.ty() == Self::WASM_TYPE {
+                    true => Some(value.$to() as $t),
+                    false => None,
+                }
+            }
+        }
+    };
+}
+
+impl_from_wasm_val!(i8, to_i32);
+impl_from_wasm_val!(u8, to_i32);
+impl_from_wasm_val!(i16, to_i32);
+impl_from_wasm_val!(u16, to_i32);
+impl_from_wasm_val!(i32, to_i32);
+impl_from_wasm_val!(u32, to_i64);
+impl_from_wasm_val!(i64, to_i64);
+impl_from_wasm_val!(f32, to_f32);
+impl_from_wasm_val!(f64, to_f64);
+impl_from_wasm_val!(i128, to_v128);
+impl FromWasmVal for ExternRef {
+    fn from_wasm_value(value: &WasmValue) -> Option<Self> {
+        match value.ty() == Self::WASM_TYPE {
+            true => Some(ExternRef { inner: *value }),
+            false => None,
+        }
+    }
+}
+
+/// Defines the function converting a list of values of Wasm types to a tuple of Rust types.
+///
+/// ```rust
+/// use bitbang::{FromWasmValList, WasmValue};
+///
+/// let values = [WasmValue::from_i32(1), WasmValue::from_f64(2.0)];
+/// assert_eq!(<(i32, f64)>::from_wasm_values(&values), Some((1, 2.0)));
+/// assert_eq!(<(i32, i32)>::from_wasm_values(&values), None);
+/// ```
+pub trait FromWasmValList: WasmValTypeList {
+    /// Returns the tuple of Rust values, or `None` if the number or the types of the given values do not match [wasm_types](crate::WasmValTypeList::wasm_types).
+    fn from_wasm_values(values: &[WasmValue]) -> Option<Self>;
+}
+
+macro_rules! impl_from_wasm_val_list {
+    ( $($o:ident),* ) => {
+        #[allow(unused_parens, unused_variables, unused_mut, non_snake_case)]
+        impl< $( $o ),* >
+            FromWasmValList
+        for ( $( $o ),* )
+        where
+            $( $o: FromWasmVal ),*
+        {
+            fn from_wasm_values(values: &[WasmValue]) -> Option<Self> {
+                if values.len() != count_idents!( $( $o ),* ) {
+                    return None;
+                }
+                let mut iter = values.iter();
+                $(
+                    let $o = $o::from_wasm_value(iter.next()?)?;
+                )*
+                Some(( $( $o ),* ))
+            }
+        }
+    };
+}
+
+impl_from_wasm_val_list!();
+impl_from_wasm_val_list!(A1);
+impl_from_wasm_val_list!(A1, A2);
+impl_from_wasm_val_list!(A1, A2, A3);
+impl_from_wasm_val_list!(A1, A2, A3, A4);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
+impl_from_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
+
+#[cfg(test)]
+mod test_from_wasm_val_list {
+    use super::*;
+
+    #[test]
+    fn test_from_wasm_values() {
+        assert_eq!(<()>::from_wasm_values(&[]), Some(()));
+        assert_eq!(<()>::from_wasm_values(&[WasmValue::from_i32(1)]), None);
+        assert_eq!(i32::from_wasm_values(&[WasmValue::from_i32(1)]), Some(1));
+        assert_eq!(
+            <(i32, i64, f32, f64)>::from_wasm_values(&[
+                WasmValue::from_i32(1),
+                WasmValue::from_i64(2),
+                WasmValue::from_f32(3.0),
+                WasmValue::from_f64(4.0),
+            ]),
+            Some((1, 2, 3.0, 4.0))
+        );
+        assert_eq!(
+            <(i32, i64)>::from_wasm_values(&[WasmValue::from_i32(1), WasmValue::from_i32(2)]),
+            None
+        );
+        assert_eq!(
+            <(i32, i32)>::from_wasm_values(&[WasmValue::from_i32(1)]),
+            None
+        );
+    }
+}
+
+/// Defines the function converting a tuple of Rust values to a list of values of Wasm types.
+///
+/// ```rust
+/// use bitbang::IntoWasmValList;
+///
+/// let values = (1i32, 2.0f64).into_wasm_values();
+/// assert_eq!(values[0].to_i32(), 1);
+/// assert_eq!(values[1].to_f64(), 2.0);
+/// ```
+pub trait IntoWasmValList: WasmValTypeList {
+    /// Returns the list of Wasm values.
+    fn into_wasm_values(self) -> Vec<WasmValue>;
+}
+
+macro_rules! impl_into_wasm_val_list {
+    ( $($o:ident),* ) => {
+        #[allow(unused_parens, non_snake_case)]
+        impl< $( $o ),* >
+            IntoWasmValList
+        for ( $( $o ),* )
+        where
+            $( $o: WasmValType + WasmVal ),*
+        {
+            fn into_wasm_values(self) -> Vec<WasmValue> {
+                let ( $( $o ),* ) = self;
+                vec![ $( $o.to_wasm_value() ),* ]
+            }
+        }
+    };
+}
+
+impl_into_wasm_val_list!();
+impl_into_wasm_val_list!(A1);
+impl_into_wasm_val_list!(A1, A2);
+impl_into_wasm_val_list!(A1, A2, A3);
+impl_into_wasm_val_list!(A1, A2, A3, A4);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
+impl_into_wasm_val_list!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
+
+/// Defines the values which can be returned by a typed host function created with [Func::wrap_fn](crate::Func::wrap_fn).
+///
+/// It is implemented for a Rust value or a tuple of Rust values, which are returned to the guest, and for `Result` of them, whose error traps the guest.
+pub trait HostFuncReturn {
+    /// The Rust types of the values returned to the guest.
+    type Rets: IntoWasmValList;
+
+    /// Returns the values to return to the guest, or the error to trap the guest with.
+    fn into_host_result(self) -> Result<Self::Rets, HostFuncError>;
+}
+impl<R> HostFuncReturn for R
+where
+    R: IntoWasmValList,
+{
+    type Rets = R;
+
+    fn into_host_result(self) -> Result<Self::Rets, HostFuncError> {
+        Ok(self)
+    }
+}
+impl<R, E> HostFuncReturn for Result<R, E>
+where
+    R: IntoWasmValList,
+    E: Into<HostFuncError>,
+{
+    type Rets = R;
+
+    fn into_host_result(self) -> Result<Self::Rets, HostFuncError> {
+        self.map_err(|e| e.into())
+    }
+}
+
+/// Aborts the guest execution from a host function with a [Trap](crate::error::Trap) carrying the formatted message.
+///
+/// The call of the guest fails with [WasmEdgeError::Trap](crate::error::WasmEdgeError::Trap).
+///
+/// ```ignore
+/// if len > MAX_LEN {
+///     bitbang::bail!("the input of {} bytes is too long", len);
+/// }
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ( $( $arg:tt )* ) => {
+        return Err($crate::error::Trap::new(format!($( $arg )*)).into())
+    };
+}
+
 /// Generates arguments of [WasmValue](crate::WasmValue) types.
 ///
 /// Notice that to use the macro, it is required to use `WasmVal` trait.
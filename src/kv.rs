@@ -0,0 +1,981 @@
+//! Defines the `host_kv` import module, which gives the guests a persistent key-value store partitioned by tenant.
+//!
+//! The keys and values are stored by a [KvStorage], which is shared by the tenants. Every tenant reads and writes its own [namespace](crate::kv::KvNamespace) under a [quota](crate::kv::KvQuota), and the guests of the tenant reach it through the import object created by [import_object](crate::kv::import_object):
+//!
+//! ```ignore
+//! let storage: Arc<dyn KvStorage> = Arc::new(MemoryStorage::new());
+//! let quota = KvQuota::new().with_max_keys(1024).with_max_bytes(1 << 20);
+//! let namespace = KvNamespace::new(Arc::clone(&storage), "tenant-a", quota);
+//! store.register_import_module(&mut executor, &kv::import_object(&namespace)?)?;
+//! ```
+//!
+//! Besides the in-memory [MemoryStorage], the crate provides the storages backed by [sled](https://docs.rs/sled) with the `sled` feature and by SQLite with the `sqlite` feature.
+
+use crate::{
+    error::{KvError, Trap, WasmEdgeError},
+    Caller, CallingFrame, ImportObject, ImportObjectBuilder, Memory, NeverType, WasmEdgeResult,
+    WasmValue,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+/// The name of the import object serving the key-value store to the guests.
+pub const KV_MODULE_NAME: &str = "host_kv";
+
+/// The code returned to the guest when the key is not found.
+pub const KV_NOT_FOUND: i32 = -1;
+
+/// The code returned to the guest when a write would exceed the quota of its namespace.
+pub const KV_QUOTA_EXCEEDED: i32 = -2;
+
+/// The code returned to the guest when the storage fails.
+pub const KV_STORAGE_FAILED: i32 = -3;
+
+/// Defines the storage of the key-value pairs of all namespaces.
+///
+/// The keys of a namespace are listed in the ascending order of their bytes.
+pub trait KvStorage: Send + Sync {
+    /// Returns the value of the given key in the namespace, or `None` if the key is not set.
+    fn get(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<Option<Vec<u8>>>;
+
+    /// Sets the value of the given key in the namespace.
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> WasmEdgeResult<()>;
+
+    /// Removes the given key from the namespace. Returns `false` if the key is not set.
+    fn delete(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<bool>;
+
+    /// Returns the keys in the namespace which start with the given prefix, in ascending order.
+    fn list(&self, namespace: &str, prefix: &[u8]) -> WasmEdgeResult<Vec<Vec<u8>>>;
+
+    /// Returns the number of the keys and the bytes stored in the namespace.
+    ///
+    /// A [KvNamespace] calls it before every write checked against its quota, so the storage is expected to keep the usage up to date with its writes rather than to scan the namespace.
+    fn usage(&self, namespace: &str) -> WasmEdgeResult<KvUsage>;
+}
+
+/// The storage used by a [namespace](crate::kv::KvNamespace).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KvUsage {
+    /// The number of the keys.
+    pub keys: usize,
+    /// The total length of the keys and the values, in bytes.
+    pub bytes: u64,
+}
+
+/// Defines the quota of a [namespace](crate::kv::KvNamespace). Every limit is unlimited unless it is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KvQuota {
+    max_keys: Option<usize>,
+    max_bytes: Option<u64>,
+    max_key_len: Option<usize>,
+    max_value_len: Option<usize>,
+}
+impl KvQuota {
+    /// Creates a new [KvQuota] without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of the keys in the namespace.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The maximum number of the keys.
+    pub fn with_max_keys(self, count: usize) -> Self {
+        Self {
+            max_keys: Some(count),
+            ..self
+        }
+    }
+
+    /// Sets the maximum total length of the keys and the values in the namespace.
+    ///
+    /// # Argument
+    ///
+    /// * `bytes` - The maximum number of bytes.
+    pub fn with_max_bytes(self, bytes: u64) -> Self {
+        Self {
+            max_bytes: Some(bytes),
+            ..self
+        }
+    }
+
+    /// Sets the maximum length of a key.
+    ///
+    /// # Argument
+    ///
+    /// * `len` - The maximum length in bytes.
+    pub fn with_max_key_len(self, len: usize) -> Self {
+        Self {
+            max_key_len: Some(len),
+            ..self
+        }
+    }
+
+    /// Sets the maximum length of a value.
+    ///
+    /// # Argument
+    ///
+    /// * `len` - The maximum length in bytes.
+    pub fn with_max_value_len(self, len: usize) -> Self {
+        Self {
+            max_value_len: Some(len),
+            ..self
+        }
+    }
+}
+
+/// Defines the namespace of a tenant in a [KvStorage], which enforces the [quota](crate::kv::KvQuota) of the tenant.
+///
+/// Cloning the namespace is cheap, since the clones share the storage. The writes through a namespace and its clones are serialized, so the quota holds as long as the namespace of a tenant is not written through another [KvNamespace] created with the same name.
+///
+/// Every write checked against the quota reads the usage of the namespace from the storage, which keeps it up to date, so the quota accounts for the writes made past the namespace as well.
+#[derive(Clone)]
+pub struct KvNamespace {
+    storage: Arc<dyn KvStorage>,
+    name: Arc<str>,
+    quota: KvQuota,
+    writes: Arc<Mutex<()>>,
+}
+impl KvNamespace {
+    /// Creates a new [KvNamespace].
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - The storage holding the key-value pairs.
+    ///
+    /// * `name` - The name of the namespace, usually the name of the tenant.
+    ///
+    /// * `quota` - The quota of the namespace.
+    pub fn new(storage: Arc<dyn KvStorage>, name: impl AsRef<str>, quota: KvQuota) -> Self {
+        Self {
+            storage,
+            name: Arc::from(name.as_ref()),
+            quota,
+            writes: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Returns the name of the namespace.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the quota of the namespace.
+    pub fn quota(&self) -> KvQuota {
+        self.quota
+    }
+
+    /// Returns the value of the given key, or `None` if the key is not set.
+    ///
+    /// # Argument
+    ///
+    /// * `key` - The key to look up.
+    ///
+    /// # Error
+    ///
+    /// If the storage fails, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn get(&self, key: impl AsRef<[u8]>) -> WasmEdgeResult<Option<Vec<u8>>> {
+        self.storage.get(&self.name, key.as_ref())
+    }
+
+    /// Sets the value of the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to set.
+    ///
+    /// * `value` - The value of the key.
+    ///
+    /// # Error
+    ///
+    /// * If the write would exceed the quota, then [WasmEdgeError::Kv(KvError::QuotaExceeded)](crate::error::KvError) is returned, and nothing is written.
+    ///
+    /// * If the storage fails, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn put(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> WasmEdgeResult<()> {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        let quota = self.quota;
+        if quota.max_key_len.is_some_and(|max| key.len() > max)
+            || quota.max_value_len.is_some_and(|max| value.len() > max)
+        {
+            return Err(self.quota_exceeded());
+        }
+
+        let _writes = self.lock_writes();
+        if quota.max_keys.is_some() || quota.max_bytes.is_some() {
+            let mut usage = self.storage.usage(&self.name)?;
+            match self.storage.get(&self.name, key)? {
+                Some(old) => {
+                    usage.bytes = usage.bytes.saturating_sub((key.len() + old.len()) as u64)
+                }
+                None => usage.keys += 1,
+            }
+            usage.bytes += (key.len() + value.len()) as u64;
+            if quota.max_keys.is_some_and(|max| usage.keys > max)
+                || quota.max_bytes.is_some_and(|max| usage.bytes > max)
+            {
+                return Err(self.quota_exceeded());
+            }
+        }
+        self.storage.put(&self.name, key, value)
+    }
+
+    /// Removes the given key. Returns `false` if the key is not set.
+    ///
+    /// # Argument
+    ///
+    /// * `key` - The key to remove.
+    ///
+    /// # Error
+    ///
+    /// If the storage fails, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn delete(&self, key: impl AsRef<[u8]>) -> WasmEdgeResult<bool> {
+        let _writes = self.lock_writes();
+        self.storage.delete(&self.name, key.as_ref())
+    }
+
+    /// Returns the keys which start with the given prefix, in ascending order.
+    ///
+    /// # Argument
+    ///
+    /// * `prefix` - The prefix of the keys. An empty prefix lists all the keys.
+    ///
+    /// # Error
+    ///
+    /// If the storage fails, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn list(&self, prefix: impl AsRef<[u8]>) -> WasmEdgeResult<Vec<Vec<u8>>> {
+        self.storage.list(&self.name, prefix.as_ref())
+    }
+
+    /// Returns the number of the keys and the bytes stored in the namespace.
+    ///
+    /// # Error
+    ///
+    /// If the storage fails, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn usage(&self) -> WasmEdgeResult<KvUsage> {
+        self.storage.usage(&self.name)
+    }
+
+    fn lock_writes(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.writes
+            .lock()
+            .expect("[bitbang] the key-value namespace is poisoned")
+    }
+
+    fn quota_exceeded(&self) -> Box<WasmEdgeError> {
+        Box::new(WasmEdgeError::Kv(KvError::QuotaExceeded(
+            self.name.to_string(),
+        )))
+    }
+}
+impl std::fmt::Debug for KvNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KvNamespace")
+            .field("name", &self.name)
+            .field("quota", &self.quota)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Defines a [KvStorage] which keeps the key-value pairs in memory. The pairs are lost when the storage is dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    namespaces: Mutex<HashMap<String, MemoryNamespace>>,
+}
+impl MemoryStorage {
+    /// Creates a new empty [MemoryStorage].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, MemoryNamespace>> {
+        self.namespaces
+            .lock()
+            .expect("[bitbang] the key-value memory storage is poisoned")
+    }
+}
+impl KvStorage for MemoryStorage {
+    fn get(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<Option<Vec<u8>>> {
+        Ok(self
+            .lock()
+            .get(namespace)
+            .and_then(|ns| ns.pairs.get(key).cloned()))
+    }
+
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> WasmEdgeResult<()> {
+        let mut namespaces = self.lock();
+        let ns = namespaces.entry(namespace.to_string()).or_default();
+        match ns.pairs.insert(key.to_vec(), value.to_vec()) {
+            Some(old) => {
+                ns.usage.bytes = ns
+                    .usage
+                    .bytes
+                    .saturating_sub((key.len() + old.len()) as u64)
+            }
+            None => ns.usage.keys += 1,
+        }
+        ns.usage.bytes += (key.len() + value.len()) as u64;
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<bool> {
+        let mut namespaces = self.lock();
+        let ns = match namespaces.get_mut(namespace) {
+            Some(ns) => ns,
+            None => return Ok(false),
+        };
+        match ns.pairs.remove(key) {
+            Some(old) => {
+                ns.usage.keys = ns.usage.keys.saturating_sub(1);
+                ns.usage.bytes = ns
+                    .usage
+                    .bytes
+                    .saturating_sub((key.len() + old.len()) as u64);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn list(&self, namespace: &str, prefix: &[u8]) -> WasmEdgeResult<Vec<Vec<u8>>> {
+        Ok(match self.lock().get(namespace) {
+            Some(ns) => ns
+                .pairs
+                .range(prefix.to_vec()..)
+                .take_while(|(key, _)| key.starts_with(prefix))
+                .map(|(key, _)| key.clone())
+                .collect(),
+            None => Vec::new(),
+        })
+    }
+
+    fn usage(&self, namespace: &str) -> WasmEdgeResult<KvUsage> {
+        Ok(self
+            .lock()
+            .get(namespace)
+            .map(|ns| ns.usage)
+            .unwrap_or_default())
+    }
+}
+
+/// The key-value pairs of a namespace in a [MemoryStorage], along with their usage.
+#[derive(Debug, Default)]
+struct MemoryNamespace {
+    pairs: BTreeMap<Vec<u8>, Vec<u8>>,
+    usage: KvUsage,
+}
+
+/// Defines a [KvStorage] backed by a [sled](https://docs.rs/sled) database, which keeps every namespace in its own tree, and the usage of the namespaces in the `kv-usage` tree.
+#[cfg(feature = "sled")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sled")))]
+#[derive(Debug, Clone)]
+pub struct SledStorage {
+    db: sled::Db,
+    usage: sled::Tree,
+}
+#[cfg(feature = "sled")]
+impl SledStorage {
+    /// Opens the sled database at the given path, and creates it if it does not exist.
+    ///
+    /// The usage of the namespaces written before the usage was kept in the database is counted once.
+    ///
+    /// # Argument
+    ///
+    /// * `path` - The path of the database directory.
+    ///
+    /// # Error
+    ///
+    /// If fail to open the database, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn open(path: impl AsRef<std::path::Path>) -> WasmEdgeResult<Self> {
+        let db = sled::open(path).map_err(storage_error)?;
+        let usage = db.open_tree("kv-usage").map_err(storage_error)?;
+        for name in db.tree_names() {
+            let namespace = match name.strip_prefix(b"kv:") {
+                Some(namespace) => namespace,
+                None => continue,
+            };
+            if usage.get(namespace).map_err(storage_error)?.is_none() {
+                let mut counted = KvUsage::default();
+                for pair in db.open_tree(&name).map_err(storage_error)?.iter() {
+                    let (key, value) = pair.map_err(storage_error)?;
+                    counted.keys += 1;
+                    counted.bytes += (key.len() + value.len()) as u64;
+                }
+                usage
+                    .insert(namespace, encode_usage(counted).to_vec())
+                    .map_err(storage_error)?;
+            }
+        }
+        Ok(Self { db, usage })
+    }
+
+    fn tree(&self, namespace: &str) -> WasmEdgeResult<sled::Tree> {
+        // the prefix keeps the namespaces apart from the default tree of sled
+        self.db
+            .open_tree(format!("kv:{namespace}"))
+            .map_err(storage_error)
+    }
+}
+#[cfg(feature = "sled")]
+impl KvStorage for SledStorage {
+    fn get(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<Option<Vec<u8>>> {
+        let value = self.tree(namespace)?.get(key).map_err(storage_error)?;
+        Ok(value.map(|value| value.to_vec()))
+    }
+
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> WasmEdgeResult<()> {
+        use sled::{transaction::ConflictableTransactionResult, Transactional};
+
+        // the pair and the usage of the namespace are written atomically
+        let tree = self.tree(namespace)?;
+        (&tree, &self.usage)
+            .transaction(
+                |(tree, usage)| -> ConflictableTransactionResult<(), std::convert::Infallible> {
+                    let mut current = decode_usage(usage.get(namespace)?.as_deref());
+                    match tree.insert(key, value)? {
+                        Some(old) => {
+                            current.bytes =
+                                current.bytes.saturating_sub((key.len() + old.len()) as u64)
+                        }
+                        None => current.keys += 1,
+                    }
+                    current.bytes += (key.len() + value.len()) as u64;
+                    usage.insert(namespace, encode_usage(current).to_vec())?;
+                    Ok(())
+                },
+            )
+            .map_err(storage_error)
+    }
+
+    fn delete(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<bool> {
+        use sled::{transaction::ConflictableTransactionResult, Transactional};
+
+        let tree = self.tree(namespace)?;
+        (&tree, &self.usage)
+            .transaction(
+                |(tree, usage)| -> ConflictableTransactionResult<bool, std::convert::Infallible> {
+                    let old = match tree.remove(key)? {
+                        Some(old) => old,
+                        None => return Ok(false),
+                    };
+                    let mut current = decode_usage(usage.get(namespace)?.as_deref());
+                    current.keys = current.keys.saturating_sub(1);
+                    current.bytes = current.bytes.saturating_sub((key.len() + old.len()) as u64);
+                    usage.insert(namespace, encode_usage(current).to_vec())?;
+                    Ok(true)
+                },
+            )
+            .map_err(storage_error)
+    }
+
+    fn list(&self, namespace: &str, prefix: &[u8]) -> WasmEdgeResult<Vec<Vec<u8>>> {
+        self.tree(namespace)?
+            .scan_prefix(prefix)
+            .keys()
+            .map(|key| key.map(|key| key.to_vec()).map_err(storage_error))
+            .collect()
+    }
+
+    fn usage(&self, namespace: &str) -> WasmEdgeResult<KvUsage> {
+        let usage = self.usage.get(namespace).map_err(storage_error)?;
+        Ok(decode_usage(usage.as_deref()))
+    }
+}
+
+/// Encodes the usage of a namespace as the little-endian numbers of its keys and bytes.
+#[cfg(feature = "sled")]
+fn encode_usage(usage: KvUsage) -> [u8; 16] {
+    let mut buf = [0; 16];
+    buf[..8].copy_from_slice(&(usage.keys as u64).to_le_bytes());
+    buf[8..].copy_from_slice(&usage.bytes.to_le_bytes());
+    buf
+}
+
+/// Decodes the usage encoded by [encode_usage], or returns an empty usage if there is none.
+#[cfg(feature = "sled")]
+fn decode_usage(bytes: Option<&[u8]>) -> KvUsage {
+    match bytes {
+        Some(bytes) if bytes.len() == 16 => {
+            let mut keys = [0; 8];
+            let mut total = [0; 8];
+            keys.copy_from_slice(&bytes[..8]);
+            total.copy_from_slice(&bytes[8..]);
+            KvUsage {
+                keys: u64::from_le_bytes(keys) as usize,
+                bytes: u64::from_le_bytes(total),
+            }
+        }
+        _ => KvUsage::default(),
+    }
+}
+
+/// Defines a [KvStorage] backed by a SQLite database, which keeps the key-value pairs of all namespaces in the `host_kv` table, and the usage of the namespaces in the `host_kv_usage` table, which triggers keep up to date.
+#[cfg(feature = "sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+#[derive(Debug)]
+pub struct SqliteStorage {
+    conn: Mutex<rusqlite::Connection>,
+}
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Opens the SQLite database at the given path, and creates it and its tables if they do not exist.
+    ///
+    /// The usage of the namespaces written before the usage was kept in the database is counted once.
+    ///
+    /// # Argument
+    ///
+    /// * `path` - The path of the database file.
+    ///
+    /// # Error
+    ///
+    /// If fail to open the database, then [WasmEdgeError::Kv(KvError::Storage)](crate::error::KvError) is returned.
+    pub fn open(path: impl AsRef<std::path::Path>) -> WasmEdgeResult<Self> {
+        let mut conn = rusqlite::Connection::open(path).map_err(storage_error)?;
+        // the connections opening the database at the same time are serialized
+        let tx = conn
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+            .map_err(storage_error)?;
+        let tracked: i64 = tx
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name = 'host_kv_insert'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(storage_error)?;
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS host_kv (
+                namespace TEXT NOT NULL,
+                key BLOB NOT NULL,
+                value BLOB NOT NULL,
+                PRIMARY KEY (namespace, key)
+            );
+            CREATE TABLE IF NOT EXISTS host_kv_usage (
+                namespace TEXT NOT NULL PRIMARY KEY,
+                keys INTEGER NOT NULL,
+                bytes INTEGER NOT NULL
+            );",
+        )
+        .map_err(storage_error)?;
+        if tracked == 0 {
+            tx.execute_batch(
+                "DELETE FROM host_kv_usage;
+                INSERT INTO host_kv_usage (namespace, keys, bytes)
+                    SELECT namespace, COUNT(*), SUM(length(key) + length(value))
+                    FROM host_kv GROUP BY namespace;
+                CREATE TRIGGER IF NOT EXISTS host_kv_insert AFTER INSERT ON host_kv BEGIN
+                    INSERT OR IGNORE INTO host_kv_usage (namespace, keys, bytes)
+                        VALUES (NEW.namespace, 0, 0);
+                    UPDATE host_kv_usage
+                        SET keys = keys + 1, bytes = bytes + length(NEW.key) + length(NEW.value)
+                        WHERE namespace = NEW.namespace;
+                END;
+                CREATE TRIGGER IF NOT EXISTS host_kv_update AFTER UPDATE ON host_kv BEGIN
+                    UPDATE host_kv_usage
+                        SET bytes = bytes - length(OLD.key) - length(OLD.value) + length(NEW.key) + length(NEW.value)
+                        WHERE namespace = NEW.namespace;
+                END;
+                CREATE TRIGGER IF NOT EXISTS host_kv_delete AFTER DELETE ON host_kv BEGIN
+                    UPDATE host_kv_usage
+                        SET keys = keys - 1, bytes = bytes - length(OLD.key) - length(OLD.value)
+                        WHERE namespace = OLD.namespace;
+                END;",
+            )
+            .map_err(storage_error)?;
+        }
+        tx.commit().map_err(storage_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, rusqlite::Connection> {
+        self.conn
+            .lock()
+            .expect("[bitbang] the key-value sqlite storage is poisoned")
+    }
+}
+#[cfg(feature = "sqlite")]
+impl KvStorage for SqliteStorage {
+    fn get(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<Option<Vec<u8>>> {
+        use rusqlite::OptionalExtension;
+
+        self.lock()
+            .query_row(
+                "SELECT value FROM host_kv WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(storage_error)
+    }
+
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> WasmEdgeResult<()> {
+        // a replacing insert would not fire the delete trigger, so an existing pair is updated instead
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(storage_error)?;
+        let updated = tx
+            .execute(
+                "UPDATE host_kv SET value = ?3 WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key, value],
+            )
+            .map_err(storage_error)?;
+        if updated == 0 {
+            tx.execute(
+                "INSERT INTO host_kv (namespace, key, value) VALUES (?1, ?2, ?3)",
+                rusqlite::params![namespace, key, value],
+            )
+            .map_err(storage_error)?;
+        }
+        tx.commit().map_err(storage_error)
+    }
+
+    fn delete(&self, namespace: &str, key: &[u8]) -> WasmEdgeResult<bool> {
+        let deleted = self
+            .lock()
+            .execute(
+                "DELETE FROM host_kv WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key],
+            )
+            .map_err(storage_error)?;
+        Ok(deleted > 0)
+    }
+
+    fn list(&self, namespace: &str, prefix: &[u8]) -> WasmEdgeResult<Vec<Vec<u8>>> {
+        let conn = self.lock();
+        let mut stmt = conn
+            .prepare(
+                "SELECT key FROM host_kv WHERE namespace = ?1 AND substr(key, 1, ?2) = ?3 ORDER BY key",
+            )
+            .map_err(storage_error)?;
+        let keys = stmt
+            .query_map(
+                rusqlite::params![namespace, prefix.len() as i64, prefix],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .map_err(storage_error)?;
+        let keys = keys.map(|key| key.map_err(storage_error)).collect();
+        keys
+    }
+
+    fn usage(&self, namespace: &str) -> WasmEdgeResult<KvUsage> {
+        use rusqlite::OptionalExtension;
+
+        let usage: Option<(i64, i64)> = self
+            .lock()
+            .query_row(
+                "SELECT keys, bytes FROM host_kv_usage WHERE namespace = ?1",
+                rusqlite::params![namespace],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(storage_error)?;
+        let (keys, bytes) = usage.unwrap_or_default();
+        Ok(KvUsage {
+            keys: keys.max(0) as usize,
+            bytes: bytes.max(0) as u64,
+        })
+    }
+}
+
+#[cfg(any(feature = "sled", feature = "sqlite"))]
+fn storage_error(err: impl std::fmt::Display) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Kv(KvError::Storage(err.to_string())))
+}
+
+/// Creates the import object named [KV_MODULE_NAME] serving the given namespace to the guests. It exports the following functions:
+///
+/// * `get(key_ptr: i32, key_len: i32, buf_ptr: i32, buf_len: i32) -> i32` copies at most `buf_len` bytes of the value of the key to the buffer, and returns the length of the value, or [KV_NOT_FOUND] if the key is not set.
+///
+/// * `put(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32) -> i32` sets the value of the key, and returns `0`, or [KV_QUOTA_EXCEEDED] if the write would exceed the quota of the namespace.
+///
+/// * `delete(key_ptr: i32, key_len: i32) -> i32` removes the key, and returns `1`, or `0` if the key is not set.
+///
+/// * `list(prefix_ptr: i32, prefix_len: i32, buf_ptr: i32, buf_len: i32) -> i32` copies at most `buf_len` bytes of the keys which start with the prefix to the buffer, each key as its length in a little-endian `u32` followed by its bytes, and returns the length of all the encoded keys.
+///
+/// Every function returns [KV_STORAGE_FAILED] if the storage fails, and traps if a pointer passed to it is out of the bounds of the guest memory.
+///
+/// # Argument
+///
+/// * `namespace` - The namespace of the tenant whose guests import the object.
+///
+/// # Error
+///
+/// If fail to create the import object, then an error is returned.
+pub fn import_object(namespace: &KvNamespace) -> WasmEdgeResult<ImportObject<NeverType>> {
+    let get_ns = namespace.clone();
+    let put_ns = namespace.clone();
+    let delete_ns = namespace.clone();
+    let list_ns = namespace.clone();
+
+    ImportObjectBuilder::new()
+        .with_func::<(i32, i32, i32, i32), i32, NeverType>(
+            "get",
+            move |frame, inputs, _data| {
+                let mut memory = memory(frame, "get")?;
+                let key = read(&memory, &inputs[0], &inputs[1], "get")?;
+                let code = match get_ns.get(key) {
+                    Ok(Some(value)) => {
+                        write(&mut memory, &value, &inputs[2], &inputs[3], "get")?;
+                        value.len() as i32
+                    }
+                    Ok(None) => KV_NOT_FOUND,
+                    Err(_) => KV_STORAGE_FAILED,
+                };
+                Ok(vec![WasmValue::from_i32(code)])
+            },
+            None,
+        )?
+        .with_func::<(i32, i32, i32, i32), i32, NeverType>(
+            "put",
+            move |frame, inputs, _data| {
+                let memory = memory(frame, "put")?;
+                let key = read(&memory, &inputs[0], &inputs[1], "put")?;
+                let value = read(&memory, &inputs[2], &inputs[3], "put")?;
+                let code = match put_ns.put(key, value) {
+                    Ok(()) => 0,
+                    Err(err) => match *err {
+                        WasmEdgeError::Kv(KvError::QuotaExceeded(_)) => KV_QUOTA_EXCEEDED,
+                        _ => KV_STORAGE_FAILED,
+                    },
+                };
+                Ok(vec![WasmValue::from_i32(code)])
+            },
+            None,
+        )?
+        .with_func::<(i32, i32), i32, NeverType>(
+            "delete",
+            move |frame, inputs, _data| {
+                let memory = memory(frame, "delete")?;
+                let key = read(&memory, &inputs[0], &inputs[1], "delete")?;
+                let code = match delete_ns.delete(key) {
+                    Ok(deleted) => deleted as i32,
+                    Err(_) => KV_STORAGE_FAILED,
+                };
+                Ok(vec![WasmValue::from_i32(code)])
+            },
+            None,
+        )?
+        .with_func::<(i32, i32, i32, i32), i32, NeverType>(
+            "list",
+            move |frame, inputs, _data| {
+                let mut memory = memory(frame, "list")?;
+                let prefix = read(&memory, &inputs[0], &inputs[1], "list")?;
+                let code = match list_ns.list(prefix) {
+                    Ok(keys) => {
+                        let mut buf = Vec::new();
+                        for key in keys.iter() {
+                            buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+                            buf.extend_from_slice(key);
+                        }
+                        write(&mut memory, &buf, &inputs[2], &inputs[3], "list")?;
+                        buf.len() as i32
+                    }
+                    Err(_) => KV_STORAGE_FAILED,
+                };
+                Ok(vec![WasmValue::from_i32(code)])
+            },
+            None,
+        )?
+        .build::<NeverType>(KV_MODULE_NAME, None)
+}
+
+/// Returns the default memory of the module instance calling the host function of the given name.
+fn memory(frame: CallingFrame, func: &str) -> Result<Memory, Trap> {
+    Caller::new(frame)
+        .memory(0)
+        .ok_or_else(|| Trap::new(format!("the caller of `{func}` has no memory")))
+}
+
+/// Reads the guest bytes at the given pointer and length.
+fn read(memory: &Memory, ptr: &WasmValue, len: &WasmValue, func: &str) -> Result<Vec<u8>, Trap> {
+    memory
+        .read(ptr.to_i32() as u32, len.to_i32() as u32)
+        .map_err(|_| Trap::new(format!("the data passed to `{func}` is out of bounds")))
+}
+
+/// Copies at most `len` bytes of the data to the guest buffer at the given pointer.
+fn write(
+    memory: &mut Memory,
+    data: &[u8],
+    ptr: &WasmValue,
+    len: &WasmValue,
+    func: &str,
+) -> Result<(), Trap> {
+    let copied = data.len().min(len.to_i32().max(0) as usize);
+    memory
+        .write(&data[..copied], ptr.to_i32() as u32)
+        .map_err(|_| Trap::new(format!("the buffer passed to `{func}` is out of bounds")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, wat2wasm, Executor, Module, Store, WasmVal};
+
+    #[test]
+    fn test_kv_namespace() {
+        let storage: Arc<dyn KvStorage> = Arc::new(MemoryStorage::new());
+        let quota = KvQuota::new()
+            .with_max_keys(2)
+            .with_max_bytes(12)
+            .with_max_value_len(8);
+        let a = KvNamespace::new(Arc::clone(&storage), "a", quota);
+        let b = KvNamespace::new(Arc::clone(&storage), "b", KvQuota::new());
+
+        assert!(a.put("k1", "v1").is_ok());
+        assert!(a.put("k2", "v2").is_ok());
+        assert!(b.put("k1", "other").is_ok());
+        assert!(b.put("z", "").is_ok());
+        assert_eq!(a.get("k1").unwrap(), Some(b"v1".to_vec()));
+        assert_eq!(b.get("k1").unwrap(), Some(b"other".to_vec()));
+        assert_eq!(a.usage().unwrap(), KvUsage { keys: 2, bytes: 8 });
+
+        // the quota is checked before writing
+        let result = a.put("k3", "v3");
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Kv(KvError::QuotaExceeded("a".into()))
+        );
+        assert!(a.put("k1", "too long value").is_err());
+        assert!(a.put("k1", "v1v1v1v1").is_err());
+        assert!(a.put("k1", "v1v1v1").is_ok());
+        assert_eq!(a.usage().unwrap(), KvUsage { keys: 2, bytes: 12 });
+        assert_eq!(a.get("k3").unwrap(), None);
+
+        // deleting frees the quota
+        assert!(a.delete("k2").unwrap());
+        assert!(!a.delete("k2").unwrap());
+        assert!(a.put("k3", "v3").is_ok());
+
+        let result = a.list("k");
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![b"k1".to_vec(), b"k3".to_vec()]);
+        assert_eq!(b.list("k").unwrap(), vec![b"k1".to_vec()]);
+        assert_eq!(b.list("").unwrap(), vec![b"k1".to_vec(), b"z".to_vec()]);
+    }
+
+    #[test]
+    fn test_kv_namespace_usage() {
+        let storage = Arc::new(MemoryStorage::new());
+        let namespace = KvNamespace::new(
+            Arc::clone(&storage) as Arc<dyn KvStorage>,
+            "a",
+            KvQuota::new().with_max_keys(2),
+        );
+
+        // the storage keeps the usage up to date with its writes
+        assert!(namespace.put("k1", "v1").is_ok());
+        assert!(namespace.put("k1", "value").is_ok());
+        assert_eq!(storage.usage("a").unwrap(), KvUsage { keys: 1, bytes: 7 });
+        assert!(!namespace.delete("k2").unwrap());
+        assert_eq!(storage.usage("a").unwrap(), KvUsage { keys: 1, bytes: 7 });
+
+        // the writes made past the namespace count against the quota
+        assert!(storage.put("a", b"k2", b"v2").is_ok());
+        assert_eq!(namespace.usage().unwrap(), KvUsage { keys: 2, bytes: 11 });
+        assert!(namespace.put("k3", "v3").is_err());
+        assert!(namespace.delete("k1").unwrap());
+        assert_eq!(namespace.usage().unwrap(), KvUsage { keys: 1, bytes: 4 });
+        assert!(namespace.put("k3", "v3").is_ok());
+        assert!(namespace.put("k4", "v4").is_err());
+        assert_eq!(storage.usage("b").unwrap(), KvUsage::default());
+    }
+
+    #[test]
+    fn test_kv_import_object() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "host_kv" "get" (func $get (param i32 i32 i32 i32) (result i32)))
+              (import "host_kv" "put" (func $put (param i32 i32 i32 i32) (result i32)))
+              (import "host_kv" "delete" (func $delete (param i32 i32) (result i32)))
+              (import "host_kv" "list" (func $list (param i32 i32 i32 i32) (result i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 0) "count")
+              (data (i32.const 16) "\2a\00\00\00")
+              (func (export "save") (result i32)
+                i32.const 0
+                i32.const 5
+                i32.const 16
+                i32.const 4
+                call $put)
+              ;; returns the length of the value, which is copied to offset 32
+              (func (export "load") (result i32)
+                i32.const 0
+                i32.const 5
+                i32.const 32
+                i32.const 4
+                call $get)
+              (func (export "loaded") (result i32)
+                i32.const 32
+                i32.load)
+              (func (export "remove") (result i32)
+                i32.const 0
+                i32.const 5
+                call $delete)
+              ;; lists all the keys to offset 64
+              (func (export "keys") (result i32)
+                i32.const 0
+                i32.const 0
+                i32.const 64
+                i32.const 64
+                call $list)
+              (func (export "oob") (result i32)
+                i32.const 0
+                i32.const 5
+                i32.const 65535
+                i32.const 4
+                call $get)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let storage: Arc<dyn KvStorage> = Arc::new(MemoryStorage::new());
+        let namespace = KvNamespace::new(
+            Arc::clone(&storage),
+            "tenant",
+            KvQuota::new().with_max_keys(1),
+        );
+        let result = import_object(&namespace);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+        let module = Module::from_bytes(None, &wasm_bytes).unwrap();
+        let instance = store
+            .register_active_module(&mut executor, &module)
+            .unwrap();
+        let run = |name: &str| {
+            let result = executor.run_func(&instance.func(name).unwrap(), params!());
+            assert!(result.is_ok());
+            result.unwrap()[0].to_i32()
+        };
+
+        assert_eq!(run("load"), KV_NOT_FOUND);
+        assert_eq!(run("save"), 0);
+        assert_eq!(namespace.get("count").unwrap(), Some(vec![42, 0, 0, 0]));
+        assert_eq!(run("load"), 4);
+        assert_eq!(run("loaded"), 42);
+        assert_eq!(run("keys"), 9);
+
+        // the quota applies to the guests
+        assert!(namespace.put("other", "").is_err());
+        assert!(storage.put("tenant", b"other", b"").is_ok());
+        assert_eq!(run("remove"), 1);
+        assert_eq!(run("remove"), 0);
+        assert_eq!(run("save"), KV_QUOTA_EXCEEDED);
+        assert!(storage.delete("tenant", b"other").unwrap());
+        assert_eq!(run("save"), 0);
+
+        // the out-of-bounds buffers trap
+        let result = executor.run_func(&instance.func("oob").unwrap(), params!());
+        assert!(result.is_err());
+    }
+}
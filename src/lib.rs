@@ -85,54 +85,113 @@
 //! This project is licensed under the terms of the [Apache 2.0 license](https://github.com/tensorflow/rust/blob/HEAD/LICENSE).
 //!
 
+// lets the code generated by the derive macros refer to this crate by name within the crate itself
+extern crate self as bitbang;
+
+#[cfg(feature = "aot")]
+#[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
+pub mod aot;
+mod bench;
+mod binary;
+pub mod bindgen;
+pub mod call;
 #[doc(hidden)]
 pub mod caller;
+#[cfg(feature = "capi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capi")))]
+pub mod capi;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod codec;
 #[doc(hidden)]
 #[cfg(feature = "aot")]
 #[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
 mod compiler;
 pub mod config;
+pub mod coverage;
+mod determinism;
+pub mod diagnostics;
 pub mod dock;
 mod executor;
 mod externals;
+mod host_api;
 mod import;
 mod instance;
 #[doc(hidden)]
 pub mod io;
+pub mod kv;
+mod linker;
 #[doc(hidden)]
 pub mod log;
+pub mod manifest;
+pub mod marshal;
+pub mod middleware;
 mod module;
 pub mod plugin;
+pub mod pool;
+pub mod replay;
+mod runner;
+pub mod runtime;
+mod scope;
 mod statistics;
 mod store;
+mod task;
+pub mod tenant;
+#[cfg(feature = "aot")]
+mod tier;
+pub mod transform;
 pub mod types;
 pub mod utils;
 #[doc(hidden)]
 pub mod vm;
 pub mod wasi;
 
+#[doc(inline)]
+pub use bench::{BenchOptions, BenchReport};
 pub use caller::Caller;
 #[doc(inline)]
 #[cfg(feature = "aot")]
 #[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
 pub use compiler::Compiler;
 #[doc(inline)]
-pub use executor::Executor;
+pub use determinism::{DeterminismIssue, DeterminismReport, NonDeterminism};
 #[doc(inline)]
-pub use externals::{Func, FuncRef, FuncTypeBuilder, Global, Memory, Table};
+pub use executor::{Executor, ExecutorBuilder};
+#[doc(inline)]
+pub use externals::{
+    AtomicWaitResult, CallContext, ExternTable, Func, FuncRef, FuncTypeBuilder, Global,
+    IntoHostFunc, Memory, MemoryImage, Table, WeakFunc,
+};
+#[doc(inline)]
+pub use host_api::{ApiVersion, HostApi, SharedFunc};
 #[doc(inline)]
 pub use import::{ImportObject, ImportObjectBuilder};
-pub use instance::{AsInstance, Instance};
+pub use instance::{AsInstance, Instance, TableFunc, WeakInstance};
 #[doc(inline)]
-pub use io::{WasmVal, WasmValType, WasmValTypeList};
+pub use io::{
+    FromWasmVal, FromWasmValList, HostFuncReturn, IntoWasmValList, WasmVal, WasmValType,
+    WasmValTypeList,
+};
+#[doc(inline)]
+pub use linker::{ImportAliases, Linker};
 #[doc(inline)]
 pub use log::LogManager;
 #[doc(inline)]
-pub use module::{ExportType, ImportType, Module};
+pub use module::{ExportType, ImportType, Module, NameSection};
+#[doc(inline)]
+pub use replay::ReplayBundle;
+#[doc(inline)]
+pub use runner::{run_wasm_file, RunOptions, RunOutput};
+#[doc(inline)]
+pub use scope::{scoped_state, scoped_state_as, OwnedState, Scope, StateMarker};
 #[doc(inline)]
-pub use statistics::Statistics;
+pub use statistics::{ExecutionReport, HostFuncMetrics, HostFuncReport, Statistics};
 #[doc(inline)]
-pub use store::Store;
+pub use store::{LiveObjects, RegisterOptions, Store};
+#[doc(inline)]
+#[cfg(feature = "aot")]
+#[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
+pub use tier::CompilationState;
 #[doc(inline)]
 pub use vm::{Vm, VmBuilder};
 
@@ -147,6 +206,9 @@ pub use bit_macro::host_function;
 /// WebAssembly value type.
 pub type WasmValue = bit_sys::types::WasmValue;
 
+/// The usage statistics of a [memory](crate::Memory), such as the peak size and the number of grow events.
+pub type MemoryStats = bit_sys::MemStat;
+
 /// This is a workaround solution to the [`never`](https://doc.rust-lang.org/std/primitive.never.html) type in Rust. It will be replaced by `!` once it is stable.
 pub type NeverType = bit_types::NeverType;
 
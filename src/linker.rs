@@ -0,0 +1,497 @@
+//! Defines Linker, which instantiates a graph of modules importing each other's exports, and ImportAliases, which renames the imports of the modules added to it.
+
+use crate::{
+    binary::Binary,
+    config::Config,
+    error::{LinkerError, WasmEdgeError},
+    Executor, Instance, Module, Store, WasmEdgeResult,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+/// Instantiates a set of interdependent [modules](crate::Module) in the order of their dependencies.
+///
+/// Each module is added under the name other modules import it by. [Linker::instantiate_graph] resolves the dependencies from the imports of the modules, and registers the modules into a [store](crate::Store) as named [module instances](crate::Instance), so that every module is instantiated after the modules it imports from.
+///
+/// ```ignore
+/// let instances = Linker::new()
+///     .with_module("app", app)?
+///     .with_module("libc", libc)?
+///     .with_module("alloc", alloc)?
+///     .instantiate_graph(&mut store, &mut executor)?;
+/// let main = instances["app"].func("main")?;
+/// ```
+///
+/// The imports of the modules added from bytes can be renamed with [ImportAliases], so that old guest binaries keep running after the host APIs are renamed.
+#[derive(Debug, Clone, Default)]
+pub struct Linker {
+    modules: Vec<(String, Module)>,
+    aliases: ImportAliases,
+}
+impl Linker {
+    /// Creates a new [Linker] without any modules.
+    pub fn new() -> Self {
+        Self {
+            modules: Vec::new(),
+            aliases: ImportAliases::default(),
+        }
+    }
+
+    /// Sets the aliases applied to the imports of the modules added afterwards with [Linker::with_module_bytes].
+    ///
+    /// # Argument
+    ///
+    /// * `aliases` - The import aliases.
+    pub fn with_aliases(self, aliases: ImportAliases) -> Self {
+        Self { aliases, ..self }
+    }
+
+    /// Loads a [module](crate::Module) from the given bytes after renaming its imports with the [aliases](crate::ImportAliases) of the linker, and adds it to the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name under which the module is registered, and by which other modules import from it.
+    ///
+    /// * `config` - The global configuration to load the module with.
+    ///
+    /// * `bytes` - The wasm bytes of the module.
+    ///
+    /// # Error
+    ///
+    /// If fail to rename the imports or to load the module, or a module of the same name is already added, then an error is returned.
+    pub fn with_module_bytes(
+        self,
+        name: impl AsRef<str>,
+        config: Option<&Config>,
+        bytes: impl AsRef<[u8]>,
+    ) -> WasmEdgeResult<Self> {
+        let module = Module::from_bytes(config, self.aliases.apply(bytes.as_ref())?)?;
+        self.with_module(name, module)
+    }
+
+    /// Adds a [module](crate::Module) to the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name under which the module is registered, and by which other modules import from it.
+    ///
+    /// * `module` - The validated [module](crate::Module) to add.
+    ///
+    /// # Error
+    ///
+    /// If a module of the same name is already added, then [WasmEdgeError::Linker(LinkerError::DuplicateModule)](crate::error::LinkerError) is returned.
+    pub fn with_module(mut self, name: impl AsRef<str>, module: Module) -> WasmEdgeResult<Self> {
+        let name = name.as_ref();
+        if self.modules.iter().any(|(n, _)| n == name) {
+            return Err(Box::new(WasmEdgeError::Linker(
+                LinkerError::DuplicateModule(name.into()),
+            )));
+        }
+        self.modules.push((name.into(), module));
+        Ok(self)
+    }
+
+    /// Returns the names of the modules in the order in which they are instantiated.
+    ///
+    /// # Argument
+    ///
+    /// * `store` - The [store](crate::Store) the modules are to be registered into. The imports from the modules outside the graph are resolved against the named module instances in it.
+    ///
+    /// # Error
+    ///
+    /// * If an import can not be resolved, then [WasmEdgeError::Linker(LinkerError::UnresolvedImport)](crate::error::LinkerError) is returned.
+    ///
+    /// * If the modules import from each other in a cycle, then [WasmEdgeError::Linker(LinkerError::Cycle)](crate::error::LinkerError) is returned with the modules on the cycle.
+    pub fn instantiation_order(&self, store: &Store) -> WasmEdgeResult<Vec<String>> {
+        let index: HashMap<&str, usize> = self
+            .modules
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, _))| (name.as_str(), idx))
+            .collect();
+
+        // collect the dependencies of each module
+        let mut deps: Vec<Vec<usize>> = Vec::with_capacity(self.modules.len());
+        for (name, module) in self.modules.iter() {
+            let mut module_deps = Vec::new();
+            for import in module.imports() {
+                let import_module = import.module_name();
+                match index.get(import_module.as_ref()) {
+                    Some(dep) => {
+                        if !module_deps.contains(dep) {
+                            module_deps.push(*dep);
+                        }
+                    }
+                    None if store.contains(import_module.as_ref()) => {}
+                    None => {
+                        return Err(Box::new(WasmEdgeError::Linker(
+                            LinkerError::UnresolvedImport {
+                                module: name.clone(),
+                                import_module: import_module.into_owned(),
+                                name: import.name().into_owned(),
+                            },
+                        )))
+                    }
+                }
+            }
+            deps.push(module_deps);
+        }
+
+        // topologically sort the modules, preferring the order in which they are added
+        let mut order = Vec::with_capacity(self.modules.len());
+        let mut done = vec![false; self.modules.len()];
+        while order.len() < self.modules.len() {
+            let next = (0..self.modules.len())
+                .find(|&idx| !done[idx] && deps[idx].iter().all(|&dep| done[dep]));
+            match next {
+                Some(idx) => {
+                    done[idx] = true;
+                    order.push(self.modules[idx].0.clone());
+                }
+                None => {
+                    let cycle = find_cycle(&deps, &done)
+                        .into_iter()
+                        .map(|idx| self.modules[idx].0.clone())
+                        .collect();
+                    return Err(Box::new(WasmEdgeError::Linker(LinkerError::Cycle(cycle))));
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Registers all the modules into the given [store](crate::Store) as named [module instances](crate::Instance) in the order of their dependencies, and returns the module instances keyed by their names.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The [store](crate::Store) to register the modules into.
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the start functions of the modules.
+    ///
+    /// # Error
+    ///
+    /// * If the instantiation order can not be resolved, then an error is returned. See [Linker::instantiation_order].
+    ///
+    /// * If fail to instantiate a module, then [WasmEdgeError::Linker(LinkerError::Instantiate)](crate::error::LinkerError) is returned with the name of the module. The modules instantiated before it remain registered in the store.
+    pub fn instantiate_graph(
+        &self,
+        store: &mut Store,
+        executor: &mut Executor,
+    ) -> WasmEdgeResult<HashMap<String, Instance>> {
+        let order = self.instantiation_order(store)?;
+
+        let mut instances = HashMap::new();
+        for name in order {
+            let (_, module) = self.modules.iter().find(|(n, _)| *n == name).unwrap();
+            let instance = store
+                .register_named_module(executor, &name, module)
+                .map_err(|error| {
+                    Box::new(WasmEdgeError::Linker(LinkerError::Instantiate {
+                        module: name.clone(),
+                        error,
+                    }))
+                })?;
+            instances.insert(name, instance);
+        }
+
+        Ok(instances)
+    }
+}
+
+/// Defines the aliases which rename the imports of guest modules before they are loaded.
+///
+/// A namespace alias renames the module name of all the imports from a module, and an import alias renames a single import, possibly to another module. The import aliases take precedence over the namespace aliases:
+///
+/// ```ignore
+/// let aliases = ImportAliases::new()
+///     .with_namespace("wasi_unstable", "wasi_snapshot_preview1")
+///     .with_import(("env", "old_fn"), ("host", "new_fn"));
+/// let linker = Linker::new()
+///     .with_aliases(aliases)
+///     .with_module_bytes("app", None, wasm_bytes)?;
+/// ```
+///
+/// The renamed imports must still match the types of the exports they resolve to.
+#[derive(Debug, Clone, Default)]
+pub struct ImportAliases {
+    namespaces: HashMap<String, String>,
+    imports: HashMap<(String, String), (String, String)>,
+}
+impl ImportAliases {
+    /// Creates an empty set of aliases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renames the module name of all the imports from the module `from` to `to`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The module name imported by the guests.
+    ///
+    /// * `to` - The module name to import from instead.
+    pub fn with_namespace(mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        self.namespaces
+            .insert(from.as_ref().to_string(), to.as_ref().to_string());
+        self
+    }
+
+    /// Renames the import of `from` as a `(module, name)` pair to `to`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The module name and the name of the import in the guests.
+    ///
+    /// * `to` - The module name and the name to import instead.
+    pub fn with_import(
+        mut self,
+        from: (impl AsRef<str>, impl AsRef<str>),
+        to: (impl AsRef<str>, impl AsRef<str>),
+    ) -> Self {
+        self.imports.insert(
+            (from.0.as_ref().to_string(), from.1.as_ref().to_string()),
+            (to.0.as_ref().to_string(), to.1.as_ref().to_string()),
+        );
+        self
+    }
+
+    /// Returns the given module bytes with the imports renamed. The bytes are returned as is if no import is renamed.
+    ///
+    /// # Argument
+    ///
+    /// * `bytes` - The wasm bytes of the module.
+    ///
+    /// # Error
+    ///
+    /// If the bytes are not a valid module binary, then an error is returned.
+    pub fn apply<'a>(&self, bytes: &'a [u8]) -> WasmEdgeResult<Cow<'a, [u8]>> {
+        if self.namespaces.is_empty() && self.imports.is_empty() {
+            return Ok(Cow::Borrowed(bytes));
+        }
+        let mut binary = Binary::parse(bytes)?;
+        let renamed = binary.rename_imports(|module, name| {
+            self.imports
+                .get(&(module.to_string(), name.to_string()))
+                .cloned()
+                .or_else(|| {
+                    self.namespaces
+                        .get(module)
+                        .map(|module| (module.clone(), name.to_string()))
+                })
+        })?;
+        match renamed {
+            0 => Ok(Cow::Borrowed(bytes)),
+            _ => Ok(Cow::Owned(binary.encode())),
+        }
+    }
+}
+
+/// Returns a cycle among the modules which are not done, starting and ending with the same module.
+fn find_cycle(deps: &[Vec<usize>], done: &[bool]) -> Vec<usize> {
+    // every pending module depends on another pending module, so walking the pending dependencies must revisit a module
+    let mut path = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = (0..deps.len()).find(|&idx| !done[idx]).unwrap();
+    while visited.insert(current) {
+        path.push(current);
+        current = *deps[current].iter().find(|&&dep| !done[dep]).unwrap();
+    }
+
+    let start = path.iter().position(|&idx| idx == current).unwrap();
+    let mut cycle = path.split_off(start);
+    cycle.push(current);
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, wat2wasm, WasmVal};
+
+    fn module(wat: &str) -> Module {
+        let wasm_bytes = wat2wasm(wat.as_bytes()).unwrap();
+        Module::from_bytes(None, wasm_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_linker_instantiate_graph() {
+        let app = module(
+            r#"
+            (module
+              (import "math" "double" (func $double (param i32) (result i32)))
+              (import "base" "one" (func $one (result i32)))
+              (func (export "run") (result i32)
+                call $one
+                call $double)
+            )
+            "#,
+        );
+        let math = module(
+            r#"
+            (module
+              (import "base" "one" (func $one (result i32)))
+              (func (export "double") (param i32) (result i32)
+                local.get 0
+                local.get 0
+                i32.add)
+            )
+            "#,
+        );
+        let base = module(
+            r#"
+            (module
+              (func (export "one") (result i32)
+                i32.const 1)
+            )
+            "#,
+        );
+
+        let result = Linker::new()
+            .with_module("app", app)
+            .and_then(|linker| linker.with_module("math", math))
+            .and_then(|linker| linker.with_module("base", base));
+        assert!(result.is_ok());
+        let linker = result.unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = linker.instantiation_order(&store);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), ["base", "math", "app"]);
+
+        let result = linker.instantiate_graph(&mut store, &mut executor);
+        assert!(result.is_ok());
+        let instances = result.unwrap();
+        assert_eq!(instances.len(), 3);
+        assert_eq!(store.named_instance_count(), 3);
+
+        let run = instances["app"].func("run").unwrap();
+        let result = executor.run_func(&run, params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 2);
+    }
+
+    #[test]
+    fn test_linker_import_aliases() {
+        let app = wat2wasm(
+            br#"
+            (module
+              (import "base_v1" "one" (func $one (result i32)))
+              (import "math" "twice" (func $twice (param i32) (result i32)))
+              (func (export "run") (result i32)
+                call $one
+                call $twice)
+            )
+            "#,
+        )
+        .unwrap();
+        let base = module(r#"(module (func (export "one") (result i32) i32.const 1))"#);
+        let math = module(
+            r#"
+            (module
+              (func (export "double") (param i32) (result i32)
+                local.get 0
+                i32.const 2
+                i32.mul)
+            )
+            "#,
+        );
+
+        // the module can not be linked without the aliases
+        let result = Linker::new()
+            .with_module_bytes("app", None, &app)
+            .and_then(|linker| linker.with_module("base", base.clone()))
+            .and_then(|linker| linker.with_module("math", math.clone()));
+        assert!(result.is_ok());
+        let store = Store::new().unwrap();
+        assert!(result.unwrap().instantiation_order(&store).is_err());
+
+        let aliases = ImportAliases::new()
+            .with_namespace("base_v1", "base")
+            .with_import(("math", "twice"), ("math", "double"));
+        let result = Linker::new()
+            .with_aliases(aliases)
+            .with_module_bytes("app", None, &app)
+            .and_then(|linker| linker.with_module("base", base))
+            .and_then(|linker| linker.with_module("math", math));
+        assert!(result.is_ok());
+        let linker = result.unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let result = linker.instantiate_graph(&mut store, &mut executor);
+        assert!(result.is_ok());
+        let run = result.unwrap()["app"].func("run").unwrap();
+        let result = executor.run_func(&run, params!());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 2);
+
+        // the bytes are kept as is if no import is renamed
+        let result = ImportAliases::new()
+            .with_namespace("other", "base")
+            .apply(&app);
+        assert!(result.is_ok());
+        assert!(matches!(result.unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_linker_diagnostics() {
+        let result = Store::new();
+        assert!(result.is_ok());
+        let store = result.unwrap();
+
+        let a = module(r#"(module (import "b" "f" (func)))"#);
+        let b = module(r#"(module (import "c" "f" (func)) (func (export "f")))"#);
+        let c = module(r#"(module (import "b" "f" (func)) (func (export "f")))"#);
+
+        // duplicate module
+        let result = Linker::new()
+            .with_module("a", a.clone())
+            .and_then(|linker| linker.with_module("a", a.clone()));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Linker(LinkerError::DuplicateModule("a".into()))
+        );
+
+        // unresolved import
+        let linker = Linker::new().with_module("a", a.clone()).unwrap();
+        let result = linker.instantiation_order(&store);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Linker(LinkerError::UnresolvedImport {
+                module: "a".into(),
+                import_module: "b".into(),
+                name: "f".into(),
+            })
+        );
+
+        // cycle
+        let linker = Linker::new()
+            .with_module("a", a)
+            .and_then(|linker| linker.with_module("b", b))
+            .and_then(|linker| linker.with_module("c", c))
+            .unwrap();
+        let result = linker.instantiation_order(&store);
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(
+            *err,
+            WasmEdgeError::Linker(LinkerError::Cycle(vec!["b".into(), "c".into(), "b".into()]))
+        );
+        assert_eq!(
+            err.to_string(),
+            "Found a cycle of module imports: b -> c -> b"
+        );
+    }
+}
@@ -0,0 +1,596 @@
+//! Defines the manifest of a module, returned by [Module::manifest](crate::Module::manifest).
+//!
+//! A manifest summarizes what a module needs from its host and what it offers, without instantiating it: the imported namespaces and their functions, the WebAssembly proposals the module requires, the limits of its memories and tables, and its exports. A plugin store, for example, can show the manifest before a module is installed, and check the required proposals against the [config](crate::config::Config) of the host:
+//!
+//! ```ignore
+//! let module = Module::from_bytes(None, wasm_bytes)?;
+//! let manifest = module.manifest()?;
+//! for namespace in manifest.namespaces() {
+//!     println!("imports from {namespace}");
+//! }
+//! let missing = manifest.unsupported_proposals(&config);
+//! ```
+//!
+//! With the `serde` feature, a manifest can be serialized to JSON with [to_json](crate::manifest::ModuleManifest::to_json).
+
+#[cfg(feature = "serde")]
+use crate::error::{MarshalError, WasmEdgeError};
+use crate::{
+    binary::{Binary, Limits, Reader, EXTERNAL_GLOBAL, EXTERNAL_MEMORY, EXTERNAL_TABLE},
+    config::Config,
+    ExternalInstanceType, Module, Mutability, RefType, ValType, WasmEdgeResult,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// The WebAssembly proposals a module can require, named after the [config options](crate::config::CommonConfigOptions) enabling them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Proposal {
+    /// Imported or exported mutable globals.
+    MutableGlobals,
+    /// The saturating float-to-int conversions, such as `i32.trunc_sat_f32_s`.
+    NonTrapConversions,
+    /// The sign-extension instructions, such as `i32.extend8_s`.
+    SignExtensionOperators,
+    /// The functions and blocks with multiple results.
+    MultiValue,
+    /// The bulk memory and table instructions, such as `memory.copy`.
+    BulkMemoryOperations,
+    /// The reference types, the `externref` values, and multiple tables.
+    ReferenceTypes,
+    /// The 128-bit SIMD instructions and values.
+    Simd,
+    /// More than one memory.
+    MultiMemories,
+    /// The shared memories and the atomic instructions.
+    Threads,
+    /// The tail calls, such as `return_call`.
+    TailCall,
+}
+impl Proposal {
+    /// Returns whether the proposal is enabled in the given [config](crate::config::Config).
+    ///
+    /// # Argument
+    ///
+    /// * `config` - The config to check.
+    pub fn is_enabled(&self, config: &Config) -> bool {
+        match self {
+            Proposal::MutableGlobals => config.mutable_globals_enabled(),
+            Proposal::NonTrapConversions => config.non_trap_conversions_enabled(),
+            Proposal::SignExtensionOperators => config.sign_extension_operators_enabled(),
+            Proposal::MultiValue => config.multi_value_enabled(),
+            Proposal::BulkMemoryOperations => config.bulk_memory_operations_enabled(),
+            Proposal::ReferenceTypes => config.reference_types_enabled(),
+            Proposal::Simd => config.simd_enabled(),
+            Proposal::MultiMemories => config.multi_memories_enabled(),
+            Proposal::Threads => config.threads_enabled(),
+            Proposal::TailCall => config.tail_call_enabled(),
+        }
+    }
+}
+impl std::fmt::Display for Proposal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Proposal::MutableGlobals => write!(f, "mutable_globals"),
+            Proposal::NonTrapConversions => write!(f, "non_trap_conversions"),
+            Proposal::SignExtensionOperators => write!(f, "sign_extension_operators"),
+            Proposal::MultiValue => write!(f, "multi_value"),
+            Proposal::BulkMemoryOperations => write!(f, "bulk_memory_operations"),
+            Proposal::ReferenceTypes => write!(f, "reference_types"),
+            Proposal::Simd => write!(f, "simd"),
+            Proposal::MultiMemories => write!(f, "multi_memories"),
+            Proposal::Threads => write!(f, "threads"),
+            Proposal::TailCall => write!(f, "tail_call"),
+        }
+    }
+}
+
+/// Describes the type of an imported or exported entity. The value types are named as in the text format, such as `i32` and `funcref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum ItemType {
+    /// A function with its parameter and result types.
+    Func {
+        params: Vec<String>,
+        results: Vec<String>,
+    },
+    /// A table with its element type and its limits, in elements.
+    Table {
+        elem_ty: String,
+        min: u32,
+        max: Option<u32>,
+    },
+    /// A memory with its limits, in pages of 64 KiB.
+    Memory {
+        min_pages: u32,
+        max_pages: Option<u32>,
+        shared: bool,
+    },
+    /// A global with its value type.
+    Global { val_ty: String, mutable: bool },
+}
+impl From<ExternalInstanceType> for ItemType {
+    fn from(ty: ExternalInstanceType) -> Self {
+        match ty {
+            ExternalInstanceType::Func(ty) => ItemType::Func {
+                params: ty
+                    .args()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|ty| val_type_name(*ty))
+                    .collect(),
+                results: ty
+                    .returns()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|ty| val_type_name(*ty))
+                    .collect(),
+            },
+            ExternalInstanceType::Table(ty) => ItemType::Table {
+                elem_ty: match ty.elem_ty() {
+                    RefType::FuncRef => "funcref",
+                    RefType::ExternRef => "externref",
+                }
+                .to_string(),
+                min: ty.minimum(),
+                max: ty.maximum(),
+            },
+            ExternalInstanceType::Memory(ty) => ItemType::Memory {
+                min_pages: ty.minimum(),
+                max_pages: ty.maximum(),
+                shared: ty.shared(),
+            },
+            ExternalInstanceType::Global(ty) => ItemType::Global {
+                val_ty: val_type_name(ty.value_ty()),
+                mutable: ty.mutability() == Mutability::Var,
+            },
+        }
+    }
+}
+
+/// Describes an imported or exported entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ManifestItem {
+    /// The name of the entity.
+    pub name: String,
+    /// The type of the entity.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub ty: ItemType,
+}
+
+/// Describes the entities a module imports from a namespace, which is the name of a module instance or an import object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImportNamespace {
+    /// The name of the namespace.
+    pub namespace: String,
+    /// The imported entities, in the order of the imports.
+    pub items: Vec<ManifestItem>,
+}
+
+/// Describes the limits of a memory of a module, either imported or defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryLimits {
+    /// The initial number of pages of 64 KiB.
+    pub min_pages: u32,
+    /// The maximum number of pages, or `None` if the memory can grow up to the limit of the host.
+    pub max_pages: Option<u32>,
+    /// Whether the memory is shared between threads.
+    pub shared: bool,
+    /// Whether the memory is imported.
+    pub imported: bool,
+}
+
+/// Describes the limits of a table of a module, either imported or defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TableLimits {
+    /// The element type, either `funcref` or `externref`.
+    pub elem_ty: String,
+    /// The initial number of elements.
+    pub min: u32,
+    /// The maximum number of elements, or `None` if the table can grow up to the limit of the host.
+    pub max: Option<u32>,
+    /// Whether the table is imported.
+    pub imported: bool,
+}
+
+/// Defines the structured summary of a module. See the [module-level documentation](crate::manifest) for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModuleManifest {
+    /// The imports, grouped by namespace in the order in which the namespaces first appear.
+    pub imports: Vec<ImportNamespace>,
+    /// The proposals the module requires beyond the WebAssembly 1.0 specification, in the order of [Proposal].
+    pub proposals: Vec<Proposal>,
+    /// The memories in the memory index space, in which the imported memories come first.
+    pub memories: Vec<MemoryLimits>,
+    /// The tables in the table index space, in which the imported tables come first.
+    pub tables: Vec<TableLimits>,
+    /// The exports, in the order of the export section.
+    pub exports: Vec<ManifestItem>,
+}
+impl ModuleManifest {
+    /// Returns the names of the imported namespaces.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.imports
+            .iter()
+            .map(|namespace| namespace.namespace.as_str())
+    }
+
+    /// Returns the exported functions, which are the entrypoints of the module.
+    pub fn entrypoints(&self) -> impl Iterator<Item = &ManifestItem> {
+        self.exports
+            .iter()
+            .filter(|item| matches!(item.ty, ItemType::Func { .. }))
+    }
+
+    /// Returns the required proposals which are not enabled in the given [config](crate::config::Config).
+    ///
+    /// # Argument
+    ///
+    /// * `config` - The config the module is to be loaded with.
+    pub fn unsupported_proposals(&self, config: &Config) -> Vec<Proposal> {
+        self.proposals
+            .iter()
+            .copied()
+            .filter(|proposal| !proposal.is_enabled(config))
+            .collect()
+    }
+
+    /// Serializes the manifest into JSON.
+    ///
+    /// # Error
+    ///
+    /// If fail to serialize the manifest, then [WasmEdgeError::Marshal(MarshalError::Codec)](crate::error::MarshalError) is returned.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn to_json(&self) -> WasmEdgeResult<String> {
+        serde_json::to_string_pretty(self).map_err(codec_error)
+    }
+
+    /// Deserializes a manifest from JSON.
+    ///
+    /// # Argument
+    ///
+    /// * `json` - The JSON created by [to_json](crate::manifest::ModuleManifest::to_json).
+    ///
+    /// # Error
+    ///
+    /// If fail to deserialize the manifest, then [WasmEdgeError::Marshal(MarshalError::Codec)](crate::error::MarshalError) is returned.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn from_json(json: impl AsRef<str>) -> WasmEdgeResult<Self> {
+        serde_json::from_str(json.as_ref()).map_err(codec_error)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn codec_error(err: impl std::fmt::Display) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Marshal(MarshalError::Codec(err.to_string())))
+}
+
+/// The parts of a manifest which are read from the module binary, since the WasmEdge library does not expose them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BinaryManifest {
+    proposals: Vec<Proposal>,
+    memories: Vec<MemoryLimits>,
+    tables: Vec<TableLimits>,
+}
+
+/// Creates the manifest of the given module from its imports and exports, and the parts read from its binary.
+pub(crate) fn build(module: &Module, parts: &BinaryManifest) -> WasmEdgeResult<ModuleManifest> {
+    let mut imports: Vec<ImportNamespace> = Vec::new();
+    for import in module.imports() {
+        let item = ManifestItem {
+            name: import.name().to_string(),
+            ty: import.ty()?.into(),
+        };
+        let namespace = import.module_name();
+        match imports.iter_mut().find(|ns| ns.namespace == namespace) {
+            Some(ns) => ns.items.push(item),
+            None => imports.push(ImportNamespace {
+                namespace: namespace.to_string(),
+                items: vec![item],
+            }),
+        }
+    }
+
+    let mut exports = Vec::new();
+    for export in module.exports() {
+        exports.push(ManifestItem {
+            name: export.name().to_string(),
+            ty: export.ty()?.into(),
+        });
+    }
+
+    Ok(ModuleManifest {
+        imports,
+        proposals: parts.proposals.clone(),
+        memories: parts.memories.clone(),
+        tables: parts.tables.clone(),
+        exports,
+    })
+}
+
+/// Reads the proposals required by the module binary, and the limits of its memories and tables.
+pub(crate) fn analyze(binary: &Binary) -> WasmEdgeResult<BinaryManifest> {
+    let mut proposals = BTreeSet::new();
+    let layout = binary.layout()?;
+    let imports = binary.imports()?;
+
+    // memories and tables
+    let imported_memories = imports
+        .iter()
+        .filter(|(_, _, kind)| *kind == EXTERNAL_MEMORY)
+        .count();
+    let memories: Vec<MemoryLimits> = layout
+        .memories
+        .iter()
+        .enumerate()
+        .map(|(idx, limits)| MemoryLimits {
+            min_pages: limits.min,
+            max_pages: limits.max,
+            shared: limits.shared,
+            imported: idx < imported_memories,
+        })
+        .collect();
+    if memories.len() > 1 {
+        proposals.insert(Proposal::MultiMemories);
+    }
+    if memories.iter().any(|memory| memory.shared) {
+        proposals.insert(Proposal::Threads);
+    }
+
+    let imported_tables = imports
+        .iter()
+        .filter(|(_, _, kind)| *kind == EXTERNAL_TABLE)
+        .count();
+    let tables: Vec<TableLimits> = layout
+        .tables
+        .iter()
+        .enumerate()
+        .map(|(idx, (elem_ty, Limits { min, max, .. }))| TableLimits {
+            elem_ty: ref_type_name(*elem_ty).to_string(),
+            min: *min,
+            max: *max,
+            imported: idx < imported_tables,
+        })
+        .collect();
+    if tables.len() > 1 || tables.iter().any(|table| table.elem_ty != "funcref") {
+        proposals.insert(Proposal::ReferenceTypes);
+    }
+
+    // globals
+    let imported_globals = imports
+        .iter()
+        .filter(|(_, _, kind)| *kind == EXTERNAL_GLOBAL)
+        .count();
+    let exported_globals = binary
+        .exports()?
+        .into_iter()
+        .filter(|(_, kind, _)| *kind == EXTERNAL_GLOBAL)
+        .map(|(_, _, idx)| idx as usize)
+        .collect::<Vec<_>>();
+    for (idx, (val_ty, mutable)) in layout.globals.iter().enumerate() {
+        if *mutable && (idx < imported_globals || exported_globals.contains(&idx)) {
+            proposals.insert(Proposal::MutableGlobals);
+        }
+        if let Some(proposal) = val_type_proposal(*val_ty) {
+            proposals.insert(proposal);
+        }
+    }
+
+    // function types
+    for ty in binary.func_types()? {
+        if ty.returns_len() > 1 {
+            proposals.insert(Proposal::MultiValue);
+        }
+        let args = ty.args().unwrap_or_default().iter();
+        for val_ty in args.chain(ty.returns().unwrap_or_default()) {
+            match val_ty {
+                ValType::V128 => {
+                    proposals.insert(Proposal::Simd);
+                }
+                ValType::FuncRef | ValType::ExternRef => {
+                    proposals.insert(Proposal::ReferenceTypes);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // instructions
+    for body in binary.func_bodies()? {
+        let mut r = Reader::new(body);
+        while !r.is_empty() {
+            // a block type given by a type index may have parameters or multiple results
+            if let [0x02..=0x04, ty, ..] = r.rest() {
+                if !matches!(ty, 0x40 | 0x6f | 0x70 | 0x7b..=0x7f) {
+                    proposals.insert(Proposal::MultiValue);
+                }
+            }
+            if let Some(proposal) = instr_proposal(r.instr()?) {
+                proposals.insert(proposal);
+            }
+        }
+    }
+
+    Ok(BinaryManifest {
+        proposals: proposals.into_iter().collect(),
+        memories,
+        tables,
+    })
+}
+
+/// Returns the proposal which introduces the given instruction, if any.
+fn instr_proposal(instr: (u8, u32)) -> Option<Proposal> {
+    match instr {
+        (0x12 | 0x13, _) => Some(Proposal::TailCall),
+        (0x1c | 0x25 | 0x26 | 0xd0..=0xd2, _) => Some(Proposal::ReferenceTypes),
+        (0xc0..=0xc4, _) => Some(Proposal::SignExtensionOperators),
+        (0xfc, 0..=7) => Some(Proposal::NonTrapConversions),
+        (0xfc, 8..=14) => Some(Proposal::BulkMemoryOperations),
+        (0xfc, _) => Some(Proposal::ReferenceTypes),
+        (0xfd, _) => Some(Proposal::Simd),
+        (0xfe, _) => Some(Proposal::Threads),
+        _ => None,
+    }
+}
+
+/// Returns the proposal which introduces the given value type, if any.
+fn val_type_proposal(val_ty: u8) -> Option<Proposal> {
+    match val_ty {
+        0x7b => Some(Proposal::Simd),
+        0x6f | 0x70 => Some(Proposal::ReferenceTypes),
+        _ => None,
+    }
+}
+
+fn ref_type_name(elem_ty: u8) -> &'static str {
+    match elem_ty {
+        0x6f => "externref",
+        _ => "funcref",
+    }
+}
+
+fn val_type_name(ty: ValType) -> String {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::FuncRef => "funcref",
+        ValType::ExternRef => "externref",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{CommonConfigOptions, ConfigBuilder},
+        wat2wasm,
+    };
+
+    #[test]
+    fn test_module_manifest() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "env" "log" (func (param i32 i32)))
+              (import "env" "now" (func (result i64)))
+              (import "host_kv" "get" (func (param i32 i32 i32 i32) (result i32)))
+              (import "env" "counter" (global (mut i32)))
+              (memory (export "memory") 1 16)
+              (table 2 funcref)
+              (func (export "run") (param i32) (result i32 i32)
+                local.get 0
+                i32.extend8_s
+                local.get 0)
+              (func (export "copy")
+                i32.const 0
+                i32.const 8
+                i32.const 8
+                memory.copy)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Module::from_bytes(None, &wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = module.manifest();
+        assert!(result.is_ok());
+        let manifest = result.unwrap();
+
+        assert_eq!(
+            manifest.namespaces().collect::<Vec<_>>(),
+            ["env", "host_kv"]
+        );
+        assert_eq!(manifest.imports[0].items.len(), 3);
+        assert_eq!(
+            manifest.imports[0].items[0],
+            ManifestItem {
+                name: "log".into(),
+                ty: ItemType::Func {
+                    params: vec!["i32".into(), "i32".into()],
+                    results: vec![],
+                },
+            }
+        );
+        assert_eq!(
+            manifest.imports[0].items[2].ty,
+            ItemType::Global {
+                val_ty: "i32".into(),
+                mutable: true,
+            }
+        );
+
+        assert_eq!(
+            manifest.proposals,
+            [
+                Proposal::MutableGlobals,
+                Proposal::SignExtensionOperators,
+                Proposal::MultiValue,
+                Proposal::BulkMemoryOperations,
+            ]
+        );
+        assert_eq!(
+            manifest.memories,
+            [MemoryLimits {
+                min_pages: 1,
+                max_pages: Some(16),
+                shared: false,
+                imported: false,
+            }]
+        );
+        assert_eq!(
+            manifest.tables,
+            [TableLimits {
+                elem_ty: "funcref".into(),
+                min: 2,
+                max: None,
+                imported: false,
+            }]
+        );
+
+        let entrypoints: Vec<&str> = manifest
+            .entrypoints()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(entrypoints, ["run", "copy"]);
+        assert_eq!(manifest.exports.len(), 3);
+
+        // the required proposals are checked against a config
+        let result = ConfigBuilder::new(CommonConfigOptions::default().multi_value(false)).build();
+        assert!(result.is_ok());
+        let config = result.unwrap();
+        assert_eq!(
+            manifest.unsupported_proposals(&config),
+            [Proposal::MultiValue]
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let result = manifest.to_json();
+            assert!(result.is_ok());
+            let json = result.unwrap();
+            assert!(json.contains(r#""kind": "func""#));
+            assert!(json.contains(r#""bulk_memory_operations""#));
+            let result = ModuleManifest::from_json(&json);
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap(), manifest);
+        }
+    }
+}
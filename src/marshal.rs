@@ -0,0 +1,355 @@
+//! Defines the marshalling of Rust values to and from guest memory.
+//!
+//! A type implementing [WasmMarshal] has a fixed little-endian layout in guest memory, which follows the `#[repr(C)]` layout of the wasm32 target: the fields are placed in declaration order, each at an offset aligned to its own alignment, and the size is rounded up to the alignment of the type. The same struct declared with `#[repr(C)]` in a guest compiled to wasm32 can therefore read and write the values directly.
+//!
+//! The trait is implemented for the integer and float primitives, `bool` and arrays, and is derived for structs with `#[derive(WasmMarshal)]`:
+//!
+//! ```ignore
+//! use bitbang::marshal::WasmMarshal;
+//!
+//! #[derive(WasmMarshal)]
+//! #[repr(C)]
+//! #[wasm_marshal(version = 2)]
+//! struct FrameInfo {
+//!     width: u32,
+//!     height: u32,
+//!     timestamp: u64,
+//!     keyframe: bool,
+//! }
+//!
+//! let ptr = info.to_guest(&mut memory, |size, align| {
+//!     let ptr = executor.run_func_typed::<i32>(&alloc, params!(size as i32, align as i32))?;
+//!     Ok(ptr as u32)
+//! })?;
+//! let info = FrameInfo::from_guest(&memory, ptr)?;
+//! ```
+//!
+//! A struct with the `version` attribute starts with a `u32` holding the version, which [from_guest](crate::marshal::WasmMarshal::from_guest) checks, so a guest built against an older layout is detected instead of being misread.
+
+use crate::{
+    error::{MarshalError, WasmEdgeError},
+    Memory, WasmEdgeResult,
+};
+
+#[doc(inline)]
+pub use bit_macro::WasmMarshal;
+
+/// Defines a type with a fixed layout in guest memory.
+///
+/// The trait can be derived for structs with `#[derive(WasmMarshal)]`; see the [module documentation](crate::marshal).
+pub trait WasmMarshal: Sized {
+    /// The size of the value in guest memory in bytes, which is a multiple of [ALIGN](crate::marshal::WasmMarshal::ALIGN).
+    const SIZE: u32;
+    /// The alignment of the value in guest memory in bytes.
+    const ALIGN: u32;
+
+    /// Encodes the value into the given buffer, whose length is [SIZE](crate::marshal::WasmMarshal::SIZE).
+    fn encode(&self, buf: &mut [u8]);
+
+    /// Decodes a value from the given buffer, whose length is [SIZE](crate::marshal::WasmMarshal::SIZE).
+    ///
+    /// # Error
+    ///
+    /// If the bytes do not hold a valid value, then an error is returned.
+    fn decode(buf: &[u8]) -> WasmEdgeResult<Self>;
+
+    /// Writes the value to the given offset of the memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to write to.
+    ///
+    /// * `offset` - The offset at which to write.
+    ///
+    /// # Error
+    ///
+    /// If fail to write to the memory, then an error is returned.
+    fn write_to(&self, memory: &mut Memory, offset: u32) -> WasmEdgeResult<()> {
+        let mut buf = vec![0; Self::SIZE as usize];
+        self.encode(&mut buf);
+        memory.write(buf, offset)
+    }
+
+    /// Reads a value from the given offset of the memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to read from.
+    ///
+    /// * `offset` - The offset from which to read.
+    ///
+    /// # Error
+    ///
+    /// If fail to read from the memory, or the bytes do not hold a valid value, then an error is returned.
+    fn read_from(memory: &Memory, offset: u32) -> WasmEdgeResult<Self> {
+        let buf = memory.read(offset, Self::SIZE)?;
+        Self::decode(&buf)
+    }
+
+    /// Allocates guest memory for the value, writes the value to it, and returns the address.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to write to.
+    ///
+    /// * `alloc` - The allocator of the guest, which is given the size and the alignment in bytes, and returns the address of the allocated memory. It usually calls an allocation function exported by the guest.
+    ///
+    /// # Error
+    ///
+    /// If fail to allocate or to write to the memory, then an error is returned.
+    fn to_guest(
+        &self,
+        memory: &mut Memory,
+        alloc: impl FnOnce(u32, u32) -> WasmEdgeResult<u32>,
+    ) -> WasmEdgeResult<u32> {
+        let ptr = alloc(Self::SIZE, Self::ALIGN)?;
+        self.write_to(memory, ptr)?;
+        Ok(ptr)
+    }
+
+    /// Reads a value at the given address of the memory. It is the same as [read_from](crate::marshal::WasmMarshal::read_from).
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to read from.
+    ///
+    /// * `ptr` - The address of the value.
+    ///
+    /// # Error
+    ///
+    /// If fail to read from the memory, or the bytes do not hold a valid value, then an error is returned.
+    fn from_guest(memory: &Memory, ptr: u32) -> WasmEdgeResult<Self> {
+        Self::read_from(memory, ptr)
+    }
+}
+
+macro_rules! impl_wasm_marshal {
+    ($($ty:ty),*) => {
+        $(
+            impl WasmMarshal for $ty {
+                const SIZE: u32 = std::mem::size_of::<$ty>() as u32;
+                const ALIGN: u32 = std::mem::size_of::<$ty>() as u32;
+
+                fn encode(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(buf: &[u8]) -> WasmEdgeResult<Self> {
+                    let mut bytes = [0; std::mem::size_of::<$ty>()];
+                    bytes.copy_from_slice(buf);
+                    Ok(<$ty>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_wasm_marshal!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl WasmMarshal for bool {
+    const SIZE: u32 = 1;
+    const ALIGN: u32 = 1;
+
+    fn encode(&self, buf: &mut [u8]) {
+        buf[0] = *self as u8;
+    }
+
+    fn decode(buf: &[u8]) -> WasmEdgeResult<Self> {
+        match buf[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Box::new(WasmEdgeError::Marshal(
+                MarshalError::InvalidValue("bool".to_string()),
+            ))),
+        }
+    }
+}
+
+impl<T: WasmMarshal, const N: usize> WasmMarshal for [T; N] {
+    const SIZE: u32 = T::SIZE * N as u32;
+    const ALIGN: u32 = T::ALIGN;
+
+    fn encode(&self, buf: &mut [u8]) {
+        for (item, chunk) in self.iter().zip(buf.chunks_exact_mut(T::SIZE as usize)) {
+            item.encode(chunk);
+        }
+    }
+
+    fn decode(buf: &[u8]) -> WasmEdgeResult<Self> {
+        let items = buf
+            .chunks_exact(T::SIZE as usize)
+            .map(T::decode)
+            .collect::<WasmEdgeResult<Vec<_>>>()?;
+        // the buffer holds exactly N items
+        Ok(items.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+/// Rounds the offset up to the given alignment. It is used by the derived implementations.
+#[doc(hidden)]
+pub const fn align_to(offset: u32, align: u32) -> u32 {
+    (offset + align - 1) / align * align
+}
+
+/// Checks the layout version read from guest memory. It is used by the derived implementations.
+#[doc(hidden)]
+pub fn check_version(ty: &str, expected: u32, buf: &[u8]) -> WasmEdgeResult<()> {
+    let found = u32::decode(&buf[..4])?;
+    if found != expected {
+        return Err(Box::new(WasmEdgeError::Marshal(
+            MarshalError::VersionMismatch {
+                ty: ty.to_string(),
+                expected,
+                found,
+            },
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryType;
+
+    #[derive(Debug, Clone, Copy, PartialEq, WasmMarshal)]
+    #[repr(C)]
+    struct Point {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, WasmMarshal)]
+    #[repr(C)]
+    struct Frame {
+        id: u8,
+        origin: Point,
+        timestamp: u64,
+        flags: [u16; 3],
+        keyframe: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, WasmMarshal)]
+    #[repr(C)]
+    #[wasm_marshal(version = 2)]
+    struct Versioned(u16, i64);
+
+    #[derive(Debug, Clone, PartialEq, WasmMarshal)]
+    #[repr(C, align(16))]
+    struct Aligned {
+        tag: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, WasmMarshal)]
+    #[repr(C)]
+    struct Pair<T> {
+        first: T,
+        second: T,
+    }
+
+    #[test]
+    fn test_marshal_layout() {
+        assert_eq!(<Point as WasmMarshal>::SIZE, 8);
+        assert_eq!(<Point as WasmMarshal>::ALIGN, 4);
+
+        // id at 0, origin at 4, timestamp at 16, flags at 24, keyframe at 30
+        assert_eq!(<Frame as WasmMarshal>::SIZE, 32);
+        assert_eq!(<Frame as WasmMarshal>::ALIGN, 8);
+
+        // version at 0, the fields at 4 and 8
+        assert_eq!(<Versioned as WasmMarshal>::SIZE, 16);
+        assert_eq!(<Versioned as WasmMarshal>::ALIGN, 8);
+
+        // the alignment of the repr raises the alignment of the fields
+        assert_eq!(<Aligned as WasmMarshal>::SIZE, 16);
+        assert_eq!(<Aligned as WasmMarshal>::ALIGN, 16);
+
+        assert_eq!(<Pair<u16> as WasmMarshal>::SIZE, 4);
+        assert_eq!(<Pair<u64> as WasmMarshal>::SIZE, 16);
+        let pair = Pair {
+            first: 1u16,
+            second: 2,
+        };
+        let mut buf = vec![0; 4];
+        pair.encode(&mut buf);
+        assert_eq!(buf, [1, 0, 2, 0]);
+        assert_eq!(Pair::<u16>::decode(&buf).unwrap(), pair);
+
+        let frame = Frame {
+            id: 7,
+            origin: Point { x: 1.5, y: -2.0 },
+            timestamp: 0x0102_0304_0506_0708,
+            flags: [1, 2, 3],
+            keyframe: true,
+        };
+        let mut buf = vec![0; 32];
+        frame.encode(&mut buf);
+        assert_eq!(buf[0], 7);
+        assert_eq!(&buf[4..8], &1.5f32.to_le_bytes());
+        assert_eq!(&buf[16..24], &0x0102_0304_0506_0708u64.to_le_bytes());
+        assert_eq!(&buf[24..30], &[1, 0, 2, 0, 3, 0]);
+        assert_eq!(buf[30], 1);
+        assert_eq!(Frame::decode(&buf).unwrap(), frame);
+
+        // an invalid bool
+        buf[30] = 2;
+        let result = Frame::decode(&buf);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Marshal(MarshalError::InvalidValue("bool".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_marshal_guest_memory() {
+        let result = MemoryType::new(1, None, false);
+        assert!(result.is_ok());
+        let result = Memory::new(result.unwrap());
+        assert!(result.is_ok());
+        let mut memory = result.unwrap();
+
+        let frame = Frame {
+            id: 1,
+            origin: Point { x: 0.5, y: 0.25 },
+            timestamp: 42,
+            flags: [0, 0, 9],
+            keyframe: false,
+        };
+        let mut requested = None;
+        let result = frame.to_guest(&mut memory, |size, align| {
+            requested = Some((size, align));
+            Ok(1024)
+        });
+        assert!(result.is_ok());
+        let ptr = result.unwrap();
+        assert_eq!(ptr, 1024);
+        assert_eq!(requested, Some((32, 8)));
+
+        let result = Frame::from_guest(&memory, ptr);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), frame);
+
+        // a versioned value
+        let value = Versioned(3, -4);
+        let result = value.write_to(&mut memory, 2048);
+        assert!(result.is_ok());
+        assert_eq!(memory.read(2048, 4).unwrap(), 2u32.to_le_bytes());
+        let result = Versioned::read_from(&memory, 2048);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), value);
+
+        // a value written with another layout version
+        assert!(memory.write(1u32.to_le_bytes(), 2048).is_ok());
+        let result = Versioned::read_from(&memory, 2048);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Marshal(MarshalError::VersionMismatch {
+                ty: "Versioned".to_string(),
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Defines the middleware layers wrapped around the function calls run by an [Executor](crate::Executor).
+//!
+//! The layers are registered with an [ExecutorBuilder](crate::ExecutorBuilder) in order: the first registered layer is the outermost one, and each layer decides whether and how to run the rest of the chain with [Next::run](crate::middleware::Next::run). Metering, timeouts, tracing and interception compose this way instead of each being a special-cased option of the executor:
+//!
+//! ```ignore
+//! let executor = ExecutorBuilder::new()
+//!     .with_config(config)
+//!     .with_middleware(Trace::new(|func, duration, _result| {
+//!         println!("{:?} took {:?}", func.name(), duration);
+//!     }))
+//!     .with_middleware(Timeout::new(Duration::from_millis(50)))
+//!     .with_middleware(|func: &Func, params: Vec<WasmValue>, next: Next<'_>| {
+//!         if func.name() == Some("forbidden") {
+//!             return Err(Box::new(WasmEdgeError::Operation("denied".to_string())));
+//!         }
+//!         next.run(params)
+//!     })
+//!     .build()?;
+//! ```
+//!
+//! The layers wrap every function call run by the executor: the calls made with [Executor::run_func](crate::Executor::run_func) and the functions built on it, such as [Executor::run_func_typed](crate::Executor::run_func_typed) and [Executor::run_func_async](crate::Executor::run_func_async), the calls of [function references](crate::FuncRef), which the layers are given as anonymous functions, and the calls reported on, recorded or replayed with [Executor::run_func_with_report](crate::Executor::run_func_with_report), [Executor::run_func_recorded](crate::Executor::run_func_recorded) and [Executor::replay](crate::Executor::replay).
+
+use crate::{
+    error::{CoreCommonError, CoreError, WasmEdgeError},
+    Executor, Func, WasmEdgeResult, WasmValue,
+};
+use bit_sys::cancel;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Defines a layer wrapped around the function calls run by an [Executor](crate::Executor).
+///
+/// The trait is implemented for the closures taking the same arguments as [call](crate::middleware::Middleware::call).
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles a function call.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// * `next` - The rest of the chain, which runs the function after the inner layers.
+    ///
+    /// # Error
+    ///
+    /// If the call fails or is rejected by the layer, then an error is returned.
+    fn call(
+        &self,
+        func: &Func,
+        params: Vec<WasmValue>,
+        next: Next<'_>,
+    ) -> WasmEdgeResult<Vec<WasmValue>>;
+}
+impl<F> Middleware for F
+where
+    F: Fn(&Func, Vec<WasmValue>, Next<'_>) -> WasmEdgeResult<Vec<WasmValue>>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn call(
+        &self,
+        func: &Func,
+        params: Vec<WasmValue>,
+        next: Next<'_>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        self(func, params, next)
+    }
+}
+
+/// The call run at the end of a middleware chain instead of [Executor::call_func](crate::Executor::call_func).
+pub(crate) type Call<'a> = dyn Fn(Vec<WasmValue>) -> WasmEdgeResult<Vec<WasmValue>> + 'a;
+
+/// The rest of a middleware chain.
+pub struct Next<'a> {
+    executor: &'a Executor,
+    func: &'a Func,
+    layers: &'a [Arc<dyn Middleware>],
+    call: Option<&'a Call<'a>>,
+}
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        executor: &'a Executor,
+        func: &'a Func,
+        layers: &'a [Arc<dyn Middleware>],
+    ) -> Self {
+        Self {
+            executor,
+            func,
+            layers,
+            call: None,
+        }
+    }
+
+    /// Creates a chain which ends with the given call, such as a call which is also reported on.
+    pub(crate) fn with_call(
+        executor: &'a Executor,
+        func: &'a Func,
+        layers: &'a [Arc<dyn Middleware>],
+        call: &'a Call<'a>,
+    ) -> Self {
+        Self {
+            executor,
+            func,
+            layers,
+            call: Some(call),
+        }
+    }
+
+    /// Runs the inner layers and then the function, and returns the results.
+    ///
+    /// # Argument
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Error
+    ///
+    /// If the call fails or is rejected by an inner layer, then an error is returned.
+    pub fn run(self, params: Vec<WasmValue>) -> WasmEdgeResult<Vec<WasmValue>> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => layer.call(self.func, params, Next { layers, ..self }),
+            None => match self.call {
+                Some(call) => call(params),
+                None => self.executor.call_func(self.func, params),
+            },
+        }
+    }
+}
+impl std::fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+/// The middleware layers of an [Executor](crate::Executor).
+#[derive(Clone, Default)]
+pub(crate) struct Layers(pub(crate) Vec<Arc<dyn Middleware>>);
+impl std::fmt::Debug for Layers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Layers").field(&self.0.len()).finish()
+    }
+}
+
+/// A layer which cancels the calls taking longer than the given duration.
+///
+/// A call is cancelled at the first host function call made after the deadline, and fails with [WasmEdgeError::ExecuteTimeout](crate::error::WasmEdgeError). The host function in progress at the deadline can check [CallingFrame::is_cancelled](crate::CallingFrame::is_cancelled) to stop early. Wasm code which makes no host function call runs to the end; combine the layer with an [instruction limit](crate::config::RuntimeConfigOptions::max_instructions) to bound it.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    duration: Duration,
+}
+impl Timeout {
+    /// Creates a [Timeout] layer.
+    ///
+    /// # Argument
+    ///
+    /// * `duration` - The maximum duration of a call.
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+}
+impl Middleware for Timeout {
+    fn call(
+        &self,
+        _func: &Func,
+        params: Vec<WasmValue>,
+        next: Next<'_>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        run_before(Instant::now() + self.duration, || next.run(params))
+    }
+}
+
+/// Runs a call which is cancelled once the given deadline passes. If the call is interrupted after the deadline, then [WasmEdgeError::ExecuteTimeout](crate::error::WasmEdgeError) is returned.
+pub(crate) fn run_before<T>(
+    deadline: Instant,
+    call: impl FnOnce() -> WasmEdgeResult<T>,
+) -> WasmEdgeResult<T> {
+    // the deadline is popped even if the call panics
+    struct Popped;
+    impl Drop for Popped {
+        fn drop(&mut self) {
+            cancel::pop_deadline();
+        }
+    }
+
+    cancel::push_deadline(deadline);
+    let result = {
+        let _popped = Popped;
+        call()
+    };
+
+    match result {
+        Err(err)
+            if *err == WasmEdgeError::Core(CoreError::Common(CoreCommonError::Interrupted))
+                && Instant::now() >= deadline =>
+        {
+            Err(Box::new(WasmEdgeError::ExecuteTimeout))
+        }
+        result => result,
+    }
+}
+
+type TraceFn = dyn Fn(&Func, Duration, &WasmEdgeResult<Vec<WasmValue>>) + Send + Sync;
+
+/// A layer which reports the duration and the outcome of each call to a callback.
+pub struct Trace {
+    callback: Box<TraceFn>,
+}
+impl Trace {
+    /// Creates a [Trace] layer.
+    ///
+    /// # Argument
+    ///
+    /// * `callback` - The callback, which is given the called function, the duration of the call, and its result.
+    pub fn new(
+        callback: impl Fn(&Func, Duration, &WasmEdgeResult<Vec<WasmValue>>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            callback: Box::new(callback),
+        }
+    }
+}
+impl Middleware for Trace {
+    fn call(
+        &self,
+        func: &Func,
+        params: Vec<WasmValue>,
+        next: Next<'_>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        let start = Instant::now();
+        let result = next.run(params);
+        (self.callback)(func, start.elapsed(), &result);
+        result
+    }
+}
+impl std::fmt::Debug for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Trace").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::HostFuncError, params, wat2wasm, CallingFrame, ExecutorBuilder, ImportObjectBuilder,
+        Module, NeverType, Store, WasmVal,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    #[test]
+    fn test_middleware_layers() {
+        // a host function sleeping for the given milliseconds
+        let sleep = |_frame: CallingFrame,
+                     inputs: Vec<WasmValue>,
+                     _data: *mut std::os::raw::c_void|
+         -> Result<Vec<WasmValue>, HostFuncError> {
+            std::thread::sleep(Duration::from_millis(inputs[0].to_i32() as u64));
+            Ok(vec![])
+        };
+        let result = ImportObjectBuilder::new()
+            .with_func::<i32, (), NeverType>("sleep", sleep, None)
+            .expect("failed to add host func")
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "sleep" (func $sleep (param i32)))
+                (func (export "nap") (param i32) (result i32)
+                    local.get 0
+                    call $sleep
+                    local.get 0
+                    call $sleep
+                    local.get 0))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let trace_events = Arc::clone(&events);
+        let intercept_events = Arc::clone(&events);
+        let result = ExecutorBuilder::new()
+            .with_middleware(Trace::new(move |func, _duration, result| {
+                trace_events.lock().unwrap().push(format!(
+                    "trace {:?} {}",
+                    func.name(),
+                    result.is_ok()
+                ));
+            }))
+            .with_middleware(Timeout::new(Duration::from_millis(50)))
+            .with_middleware(
+                move |_func: &Func, params: Vec<WasmValue>, next: Next<'_>| {
+                    intercept_events
+                        .lock()
+                        .unwrap()
+                        .push("intercept".to_string());
+                    // cap the naps at 100 milliseconds
+                    let ms = params[0].to_i32().min(100);
+                    next.run(vec![WasmValue::from_i32(ms)])
+                },
+            )
+            .build();
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let nap = result.unwrap().func("nap").unwrap();
+
+        // a short call passes through all layers
+        let result = executor.run_func(&nap, params!(1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 1);
+
+        // the interceptor caps the nap, and the timeout cancels the second host call
+        let result = executor.run_func(&nap, params!(1000));
+        assert!(result.is_err());
+        assert_eq!(*result.unwrap_err(), WasmEdgeError::ExecuteTimeout);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "intercept",
+                "trace Some(\"nap\") true",
+                "intercept",
+                "trace Some(\"nap\") false"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_middleware_entry_points() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (func (export "double") (param i32) (result i32)
+                    local.get 0
+                    i32.const 2
+                    i32.mul))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // a layer counting the calls and replacing the argument
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer_calls = Arc::clone(&calls);
+        let result = ExecutorBuilder::new()
+            .with_middleware(
+                move |_func: &Func, _params: Vec<WasmValue>, next: Next<'_>| {
+                    layer_calls.fetch_add(1, Ordering::SeqCst);
+                    next.run(vec![WasmValue::from_i32(21)])
+                },
+            )
+            .build();
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let mut store = Store::new().unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let double = result.unwrap().func("double").unwrap();
+
+        let result = executor.run_func_with_report(&double, params!(1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().0[0].to_i32(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let result = executor.run_func_ref(&double.as_ref(), params!(1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let result = double.as_ref().run(&executor, params!(1));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_middleware_timeout_panic() {
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), (), NeverType>(
+                "ping",
+                |_frame: CallingFrame,
+                 _inputs: Vec<WasmValue>,
+                 _data: *mut std::os::raw::c_void|
+                 -> Result<Vec<WasmValue>, HostFuncError> { Ok(vec![]) },
+                None,
+            )
+            .expect("failed to add host func")
+            .build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "ping" (func $ping))
+                (func (export "run")
+                    call $ping))
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+
+        // a layer panicking inside the timeout
+        let result = ExecutorBuilder::new()
+            .with_middleware(Timeout::new(Duration::ZERO))
+            .with_middleware(|_func: &Func, _params: Vec<WasmValue>, _next: Next<'_>| {
+                panic!("rejected")
+            })
+            .build();
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let mut store = Store::new().unwrap();
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let run = result.unwrap().func("run").unwrap();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            executor.run_func(&run, params!())
+        }));
+        assert!(result.is_err());
+
+        // the passed deadline of the panicked call does not interrupt the next host calls
+        let result = Executor::new(None, None).unwrap().run_func(&run, params!());
+        assert!(result.is_ok());
+    }
+}
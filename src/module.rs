@@ -1,15 +1,59 @@
 //! Defines WasmEdge AST Module, ImportType, and ExportType.
 
-use crate::{config::Config, ExternalInstanceType, WasmEdgeResult};
+#[cfg(feature = "aot")]
+use crate::{
+    aot::{AotVerifier, PrivateDir},
+    tier::{CompilationState, Tier},
+};
+use crate::{
+    binary::{
+        has_custom_section, Binary, ElemSegment, EXTERNAL_FUNC, EXTERNAL_TABLE, SECTION_START,
+    },
+    config::Config,
+    determinism::{self, DeterminismReport},
+    diagnostics::{HandleGuard, HandleKind},
+    error::WasmEdgeError,
+    manifest::{self, BinaryManifest, ModuleManifest},
+    transform::{self, ModuleTransform},
+    wat2wasm, ExternalInstanceType, WasmEdgeResult,
+};
 use bit_sys as sys;
-use std::{borrow::Cow, marker::PhantomData, path::Path};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
 
 /// Defines compiled in-memory representation of an input WASM binary.
 ///
 /// A [Module] is a compiled in-memory representation of an input WebAssembly binary. In the instantiation process, a [Module] is instatiated to a module [instance](crate::Instance), from which the exported [function](crate::Func), [table](crate::Table), [memory](crate::Memory), and [global](crate::Global) instances can be fetched.
+///
+/// The compiled code of a [Module] is reference-counted, so cloning a [Module] is cheap: the clones share the same compiled code instead of copying it. A [Module] is also `Send` and `Sync`, so a module can be loaded and validated once, then its clones can be handed to many threads, each of which instantiates it into its own [store](crate::Store) with its own [executor](crate::Executor). The compiled code is released when the last clone and the last [module instance](crate::Instance) created from it are dropped.
+///
+/// ```ignore
+/// let module = Module::from_file(None, "app.wasm")?;
+/// let workers: Vec<_> = (0..64)
+///     .map(|_| {
+///         let module = module.clone();
+///         std::thread::spawn(move || {
+///             let mut executor = Executor::new(None, None)?;
+///             let mut store = Store::new()?;
+///             let instance = store.register_active_module(&mut executor, &module)?;
+///             // serve requests with the instance
+///             # Ok::<(), Box<WasmEdgeError>>(())
+///         })
+///     })
+///     .collect();
+/// ```
 #[derive(Debug, Clone)]
 pub struct Module {
     pub(crate) inner: sys::Module,
+    pub(crate) info: Arc<ModuleInfo>,
+    #[cfg(feature = "aot")]
+    pub(crate) tier: Option<Arc<Tier>>,
+    pub(crate) _guard: HandleGuard,
 }
 impl Module {
     /// Returns a validated module from a file.
@@ -26,17 +70,71 @@ impl Module {
     pub fn from_file(config: Option<&Config>, file: impl AsRef<Path>) -> WasmEdgeResult<Self> {
         let inner_config = config.map(|cfg| &cfg.inner);
 
-        // load module
+        // the file is read once: a wasm binary or a module in the text format is loaded from its bytes
+        let bytes = std::fs::read(file.as_ref()).ok();
+        let wasm = bytes.as_deref().and_then(|bytes| wat2wasm(bytes).ok());
+        if let Some(wasm) = wasm.as_deref() {
+            if !has_custom_section(wasm, AOT_SECTION) {
+                return Self::from_bytes(config, wasm);
+            }
+        }
+
+        // the AOT compiled code is only loaded by the loader from the file
         let inner_module = sys::Loader::create(inner_config)?.from_file(file.as_ref())?;
 
         // validate module
         sys::Validator::create(inner_config)?.validate(&inner_module)?;
 
+        let info = wasm
+            .map(|wasm| ModuleInfo::parse(&wasm))
+            .unwrap_or_default();
+
         Ok(Self {
             inner: inner_module,
+            info: Arc::new(info),
+            #[cfg(feature = "aot")]
+            tier: None,
+            _guard: HandleGuard::new(HandleKind::Module),
         })
     }
 
+    /// Returns a validated module from an AOT artifact, which is checked against its [metadata](crate::aot::AotMetadata) before it is loaded.
+    ///
+    /// The artifact is read once, and the checked content is loaded from a private copy, so the file can not be replaced between the check and the load.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The global configuration.
+    ///
+    /// * `file` - An AOT artifact generated by the [Compiler](crate::Compiler).
+    ///
+    /// * `verifier` - The checks to make on the artifact.
+    ///
+    /// # Error
+    ///
+    /// If the artifact fails any check, then [WasmEdgeError::Aot](crate::error::WasmEdgeError::Aot) is returned. If fail to load and valiate the module, returns an error.
+    #[cfg(feature = "aot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
+    pub fn from_file_verified(
+        config: Option<&Config>,
+        file: impl AsRef<Path>,
+        verifier: &AotVerifier,
+    ) -> WasmEdgeResult<Self> {
+        let content = std::fs::read(file.as_ref())
+            .map_err(|err| Box::new(WasmEdgeError::Operation(err.to_string())))?;
+        verifier.verify_content(&content)?;
+
+        // the native code is only loaded from a file, so the checked content is written where only this user can write
+        let dir = PrivateDir::new()?;
+        let name = file
+            .as_ref()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("artifact");
+        let copy = dir.write(name, &content)?;
+        Self::from_file(config, copy)
+    }
+
     /// Loads a WebAssembly binary module from in-memory bytes.
     ///
     /// # Arguments
@@ -59,9 +157,100 @@ impl Module {
 
         Ok(Self {
             inner: inner_module,
+            info: Arc::new(ModuleInfo::parse(bytes.as_ref())),
+            #[cfg(feature = "aot")]
+            tier: None,
+            _guard: HandleGuard::new(HandleKind::Module),
         })
     }
 
+    /// Loads a WebAssembly binary module from in-memory bytes after rewriting them with the given [transforms](crate::transform::ModuleTransform), which are applied in order.
+    ///
+    /// The transformed bytes are cached by the input bytes and the ids of the transforms, so loading the same bytes through the same transforms again does not run them. See [transform](crate::transform) for the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The global configuration.
+    ///
+    /// * `bytes` - The in-memory bytes to be transformed and parsed.
+    ///
+    /// * `transforms` - The transforms to apply.
+    ///
+    /// # Error
+    ///
+    /// If a transform fails, then its error is returned. If fail to load and valiate the transformed module, returns an error.
+    pub fn from_bytes_with(
+        config: Option<&Config>,
+        bytes: impl AsRef<[u8]>,
+        transforms: &[&dyn ModuleTransform],
+    ) -> WasmEdgeResult<Self> {
+        let bytes = transform::apply(bytes.as_ref(), transforms)?;
+        Self::from_bytes(config, bytes)
+    }
+
+    /// Loads a WebAssembly binary module from in-memory bytes to run interpreted, and compiles it ahead-of-time on a background thread.
+    ///
+    /// Once the compilation is done, the module instances created from the module and its clones run the compiled code, while the instances created before keep running interpreted. The module starts quickly, and reaches the speed of the compiled code without orchestrating the compilation. [Module::compilation_state] tells the progress of the compilation. The compilation can not be cancelled, so dropping the last clone of the module waits for it to finish.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The global configuration, which is also used to compile the module.
+    ///
+    /// * `bytes` - The in-memory bytes to be parsed.
+    ///
+    /// # Error
+    ///
+    /// If fail to load and valiate the WebAssembly module from the given in-memory bytes, returns an error. The errors of the compilation are reported by [Module::compilation_state].
+    #[cfg(feature = "aot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
+    pub fn from_bytes_tiered(
+        config: Option<&Config>,
+        bytes: impl AsRef<[u8]>,
+    ) -> WasmEdgeResult<Self> {
+        let mut module = Self::from_bytes(config, bytes.as_ref())?;
+        module.tier = Some(Tier::spawn(config, bytes.as_ref().to_vec()));
+        Ok(module)
+    }
+
+    /// Returns the state of the background compilation of the module, or `None` if the module is not loaded with [Module::from_bytes_tiered].
+    #[cfg(feature = "aot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "aot")))]
+    pub fn compilation_state(&self) -> Option<CompilationState> {
+        self.tier.as_ref().map(|tier| tier.state())
+    }
+
+    /// Returns the code the module instances are created from, which is the compiled code once the background compilation is done.
+    pub(crate) fn code(&self) -> &sys::Module {
+        #[cfg(feature = "aot")]
+        if let Some(compiled) = self.tier.as_ref().and_then(|tier| tier.compiled()) {
+            return compiled;
+        }
+        &self.inner
+    }
+
+    /// Loads a WebAssembly binary module from in-memory bytes, and defers the execution of its start function.
+    ///
+    /// The start function of the module is not run when the module is instantiated. Instead, it is exported from the [module instance](crate::Instance) under the name `__bitbang_start`, and run explicitly with [Instance::run_start](crate::Instance::run_start). This allows the host to set up additional state between the instantiation and the initialization of the guest.
+    ///
+    /// If the module has no start function, the module is loaded as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The global configuration.
+    ///
+    /// * `bytes` - The in-memory bytes to be parsed.
+    ///
+    /// # Error
+    ///
+    /// If fail to load and valiate the WebAssembly module from the given in-memory bytes, returns an error.
+    pub fn from_bytes_deferred_start(
+        config: Option<&Config>,
+        bytes: impl AsRef<[u8]>,
+    ) -> WasmEdgeResult<Self> {
+        let bytes = defer_start(bytes.as_ref())?;
+        Self::from_bytes(config, bytes)
+    }
+
     /// Returns the count of the imported WasmEdge instances in the [module](crate::Module).
     pub fn count_of_imports(&self) -> u32 {
         self.inner.count_of_imports()
@@ -98,6 +287,44 @@ impl Module {
         exports
     }
 
+    /// Returns the [name section](crate::NameSection) of the [module](crate::Module), which maps the function and local indices to their symbol names.
+    ///
+    /// If the module has no name section, or the module is loaded from an AOT shared library, then the returned name section is empty.
+    pub fn name_section(&self) -> &NameSection {
+        &self.info.names
+    }
+
+    /// Returns the instructions and the imports of the [module](crate::Module) which can introduce non-determinism, such as the floating-point instructions producing NaNs with platform-dependent payloads, the reinterpretations of floats as integers, and the WASI clocks. See [DeterminismReport](crate::DeterminismReport).
+    ///
+    /// The module binary is analyzed on the first call, and the clones of the module share the result.
+    ///
+    /// # Error
+    ///
+    /// If the module is loaded from an AOT shared library, or its binary uses an instruction the analysis does not know, then an error is returned.
+    pub fn check_determinism(&self) -> WasmEdgeResult<DeterminismReport> {
+        self.info.determinism().cloned().ok_or_else(|| {
+            Box::new(WasmEdgeError::Operation(
+                "the module binary can not be analyzed".to_string(),
+            ))
+        })
+    }
+
+    /// Returns the [manifest](crate::manifest::ModuleManifest) of the [module](crate::Module), which summarizes its imports, the proposals it requires, the limits of its memories and tables, and its exports.
+    ///
+    /// The module binary is analyzed on the first call, and the clones of the module share the result.
+    ///
+    /// # Error
+    ///
+    /// If the module is loaded from an AOT shared library, or its binary uses an instruction the analysis does not know, then an error is returned.
+    pub fn manifest(&self) -> WasmEdgeResult<ModuleManifest> {
+        let parts = self.info.manifest().ok_or_else(|| {
+            Box::new(WasmEdgeError::Operation(
+                "the module binary can not be analyzed".to_string(),
+            ))
+        })?;
+        manifest::build(self, parts)
+    }
+
     /// Gets the [export type](crate::ExportType) by the name of a specific exported WasmEdge instance, such as func, table, global or memory instance.
     ///
     /// # Argument
@@ -116,6 +343,179 @@ impl Module {
     }
 }
 
+/// Defines the symbol names of a [module](crate::Module), parsed from the `name` custom section of its binary.
+///
+/// The names are used to show the functions of a module in diagnostics as `my_module::process_frame` instead of `func[37]`; see [NameSection::symbol](crate::NameSection::symbol).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameSection {
+    pub(crate) module_name: Option<String>,
+    pub(crate) func_names: HashMap<u32, String>,
+    pub(crate) local_names: HashMap<u32, HashMap<u32, String>>,
+}
+impl NameSection {
+    /// Returns the name of the module, if any.
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name.as_deref()
+    }
+
+    /// Returns the name of a function.
+    ///
+    /// # Argument
+    ///
+    /// * `func_idx` - The index of the function in the function index space of the module, which includes the imported functions.
+    pub fn func_name(&self, func_idx: u32) -> Option<&str> {
+        self.func_names.get(&func_idx).map(String::as_str)
+    }
+
+    /// Returns the names of all the named functions, keyed by function index.
+    pub fn func_names(&self) -> &HashMap<u32, String> {
+        &self.func_names
+    }
+
+    /// Returns the name of a local, including the parameters, of a function.
+    ///
+    /// # Arguments
+    ///
+    /// * `func_idx` - The index of the function.
+    ///
+    /// * `local_idx` - The index of the local in the function. The parameters come first.
+    pub fn local_name(&self, func_idx: u32, local_idx: u32) -> Option<&str> {
+        self.local_names
+            .get(&func_idx)?
+            .get(&local_idx)
+            .map(String::as_str)
+    }
+
+    /// Returns the symbol of a function for diagnostics.
+    ///
+    /// The symbol is `module::function` if both the module and the function are named, `function` if only the function is named, and `func[idx]` otherwise.
+    ///
+    /// # Argument
+    ///
+    /// * `func_idx` - The index of the function.
+    pub fn symbol(&self, func_idx: u32) -> String {
+        match (self.module_name(), self.func_name(func_idx)) {
+            (Some(module), Some(func)) => format!("{module}::{func}"),
+            (None, Some(func)) => func.to_string(),
+            (_, None) => format!("func[{func_idx}]"),
+        }
+    }
+
+    /// Checks if the name section has no names.
+    pub fn is_empty(&self) -> bool {
+        self.module_name.is_none() && self.func_names.is_empty() && self.local_names.is_empty()
+    }
+}
+
+/// Defines the information of a module binary which is not exposed by the WasmEdge library, such as the symbol names.
+#[derive(Debug, Default)]
+pub(crate) struct ModuleInfo {
+    /// The name section.
+    pub(crate) names: NameSection,
+    /// The indices of the exported tables, keyed by export name.
+    table_exports: HashMap<String, u32>,
+    /// The active element segments with constant offsets.
+    elements: Vec<ElemSegment>,
+    /// The parsed module binary, kept for the analyses run on demand.
+    binary: Option<Binary>,
+    /// The sources of non-determinism, analyzed on the first request, or `None` if the code cannot be analyzed.
+    determinism: OnceLock<Option<DeterminismReport>>,
+    /// The parts of the manifest read from the binary on the first request, or `None` if the code cannot be analyzed.
+    manifest: OnceLock<Option<BinaryManifest>>,
+}
+impl ModuleInfo {
+    /// Parses the information from the given module binary. If the binary cannot be parsed, such as an AOT shared library, then the information is empty.
+    fn parse(bytes: &[u8]) -> Self {
+        Self::try_parse(bytes).unwrap_or_default()
+    }
+
+    fn try_parse(bytes: &[u8]) -> WasmEdgeResult<Self> {
+        let binary = Binary::parse(bytes)?;
+        let table_exports = binary
+            .exports()?
+            .into_iter()
+            .filter(|(_, kind, _)| *kind == EXTERNAL_TABLE)
+            .map(|(name, _, idx)| (name, idx))
+            .collect();
+
+        Ok(Self {
+            names: binary.name_section()?,
+            table_exports,
+            elements: binary.table_elements()?,
+            determinism: OnceLock::new(),
+            manifest: OnceLock::new(),
+            binary: Some(binary),
+        })
+    }
+
+    /// Returns the sources of non-determinism, analyzing the module binary on the first call.
+    fn determinism(&self) -> Option<&DeterminismReport> {
+        self.determinism
+            .get_or_init(|| {
+                let binary = self.binary.as_ref()?;
+                determinism::analyze(binary, &self.names).ok()
+            })
+            .as_ref()
+    }
+
+    /// Returns the parts of the manifest read from the module binary, analyzing it on the first call.
+    fn manifest(&self) -> Option<&BinaryManifest> {
+        self.manifest
+            .get_or_init(|| manifest::analyze(self.binary.as_ref()?).ok())
+            .as_ref()
+    }
+
+    /// Returns the slots of the exported tables, as `(table name, slot)` pairs, at which the element segments place a function with a name in the name section.
+    pub(crate) fn named_segment_slots(&self) -> Vec<(&str, u32)> {
+        let mut slots = Vec::new();
+        for (table_name, table) in self.table_exports.iter() {
+            for seg in self.elements.iter().filter(|seg| seg.table == *table) {
+                for (i, func) in seg.funcs.iter().enumerate() {
+                    if func.is_some_and(|idx| self.names.func_names.contains_key(&idx)) {
+                        slots.push((table_name.as_str(), seg.offset.saturating_add(i as u32)));
+                    }
+                }
+            }
+        }
+        slots
+    }
+
+    /// Returns the index of the function which the element segments place at the given slot of the exported table.
+    pub(crate) fn table_func(&self, table_name: &str, slot: u32) -> Option<u32> {
+        let table = *self.table_exports.get(table_name)?;
+        // a later segment overwrites the slots of an earlier one
+        self.elements
+            .iter()
+            .rev()
+            .filter(|seg| seg.table == table && slot >= seg.offset)
+            .find_map(|seg| seg.funcs.get((slot - seg.offset) as usize))
+            .copied()
+            .flatten()
+    }
+}
+
+/// The name of the custom section which holds the AOT compiled code of a universal wasm file.
+const AOT_SECTION: &str = "wasmedge";
+
+/// The name under which the deferred start function is exported.
+pub(crate) const DEFERRED_START_EXPORT: &str = "__bitbang_start";
+
+/// Removes the start section from the given module binary, and exports the start function instead.
+fn defer_start(bytes: &[u8]) -> WasmEdgeResult<Cow<'_, [u8]>> {
+    let mut binary = Binary::parse(bytes)?;
+    let start_func = match binary.start_func()? {
+        Some(idx) => idx,
+        None => return Ok(Cow::Borrowed(bytes)),
+    };
+
+    // the export section, if any, precedes the start section
+    let pos = binary.position(SECTION_START).unwrap();
+    binary.sections.remove(pos);
+    binary.add_export(DEFERRED_START_EXPORT, EXTERNAL_FUNC, start_func, pos)?;
+
+    Ok(Cow::Owned(binary.encode()))
+}
+
 /// Defines the types of the imported instances.
 #[derive(Debug)]
 pub struct ImportType<'module> {
@@ -164,7 +564,7 @@ mod tests {
     use super::*;
     use crate::{
         error::{CoreError, CoreLoadError, WasmEdgeError},
-        wat2wasm,
+        params, wat2wasm, Executor, Store, WasmVal,
     };
 
     #[test]
@@ -201,6 +601,54 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_module_name_section() {
+        let wat = br#"
+            (module $my_module
+              (import "env" "log" (func $log (param i32)))
+              (func $process_frame (export "process") (param $frame i32) (local $tmp i64)
+                local.get $frame
+                call $log)
+              (func (result i32)
+                i32.const 0)
+            )
+            "#;
+
+        let result = Module::from_bytes(None, wat2wasm(wat).unwrap());
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let names = module.name_section();
+        assert!(!names.is_empty());
+        assert_eq!(names.module_name(), Some("my_module"));
+        assert_eq!(names.func_name(0), Some("log"));
+        assert_eq!(names.func_name(1), Some("process_frame"));
+        assert_eq!(names.func_name(2), None);
+        assert_eq!(names.local_name(1, 0), Some("frame"));
+        assert_eq!(names.local_name(1, 1), Some("tmp"));
+        assert_eq!(names.symbol(1), "my_module::process_frame");
+        assert_eq!(names.symbol(2), "func[2]");
+
+        // a module loaded from a file in the text format
+        let file = std::env::temp_dir().join("bitbang_test_name_section.wat");
+        std::fs::write(&file, wat).unwrap();
+        let result = Module::from_file(None, &file);
+        std::fs::remove_file(&file).unwrap();
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        assert_eq!(module.name_section().symbol(1), "my_module::process_frame");
+
+        // a module without names
+        let file = std::env::current_dir()
+            .unwrap()
+            .join("examples/wasmedge-sys/data/fibonacci.wat");
+        let result = Module::from_file(None, file);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        assert!(module.name_section().is_empty());
+        assert_eq!(module.name_section().symbol(0), "func[0]");
+    }
+
     #[test]
     #[allow(clippy::assertions_on_result_states)]
     fn test_module_from_bytes() {
@@ -333,6 +781,64 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "aot")]
+    fn test_module_from_bytes_tiered() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add))
+            "#,
+        )
+        .unwrap();
+
+        // a module not loaded as tiered has no compilation state
+        let result = Module::from_bytes(None, &wasm_bytes);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().compilation_state(), None);
+
+        let result = Module::from_bytes_tiered(None, &wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+        assert!(module.compilation_state().is_some());
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+
+        // the module is callable while it is being compiled
+        let result = store.register_named_module(&mut executor, "interpreted", &module);
+        assert!(result.is_ok());
+        let add = result.unwrap().func("add").unwrap();
+        let result = executor.run_func(&add, params!(1, 2));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 3);
+
+        // wait for the compilation, which the clones share
+        let clone = module.clone();
+        let start = std::time::Instant::now();
+        while clone.compilation_state() == Some(CompilationState::Compiling) {
+            assert!(start.elapsed() < std::time::Duration::from_secs(60));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(module.compilation_state(), Some(CompilationState::Compiled));
+
+        // the instances created from now on run the compiled code
+        let result = store.register_named_module(&mut executor, "compiled", &module);
+        assert!(result.is_ok());
+        let add = result.unwrap().func("add").unwrap();
+        let result = executor.run_func(&add, params!(2, 3));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 5);
+
+        // a module dropped while it is being compiled waits for the compilation thread
+        let result = Module::from_bytes_tiered(None, &wasm_bytes);
+        assert!(result.is_ok());
+        drop(result.unwrap());
+    }
+
     #[test]
     #[allow(clippy::assertions_on_result_states)]
     fn test_module_clone() {
@@ -461,4 +967,63 @@ mod tests {
         let module_clone = module.clone();
         assert_eq!(module.exports().len(), module_clone.exports().len());
     }
+
+    #[test]
+    fn test_module_share_across_stores() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Module>();
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (global $counter (mut i32) (i32.const 0))
+              (func (export "next") (result i32)
+                global.get $counter
+                i32.const 1
+                i32.add
+                global.set $counter
+                global.get $counter)
+            )
+            "#,
+        )
+        .unwrap();
+
+        // load and validate the module only once
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // instantiate the clones of the module in separate stores on separate threads
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let module = module.clone();
+                std::thread::spawn(move || {
+                    let mut executor = Executor::new(None, None).unwrap();
+                    let mut store = Store::new().unwrap();
+                    let instance = store
+                        .register_active_module(&mut executor, &module)
+                        .unwrap();
+                    let next = instance.func("next").unwrap();
+                    let mut last = 0;
+                    for _ in 0..3 {
+                        last = executor.run_func(&next, params!()).unwrap()[0].to_i32();
+                    }
+                    last
+                })
+            })
+            .collect();
+
+        // each instance has its own state
+        for handle in handles {
+            let result = handle.join();
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap(), 3);
+        }
+
+        // the original module is still usable
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+    }
 }
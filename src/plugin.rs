@@ -1,10 +1,15 @@
 //! Defines plugin related structs.
 
 use crate::{
-    error::HostFuncError, instance::Instance, io::WasmValTypeList, CallingFrame, FuncType, Global,
-    Memory, Table, WasmEdgeResult, WasmValue,
+    diagnostics::{HandleGuard, HandleKind},
+    error::HostFuncError,
+    instance::Instance,
+    io::WasmValTypeList,
+    runtime::{self, AsyncRuntime, BoxFuture},
+    CallingFrame, FuncType, Global, Memory, NeverType, Table, WasmEdgeResult, WasmValue,
 };
 use bit_sys::{self as sys, AsImport};
+use std::sync::Arc;
 #[cfg(feature = "wasi_nn")]
 use wasmedge_types::error::WasmEdgeError;
 
@@ -408,9 +413,13 @@ impl Plugin {
     ///
     /// If failed to return the plugin module instance, then return [PluginError::Create](wasmedge_types::error::PluginError::Create) error.
     pub fn mod_instance(&self, name: impl AsRef<str>) -> WasmEdgeResult<PluginInstance> {
-        self.inner
-            .mod_instance(name.as_ref())
-            .map(|i| Instance { inner: i })
+        self.inner.mod_instance(name.as_ref()).map(|i| Instance {
+            inner: i,
+            baseline: None,
+            module_info: None,
+            segment_funcs: None,
+            _guard: HandleGuard::new(HandleKind::Instance),
+        })
     }
 }
 
@@ -586,45 +595,40 @@ impl<T: ?Sized + Send + Sync + Clone> PluginModuleBuilder<T> {
         Ok(self)
     }
 
-    /// Adds an [async host function](crate::Func) to the [PluginModule] to create.
-    ///
-    /// N.B. that this function can be used in thread-safe scenarios.
+    /// Adds an async [host function](crate::Func) to the [PluginModule] to create. See [Func::wrap_async](crate::Func::wrap_async) for how the future is run.
     ///
     /// # Arguments
     ///
     /// * `name` - The exported name of the [host function](crate::Func) to add.
     ///
-    /// * `real_func` - The native function.
+    /// * `runtime` - The async runtime which drives the future.
+    ///
+    /// * `real_func` - The native function returning the future.
     ///
     /// # error
     ///
     /// If fail to create or add the [host function](crate::Func), then an error is returned.
-    #[cfg(all(feature = "async", target_os = "linux"))]
-    #[cfg_attr(docsrs, doc(cfg(all(feature = "async", target_os = "linux"))))]
-    pub fn with_async_func<Args, Rets, D>(
+    pub fn with_async_func<Args, Rets>(
         mut self,
         name: impl AsRef<str>,
+        runtime: Arc<dyn AsyncRuntime>,
         real_func: impl Fn(
                 CallingFrame,
                 Vec<WasmValue>,
-                *mut std::os::raw::c_void,
-            ) -> Box<
-                dyn std::future::Future<Output = Result<Vec<WasmValue>, HostFuncError>> + Send,
-            > + Send
+            ) -> BoxFuture<'static, Result<Vec<WasmValue>, HostFuncError>>
+            + Send
             + Sync
             + 'static,
-        data: Option<Box<D>>,
     ) -> WasmEdgeResult<Self>
     where
         Args: WasmValTypeList,
         Rets: WasmValTypeList,
-        D: Send + Sync,
     {
+        let boxed_func = runtime::async_host_func(runtime, real_func);
         let args = Args::wasm_types();
         let returns = Rets::wasm_types();
         let ty = FuncType::new(Some(args.to_vec()), Some(returns.to_vec()));
-        let inner_func =
-            sys::Function::create_async_func(&ty.into(), Box::new(real_func), data, 0)?;
+        let inner_func = sys::Function::create::<NeverType>(&ty.into(), boxed_func, None, 0)?;
         self.funcs.push((name.as_ref().to_owned(), inner_func));
         Ok(self)
     }
@@ -0,0 +1,310 @@
+//! Defines ExecutorPool, which runs independent function calls in parallel on a pool of worker threads.
+
+use crate::{
+    error::{PoolError, WasmEdgeError},
+    ExecutionReport, Executor, Func, WasmEdgeResult, WasmValue,
+};
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+type TaskFn = dyn FnOnce() -> TaskOutcome + Send;
+
+/// The outcome of a [task](crate::pool::PoolTask): the results and the [report](crate::ExecutionReport) of the call, or the error the call failed with.
+pub type TaskOutcome = WasmEdgeResult<(Vec<WasmValue>, ExecutionReport)>;
+
+/// Defines a function call to run on an [ExecutorPool].
+pub struct PoolTask {
+    run: Box<TaskFn>,
+}
+impl PoolTask {
+    /// Creates a task which runs a function instance with [Executor::run_func_with_report](crate::Executor::run_func_with_report).
+    ///
+    /// The tasks running in parallel are expected to call the functions of different [module instances](crate::Instance), since a module instance does not guard its memory and globals against concurrent calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) to run the function with.
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    pub fn new(
+        executor: &Executor,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> Self {
+        let executor = executor.clone();
+        let func = func.clone();
+        let params: Vec<WasmValue> = params.into_iter().collect();
+        Self::from_fn(move || executor.run_func_with_report(&func, params))
+    }
+
+    /// Creates a task which runs the given closure, for example one instantiating a module in a fresh [store](crate::Store) on the worker before calling it.
+    ///
+    /// # Argument
+    ///
+    /// * `f` - The closure to run, which returns the results and the report of the call.
+    pub fn from_fn(f: impl FnOnce() -> TaskOutcome + Send + 'static) -> Self {
+        Self { run: Box::new(f) }
+    }
+}
+impl std::fmt::Debug for PoolTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoolTask").finish_non_exhaustive()
+    }
+}
+
+/// Runs independent function calls on a shared pool of worker threads, and collects the outcome of every call.
+///
+/// Each [task](crate::pool::PoolTask) is isolated from the others: a task which traps or panics only fails its own call, and the worker running it goes on with the next task. A host function which panics during a call traps the call with a [Trap](crate::error::Trap) instead of aborting the process.
+///
+/// The tasks of a batch run concurrently, so the tasks calling into the same [module instance](crate::Instance) are to be run in different batches.
+///
+/// ```ignore
+/// let pool = ExecutorPool::new(4);
+/// let tasks = instances
+///     .iter()
+///     .map(|instance| PoolTask::new(&executor, &instance.func("process")?, params!(42)))
+///     .collect::<Vec<_>>();
+/// for outcome in pool.join_all(tasks) {
+///     match outcome {
+///         Ok((returns, report)) => println!("{:?} in {:?}", returns, report.duration),
+///         Err(err) => println!("failed: {err}"),
+///     }
+/// }
+/// ```
+///
+/// When the pool is dropped, the queued tasks are finished before the workers exit.
+#[derive(Debug)]
+pub struct ExecutorPool {
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+impl ExecutorPool {
+    /// Creates a new [ExecutorPool] with the given number of worker threads.
+    ///
+    /// # Argument
+    ///
+    /// * `workers` - The number of worker threads. At least one worker is created.
+    pub fn new(workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    let job = receiver
+                        .lock()
+                        .expect("[bitbang] the pool job queue is poisoned")
+                        .recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// Returns the number of worker threads.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Runs the given tasks on the workers, blocks until all of them finish, and returns the outcome of every task in the order of the tasks.
+    ///
+    /// # Argument
+    ///
+    /// * `tasks` - The tasks to run.
+    ///
+    /// # Error
+    ///
+    /// The errors are returned per task:
+    ///
+    /// * If the call fails, for example because it traps, then the error of the call is returned for the task.
+    ///
+    /// * If the task panics, then [WasmEdgeError::Pool(PoolError::Panicked)](crate::error::PoolError) is returned for the task.
+    ///
+    /// * If the task can not be queued, then [WasmEdgeError::Pool(PoolError::ShutDown)](crate::error::PoolError) is returned for the task.
+    pub fn join_all(&self, tasks: impl IntoIterator<Item = PoolTask>) -> Vec<TaskOutcome> {
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        let mut outcomes: Vec<Option<TaskOutcome>> = Vec::new();
+        for (index, task) in tasks.into_iter().enumerate() {
+            let result_sender = result_sender.clone();
+            let sent = self.spawn(task, move |outcome| {
+                let _ = result_sender.send((index, outcome));
+            });
+            outcomes.push(match sent {
+                true => None,
+                false => Some(Err(Box::new(WasmEdgeError::Pool(PoolError::ShutDown)))),
+            });
+        }
+        // the receiver is closed once every queued job has reported or been dropped
+        drop(result_sender);
+
+        for (index, outcome) in result_receiver.iter() {
+            outcomes[index] = Some(outcome);
+        }
+        outcomes
+            .into_iter()
+            .map(|outcome| {
+                outcome.unwrap_or_else(|| Err(Box::new(WasmEdgeError::Pool(PoolError::ShutDown))))
+            })
+            .collect()
+    }
+
+    /// Queues a task on the workers without waiting for it, and passes its outcome to `done` on the worker once the task finishes. A panic of the task is returned as [WasmEdgeError::Pool(PoolError::Panicked)](crate::error::PoolError).
+    ///
+    /// Returns `false` if the task can not be queued, in which case the task and `done` are dropped without being run.
+    pub(crate) fn spawn(
+        &self,
+        task: PoolTask,
+        done: impl FnOnce(TaskOutcome) + Send + 'static,
+    ) -> bool {
+        let job: Job = Box::new(move || {
+            let outcome =
+                panic::catch_unwind(AssertUnwindSafe(task.run)).unwrap_or_else(|payload| {
+                    Err(Box::new(WasmEdgeError::Pool(PoolError::Panicked(
+                        panic_message(payload),
+                    ))))
+                });
+            done(outcome);
+        });
+
+        match &self.sender {
+            Some(sender) => sender.send(job).is_ok(),
+            None => false,
+        }
+    }
+}
+impl Drop for ExecutorPool {
+    fn drop(&mut self) {
+        // closing the queue stops the workers once the queued tasks are finished
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Extracts the message of a panic payload.
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{CoreError, CoreExecutionError, Trap},
+        params, wat2wasm, CallingFrame, Module, NeverType, Store, WasmVal,
+    };
+
+    #[test]
+    fn test_executor_pool_join_all() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (global $calls (mut i32) (i32.const 0))
+              (func (export "count") (param i32) (result i32)
+                global.get $calls
+                local.get 0
+                i32.add
+                global.set $calls
+                global.get $calls)
+              (func (export "trap") unreachable)
+            )
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let mut instances = Vec::new();
+        for i in 0..5 {
+            let result = store.register_named_module(&mut executor, format!("m{i}"), &module);
+            assert!(result.is_ok());
+            instances.push(result.unwrap());
+        }
+
+        let pool = ExecutorPool::new(2);
+        assert_eq!(pool.workers(), 2);
+
+        // every instance keeps its own state
+        let mut tasks: Vec<_> = instances
+            .iter()
+            .enumerate()
+            .map(|(i, instance)| {
+                PoolTask::new(
+                    &executor,
+                    &instance.func("count").unwrap(),
+                    params!(i as i32),
+                )
+            })
+            .collect();
+        tasks.push(PoolTask::new(
+            &executor,
+            &instances[4].func("trap").unwrap(),
+            params!(),
+        ));
+        tasks.push(PoolTask::from_fn(|| panic!("broken task")));
+        let result = Func::wrap::<(), (), NeverType>(
+            |_frame: CallingFrame, _inputs: Vec<WasmValue>, _data: *mut std::os::raw::c_void| {
+                panic!("broken host function")
+            },
+            None,
+        );
+        assert!(result.is_ok());
+        tasks.push(PoolTask::new(&executor, &result.unwrap(), params!()));
+
+        let outcomes = pool.join_all(tasks);
+        assert_eq!(outcomes.len(), 7);
+        for (i, outcome) in outcomes.iter().take(4).enumerate() {
+            assert!(outcome.is_ok());
+            let (returns, report) = outcome.as_ref().unwrap();
+            assert_eq!(returns[0].to_i32(), i as i32);
+            assert_eq!(report.host_calls, 0);
+        }
+        assert_eq!(
+            **outcomes[4].as_ref().unwrap_err(),
+            WasmEdgeError::Core(CoreError::Execution(CoreExecutionError::Unreachable))
+        );
+        assert_eq!(
+            **outcomes[5].as_ref().unwrap_err(),
+            WasmEdgeError::Pool(PoolError::Panicked("broken task".into()))
+        );
+        assert_eq!(
+            **outcomes[6].as_ref().unwrap_err(),
+            WasmEdgeError::Trap(Trap::new("host function panicked: broken host function"))
+        );
+
+        // the workers survive the failed tasks
+        let outcomes = pool.join_all(vec![PoolTask::new(
+            &executor,
+            &instances[1].func("count").unwrap(),
+            params!(10),
+        )]);
+        assert!(outcomes[0].is_ok());
+        assert_eq!(outcomes[0].as_ref().unwrap().0[0].to_i32(), 11);
+    }
+}
@@ -0,0 +1,689 @@
+//! Defines ReplayBundle, the single-file artifact used to reproduce the execution of a wasm function.
+//!
+//! A [ReplayBundle] is created by [Executor::run_func_recorded](crate::Executor::run_func_recorded). It contains the module, the configuration, the input arguments, the seed of the host-side randomness, and the results of all host function calls made during the execution. [Executor::replay](crate::Executor::replay) re-runs the function without the original host functions: every host function call is served from the recorded results, so the execution is reproduced exactly as long as the guest is deterministic.
+//!
+//! Notice that only the host functions created with this crate are recorded. The calls to the host functions implemented by WasmEdge itself, such as the WASI functions, are not recorded; a module which imports them can not be replayed.
+
+use crate::{
+    config::{CommonConfigOptions, Config, ConfigBuilder, RuntimeConfigOptions},
+    error::{HostFuncError, ReplayError, Trap, WasmEdgeError},
+    ExternalInstanceType, ImportObject, ImportObjectBuilder, Memory, Module, NeverType, RefType,
+    Table, ValType, WasmEdgeResult, WasmValue,
+};
+use bit_sys::replay::{HostCallRecord, REPLAY_DIVERGED_CODE};
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    path::Path,
+};
+
+const MAGIC: &[u8; 4] = b"BBRB";
+const VERSION: u32 = 2;
+
+thread_local! {
+    static RNG_STATE: Cell<Option<u64>> = Cell::new(None);
+}
+
+/// Returns the next pseudo-random number of the current thread.
+///
+/// While a function is recorded or replayed, the sequence is seeded with the seed stored in the [ReplayBundle], so host code which draws its randomness from this function behaves the same in both runs. Otherwise, the sequence is seeded randomly.
+pub fn random_u64() -> u64 {
+    RNG_STATE.with(|state| {
+        let mut x = match state.get() {
+            Some(x) => x,
+            None => RandomState::new().build_hasher().finish(),
+        };
+
+        // splitmix64
+        x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(Some(x));
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+/// Seeds the pseudo-random number sequence of the current thread.
+pub(crate) fn seed(seed: u64) {
+    RNG_STATE.with(|state| state.set(Some(seed)));
+}
+
+/// Returns the FNV-1a hash of the given module binary.
+pub(crate) fn module_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// The subset of a [Config](crate::config::Config) which affects the execution of a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ConfigSnapshot {
+    proposals: [bool; 12],
+    max_memory_pages: u32,
+}
+impl ConfigSnapshot {
+    fn new(config: &Config) -> Self {
+        Self {
+            proposals: [
+                config.mutable_globals_enabled(),
+                config.non_trap_conversions_enabled(),
+                config.sign_extension_operators_enabled(),
+                config.multi_value_enabled(),
+                config.bulk_memory_operations_enabled(),
+                config.reference_types_enabled(),
+                config.simd_enabled(),
+                config.multi_memories_enabled(),
+                config.threads_enabled(),
+                config.tail_call_enabled(),
+                config.function_references_enabled(),
+                config.interpreter_mode_enabled(),
+            ],
+            max_memory_pages: config.max_memory_pages(),
+        }
+    }
+
+    fn build(&self) -> WasmEdgeResult<Config> {
+        let p = self.proposals;
+        let options = CommonConfigOptions::new()
+            .mutable_globals(p[0])
+            .non_trap_conversions(p[1])
+            .sign_extension_operators(p[2])
+            .multi_value(p[3])
+            .bulk_memory_operations(p[4])
+            .reference_types(p[5])
+            .simd(p[6])
+            .multi_memories(p[7])
+            .threads(p[8])
+            .tail_call(p[9])
+            .function_references(p[10])
+            .interpreter_mode(p[11]);
+
+        ConfigBuilder::new(options)
+            .with_runtime_config(
+                RuntimeConfigOptions::new().max_memory_pages(self.max_memory_pages),
+            )
+            .build()
+    }
+}
+
+/// Defines a self-contained artifact to reproduce the execution of a wasm function.
+///
+/// See the [module-level documentation](crate::replay) for details.
+#[derive(Debug, Clone)]
+pub struct ReplayBundle {
+    pub(crate) module_hash: u64,
+    pub(crate) module: Vec<u8>,
+    pub(crate) config: Option<ConfigSnapshot>,
+    pub(crate) func_name: String,
+    pub(crate) params: Vec<WasmValue>,
+    pub(crate) seed: u64,
+    pub(crate) host_calls: Vec<HostCallRecord>,
+    pub(crate) outcome: Result<Vec<WasmValue>, String>,
+}
+impl ReplayBundle {
+    pub(crate) fn new(
+        module: Vec<u8>,
+        config: Option<&Config>,
+        func_name: String,
+        params: Vec<WasmValue>,
+        seed: u64,
+        host_calls: Vec<HostCallRecord>,
+        outcome: Result<Vec<WasmValue>, String>,
+    ) -> Self {
+        Self {
+            module_hash: module_hash(&module),
+            module,
+            config: config.map(ConfigSnapshot::new),
+            func_name,
+            params,
+            seed,
+            host_calls,
+            outcome,
+        }
+    }
+
+    /// Returns the FNV-1a hash of the recorded module binary.
+    pub fn module_hash(&self) -> u64 {
+        self.module_hash
+    }
+
+    /// Returns the recorded module binary.
+    pub fn module(&self) -> &[u8] {
+        &self.module
+    }
+
+    /// Returns the name of the recorded function.
+    pub fn func_name(&self) -> &str {
+        &self.func_name
+    }
+
+    /// Returns the arguments passed to the recorded function.
+    pub fn params(&self) -> &[WasmValue] {
+        &self.params
+    }
+
+    /// Returns the seed of the host-side randomness. See [random_u64](crate::replay::random_u64).
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of the recorded host function calls.
+    pub fn host_call_count(&self) -> usize {
+        self.host_calls.len()
+    }
+
+    /// Returns the values returned by the recorded function, or `None` if the function failed.
+    pub fn returns(&self) -> Option<&[WasmValue]> {
+        self.outcome.as_deref().ok()
+    }
+
+    /// Returns the error message of the recorded function, or `None` if the function succeeded.
+    pub fn failure(&self) -> Option<&str> {
+        self.outcome.as_ref().err().map(|msg| msg.as_str())
+    }
+
+    /// Serializes this bundle into bytes.
+    ///
+    /// # Error
+    ///
+    /// If the arguments or the results contain a non-null reference, which can not be serialized, then [WasmEdgeError::Replay(ReplayError::UnsupportedValue)](crate::error::ReplayError) is returned.
+    pub fn to_bytes(&self) -> WasmEdgeResult<Vec<u8>> {
+        let mut w = Writer::default();
+        w.bytes(MAGIC);
+        w.u32(VERSION);
+        w.u64(self.module_hash);
+        w.blob(&self.module);
+        match &self.config {
+            Some(config) => {
+                w.u8(1);
+                for enabled in config.proposals {
+                    w.u8(enabled as u8);
+                }
+                w.u32(config.max_memory_pages);
+            }
+            None => w.u8(0),
+        }
+        w.str(&self.func_name);
+        w.values(&self.params)?;
+        w.u64(self.seed);
+        w.u32(self.host_calls.len() as u32);
+        for call in self.host_calls.iter() {
+            w.str(&call.mod_name);
+            w.str(&call.name);
+            w.values(&call.params)?;
+            match &call.result {
+                Ok(returns) => {
+                    w.u8(0);
+                    w.values(returns)?;
+                }
+                Err(HostFuncError::User(code)) => {
+                    w.u8(1);
+                    w.u32(*code);
+                }
+                Err(HostFuncError::Runtime(code)) => {
+                    w.u8(2);
+                    w.u32(*code);
+                }
+                Err(HostFuncError::Trap(trap)) => {
+                    w.u8(3);
+                    w.u32(trap.code());
+                    w.str(trap.message());
+                }
+            }
+        }
+        match &self.outcome {
+            Ok(returns) => {
+                w.u8(0);
+                w.values(returns)?;
+            }
+            Err(msg) => {
+                w.u8(1);
+                w.str(msg);
+            }
+        }
+        Ok(w.0)
+    }
+
+    /// Deserializes a bundle from bytes.
+    ///
+    /// # Argument
+    ///
+    /// * `bytes` - The bytes created by [ReplayBundle::to_bytes].
+    ///
+    /// # Error
+    ///
+    /// If the bytes are not a valid bundle, or the module binary does not match the recorded hash, then an error is returned.
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> WasmEdgeResult<Self> {
+        let mut r = Reader(bytes.as_ref());
+        if r.bytes(MAGIC.len())? != MAGIC {
+            return Err(malformed("invalid magic number"));
+        }
+        let version = r.u32()?;
+        if version != VERSION {
+            return Err(Box::new(WasmEdgeError::Replay(
+                ReplayError::UnsupportedVersion(version),
+            )));
+        }
+        let expected_hash = r.u64()?;
+        let module = r.blob()?.to_vec();
+        let config = match r.u8()? {
+            0 => None,
+            1 => {
+                let mut proposals = [false; 12];
+                for enabled in proposals.iter_mut() {
+                    *enabled = r.u8()? != 0;
+                }
+                Some(ConfigSnapshot {
+                    proposals,
+                    max_memory_pages: r.u32()?,
+                })
+            }
+            _ => return Err(malformed("invalid config flag")),
+        };
+        let func_name = r.str()?;
+        let params = r.values()?;
+        let seed = r.u64()?;
+        let count = r.u32()?;
+        let mut host_calls = Vec::new();
+        for _ in 0..count {
+            let mod_name = r.str()?;
+            let name = r.str()?;
+            let params = r.values()?;
+            let result = match r.u8()? {
+                0 => Ok(r.values()?),
+                1 => Err(HostFuncError::User(r.u32()?)),
+                2 => Err(HostFuncError::Runtime(r.u32()?)),
+                3 => {
+                    let code = r.u32()?;
+                    Err(HostFuncError::Trap(Trap::new(r.str()?).with_code(code)))
+                }
+                _ => return Err(malformed("invalid host call result")),
+            };
+            host_calls.push(HostCallRecord {
+                mod_name,
+                name,
+                params,
+                result,
+            });
+        }
+        let outcome = match r.u8()? {
+            0 => Ok(r.values()?),
+            1 => Err(r.str()?),
+            _ => return Err(malformed("invalid outcome")),
+        };
+
+        let actual_hash = module_hash(&module);
+        if actual_hash != expected_hash {
+            return Err(Box::new(WasmEdgeError::Replay(
+                ReplayError::ModuleHashMismatch {
+                    expected: expected_hash,
+                    actual: actual_hash,
+                },
+            )));
+        }
+
+        Ok(Self {
+            module_hash: expected_hash,
+            module,
+            config,
+            func_name,
+            params,
+            seed,
+            host_calls,
+            outcome,
+        })
+    }
+
+    /// Writes this bundle to a file.
+    ///
+    /// # Argument
+    ///
+    /// * `path` - The path to the target file.
+    ///
+    /// # Error
+    ///
+    /// If fail to serialize the bundle or to write the file, then an error is returned.
+    pub fn save(&self, path: impl AsRef<Path>) -> WasmEdgeResult<()> {
+        std::fs::write(path, self.to_bytes()?)
+            .map_err(|e| Box::new(WasmEdgeError::Replay(ReplayError::Io(e.to_string()))))
+    }
+
+    /// Reads a bundle from a file.
+    ///
+    /// # Argument
+    ///
+    /// * `path` - The path to the file written by [ReplayBundle::save].
+    ///
+    /// # Error
+    ///
+    /// If fail to read the file or to deserialize the bundle, then an error is returned.
+    pub fn load(path: impl AsRef<Path>) -> WasmEdgeResult<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| Box::new(WasmEdgeError::Replay(ReplayError::Io(e.to_string()))))?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Checks that the recorded module binary matches its recorded hash.
+    pub(crate) fn check_module_hash(&self) -> WasmEdgeResult<()> {
+        let actual = module_hash(&self.module);
+        match actual == self.module_hash {
+            true => Ok(()),
+            false => Err(Box::new(WasmEdgeError::Replay(
+                ReplayError::ModuleHashMismatch {
+                    expected: self.module_hash,
+                    actual,
+                },
+            ))),
+        }
+    }
+
+    /// Returns the configuration the bundle was recorded with.
+    pub(crate) fn config(&self) -> WasmEdgeResult<Option<Config>> {
+        self.config
+            .as_ref()
+            .map(|config| config.build())
+            .transpose()
+    }
+}
+
+/// Creates the import objects which satisfy the imports of the given module during replay.
+///
+/// The stubbed host functions are never invoked, since their calls are served from the recorded results.
+pub(crate) fn stub_imports(module: &Module) -> WasmEdgeResult<Vec<ImportObject<NeverType>>> {
+    let mut builders: Vec<(String, ImportObjectBuilder)> = Vec::new();
+    for import in module.imports() {
+        let mod_name = import.module_name().to_string();
+        let name = import.name().to_string();
+        let idx = match builders.iter().position(|(n, _)| *n == mod_name) {
+            Some(idx) => idx,
+            None => {
+                builders.push((mod_name.clone(), ImportObjectBuilder::new()));
+                builders.len() - 1
+            }
+        };
+        let (_, builder) = builders.remove(idx);
+        let builder = match import.ty()? {
+            ExternalInstanceType::Func(ty) => builder.with_func_by_type::<NeverType>(
+                &name,
+                ty,
+                |_, _, _| Err(HostFuncError::Runtime(REPLAY_DIVERGED_CODE)),
+                None,
+            )?,
+            ExternalInstanceType::Memory(ty) => builder.with_memory(&name, Memory::new(ty)?),
+            ExternalInstanceType::Table(ty) => builder.with_table(&name, Table::new(ty)?),
+            ExternalInstanceType::Global(_) => {
+                return Err(Box::new(WasmEdgeError::Replay(
+                    ReplayError::UnsupportedImport(format!("{mod_name}::{name}")),
+                )))
+            }
+        };
+        builders.insert(idx, (mod_name, builder));
+    }
+
+    builders
+        .into_iter()
+        .map(|(mod_name, builder)| builder.build::<NeverType>(mod_name, None))
+        .collect()
+}
+
+fn malformed(msg: &str) -> Box<WasmEdgeError> {
+    Box::new(WasmEdgeError::Replay(ReplayError::Malformed(msg.into())))
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    fn blob(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.bytes(bytes);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.blob(s.as_bytes());
+    }
+
+    fn values(&mut self, values: &[WasmValue]) -> WasmEdgeResult<()> {
+        self.u32(values.len() as u32);
+        for value in values {
+            let (tag, bits) = match value.ty() {
+                ValType::I32 => (0, value.to_i32() as u32 as u128),
+                ValType::I64 => (1, value.to_i64() as u64 as u128),
+                ValType::F32 => (2, value.to_f32().to_bits() as u128),
+                ValType::F64 => (3, value.to_f64().to_bits() as u128),
+                ValType::V128 => (4, value.to_v128() as u128),
+                ValType::FuncRef if value.is_null_ref() => (5, 0),
+                ValType::ExternRef if value.is_null_ref() => (6, 0),
+                ty => {
+                    return Err(Box::new(WasmEdgeError::Replay(
+                        ReplayError::UnsupportedValue(format!("{ty:?}")),
+                    )))
+                }
+            };
+            self.u8(tag);
+            self.bytes(&bits.to_le_bytes());
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> WasmEdgeResult<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(malformed("unexpected end of data"));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> WasmEdgeResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> WasmEdgeResult<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> WasmEdgeResult<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn blob(&mut self) -> WasmEdgeResult<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    fn str(&mut self) -> WasmEdgeResult<String> {
+        std::str::from_utf8(self.blob()?)
+            .map(|s| s.to_string())
+            .map_err(|e| Box::new(WasmEdgeError::Utf8(e)))
+    }
+
+    fn values(&mut self) -> WasmEdgeResult<Vec<WasmValue>> {
+        let count = self.u32()?;
+        let mut values = Vec::new();
+        for _ in 0..count {
+            let tag = self.u8()?;
+            let bits = u128::from_le_bytes(self.bytes(16)?.try_into().unwrap());
+            let value = match tag {
+                0 => WasmValue::from_i32(bits as u32 as i32),
+                1 => WasmValue::from_i64(bits as u64 as i64),
+                2 => WasmValue::from_f32(f32::from_bits(bits as u32)),
+                3 => WasmValue::from_f64(f64::from_bits(bits as u64)),
+                4 => WasmValue::from_v128(bits as i128),
+                5 => WasmValue::from_null_ref(RefType::FuncRef),
+                6 => WasmValue::from_null_ref(RefType::ExternRef),
+                _ => return Err(malformed("invalid value tag")),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, wat2wasm, CallingFrame, Executor, Store, WasmVal};
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    static COUNTER: AtomicI32 = AtomicI32::new(0);
+
+    #[test]
+    fn test_replay_bundle() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "env" "next" (func $next (result i32)))
+              (func (export "sum") (param i32) (result i32)
+                call $next
+                call $next
+                i32.add
+                local.get 0
+                i32.add)
+            )
+            "#,
+        )
+        .unwrap();
+
+        // record a run with a stateful host function
+        let result = ImportObjectBuilder::new()
+            .with_func::<(), i32, NeverType>("next", real_next, None)
+            .expect("failed to add host function")
+            .build::<NeverType>("env", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+
+        let result = Module::from_bytes(None, &wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let sum = instance.func("sum").unwrap();
+
+        let result = executor.run_func_recorded(&wasm_bytes, None, &sum, params!(100), 42);
+        assert!(result.is_ok());
+        let bundle = result.unwrap();
+        assert_eq!(bundle.func_name(), "sum");
+        assert_eq!(bundle.host_call_count(), 2);
+        assert_eq!(bundle.seed(), 42);
+        assert_eq!(bundle.module_hash(), module_hash(&wasm_bytes));
+        assert_eq!(bundle.host_calls[0].mod_name, "env");
+        assert_eq!(bundle.host_calls[0].name, "next");
+        let expected = bundle.returns().unwrap()[0].to_i32();
+
+        // a function which does not come from the given module is rejected
+        let other =
+            wat2wasm(br#"(module (func (export "sum") (result i32) i32.const 0))"#).unwrap();
+        let result = executor.run_func_recorded(&other, None, &sum, params!(100), 42);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Replay(ReplayError::FuncNotInModule("sum".into()))
+        );
+
+        // serialize and deserialize the bundle
+        let result = bundle.to_bytes();
+        assert!(result.is_ok());
+        let bytes = result.unwrap();
+        let result = ReplayBundle::from_bytes(&bytes);
+        assert!(result.is_ok());
+        let bundle = result.unwrap();
+        assert!(ReplayBundle::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // the counter has moved on, but the replay reproduces the recorded result
+        COUNTER.fetch_add(1000, Ordering::SeqCst);
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+        let result = executor.replay(&bundle);
+        assert!(result.is_ok());
+        let returns = result.unwrap();
+        assert_eq!(returns[0].to_i32(), expected);
+
+        // a tampered module is rejected
+        let mut tampered = bundle.clone();
+        tampered.module.push(0);
+        let result = executor.replay(&tampered);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Replay(ReplayError::ModuleHashMismatch {
+                expected: bundle.module_hash(),
+                actual: module_hash(&tampered.module),
+            })
+        );
+
+        // a record made by another import diverges
+        let mut renamed = bundle.clone();
+        renamed.host_calls[1].name = "prev".into();
+        let result = executor.replay(&renamed);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Replay(ReplayError::Diverged)
+        );
+
+        // a replay which makes fewer host calls diverges
+        let mut truncated = bundle.clone();
+        truncated.host_calls.truncate(1);
+        let result = executor.replay(&truncated);
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Replay(ReplayError::Diverged)
+        );
+    }
+
+    #[test]
+    fn test_replay_random_u64() {
+        seed(7);
+        let a = (random_u64(), random_u64());
+        seed(7);
+        let b = (random_u64(), random_u64());
+        assert_eq!(a, b);
+        assert_ne!(a.0, a.1);
+    }
+
+    fn real_next(
+        _frame: CallingFrame,
+        _inputs: Vec<WasmValue>,
+        _data: *mut std::os::raw::c_void,
+    ) -> Result<Vec<WasmValue>, HostFuncError> {
+        Ok(vec![WasmValue::from_i32(
+            COUNTER.fetch_add(1, Ordering::SeqCst),
+        )])
+    }
+}
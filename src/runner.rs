@@ -0,0 +1,399 @@
+//! Defines run_wasm_file, a one-call runner of WASI programs.
+
+use crate::{
+    config::{
+        CommonConfigOptions, ConfigBuilder, HostRegistrationConfigOptions, RuntimeConfigOptions,
+        StatisticsConfigOptions,
+    },
+    error::WasmEdgeError,
+    wasi::Preopen,
+    Statistics, VmBuilder, WasmEdgeResult, WasmValue,
+};
+use std::path::Path;
+
+/// Defines the options of [run_wasm_file](crate::run_wasm_file).
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    common_config: CommonConfigOptions,
+    max_memory_pages: Option<u32>,
+    args: Vec<String>,
+    envs: Vec<String>,
+    preopens: Vec<Preopen>,
+    capture_stdio: bool,
+}
+impl RunOptions {
+    /// Creates a new [RunOptions] with the default configuration, no arguments, no environment variables, no pre-opened directories, and the standard streams not captured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [CommonConfigOptions](crate::config::CommonConfigOptions) used to load and run the module.
+    ///
+    /// # Argument
+    ///
+    /// * `options` - The common configuration options.
+    pub fn with_common_config(self, options: CommonConfigOptions) -> Self {
+        Self {
+            common_config: options,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of the memory pages available.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The page count (64KB per page).
+    pub fn with_max_memory_pages(self, count: u32) -> Self {
+        Self {
+            max_memory_pages: Some(count),
+            ..self
+        }
+    }
+
+    /// Appends the commandline arguments passed to the program. The program name is prepended automatically.
+    ///
+    /// # Argument
+    ///
+    /// * `args` - The commandline arguments.
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds an environment variable visible to the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the environment variable.
+    ///
+    /// * `value` - The value of the environment variable.
+    pub fn with_env(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.envs
+            .push(format!("{}={}", name.as_ref(), value.as_ref()));
+        self
+    }
+
+    /// Adds a directory pre-opened for the program.
+    ///
+    /// # Argument
+    ///
+    /// * `preopen` - The [directory](crate::wasi::Preopen) to pre-open.
+    pub fn with_preopen(mut self, preopen: Preopen) -> Self {
+        self.preopens.push(preopen);
+        self
+    }
+
+    /// Sets if the standard output and the standard error written by the program are captured.
+    ///
+    /// The capture temporarily redirects the standard streams of the whole process, so the output written by other threads of the process during the run is captured as well, and the runs capturing the standard streams are serialized. The capture is only supported on Unix-like systems, and is ignored elsewhere.
+    ///
+    /// # Argument
+    ///
+    /// * `enable` - Whether the standard streams are captured.
+    pub fn with_capture_stdio(self, enable: bool) -> Self {
+        Self {
+            capture_stdio: enable,
+            ..self
+        }
+    }
+}
+
+/// The outcome of [run_wasm_file](crate::run_wasm_file).
+#[derive(Debug, Clone)]
+pub struct RunOutput {
+    /// The values returned by the function.
+    pub returns: Vec<WasmValue>,
+    /// The WASI exit code, which is `0` if the program returns without calling `proc_exit`.
+    pub exit_code: u32,
+    /// The captured standard output, or `None` if it is not captured.
+    pub stdout: Option<Vec<u8>>,
+    /// The captured standard error, or `None` if it is not captured.
+    pub stderr: Option<Vec<u8>>,
+    /// The [statistics](crate::Statistics) of the run, with instruction counting, cost measuring and time measuring enabled.
+    pub statistics: Statistics,
+}
+
+/// Loads a wasm file and runs one of its exported functions with WASI enabled, in a single call.
+///
+/// The [config](crate::config::Config), [statistics](crate::Statistics), [store](crate::Store), [executor](crate::Executor) and the WASI module instance are all created for the run, and dropped afterwards.
+///
+/// ```ignore
+/// let output = bitbang::run_wasm_file(
+///     "hello.wasm",
+///     "_start",
+///     params!(),
+///     RunOptions::new().with_args(["--name", "world"]).with_capture_stdio(true),
+/// )?;
+/// assert_eq!(output.exit_code, 0);
+/// assert_eq!(output.stdout.unwrap(), b"hello, world\n");
+/// ```
+///
+/// # Arguments
+///
+/// * `path` - The path to a wasm file or an AOT wasm file.
+///
+/// * `func_name` - The name of the exported function to run, for example `_start` for a WASI command.
+///
+/// * `params` - The arguments to pass to the function.
+///
+/// * `options` - The [options](crate::RunOptions) of the run.
+///
+/// # Error
+///
+/// If fail to load the wasm file or to run the function, then an error is returned. A call to `proc_exit` is not an error; its code is returned as [RunOutput::exit_code](crate::RunOutput::exit_code).
+pub fn run_wasm_file(
+    path: impl AsRef<Path>,
+    func_name: impl AsRef<str>,
+    params: impl IntoIterator<Item = WasmValue>,
+    options: RunOptions,
+) -> WasmEdgeResult<RunOutput> {
+    let mut runtime_config = RuntimeConfigOptions::new();
+    if let Some(count) = options.max_memory_pages {
+        runtime_config = runtime_config.max_memory_pages(count);
+    }
+    let config = ConfigBuilder::new(options.common_config)
+        .with_statistics_config(
+            StatisticsConfigOptions::new()
+                .count_instructions(true)
+                .measure_cost(true)
+                .measure_time(true),
+        )
+        .with_runtime_config(runtime_config)
+        .with_host_registration_config(HostRegistrationConfigOptions::new().wasi(true))
+        .build()?;
+
+    let mut vm = VmBuilder::new()
+        .with_config(config)
+        .with_statistics(Statistics::new()?)
+        .build()?;
+
+    // initialize the wasi module instance
+    let path = path.as_ref();
+    let program = path.to_string_lossy().into_owned();
+    let args = std::iter::once(program.as_str())
+        .chain(options.args.iter().map(String::as_str))
+        .collect();
+    let envs = options.envs.iter().map(String::as_str).collect();
+    let wasi = vm.wasi_module_mut().ok_or_else(|| {
+        Box::new(WasmEdgeError::Operation(
+            "Fail to create the WASI module instance".into(),
+        ))
+    })?;
+    wasi.initialize_with_preopens(Some(args), Some(envs), options.preopens)?;
+
+    let func_name = func_name.as_ref();
+    let (returns, stdout, stderr) = if options.capture_stdio {
+        capture_stdio(|| vm.run_func_from_file(path, func_name, params))?
+    } else {
+        (vm.run_func_from_file(path, func_name, params)?, None, None)
+    };
+
+    let exit_code = vm.wasi_module().map_or(0, |wasi| wasi.exit_code());
+    let statistics = vm.statistics().cloned().expect("the statistics are set");
+
+    Ok(RunOutput {
+        returns,
+        exit_code,
+        stdout,
+        stderr,
+        statistics,
+    })
+}
+
+type Captured<T> = (T, Option<Vec<u8>>, Option<Vec<u8>>);
+
+/// Runs the closure while the standard output and the standard error of the process are redirected, and returns the captured bytes.
+#[cfg(unix)]
+fn capture_stdio<T>(f: impl FnOnce() -> WasmEdgeResult<T>) -> WasmEdgeResult<Captured<T>> {
+    use std::{
+        fs::File,
+        io::{Read, Seek, SeekFrom, Write},
+        os::{raw::c_int, unix::io::AsRawFd},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    };
+
+    extern "C" {
+        fn dup(fd: c_int) -> c_int;
+        fn dup2(src: c_int, dst: c_int) -> c_int;
+        fn close(fd: c_int) -> c_int;
+    }
+
+    static CAPTURE_LOCK: Mutex<()> = Mutex::new(());
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    /// Restores the redirected file descriptor when dropped, even if the closure panics.
+    struct Redirect {
+        fd: c_int,
+        saved: c_int,
+    }
+    impl Redirect {
+        fn new(fd: c_int, file: &File) -> std::io::Result<Self> {
+            // SAFETY: `dup` and `dup2` only operate on the file descriptor table.
+            let saved = unsafe { dup(fd) };
+            if saved < 0 || unsafe { dup2(file.as_raw_fd(), fd) } < 0 {
+                let err = std::io::Error::last_os_error();
+                if saved >= 0 {
+                    unsafe { close(saved) };
+                }
+                return Err(err);
+            }
+            Ok(Self { fd, saved })
+        }
+    }
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            let _ = std::io::stdout().flush();
+            let _ = std::io::stderr().flush();
+            // SAFETY: `saved` is a valid file descriptor owned by this guard.
+            unsafe {
+                dup2(self.saved, self.fd);
+                close(self.saved);
+            }
+        }
+    }
+
+    fn temp_file() -> std::io::Result<File> {
+        let path = std::env::temp_dir().join(format!(
+            "bitbang-stdio-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(file)
+    }
+
+    fn read_back(mut file: File) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    let io_error = |err: std::io::Error| {
+        Box::new(WasmEdgeError::Operation(format!(
+            "Fail to capture the standard streams: {err}"
+        )))
+    };
+
+    let _lock = CAPTURE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let stdout = temp_file().map_err(io_error)?;
+    let stderr = temp_file().map_err(io_error)?;
+
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    let result = {
+        let _stdout = Redirect::new(1, &stdout).map_err(io_error)?;
+        let _stderr = Redirect::new(2, &stderr).map_err(io_error)?;
+        f()
+    }?;
+
+    Ok((
+        result,
+        Some(read_back(stdout).map_err(io_error)?),
+        Some(read_back(stderr).map_err(io_error)?),
+    ))
+}
+
+/// Runs the closure without capturing the standard streams, which is not supported on this platform.
+#[cfg(not(unix))]
+fn capture_stdio<T>(f: impl FnOnce() -> WasmEdgeResult<T>) -> WasmEdgeResult<Captured<T>> {
+    Ok((f()?, None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, wat2wasm, WasmVal};
+
+    fn write_wasm(name: &str, wat: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{name}-{}.wasm", std::process::id()));
+        std::fs::write(&path, wat2wasm(wat).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_run_wasm_file() {
+        let path = write_wasm(
+            "bitbang-run-add",
+            br#"
+            (module
+              (func (export "add") (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                i32.add)
+            )
+            "#,
+        );
+
+        let result = run_wasm_file(&path, "add", params!(2, 3), RunOptions::new());
+        assert!(result.is_ok());
+        let output = result.unwrap();
+        assert_eq!(output.returns.len(), 1);
+        assert_eq!(output.returns[0].to_i32(), 5);
+        assert_eq!(output.exit_code, 0);
+        assert!(output.stdout.is_none());
+        assert!(output.statistics.count() > 0);
+
+        // the function does not exist
+        let result = run_wasm_file(&path, "sub", params!(2, 3), RunOptions::new());
+        assert!(result.is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_wasm_file_wasi() {
+        let path = write_wasm(
+            "bitbang-run-hello",
+            br#"
+            (module
+              (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+              (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 16) "hello\n")
+              (func (export "_start")
+                ;; iovec { buf = 16, len = 6 } at offset 0
+                i32.const 0
+                i32.const 16
+                i32.store
+                i32.const 4
+                i32.const 6
+                i32.store
+                i32.const 1
+                i32.const 0
+                i32.const 1
+                i32.const 8
+                call $fd_write
+                drop
+                i32.const 3
+                call $proc_exit)
+            )
+            "#,
+        );
+
+        let options = RunOptions::new()
+            .with_args(["--flag"])
+            .with_env("KEY", "VALUE")
+            .with_capture_stdio(true);
+        let result = run_wasm_file(&path, "_start", params!(), options);
+        assert!(result.is_ok());
+        let output = result.unwrap();
+        assert!(output.returns.is_empty());
+        assert_eq!(output.exit_code, 3);
+        assert_eq!(output.stdout.unwrap(), b"hello\n");
+        assert_eq!(output.stderr.unwrap(), b"");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+//! Defines the async runtimes which drive the futures of the async host functions.
+//!
+//! [Executor::run_func_async](crate::Executor::run_func_async) does not depend on any specific async runtime. An async host function, created with [Func::wrap_async](crate::Func::wrap_async) or [ImportObjectBuilder::with_async_func](crate::ImportObjectBuilder::with_async_func), blocks the thread running the guest on its future with the given [AsyncRuntime], so the future can use the timers and the I/O of that runtime. The implementations for [tokio](https://docs.rs/tokio) and [async-std](https://docs.rs/async-std) are available with the `tokio` and `async-std` features.
+//!
+//! ```ignore
+//! let runtime = Arc::new(TokioRuntime::current());
+//! let fetch = Func::wrap_async::<i32, i32>(runtime, |_frame, inputs| {
+//!     Box::pin(async move {
+//!         let len = fetch_len(inputs[0].to_i32()).await;
+//!         Ok(vec![WasmValue::from_i32(len)])
+//!     })
+//! })?;
+//! let returns = executor.run_func_async(&run, params!()).await?;
+//! ```
+
+#[cfg(feature = "tokio")]
+use crate::error::Trap;
+use crate::{error::HostFuncError, externals::BoxedHostFn, CallingFrame, WasmValue};
+use bit_sys::cancel::CANCELLED_CODE;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// A boxed future which can be sent across threads.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Defines the operations of an async runtime used by the async host functions.
+pub trait AsyncRuntime: Send + Sync + 'static {
+    /// Spawns a future to run in the background.
+    ///
+    /// # Argument
+    ///
+    /// * `future` - The future to run.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// Returns a future which completes after the given duration.
+    ///
+    /// # Argument
+    ///
+    /// * `duration` - The duration to wait.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Blocks the current thread until the given future completes.
+    ///
+    /// It is called on the thread running the guest, which is not a worker thread of the runtime when the guest runs with [Executor::run_func_async](crate::Executor::run_func_async). A guest run with [Executor::run_func](crate::Executor::run_func) from inside the runtime calls it on a worker thread.
+    ///
+    /// # Argument
+    ///
+    /// * `future` - The future to run.
+    ///
+    /// # Error
+    ///
+    /// If the runtime cannot be blocked on from the current thread, then an error is returned, which fails the host call.
+    fn block_on(&self, future: BoxFuture<'_, ()>) -> Result<(), HostFuncError>;
+}
+
+/// The [AsyncRuntime] backed by a [tokio](https://docs.rs/tokio) runtime.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug, Clone)]
+pub struct TokioRuntime {
+    handle: tokio::runtime::Handle,
+}
+#[cfg(feature = "tokio")]
+impl TokioRuntime {
+    /// Creates a [TokioRuntime] with the given handle of a tokio runtime.
+    ///
+    /// # Argument
+    ///
+    /// * `handle` - The handle of the tokio runtime.
+    pub fn new(handle: tokio::runtime::Handle) -> Self {
+        Self { handle }
+    }
+
+    /// Creates a [TokioRuntime] with the handle of the tokio runtime of the current context.
+    ///
+    /// # Panic
+    ///
+    /// If it is not called in the context of a tokio runtime, then it panics.
+    pub fn current() -> Self {
+        Self::new(tokio::runtime::Handle::current())
+    }
+}
+#[cfg(feature = "tokio")]
+impl AsyncRuntime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        self.handle.spawn(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    /// Blocks the current thread until the given future completes.
+    ///
+    /// On a worker thread of a multi-thread runtime, the other tasks of the worker are handed over to another worker while the thread blocks. A thread running a task of a current-thread runtime cannot block on the runtime without stalling it, so the host call fails with a [Trap](crate::error::Trap) instead; run the guest with [Executor::run_func_async](crate::Executor::run_func_async) there.
+    fn block_on(&self, future: BoxFuture<'_, ()>) -> Result<(), HostFuncError> {
+        if let Ok(current) = tokio::runtime::Handle::try_current() {
+            if current.runtime_flavor() == tokio::runtime::RuntimeFlavor::CurrentThread {
+                return Err(HostFuncError::Trap(Trap::new(
+                    "cannot block on a current-thread tokio runtime from inside it",
+                )));
+            }
+        }
+        tokio::task::block_in_place(|| self.handle.block_on(future));
+        Ok(())
+    }
+}
+
+/// The [AsyncRuntime] backed by the global [async-std](https://docs.rs/async-std) executor.
+#[cfg(feature = "async-std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async-std")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdRuntime;
+#[cfg(feature = "async-std")]
+impl AsyncRuntime for AsyncStdRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        async_std::task::spawn(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
+    fn block_on(&self, future: BoxFuture<'_, ()>) -> Result<(), HostFuncError> {
+        async_std::task::block_on(future);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct AbortSignal {
+    aborted: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+impl AbortSignal {
+    fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+        let waker = self
+            .waker
+            .lock()
+            .expect("[bitbang] the abort signal is poisoned")
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A future which resolves to `None` as soon as its signal is aborted, dropping the inner future.
+struct Abortable<T> {
+    future: Option<BoxFuture<'static, T>>,
+    signal: Arc<AbortSignal>,
+}
+impl<T> Future for Abortable<T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        *self
+            .signal
+            .waker
+            .lock()
+            .expect("[bitbang] the abort signal is poisoned") = Some(cx.waker().clone());
+        if self.signal.aborted.load(Ordering::SeqCst) {
+            self.future = None;
+            return Poll::Ready(None);
+        }
+        match self.future.as_mut() {
+            Some(future) => future.as_mut().poll(cx).map(Some),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// Wraps an async host function into a native function, which blocks on the future with the given runtime.
+///
+/// The future is dropped as soon as the guest call is cancelled, and the host call fails with the `Interrupted` error.
+pub(crate) fn async_host_func(
+    runtime: Arc<dyn AsyncRuntime>,
+    real_func: impl Fn(
+            CallingFrame,
+            Vec<WasmValue>,
+        ) -> BoxFuture<'static, Result<Vec<WasmValue>, HostFuncError>>
+        + Send
+        + Sync
+        + 'static,
+) -> BoxedHostFn {
+    Box::new(
+        move |frame: CallingFrame, inputs: Vec<WasmValue>, _data: *mut std::os::raw::c_void| {
+            let signal = Arc::new(AbortSignal::default());
+            let hook_signal = Arc::clone(&signal);
+            frame.on_cancel(move || hook_signal.abort());
+
+            let future = Abortable {
+                future: Some(real_func(frame, inputs)),
+                signal,
+            };
+            let mut output = None;
+            runtime.block_on(Box::pin(async {
+                output = future.await;
+            }))?;
+            output.unwrap_or(Err(HostFuncError::Runtime(CANCELLED_CODE)))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "tokio")]
+    use super::*;
+    #[cfg(feature = "tokio")]
+    use crate::{
+        error::WasmEdgeError, params, wat2wasm, Executor, Func, ImportObjectBuilder, Module,
+        NeverType, Store, WasmVal,
+    };
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_runtime_async_host_func() {
+        struct DropFlag(Arc<AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let runtime: Arc<dyn AsyncRuntime> = Arc::new(TokioRuntime::current());
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        // an async host function sleeping for the given milliseconds
+        let sleep_runtime = Arc::clone(&runtime);
+        let sleep_dropped = Arc::clone(&dropped);
+        let result = ImportObjectBuilder::new().with_async_func::<i32, i32>(
+            "sleep",
+            Arc::clone(&runtime),
+            move |_frame, inputs| {
+                let sleep = sleep_runtime.sleep(Duration::from_millis(inputs[0].to_i32() as u64));
+                let flag = DropFlag(Arc::clone(&sleep_dropped));
+                Box::pin(async move {
+                    let _flag = flag;
+                    sleep.await;
+                    Ok(vec![WasmValue::from_i32(inputs[0].to_i32())])
+                })
+            },
+        );
+        assert!(result.is_ok());
+        let result = result.unwrap().build::<NeverType>("host", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+                (import "host" "sleep" (func $sleep (param i32) (result i32)))
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $sleep))
+            "#,
+        )
+        .unwrap();
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        assert!(store.register_import_module(&mut executor, &import).is_ok());
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let run = result.unwrap().func("run").unwrap();
+
+        // the future completes
+        let result = executor.run_func_async(&run, params!(10)).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 10);
+        assert!(dropped.swap(false, Ordering::SeqCst));
+
+        // the future is dropped when the call times out
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            executor.run_func_async(&run, params!(60_000)),
+        )
+        .await;
+        assert!(result.is_err());
+        runtime.sleep(Duration::from_millis(100)).await;
+        assert!(dropped.load(Ordering::SeqCst));
+
+        // a guest run synchronously on a worker thread blocks in place
+        let result = executor.run_func(&run, params!(10));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 10);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_runtime_current_thread() {
+        let runtime: Arc<dyn AsyncRuntime> = Arc::new(TokioRuntime::current());
+        let result = Func::wrap_async::<(), i32>(runtime, |_frame, _inputs| {
+            Box::pin(async { Ok(vec![WasmValue::from_i32(1)]) })
+        });
+        assert!(result.is_ok());
+        let one = result.unwrap();
+
+        let executor = Executor::new(None, None).unwrap();
+
+        // the thread driving the runtime cannot block on it
+        let result = executor.run_func(&one, params!());
+        assert!(result.is_err());
+        assert!(matches!(*result.unwrap_err(), WasmEdgeError::Trap(_)));
+
+        let result = executor.run_func_async(&one, params!()).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 1);
+    }
+}
@@ -0,0 +1,348 @@
+//! Defines Scope, which lends borrowed host state to the host functions for the duration of a call.
+
+use crate::{io::FromWasmValList, Executor, Func, FuncRef, WasmEdgeResult, WasmValue};
+use std::{any::TypeId, cell::Cell, marker::PhantomData, ptr::NonNull};
+
+thread_local! {
+    static SCOPED_STATE: Cell<Option<(TypeId, NonNull<()>)>> = Cell::new(None);
+}
+
+/// Restores the previous scoped state when dropped, even if the scope unwinds.
+struct Restore(Option<(TypeId, NonNull<()>)>);
+impl Drop for Restore {
+    fn drop(&mut self) {
+        SCOPED_STATE.with(|state| state.set(self.0.take()));
+    }
+}
+
+/// Names the type of a host state which may borrow data, so that it can be lent by [Executor::scope_as](crate::Executor::scope_as).
+///
+/// A host state is looked up by its type, which only works for `'static` types. A marker is a `'static` type standing for a state type whose lifetime is erased:
+///
+/// ```ignore
+/// struct Request<'a> {
+///     body: &'a [u8],
+/// }
+///
+/// struct RequestMarker;
+/// impl StateMarker for RequestMarker {
+///     type State<'a> = Request<'a>;
+/// }
+///
+/// let mut request = Request { body: &bytes };
+/// executor.scope_as::<RequestMarker, _>(&mut request, |scope| scope.run_func(&handle, params!()))?;
+///
+/// // inside the host function
+/// let len = bitbang::scoped_state_as::<RequestMarker, _>(|request| request.body.len()).unwrap_or(0);
+/// ```
+///
+/// The state lent with a marker is only visible to [scoped_state_as](crate::scoped_state_as) called with the same marker.
+pub trait StateMarker: 'static {
+    /// The type of the lent host state.
+    type State<'a>;
+}
+
+/// The [marker](crate::StateMarker) of a `'static` host state, used by [Executor::scope](crate::Executor::scope) and [scoped_state](crate::scoped_state).
+#[derive(Debug)]
+pub struct OwnedState<S: 'static>(PhantomData<S>);
+impl<S: 'static> StateMarker for OwnedState<S> {
+    type State<'a> = S;
+}
+
+/// Defines a scope in which the host functions can borrow the host state lent by [Executor::scope](crate::Executor::scope).
+///
+/// The functions run through a [Scope] are executed on the current thread, and the host functions they call can access the lent state with [scoped_state](crate::scoped_state).
+#[derive(Debug)]
+pub struct Scope<'a, S> {
+    executor: &'a Executor,
+    _state: PhantomData<&'a mut S>,
+}
+impl<'a, S> Scope<'a, S> {
+    /// Runs a function instance and returns the results.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function, then an error is returned.
+    pub fn run_func(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        self.executor.run_func(func, params)
+    }
+
+    /// Runs a function instance and returns the results converted to the given Rust types. See [Executor::run_func_typed](crate::Executor::run_func_typed).
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function, or the results do not match the given Rust types, then an error is returned.
+    pub fn run_func_typed<R>(
+        &self,
+        func: &Func,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<R>
+    where
+        R: FromWasmValList,
+    {
+        self.executor.run_func_typed(func, params)
+    }
+
+    /// Runs a function reference instance and returns the results.
+    ///
+    /// # Arguments
+    ///
+    /// * `func_ref` - The function reference instance to run.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Errors
+    ///
+    /// If fail to run the function, then an error is returned.
+    pub fn run_func_ref(
+        &self,
+        func_ref: &FuncRef,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<Vec<WasmValue>> {
+        self.executor.run_func_ref(func_ref, params)
+    }
+}
+
+impl Executor {
+    /// Lends the given host state to the host functions called inside the given closure.
+    ///
+    /// The state is only borrowed for the duration of the closure, so request-scoped data can be passed to the host functions without copying it or moving it into the [store](crate::Store). Inside a host function, the state is accessed with [scoped_state](crate::scoped_state).
+    ///
+    /// ```ignore
+    /// let mut request = Request::parse(bytes)?;
+    /// let returns = executor.scope(&mut request, |scope| scope.run_func(&handle, params!()))?;
+    ///
+    /// // inside the host function
+    /// let len = bitbang::scoped_state(|request: &mut Request| request.body.len()).unwrap_or(0);
+    /// ```
+    ///
+    /// Scopes can be nested; the innermost state is visible to the host functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The host state to lend.
+    ///
+    /// * `f` - The closure running the functions.
+    pub fn scope<S, R>(&self, state: &mut S, f: impl FnOnce(&Scope<'_, S>) -> R) -> R
+    where
+        S: 'static,
+    {
+        self.scope_as::<OwnedState<S>, R>(state, f)
+    }
+
+    /// Lends the given host state, which may borrow data, to the host functions called inside the given closure.
+    ///
+    /// The state is named by the given [marker](crate::StateMarker), and is accessed inside a host function with [scoped_state_as](crate::scoped_state_as) and the same marker. See [Executor::scope](crate::Executor::scope) for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The host state to lend.
+    ///
+    /// * `f` - The closure running the functions.
+    pub fn scope_as<'s, M, R>(
+        &self,
+        state: &mut M::State<'s>,
+        f: impl FnOnce(&Scope<'_, M::State<'s>>) -> R,
+    ) -> R
+    where
+        M: StateMarker,
+    {
+        let ptr = NonNull::from(state).cast::<()>();
+        let previous = SCOPED_STATE.with(|s| s.replace(Some((TypeId::of::<M>(), ptr))));
+        let _restore = Restore(previous);
+
+        f(&Scope {
+            executor: self,
+            _state: PhantomData,
+        })
+    }
+}
+
+/// Calls the given closure with the host state lent by the innermost [Executor::scope](crate::Executor::scope) on the current thread, and returns its result.
+///
+/// Returns `None` if there is no scope, if the lent state is not of type `S`, or if the state is already borrowed by an outer call to this function.
+///
+/// # Argument
+///
+/// * `f` - The closure accessing the state.
+pub fn scoped_state<S, R>(f: impl FnOnce(&mut S) -> R) -> Option<R>
+where
+    S: 'static,
+{
+    scoped_state_as::<OwnedState<S>, R>(f)
+}
+
+/// Calls the given closure with the host state lent by the innermost [Executor::scope_as](crate::Executor::scope_as) on the current thread, and returns its result.
+///
+/// Returns `None` if there is no scope, if the lent state is not named by the marker `M`, or if the state is already borrowed by an outer call to this function.
+///
+/// The closure accepts the state with any lifetime, so the borrow can not escape it.
+///
+/// # Argument
+///
+/// * `f` - The closure accessing the state.
+pub fn scoped_state_as<M, R>(f: impl for<'a> FnOnce(&mut M::State<'a>) -> R) -> Option<R>
+where
+    M: StateMarker,
+{
+    // take the state out while it is borrowed, so that a re-entrant call can not alias it
+    let taken = SCOPED_STATE.with(|s| s.take());
+    let _restore = Restore(taken);
+    match taken {
+        Some((ty, ptr)) if ty == TypeId::of::<M>() => {
+            // SAFETY: the pointer is created from a `&mut M::State<'s>` which outlives the enclosing scope, and it is exclusively borrowed here. The closure is generic over the lifetime, so it can not keep the borrow.
+            let state = unsafe { &mut *ptr.cast::<M::State<'_>>().as_ptr() };
+            Some(f(state))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::HostFuncError, params, wat2wasm, ImportObjectBuilder, Module, NeverType, Store,
+        WasmVal,
+    };
+
+    #[test]
+    fn test_scope_borrowed_state() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "env" "push" (func $push (param i32) (result i32)))
+              (func (export "run") (param i32) (result i32)
+                local.get 0
+                call $push)
+            )
+            "#,
+        )
+        .unwrap();
+
+        // the host function appends its argument to the borrowed vector, and returns its length
+        let result = ImportObjectBuilder::new()
+            .with_func::<i32, i32, NeverType>(
+                "push",
+                |_frame, inputs, _data| {
+                    let value = inputs[0].to_i32();
+                    match scoped_state(|log: &mut Vec<i32>| {
+                        log.push(value);
+                        log.len() as i32
+                    }) {
+                        Some(len) => Ok(vec![WasmValue::from_i32(len)]),
+                        None => Err(HostFuncError::User(1)),
+                    }
+                },
+                None,
+            )
+            .expect("failed to add host function")
+            .build::<NeverType>("env", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let run = instance.func("run").unwrap();
+
+        // run the function twice in a scope
+        let mut log = Vec::new();
+        let result = executor.scope(&mut log, |scope| {
+            scope.run_func(&run, params!(10))?;
+            scope.run_func_typed::<i32>(&run, params!(20))
+        });
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(log, vec![10, 20]);
+
+        // the state is not visible outside the scope
+        let result = executor.run_func(&run, params!(30));
+        assert!(result.is_err());
+
+        // the state of a wrong type is not visible
+        let mut wrong = String::new();
+        let result = executor.scope(&mut wrong, |scope| scope.run_func(&run, params!(40)));
+        assert!(result.is_err());
+        assert_eq!(log, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_scope_nested() {
+        let executor = Executor::new(None, None).unwrap();
+        let mut outer = 1i32;
+        let mut inner = 2i64;
+        executor.scope(&mut outer, |_| {
+            assert_eq!(scoped_state(|v: &mut i32| *v), Some(1));
+            executor.scope(&mut inner, |_| {
+                assert_eq!(scoped_state(|v: &mut i64| *v), Some(2));
+                assert_eq!(scoped_state(|v: &mut i32| *v), None);
+            });
+            assert_eq!(scoped_state(|v: &mut i32| *v), Some(1));
+
+            // re-entrant borrows are rejected
+            assert_eq!(
+                scoped_state(|_: &mut i32| scoped_state(|v: &mut i32| *v)),
+                Some(None)
+            );
+        });
+        assert_eq!(scoped_state(|v: &mut i32| *v), None);
+    }
+
+    #[test]
+    fn test_scope_borrowed_lifetime() {
+        struct View<'a> {
+            data: &'a mut [u8],
+        }
+        struct ViewMarker;
+        impl StateMarker for ViewMarker {
+            type State<'a> = View<'a>;
+        }
+
+        let executor = Executor::new(None, None).unwrap();
+        let mut data = vec![1u8, 2, 3];
+        let mut view = View { data: &mut data };
+        executor.scope_as::<ViewMarker, _>(&mut view, |_| {
+            let result = scoped_state_as::<ViewMarker, _>(|view| {
+                view.data[0] = 10;
+                view.data.len()
+            });
+            assert_eq!(result, Some(3));
+
+            // the state is named by the marker, not by its type
+            assert_eq!(scoped_state(|_: &mut Vec<u8>| ()), None);
+        });
+        assert_eq!(data, vec![10, 2, 3]);
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::WasmEdgeResult;
 use bit_sys as sys;
+use std::time::Duration;
 
 /// Used to collect statistics of the WasmEdge runtime, such as the count of instructions in execution.
 #[derive(Debug, Clone)]
@@ -71,4 +72,95 @@ impl Statistics {
     pub fn set_cost_limit(&mut self, limit: u64) {
         self.inner.set_cost_limit(limit)
     }
+
+    /// Returns the cost limit in execution, which is `u64::MAX` if it is never set.
+    pub fn cost_limit(&self) -> u64 {
+        self.inner.cost_limit()
+    }
+}
+
+/// The call metrics of a single host function, collected by a [HostFuncReport].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostFuncMetrics {
+    /// The name of the [module instance](crate::Instance) that exports the host function.
+    pub mod_name: String,
+    /// The exported name of the host function.
+    pub func_name: String,
+    /// The number of times the host function has been called.
+    pub calls: u64,
+    /// The cumulative time spent inside the host function.
+    pub total_time: Duration,
+}
+impl HostFuncMetrics {
+    /// Returns the average time spent per call, or `None` if the host function has never been called.
+    pub fn average_time(&self) -> Option<Duration> {
+        match self.calls {
+            0 => None,
+            calls => Some(Duration::from_nanos(
+                (self.total_time.as_nanos() / calls as u128) as u64,
+            )),
+        }
+    }
+}
+
+/// A report of the time spent inside the host functions registered in a [store](crate::Store).
+///
+/// A [HostFuncReport] is retrieved by calling [Store::host_func_report](crate::Store::host_func_report). Comparing
+/// the [total time](crate::HostFuncReport::total_time) with the wall-clock time of a call tells whether the latency
+/// comes from the guest or from the host imports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostFuncReport {
+    pub(crate) entries: Vec<HostFuncMetrics>,
+}
+impl HostFuncReport {
+    /// Returns the metrics of all host functions in the report.
+    pub fn entries(&self) -> &[HostFuncMetrics] {
+        &self.entries
+    }
+
+    /// Returns the metrics of the host function with the given module name and function name.
+    ///
+    /// # Arguments
+    ///
+    /// * `mod_name` - The name of the module instance that exports the host function.
+    ///
+    /// * `func_name` - The exported name of the host function.
+    pub fn get(
+        &self,
+        mod_name: impl AsRef<str>,
+        func_name: impl AsRef<str>,
+    ) -> Option<&HostFuncMetrics> {
+        self.entries
+            .iter()
+            .find(|m| m.mod_name == mod_name.as_ref() && m.func_name == func_name.as_ref())
+    }
+
+    /// Returns the total number of host function calls in the report.
+    pub fn total_calls(&self) -> u64 {
+        self.entries.iter().map(|m| m.calls).sum()
+    }
+
+    /// Returns the cumulative time spent inside all host functions in the report.
+    pub fn total_time(&self) -> Duration {
+        self.entries.iter().map(|m| m.total_time).sum()
+    }
+}
+
+/// A report of a single function call, returned by [Executor::run_func_with_report](crate::Executor::run_func_with_report) along with the results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// The wall-clock time of the call.
+    pub duration: Duration,
+    /// The number of the instructions executed. It is zero unless instruction counting is enabled in the [config](crate::config::StatisticsConfigOptions) of the executor.
+    pub instructions: u64,
+    /// The cost of the executed instructions. It is zero unless cost measuring is enabled in the [config](crate::config::StatisticsConfigOptions) of the executor.
+    pub fuel_used: u64,
+    /// The number of the pages the memories of the called module instance have grown by. The exported memories are observed from the start of the call, and a memory which is not exported from the first host function call on.
+    pub memory_grown: u32,
+    /// The number of the host function calls.
+    pub host_calls: u64,
+    /// The time spent inside the host functions.
+    pub host_time: Duration,
+    /// The time spent executing wasm, which is the rest of the [duration](crate::ExecutionReport::duration).
+    pub guest_time: Duration,
 }
@@ -1,12 +1,27 @@
 //! Defines WasmEdge Store struct.
 
-use crate::{plugin::PluginInstance, Executor, ImportObject, Instance, Module, WasmEdgeResult};
+use crate::{
+    diagnostics::{HandleGuard, HandleKind},
+    instance::{capture_segment_funcs, Baseline, WASI_INITIALIZE},
+    plugin::PluginInstance,
+    task,
+    wasi::{WasiKind, WASI_MODULE_NAMES},
+    Executor, HostFuncMetrics, HostFuncReport, ImportObject, Instance, MemoryImage, Module,
+    WasmEdgeResult, WeakInstance,
+};
 use bit_sys as sys;
+use std::sync::{Arc, Mutex};
 
 /// Represents all global state that can be manipulated by WebAssembly programs. A [store](crate::Store) consists of the runtime representation of all instances of [functions](crate::Func), [tables](crate::Table), [memories](crate::Memory), and [globals](crate::Global).
+///
+/// The [executor](crate::Executor) most recently used to register a module into the [store](crate::Store) is associated with it, so the functions of the store can be run with [Func::call](crate::Func::call) given the store only.
+///
+/// The [module instances](crate::Instance) registered into the [store](crate::Store), asynchronously or not, are tracked with [weak handles](crate::WeakInstance), so [Store::live_objects](crate::Store::live_objects) reports the ones which are still alive. The tracking of the dropped ones is reclaimed by [Store::gc](crate::Store::gc) and by every registration, so it does not grow with the number of the registrations.
 #[derive(Debug, Clone)]
 pub struct Store {
     pub(crate) inner: sys::Store,
+    pub(crate) executor: Option<Executor>,
+    pub(crate) tracked: Arc<Mutex<Vec<WeakInstance>>>,
 }
 impl Store {
     /// Creates a new [Store].
@@ -16,7 +31,11 @@ impl Store {
     /// If fail to create a new [Store], then an error is returned.
     pub fn new() -> WasmEdgeResult<Self> {
         let inner = sys::Store::create()?;
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            executor: None,
+            tracked: Arc::new(Mutex::new(Vec::new())),
+        })
     }
 
     /// Registers and instantiates a WasmEdge [import object](crate::ImportObject) into this [store](crate::Store).
@@ -38,6 +57,7 @@ impl Store {
     where
         T: ?Sized + Send + Sync + Clone,
     {
+        self.executor = Some(executor.clone());
         executor
             .inner
             .register_import_module(&self.inner, &import.0)
@@ -45,7 +65,7 @@ impl Store {
 
     /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as a named [module instance](crate::Instance), and returns the module instance.
     ///
-    /// Instantiates the given WasmEdge [compiled module](crate::Module), including the [functions](crate::Func), [memories](crate::Memory), [tables](crate::Table), and [globals](crate::Global) it hosts; and then, registers the [module instance](crate::Instance) into the [store](crate::Store) with the given name.
+    /// Instantiates the given WasmEdge [compiled module](crate::Module), including the [functions](crate::Func), [memories](crate::Memory), [tables](crate::Table), and [globals](crate::Global) it hosts; and then, registers the [module instance](crate::Instance) into the [store](crate::Store) with the given name. The `_initialize` function of a WASI reactor is not run; see [RegisterOptions::with_initialize](crate::RegisterOptions::with_initialize).
     ///
     /// # Arguments
     ///
@@ -64,17 +84,20 @@ impl Store {
         mod_name: impl AsRef<str>,
         module: &Module,
     ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
         let inner_instance =
             executor
                 .inner
-                .register_named_module(&self.inner, &module.inner, mod_name.as_ref())?;
-        Ok(Instance {
-            inner: inner_instance,
-        })
+                .register_named_module(&self.inner, module.code(), mod_name.as_ref())?;
+        let instance = instantiated(executor, module, inner_instance, &RegisterOptions::new())?;
+        self.track(&instance);
+        Ok(instance)
     }
 
     /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as an anonymous active [module instance](crate::Instance), and returns the module instance.
     ///
+    /// The `_initialize` function of a WASI reactor is not run; see [RegisterOptions::with_initialize](crate::RegisterOptions::with_initialize).
+    ///
     /// # Arguments
     ///
     /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
@@ -89,11 +112,186 @@ impl Store {
         executor: &mut Executor,
         module: &Module,
     ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
         let inner = executor
             .inner
-            .register_active_module(&self.inner, &module.inner)?;
+            .register_active_module(&self.inner, module.code())?;
 
-        Ok(Instance { inner })
+        let instance = instantiated(executor, module, inner, &RegisterOptions::new())?;
+        self.track(&instance);
+        Ok(instance)
+    }
+
+    /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as a named [module instance](crate::Instance), writes the given [memory images](crate::MemoryImage) over the initialized memories, and returns the module instance.
+    ///
+    /// The images replace the contents of the data segments, and the `_initialize` function of a WASI reactor is not run, so a pre-warmed heap is restored without running the initialization code of the guest. The start function of the module, if any, still runs during the instantiation. The post-instantiation state is captured, so [Instance::reset](crate::Instance::reset) restores the memories to the images.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `mod_name` - The exported name of the registered [module](crate::Module).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// * `images` - The images of the exported memories of the module.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), or an image names a memory the module does not export, or a memory cannot grow to the size of its image, then an error is returned, and the module instance is unregistered from the store.
+    pub fn register_named_module_with_images(
+        &mut self,
+        executor: &mut Executor,
+        mod_name: impl AsRef<str>,
+        module: &Module,
+        images: impl IntoIterator<Item = MemoryImage>,
+    ) -> WasmEdgeResult<Instance> {
+        let options = RegisterOptions::new().with_images(images).with_reset(true);
+        self.register_named_module_with_options(executor, mod_name, module, &options)
+    }
+
+    /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as an anonymous active [module instance](crate::Instance), writes the given [memory images](crate::MemoryImage) over the initialized memories, and returns the module instance.
+    ///
+    /// See [Store::register_named_module_with_images](crate::Store::register_named_module_with_images) for how the images are applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// * `images` - The images of the exported memories of the module.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), or an image names a memory the module does not export, or a memory cannot grow to the size of its image, then an error is returned.
+    pub fn register_active_module_with_images(
+        &mut self,
+        executor: &mut Executor,
+        module: &Module,
+        images: impl IntoIterator<Item = MemoryImage>,
+    ) -> WasmEdgeResult<Instance> {
+        let options = RegisterOptions::new().with_images(images).with_reset(true);
+        self.register_active_module_with_options(executor, module, &options)
+    }
+
+    /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as a named [module instance](crate::Instance) with the given [options](crate::RegisterOptions), and returns the module instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `mod_name` - The exported name of the registered [module](crate::Module).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// * `options` - The options of the instantiation.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), or to apply the options, then an error is returned, and the module instance is unregistered from the store.
+    pub fn register_named_module_with_options(
+        &mut self,
+        executor: &mut Executor,
+        mod_name: impl AsRef<str>,
+        module: &Module,
+        options: &RegisterOptions,
+    ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
+        let inner_instance =
+            executor
+                .inner
+                .register_named_module(&self.inner, module.code(), mod_name.as_ref())?;
+        // if the instantiation can not be completed, the module instance is deleted when dropped, which unregisters it from the store, so the name can be registered again
+        let instance = instantiated(executor, module, inner_instance, options)?;
+        self.track(&instance);
+        Ok(instance)
+    }
+
+    /// Registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as an anonymous active [module instance](crate::Instance) with the given [options](crate::RegisterOptions), and returns the module instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// * `options` - The options of the instantiation.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), or to apply the options, then an error is returned.
+    pub fn register_active_module_with_options(
+        &mut self,
+        executor: &mut Executor,
+        module: &Module,
+        options: &RegisterOptions,
+    ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
+        let inner = executor
+            .inner
+            .register_active_module(&self.inner, module.code())?;
+
+        let instance = instantiated(executor, module, inner, options)?;
+        self.track(&instance);
+        Ok(instance)
+    }
+
+    /// Asynchronously registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as a named [module instance](crate::Instance), and returns the module instance.
+    ///
+    /// The instantiation, including the initialization of the data and element segments and the execution of the start function, runs on a dedicated thread, so it does not block the thread polling the returned future. The start function may call blocking host functions, such as the ones which wait on I/O. If the future is dropped before completion, the instantiation is cancelled as described in [Executor::run_func_async](crate::Executor::run_func_async).
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `mod_name` - The exported name of the registered [module](crate::Module).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), then an error is returned.
+    pub async fn register_named_module_async(
+        &mut self,
+        executor: &mut Executor,
+        mod_name: impl AsRef<str>,
+        module: &Module,
+    ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
+        let mut store = self.clone();
+        let mut executor = executor.clone();
+        let module = module.clone();
+        let mod_name = mod_name.as_ref().to_string();
+
+        task::spawn_blocking(move || store.register_named_module(&mut executor, mod_name, &module))
+            .await
+    }
+
+    /// Asynchronously registers and instantiates a WasmEdge [compiled module](crate::Module) into this [store](crate::Store) as an anonymous active [module instance](crate::Instance), and returns the module instance.
+    ///
+    /// See [Store::register_named_module_async](crate::Store::register_named_module_async) for how the instantiation runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The [executor](crate::Executor) that runs the host functions in this [store](crate::Store).
+    ///
+    /// * `module` - The validated [module](crate::Module) to be registered.
+    ///
+    /// # Error
+    ///
+    /// If fail to register the given [module](crate::Module), then an error is returned.
+    pub async fn register_active_module_async(
+        &mut self,
+        executor: &mut Executor,
+        module: &Module,
+    ) -> WasmEdgeResult<Instance> {
+        self.executor = Some(executor.clone());
+        let mut store = self.clone();
+        let mut executor = executor.clone();
+        let module = module.clone();
+
+        task::spawn_blocking(move || store.register_active_module(&mut executor, &module)).await
     }
 
     /// Registers a PluginInstance into this store.
@@ -112,6 +310,7 @@ impl Store {
         executor: &mut Executor,
         plugin: &PluginInstance,
     ) -> WasmEdgeResult<()> {
+        self.executor = Some(executor.clone());
         executor
             .inner
             .register_plugin_instance(&self.inner, &plugin.inner)
@@ -140,6 +339,10 @@ impl Store {
 
         Ok(Instance {
             inner: inner_instance,
+            baseline: None,
+            module_info: None,
+            segment_funcs: None,
+            _guard: HandleGuard::new(HandleKind::Instance),
         })
     }
 
@@ -152,6 +355,187 @@ impl Store {
     pub fn contains(&self, mod_name: impl AsRef<str>) -> bool {
         self.inner.contains(mod_name.as_ref())
     }
+
+    /// Returns the cumulative time and call count spent inside each host function exported by the named [module instances](crate::Instance) in this [store](crate::Store).
+    ///
+    /// Wasm functions are not included in the report. Notice that the metrics are kept by the host function itself, so a host function registered into more than one store reports the calls from all of them.
+    pub fn host_func_report(&self) -> HostFuncReport {
+        let mut entries = Vec::new();
+        for mod_name in self.instance_names() {
+            let instance = match self.inner.module(&mod_name) {
+                Ok(instance) => instance,
+                Err(_) => continue,
+            };
+            for func_name in instance.func_names().unwrap_or_default() {
+                let stat = match instance.get_func(&func_name) {
+                    Ok(func) => func.call_stat(),
+                    Err(_) => None,
+                };
+                if let Some(stat) = stat {
+                    entries.push(HostFuncMetrics {
+                        mod_name: mod_name.clone(),
+                        func_name,
+                        calls: stat.calls,
+                        total_time: stat.elapsed,
+                    });
+                }
+            }
+        }
+
+        HostFuncReport { entries }
+    }
+
+    /// Resets the metrics of all host functions exported by the named [module instances](crate::Instance) in this [store](crate::Store).
+    pub fn reset_host_func_report(&self) {
+        for mod_name in self.instance_names() {
+            if let Ok(instance) = self.inner.module(&mod_name) {
+                for func_name in instance.func_names().unwrap_or_default() {
+                    if let Ok(func) = instance.get_func(&func_name) {
+                        func.reset_call_stat();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reclaims the tracking of the [module instances](crate::Instance) registered into this [store](crate::Store) whose handles are all dropped, and returns the number of the reclaimed module instances.
+    ///
+    /// The runtime resources of a module instance, including its memories, tables, and globals, are released, and a named module instance is unregistered from the store, as soon as its last handle is dropped. [Weak handles](crate::WeakInstance) do not keep a module instance alive. Long-running hosts call this function after dropping the module instances of finished guests, and check with [Store::live_objects](crate::Store::live_objects) that none of them lingers.
+    pub fn gc(&self) -> usize {
+        let mut tracked = self
+            .tracked
+            .lock()
+            .expect("[bitbang] the tracked instances of the store are poisoned");
+        let before = tracked.len();
+        tracked.retain(WeakInstance::is_alive);
+        before - tracked.len()
+    }
+
+    /// Returns the objects of this [store](crate::Store) which are still alive.
+    pub fn live_objects(&self) -> LiveObjects {
+        let tracked = self
+            .tracked
+            .lock()
+            .expect("[bitbang] the tracked instances of the store are poisoned");
+        let instances: Vec<Option<String>> = tracked
+            .iter()
+            .filter(|instance| instance.is_alive())
+            .map(|instance| instance.name().map(String::from))
+            .collect();
+
+        LiveObjects {
+            instances,
+            tracked: tracked.len(),
+            named_instances: self.instance_names(),
+        }
+    }
+
+    /// Starts tracking the given module instance registered into this [store](crate::Store), and reclaims the tracking of the dropped ones.
+    fn track(&self, instance: &Instance) {
+        let mut tracked = self
+            .tracked
+            .lock()
+            .expect("[bitbang] the tracked instances of the store are poisoned");
+        tracked.retain(WeakInstance::is_alive);
+        tracked.push(instance.downgrade());
+    }
+}
+
+/// Defines the options of registering a [module](crate::Module) into a [store](crate::Store) with [Store::register_named_module_with_options](crate::Store::register_named_module_with_options) or [Store::register_active_module_with_options](crate::Store::register_active_module_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct RegisterOptions {
+    images: Vec<MemoryImage>,
+    reset: bool,
+    initialize: bool,
+}
+impl RegisterOptions {
+    /// Creates the default options, which register a module the same way as [Store::register_named_module](crate::Store::register_named_module).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [memory images](crate::MemoryImage) written over the initialized memories. See [Store::register_named_module_with_images](crate::Store::register_named_module_with_images) for how the images are applied.
+    ///
+    /// # Argument
+    ///
+    /// * `images` - The images of the exported memories of the module.
+    pub fn with_images(mut self, images: impl IntoIterator<Item = MemoryImage>) -> Self {
+        self.images = images.into_iter().collect();
+        self
+    }
+
+    /// Sets whether the `_initialize` function of a WASI reactor is run after the instantiation; see [Instance::wasi_kind](crate::Instance::wasi_kind). It is disabled by default, and it is ignored if memory images are given, since the images already hold the initialized heap.
+    ///
+    /// # Argument
+    ///
+    /// * `enable` - Whether to initialize a WASI reactor.
+    pub fn with_initialize(mut self, enable: bool) -> Self {
+        self.initialize = enable;
+        self
+    }
+
+    /// Sets whether the state of the exported memories, mutable globals and tables is captured after the instantiation, so that [Instance::reset](crate::Instance::reset) can restore it. Capturing copies all non-zero memory pages and all table elements, so it is disabled by default.
+    ///
+    /// # Argument
+    ///
+    /// * `enable` - Whether to capture the post-instantiation state.
+    pub fn with_reset(mut self, enable: bool) -> Self {
+        self.reset = enable;
+        self
+    }
+}
+
+/// Describes the objects of a [store](crate::Store) which are still alive, returned by [Store::live_objects](crate::Store::live_objects).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveObjects {
+    /// The names of the alive [module instances](crate::Instance) registered into the store with the [Store](crate::Store) functions, or `None` for the active module instances.
+    pub instances: Vec<Option<String>>,
+    /// The number of the module instances tracked by the store, including the dropped ones which are not yet reclaimed with [Store::gc](crate::Store::gc) or a later registration.
+    pub tracked: usize,
+    /// The names of all named module instances in the store, including the [import objects](crate::ImportObject) and the plugin instances.
+    pub named_instances: Vec<String>,
+}
+impl LiveObjects {
+    /// Checks if no [module instance](crate::Instance) registered with the [Store](crate::Store) functions is alive.
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
+
+/// Completes the instantiation of a module as requested by the options: writes the memory images, or runs the `_initialize` function of a WASI reactor if there are none, and captures the state [Instance::reset](crate::Instance::reset) restores.
+fn instantiated(
+    executor: &Executor,
+    module: &Module,
+    inner: sys::Instance,
+    options: &RegisterOptions,
+) -> WasmEdgeResult<Instance> {
+    let mut instance = Instance {
+        inner,
+        baseline: None,
+        module_info: Some(module.info.clone()),
+        segment_funcs: None,
+        _guard: HandleGuard::new(HandleKind::Instance),
+    };
+    instance.segment_funcs = Some(Arc::new(capture_segment_funcs(&instance, &module.info)?));
+
+    if !options.images.is_empty() {
+        for image in options.images.iter() {
+            image.apply(&instance)?;
+        }
+    } else if options.initialize
+        && instance.wasi_kind() == Some(WasiKind::Reactor)
+        && module
+            .imports()
+            .iter()
+            .any(|import| WASI_MODULE_NAMES.contains(&import.module_name().as_ref()))
+    {
+        executor.run_func(&instance.func(WASI_INITIALIZE)?, [])?;
+    }
+
+    if options.reset {
+        instance.baseline = Some(Arc::new(Baseline::capture(&instance)?));
+    }
+    Ok(instance)
 }
 
 #[cfg(test)]
@@ -160,9 +544,11 @@ mod tests {
     use crate::{
         config::{CommonConfigOptions, ConfigBuilder},
         error::HostFuncError,
+        params,
         types::Val,
-        CallingFrame, Executor, Global, GlobalType, ImportObjectBuilder, Memory, MemoryType,
-        Module, Mutability, NeverType, RefType, Statistics, Table, TableType, ValType, WasmValue,
+        CallingFrame, Executor, Func, Global, GlobalType, ImportObjectBuilder, Memory, MemoryType,
+        Module, Mutability, NeverType, RefType, Statistics, Table, TableType, ValType, WasmVal,
+        WasmValue,
     };
 
     #[test]
@@ -305,6 +691,55 @@ mod tests {
         assert_eq!(instance.name().unwrap(), "extern-module");
     }
 
+    #[tokio::test]
+    async fn test_store_register_module_async() {
+        // create an executor
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        // create a store
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        // load wasm module
+        let file = std::env::current_dir()
+            .unwrap()
+            .join("examples/wasmedge-sys/data/fibonacci.wat");
+
+        let result = Module::from_file(None, file);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // register the module asynchronously as a named module and an active module
+        let result = store
+            .register_named_module_async(&mut executor, "extern-module", &module)
+            .await;
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert_eq!(instance.name().unwrap(), "extern-module");
+        assert_eq!(store.instance_names(), ["extern-module"]);
+
+        let result = store
+            .register_active_module_async(&mut executor, &module)
+            .await;
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        assert!(instance.name().is_none());
+
+        // the instances registered asynchronously are tracked
+        let live = store.live_objects();
+        assert_eq!(live.instances, [Some("extern-module".to_string()), None]);
+        assert_eq!(live.tracked, 2);
+
+        // the instances are usable on the current thread
+        let fib = instance.func("fib").unwrap();
+        let result = executor.run_func_typed::<i32>(&fib, vec![WasmValue::from_i32(5)]);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 8);
+    }
+
     #[test]
     fn test_store_register_active_module() {
         // create an executor
@@ -435,6 +870,231 @@ mod tests {
         assert_eq!(instance.name().unwrap(), mod_names[1]);
     }
 
+    #[test]
+    fn test_store_host_func_report() {
+        // create an ImportModule instance
+        let result = ImportObjectBuilder::new()
+            .with_func::<(i32, i32), i32, NeverType>("add", real_add, None)
+            .expect("failed to add host function")
+            .build::<NeverType>("extern-module", None);
+        assert!(result.is_ok());
+        let import = result.unwrap();
+
+        // create an executor
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        // create a store
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = store.register_import_module(&mut executor, &import);
+        assert!(result.is_ok());
+
+        // no calls yet
+        let report = store.host_func_report();
+        assert_eq!(report.entries().len(), 1);
+        assert_eq!(report.total_calls(), 0);
+
+        // call the host function twice
+        let result = store.named_instance("extern-module");
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let add = instance.func("add").unwrap();
+        for _ in 0..2 {
+            let result =
+                executor.run_func(&add, vec![WasmValue::from_i32(1), WasmValue::from_i32(2)]);
+            assert!(result.is_ok());
+        }
+
+        let report = store.host_func_report();
+        let result = report.get("extern-module", "add");
+        assert!(result.is_some());
+        let metrics = result.unwrap();
+        assert_eq!(metrics.calls, 2);
+        assert!(metrics.average_time().is_some());
+        assert_eq!(report.total_calls(), 2);
+
+        // reset the metrics
+        store.reset_host_func_report();
+        assert_eq!(store.host_func_report().total_calls(), 0);
+    }
+
+    #[test]
+    fn test_store_register_module_with_images() {
+        let wasm_bytes = crate::wat2wasm(
+            br#"
+            (module
+              (memory (export "memory") 1 4)
+              (data (i32.const 0) "init")
+              (data (i32.const 100) "segment")
+              (func (export "load") (param i32) (result i32)
+                local.get 0
+                i32.load8_u)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        // capture the memory of a warmed-up instance
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let mut memory = instance.memory("memory").unwrap();
+        assert!(memory.write(b"warm", 0).is_ok());
+        assert!(memory.grow(1).is_ok());
+        assert!(memory.write(b"heap", 70000).is_ok());
+        let result = MemoryImage::capture(&instance, "memory");
+        assert!(result.is_ok());
+        let image = result.unwrap();
+        assert_eq!(image.memory_name(), "memory");
+        assert_eq!(image.data().len(), 70004);
+
+        // the image overrides the data segments and grows the memory
+        let result = store.register_named_module_with_images(
+            &mut executor,
+            "warm",
+            &module,
+            [image, MemoryImage::new("memory", b"wa".to_vec())],
+        );
+        assert!(result.is_ok());
+        let instance = result.unwrap();
+        let mut memory = instance.memory("memory").unwrap();
+        assert_eq!(memory.page(), 2);
+        assert_eq!(memory.read(0, 4).unwrap(), b"wa\0\0");
+        assert_eq!(memory.read(100, 7).unwrap(), vec![0; 7]);
+        assert_eq!(memory.read(70000, 4).unwrap(), vec![0; 4]);
+
+        // reset restores the image
+        assert!(memory.write(b"dirty", 0).is_ok());
+        assert!(instance.reset().is_ok());
+        let load = instance.func("load").unwrap();
+        let result = executor.run_func_typed::<i32>(&load, vec![WasmValue::from_i32(0)]);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), b'w' as i32);
+
+        // an image of a memory which is not exported
+        let result = store.register_active_module_with_images(
+            &mut executor,
+            &module,
+            [MemoryImage::new("heap", b"data".to_vec())],
+        );
+        assert!(result.is_err());
+
+        // a named module instance whose image fails to apply is unregistered
+        let result = store.register_named_module_with_images(
+            &mut executor,
+            "broken",
+            &module,
+            [MemoryImage::new("heap", b"data".to_vec())],
+        );
+        assert!(result.is_err());
+        assert!(!store.contains("broken"));
+        let result = store.register_named_module(&mut executor, "broken", &module);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_store_gc() {
+        let wasm_bytes = crate::wat2wasm(
+            br#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "answer") (result i32)
+                i32.const 42)
+            )
+            "#,
+        )
+        .unwrap();
+
+        let result = Executor::new(None, None);
+        assert!(result.is_ok());
+        let mut executor = result.unwrap();
+
+        let result = Store::new();
+        assert!(result.is_ok());
+        let mut store = result.unwrap();
+
+        let result = Module::from_bytes(None, wasm_bytes);
+        assert!(result.is_ok());
+        let module = result.unwrap();
+
+        let result = store.register_named_module(&mut executor, "guest", &module);
+        assert!(result.is_ok());
+        let named = result.unwrap();
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let active = result.unwrap();
+
+        let live = store.live_objects();
+        assert_eq!(live.instances, [Some("guest".to_string()), None]);
+        assert_eq!(live.tracked, 2);
+        assert_eq!(live.named_instances, ["guest"]);
+
+        // the weak handles do not keep the instances alive
+        let weak_named = named.downgrade();
+        assert_eq!(weak_named.name(), Some("guest"));
+        let result = weak_named.upgrade();
+        assert!(result.is_some());
+        let answer = result.unwrap().func("answer").unwrap();
+        assert_eq!(executor.run_func(&answer, []).unwrap()[0].to_i32(), 42);
+        drop(answer);
+
+        drop(named);
+        assert!(!weak_named.is_alive());
+        assert!(weak_named.upgrade().is_none());
+        assert!(!store.contains("guest"));
+        assert_eq!(store.gc(), 1);
+        assert_eq!(store.gc(), 0);
+
+        drop(active);
+        assert_eq!(store.gc(), 1);
+        let live = store.live_objects();
+        assert!(live.is_empty());
+        assert_eq!(live.tracked, 0);
+        assert!(live.named_instances.is_empty());
+
+        // a registration reclaims the tracking of the dropped instances
+        for _ in 0..3 {
+            let result = store.register_active_module(&mut executor, &module);
+            assert!(result.is_ok());
+        }
+        let result = store.register_active_module(&mut executor, &module);
+        assert!(result.is_ok());
+        let live = store.live_objects();
+        assert_eq!(live.instances, [None]);
+        assert_eq!(live.tracked, 1);
+        drop(result);
+        assert_eq!(store.gc(), 1);
+
+        // a host function is deleted with its last handle
+        let result = Func::wrap::<(i32, i32), i32, NeverType>(real_add, None);
+        assert!(result.is_ok());
+        let add = result.unwrap();
+        let weak_add = add.downgrade();
+        let result = weak_add.upgrade();
+        assert!(result.is_some());
+        let result = executor.run_func(&result.unwrap(), params!(1, 2));
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 3);
+        drop(add);
+        assert!(weak_add.upgrade().is_none());
+    }
+
     fn real_add(
         _frame: CallingFrame,
         inputs: Vec<WasmValue>,
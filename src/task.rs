@@ -0,0 +1,217 @@
+//! Defines BlockingTask, a future that runs a blocking operation on a pool of blocking threads.
+
+use bit_sys::cancel::{self, CancelToken};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, OnceLock},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+/// How long an idle blocking thread waits for a new operation before it exits.
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct PoolState {
+    jobs: VecDeque<Job>,
+    idle: usize,
+}
+
+/// The threads running the blocking operations. A thread is spawned only if no idle thread can take the operation, and exits after being idle for [KEEP_ALIVE].
+#[derive(Default)]
+struct Pool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+impl Pool {
+    fn global() -> &'static Arc<Pool> {
+        static POOL: OnceLock<Arc<Pool>> = OnceLock::new();
+        POOL.get_or_init(Default::default)
+    }
+
+    fn execute(self: &Arc<Self>, job: Job) {
+        let mut state = self
+            .state
+            .lock()
+            .expect("[bitbang] the blocking pool is poisoned");
+        state.jobs.push_back(job);
+        match state.jobs.len() > state.idle {
+            true => {
+                drop(state);
+                let pool = Arc::clone(self);
+                thread::Builder::new()
+                    .name("bitbang-blocking".into())
+                    .spawn(move || pool.work())
+                    .expect("[bitbang] failed to spawn a blocking thread");
+            }
+            false => self.available.notify_one(),
+        }
+    }
+
+    fn work(&self) {
+        let mut state = self
+            .state
+            .lock()
+            .expect("[bitbang] the blocking pool is poisoned");
+        loop {
+            match state.jobs.pop_front() {
+                Some(job) => {
+                    drop(state);
+                    job();
+                    state = self
+                        .state
+                        .lock()
+                        .expect("[bitbang] the blocking pool is poisoned");
+                }
+                None => {
+                    state.idle += 1;
+                    let (guard, timeout) = self
+                        .available
+                        .wait_timeout(state, KEEP_ALIVE)
+                        .expect("[bitbang] the blocking pool is poisoned");
+                    state = guard;
+                    state.idle -= 1;
+                    if timeout.timed_out() && state.jobs.is_empty() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    output: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// A future which resolves to the output of a blocking operation running on a blocking thread.
+///
+/// If the operation panics, the panic is resumed on the task awaiting the future.
+///
+/// The future does not depend on any specific async runtime. If it is dropped before completion, the wasm function calls made by the operation are cancelled: the host function in progress is notified through its [on_cancel](crate::CallingFrame::on_cancel) hooks, and the next host call fails with the `Interrupted` error. The output of the operation is discarded.
+#[derive(Debug)]
+pub(crate) struct BlockingTask<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+    token: CancelToken,
+}
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self
+            .shared
+            .lock()
+            .expect("[bitbang] the blocking task is poisoned");
+        match shared.output.take() {
+            Some(Ok(output)) => Poll::Ready(output),
+            Some(Err(payload)) => {
+                drop(shared);
+                panic::resume_unwind(payload)
+            }
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+impl<T> Drop for BlockingTask<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// Runs the given blocking operation on a blocking thread, and returns a future resolving to its output.
+///
+/// # Argument
+///
+/// * `f` - The blocking operation.
+pub(crate) fn spawn_blocking<T, F>(f: F) -> BlockingTask<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Shared {
+        output: None,
+        waker: None,
+    }));
+
+    let token = CancelToken::new();
+
+    let task_shared = Arc::clone(&shared);
+    let task_token = token.clone();
+    Pool::global().execute(Box::new(move || {
+        cancel::enter(task_token);
+        let output = panic::catch_unwind(AssertUnwindSafe(f));
+        cancel::leave();
+        let waker = {
+            let mut shared = task_shared
+                .lock()
+                .expect("[bitbang] the blocking task is poisoned");
+            shared.output = Some(output);
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }));
+
+    BlockingTask { shared, token }
+}
+
+/// Runs a future to completion by parking the current thread, so that the tests do not depend on any async runtime.
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(thread::Thread);
+    impl std::task::Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_blocking() {
+        let result = block_on(spawn_blocking(|| 1 + 2));
+        assert_eq!(result, 3);
+
+        // the threads are reused by the following operations
+        let results = (0..8)
+            .map(|i| spawn_blocking(move || i * 2))
+            .map(block_on)
+            .collect::<Vec<_>>();
+        assert_eq!(results, (0..8).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_spawn_blocking_panic() {
+        // the panic of the operation is resumed by the awaiting task instead of leaving the future pending
+        let result = panic::catch_unwind(|| block_on(spawn_blocking(|| -> i32 { panic!("boom") })));
+        assert!(result.is_err());
+
+        // the pool keeps working
+        assert_eq!(block_on(spawn_blocking(|| 42)), 42);
+    }
+}
@@ -0,0 +1,754 @@
+//! Defines TenantManager, which runs the calls of multiple tenants on a shared worker pool under per-tenant quotas.
+
+use crate::{
+    config::{CommonConfigOptions, ConfigBuilder, RuntimeConfigOptions, StatisticsConfigOptions},
+    error::{PoolError, TenantError, WasmEdgeError},
+    pool::{ExecutorPool, PoolTask, TaskOutcome},
+    wasi::WASI_MODULE_NAMES,
+    ExecutionReport, Executor, ImportObject, Module, NeverType, Statistics, Store, WasmEdgeResult,
+    WasmValue,
+};
+use bit_sys as sys;
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type ImportsFn = dyn Fn() -> WasmEdgeResult<Vec<ImportObject<NeverType>>> + Send + Sync;
+
+/// Defines the resource quota of a tenant. Every limit is unlimited unless it is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TenantQuota {
+    fuel_per_sec: Option<u64>,
+    max_memory_pages: Option<u32>,
+    max_concurrent_calls: Option<usize>,
+}
+impl TenantQuota {
+    /// Creates a new [TenantQuota] without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the fuel a tenant is granted per second.
+    ///
+    /// The fuel is the cost of the executed instructions, one unit per instruction. Unused fuel is accumulated up to one second's worth. A call is admitted while the tenant has fuel left, may use up to the fuel accumulated when it is admitted, and is aborted with a cost-limit error beyond that. The fuel a call consumed is charged when the call finishes, so the concurrent calls of a tenant all run, and the fuel they use beyond the accumulated one is a debt which the tenant pays off before its next call is admitted.
+    ///
+    /// # Argument
+    ///
+    /// * `fuel` - The fuel granted per second.
+    pub fn with_fuel_per_sec(self, fuel: u64) -> Self {
+        Self {
+            fuel_per_sec: Some(fuel),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of memory pages (64KiB per page) a memory of the tenant can hold.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The maximum number of pages.
+    pub fn with_max_memory_pages(self, count: u32) -> Self {
+        Self {
+            max_memory_pages: Some(count),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of calls of the tenant which are queued or running at the same time.
+    ///
+    /// # Argument
+    ///
+    /// * `count` - The maximum number of concurrent calls.
+    pub fn with_max_concurrent_calls(self, count: usize) -> Self {
+        Self {
+            max_concurrent_calls: Some(count),
+            ..self
+        }
+    }
+
+    /// Returns the fuel granted per second, or `None` if unlimited.
+    pub fn fuel_per_sec(&self) -> Option<u64> {
+        self.fuel_per_sec
+    }
+
+    /// Returns the maximum number of memory pages, or `None` if unlimited.
+    pub fn max_memory_pages(&self) -> Option<u32> {
+        self.max_memory_pages
+    }
+
+    /// Returns the maximum number of concurrent calls, or `None` if unlimited.
+    pub fn max_concurrent_calls(&self) -> Option<usize> {
+        self.max_concurrent_calls
+    }
+}
+
+/// The resource usage of a tenant, collected by a [TenantManager].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantUsage {
+    /// The number of finished calls, including the failed ones.
+    pub calls: u64,
+    /// The number of finished calls which returned an error.
+    pub failed_calls: u64,
+    /// The number of calls rejected because of the quota.
+    pub rejected_calls: u64,
+    /// The cumulative fuel consumed by the finished calls.
+    pub fuel_consumed: u64,
+    /// The cumulative time the workers spent on the calls.
+    pub busy_time: Duration,
+    /// The number of calls which are queued or running.
+    pub active_calls: usize,
+    /// The highest number of calls which were queued or running at the same time.
+    pub peak_concurrent_calls: usize,
+}
+
+struct Tenant {
+    quota: TenantQuota,
+    usage: TenantUsage,
+    fuel: f64,
+    refilled_at: Instant,
+    imports: Option<Arc<ImportsFn>>,
+}
+impl Tenant {
+    fn new(quota: TenantQuota) -> Self {
+        Self {
+            quota,
+            usage: TenantUsage::default(),
+            fuel: quota.fuel_per_sec.unwrap_or_default() as f64,
+            refilled_at: Instant::now(),
+            imports: None,
+        }
+    }
+
+    /// Accumulates the fuel granted since the last refill, up to one second's worth.
+    fn refill(&mut self) {
+        if let Some(rate) = self.quota.fuel_per_sec {
+            let now = Instant::now();
+            let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+            self.fuel = (self.fuel + rate as f64 * elapsed).min(rate as f64);
+            self.refilled_at = now;
+        }
+    }
+}
+
+impl std::fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tenant")
+            .field("quota", &self.quota)
+            .field("usage", &self.usage)
+            .field("fuel", &self.fuel)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs the function calls of multiple tenants on a shared [pool](crate::pool::ExecutorPool) of worker threads, enforcing a [quota](crate::tenant::TenantQuota) per tenant and keeping track of the [usage](crate::tenant::TenantUsage) of each tenant.
+///
+/// Every call instantiates the given [module](crate::Module) in a fresh [store](crate::Store) with an [executor](crate::Executor) configured by the quota of the tenant, so the calls of different tenants never share any state. The imports of the module are satisfied by the [import objects](crate::ImportObject) created for the call with the factory set by [set_imports](crate::tenant::TenantManager::set_imports), and a module importing WASI is given a WASI instance without arguments, environment variables or preopened directories.
+///
+/// ```ignore
+/// let manager = TenantManager::new(8);
+/// manager.add_tenant("acme", TenantQuota::new().with_fuel_per_sec(1_000_000).with_max_concurrent_calls(4))?;
+///
+/// let returns = manager.call("acme", &module, "handle", params!(42))?.wait()?;
+/// println!("{:?}", manager.usage("acme"));
+/// ```
+///
+/// When the manager is dropped, the queued calls are finished before the workers exit.
+#[derive(Debug)]
+pub struct TenantManager {
+    tenants: Arc<Mutex<HashMap<String, Tenant>>>,
+    pool: ExecutorPool,
+}
+impl TenantManager {
+    /// Creates a new [TenantManager] with the given number of worker threads.
+    ///
+    /// # Argument
+    ///
+    /// * `workers` - The number of worker threads. At least one worker is created.
+    pub fn new(workers: usize) -> Self {
+        Self {
+            tenants: Arc::new(Mutex::new(HashMap::new())),
+            pool: ExecutorPool::new(workers),
+        }
+    }
+
+    /// Adds a tenant with the given quota.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The unique name of the tenant.
+    ///
+    /// * `quota` - The resource quota of the tenant.
+    ///
+    /// # Error
+    ///
+    /// If a tenant of the same name exists, then [WasmEdgeError::Tenant(TenantError::Duplicate)](crate::error::TenantError) is returned.
+    pub fn add_tenant(&self, name: impl AsRef<str>, quota: TenantQuota) -> WasmEdgeResult<()> {
+        let mut tenants = self.lock();
+        let name = name.as_ref();
+        if tenants.contains_key(name) {
+            return Err(Box::new(WasmEdgeError::Tenant(TenantError::Duplicate(
+                name.into(),
+            ))));
+        }
+        tenants.insert(name.into(), Tenant::new(quota));
+        Ok(())
+    }
+
+    /// Removes a tenant and returns its usage. The calls of the tenant which are queued or running are still finished.
+    ///
+    /// # Argument
+    ///
+    /// * `name` - The name of the tenant.
+    ///
+    /// # Error
+    ///
+    /// If the tenant does not exist, then [WasmEdgeError::Tenant(TenantError::NotFound)](crate::error::TenantError) is returned.
+    pub fn remove_tenant(&self, name: impl AsRef<str>) -> WasmEdgeResult<TenantUsage> {
+        let name = name.as_ref();
+        match self.lock().remove(name) {
+            Some(tenant) => Ok(tenant.usage),
+            None => Err(Box::new(WasmEdgeError::Tenant(TenantError::NotFound(
+                name.into(),
+            )))),
+        }
+    }
+
+    /// Changes the quota of a tenant. The new quota applies to the calls submitted afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tenant.
+    ///
+    /// * `quota` - The new resource quota of the tenant.
+    ///
+    /// # Error
+    ///
+    /// If the tenant does not exist, then [WasmEdgeError::Tenant(TenantError::NotFound)](crate::error::TenantError) is returned.
+    pub fn set_quota(&self, name: impl AsRef<str>, quota: TenantQuota) -> WasmEdgeResult<()> {
+        let name = name.as_ref();
+        match self.lock().get_mut(name) {
+            Some(tenant) => {
+                tenant.refill();
+                let rate = quota.fuel_per_sec.unwrap_or_default() as f64;
+                tenant.fuel = match tenant.quota.fuel_per_sec {
+                    Some(_) => tenant.fuel.min(rate),
+                    None => rate,
+                };
+                tenant.quota = quota;
+                Ok(())
+            }
+            None => Err(Box::new(WasmEdgeError::Tenant(TenantError::NotFound(
+                name.into(),
+            )))),
+        }
+    }
+
+    /// Sets the factory of the import objects of a tenant. Every call of the tenant submitted afterwards creates its own import objects with the factory, and registers them into its fresh store before instantiating the module.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tenant.
+    ///
+    /// * `imports` - The factory creating the import objects of a call, for example the [key-value store](crate::kv::import_object) of the tenant.
+    ///
+    /// # Error
+    ///
+    /// If the tenant does not exist, then [WasmEdgeError::Tenant(TenantError::NotFound)](crate::error::TenantError) is returned.
+    pub fn set_imports(
+        &self,
+        name: impl AsRef<str>,
+        imports: impl Fn() -> WasmEdgeResult<Vec<ImportObject<NeverType>>> + Send + Sync + 'static,
+    ) -> WasmEdgeResult<()> {
+        let name = name.as_ref();
+        match self.lock().get_mut(name) {
+            Some(tenant) => {
+                tenant.imports = Some(Arc::new(imports));
+                Ok(())
+            }
+            None => Err(Box::new(WasmEdgeError::Tenant(TenantError::NotFound(
+                name.into(),
+            )))),
+        }
+    }
+
+    /// Returns the usage of a tenant, or `None` if the tenant does not exist.
+    ///
+    /// # Argument
+    ///
+    /// * `name` - The name of the tenant.
+    pub fn usage(&self, name: impl AsRef<str>) -> Option<TenantUsage> {
+        self.lock()
+            .get(name.as_ref())
+            .map(|tenant| tenant.usage.clone())
+    }
+
+    /// Returns the usage of all tenants, keyed by the names of the tenants.
+    pub fn usage_report(&self) -> HashMap<String, TenantUsage> {
+        self.lock()
+            .iter()
+            .map(|(name, tenant)| (name.clone(), tenant.usage.clone()))
+            .collect()
+    }
+
+    /// Submits a call of an exported function of the given [module](crate::Module) on behalf of a tenant, and returns a handle to wait for the results.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant` - The name of the tenant.
+    ///
+    /// * `module` - The [module](crate::Module) to instantiate for the call, along with the [imports](crate::tenant::TenantManager::set_imports) of the tenant.
+    ///
+    /// * `func_name` - The name of the exported function to call.
+    ///
+    /// * `params` - The arguments to pass to the function.
+    ///
+    /// # Error
+    ///
+    /// * If the tenant does not exist, then [WasmEdgeError::Tenant(TenantError::NotFound)](crate::error::TenantError) is returned.
+    ///
+    /// * If the tenant has reached its limit of concurrent calls, then [WasmEdgeError::Tenant(TenantError::ConcurrencyLimitExceeded)](crate::error::TenantError) is returned.
+    ///
+    /// * If the tenant has no fuel left, then [WasmEdgeError::Tenant(TenantError::FuelExhausted)](crate::error::TenantError) is returned.
+    pub fn call(
+        &self,
+        tenant: impl AsRef<str>,
+        module: &Module,
+        func_name: impl AsRef<str>,
+        params: impl IntoIterator<Item = WasmValue>,
+    ) -> WasmEdgeResult<TenantCall> {
+        let name = tenant.as_ref().to_string();
+
+        // admit the call under the quota of the tenant
+        let (max_memory_pages, fuel_limit, imports) = {
+            let mut tenants = self.lock();
+            let tenant = match tenants.get_mut(&name) {
+                Some(tenant) => tenant,
+                None => return Err(Box::new(WasmEdgeError::Tenant(TenantError::NotFound(name)))),
+            };
+            tenant.refill();
+
+            if let Some(max) = tenant.quota.max_concurrent_calls {
+                if tenant.usage.active_calls >= max {
+                    tenant.usage.rejected_calls += 1;
+                    return Err(Box::new(WasmEdgeError::Tenant(
+                        TenantError::ConcurrencyLimitExceeded(name),
+                    )));
+                }
+            }
+            // the fuel is charged when the call finishes, so the concurrent calls are not starved
+            let fuel_limit = tenant
+                .quota
+                .fuel_per_sec
+                .map(|_| tenant.fuel.max(0.0) as u64);
+            if fuel_limit == Some(0) {
+                tenant.usage.rejected_calls += 1;
+                return Err(Box::new(WasmEdgeError::Tenant(TenantError::FuelExhausted(
+                    name,
+                ))));
+            }
+
+            tenant.usage.active_calls += 1;
+            tenant.usage.peak_concurrent_calls = tenant
+                .usage
+                .peak_concurrent_calls
+                .max(tenant.usage.active_calls);
+            (
+                tenant.quota.max_memory_pages,
+                fuel_limit,
+                tenant.imports.clone(),
+            )
+        };
+
+        let (result_sender, result_receiver) = mpsc::channel();
+        let module = module.clone();
+        let func_name = func_name.as_ref().to_string();
+        let params: Vec<WasmValue> = params.into_iter().collect();
+        let settle = Settle {
+            tenants: Arc::clone(&self.tenants),
+            name: name.clone(),
+            started: None,
+            stat: None,
+            succeeded: false,
+        };
+        // the call is settled when the task returns or unwinds, before its outcome is sent
+        let task = PoolTask::from_fn(move || {
+            let mut settle = settle;
+            let started = Instant::now();
+            settle.started = Some(started);
+            let returns = execute(
+                &module,
+                &func_name,
+                params,
+                max_memory_pages,
+                fuel_limit,
+                imports.as_deref(),
+                &mut settle.stat,
+            )?;
+            settle.succeeded = true;
+            let report = ExecutionReport {
+                duration: started.elapsed(),
+                instructions: settle.stat.as_ref().map_or(0, Statistics::count),
+                fuel_used: settle.stat.as_ref().map_or(0, Statistics::cost),
+                ..Default::default()
+            };
+            Ok((returns, report))
+        });
+        let done = move |outcome: TaskOutcome| {
+            let result = match outcome {
+                Ok((returns, _report)) => Ok(returns),
+                Err(err) => match *err {
+                    WasmEdgeError::Pool(PoolError::Panicked(message)) => Err(Box::new(
+                        WasmEdgeError::Tenant(TenantError::Panicked(message)),
+                    )),
+                    err => Err(Box::new(err)),
+                },
+            };
+            // the caller may have dropped the handle
+            let _ = result_sender.send(result);
+        };
+
+        // a task which is not queued settles the call when it is dropped
+        if !self.pool.spawn(task, done) {
+            return Err(Box::new(WasmEdgeError::Tenant(TenantError::ShutDown)));
+        }
+
+        Ok(TenantCall {
+            receiver: result_receiver,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Tenant>> {
+        self.tenants
+            .lock()
+            .expect("[bitbang] the tenant registry is poisoned")
+    }
+}
+
+/// Settles the usage of an admitted call when dropped, even if the call panics or is never run: releases its concurrency slot, and charges the fuel the call consumed.
+struct Settle {
+    tenants: Arc<Mutex<HashMap<String, Tenant>>>,
+    name: String,
+    started: Option<Instant>,
+    // the statistics of the call, which measure the fuel it consumed
+    stat: Option<Statistics>,
+    succeeded: bool,
+}
+impl Drop for Settle {
+    fn drop(&mut self) {
+        let mut tenants = match self.tenants.lock() {
+            Ok(tenants) => tenants,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let tenant = match tenants.get_mut(&self.name) {
+            Some(tenant) => tenant,
+            None => return,
+        };
+
+        tenant.usage.active_calls = tenant.usage.active_calls.saturating_sub(1);
+        let fuel = self.stat.as_ref().map_or(0, Statistics::cost);
+        if let Some(started) = self.started {
+            tenant.usage.calls += 1;
+            if !self.succeeded {
+                tenant.usage.failed_calls += 1;
+            }
+            tenant.usage.fuel_consumed += fuel;
+            tenant.usage.busy_time += started.elapsed();
+        }
+        if tenant.quota.fuel_per_sec.is_some() {
+            tenant.refill();
+            tenant.fuel -= fuel as f64;
+        }
+    }
+}
+
+/// A handle to a call submitted to a [TenantManager].
+#[derive(Debug)]
+pub struct TenantCall {
+    receiver: mpsc::Receiver<WasmEdgeResult<Vec<WasmValue>>>,
+}
+impl TenantCall {
+    /// Blocks until the call finishes, and returns its results.
+    ///
+    /// # Error
+    ///
+    /// * If the call fails, then the error of the call is returned.
+    ///
+    /// * If the call panics, then [WasmEdgeError::Tenant(TenantError::Panicked)](crate::error::TenantError) is returned.
+    ///
+    /// * If the manager is dropped before the call runs, then [WasmEdgeError::Tenant(TenantError::ShutDown)](crate::error::TenantError) is returned.
+    pub fn wait(self) -> WasmEdgeResult<Vec<WasmValue>> {
+        match self.receiver.recv() {
+            Ok(result) => result,
+            Err(_) => Err(Box::new(WasmEdgeError::Tenant(TenantError::ShutDown))),
+        }
+    }
+}
+
+/// Instantiates the module in a fresh store along with its imports, and calls the function. The statistics of the call are stored in `stat` once created.
+fn execute(
+    module: &Module,
+    func_name: &str,
+    params: Vec<WasmValue>,
+    max_memory_pages: Option<u32>,
+    fuel_limit: Option<u64>,
+    imports: Option<&ImportsFn>,
+    stat: &mut Option<Statistics>,
+) -> WasmEdgeResult<Vec<WasmValue>> {
+    let mut runtime_config = RuntimeConfigOptions::new();
+    if let Some(count) = max_memory_pages {
+        runtime_config = runtime_config.max_memory_pages(count);
+    }
+    let config = ConfigBuilder::new(CommonConfigOptions::default())
+        .with_statistics_config(StatisticsConfigOptions::new().measure_cost(true))
+        .with_runtime_config(runtime_config)
+        .build()?;
+
+    let stat = stat.insert(Statistics::new()?);
+    if let Some(limit) = fuel_limit {
+        stat.set_cost_limit(limit);
+    }
+
+    // the import objects outlive the store they are registered into
+    let imports = match imports {
+        Some(imports) => imports()?,
+        None => Vec::new(),
+    };
+    let wasi = match module
+        .imports()
+        .iter()
+        .any(|import| WASI_MODULE_NAMES.contains(&import.module_name().as_ref()))
+    {
+        true => Some(sys::WasiModule::create(None, None, None)?),
+        false => None,
+    };
+
+    let mut executor = Executor::new(Some(&config), Some(stat))?;
+    let mut store = Store::new()?;
+    if let Some(wasi) = &wasi {
+        executor
+            .inner
+            .register_wasi_instance(&store.inner, &sys::WasiInstance::Wasi(wasi.clone()))?;
+    }
+    for import in imports.iter() {
+        store.register_import_module(&mut executor, import)?;
+    }
+    let instance = store.register_active_module(&mut executor, module)?;
+    let func = instance.func(func_name)?;
+    executor.run_func(&func, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{CoreCommonError, CoreError},
+        params, wat2wasm, ImportObjectBuilder, WasmVal,
+    };
+
+    fn module() -> Module {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (func (export "spin") (param i32) (result i32)
+                (local i32)
+                (block
+                  (loop
+                    local.get 1
+                    local.get 0
+                    i32.ge_u
+                    br_if 1
+                    local.get 1
+                    i32.const 1
+                    i32.add
+                    local.set 1
+                    br 0))
+                local.get 1)
+            )
+            "#,
+        )
+        .unwrap();
+        Module::from_bytes(None, wasm_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_tenant_manager_call() {
+        let module = module();
+        let manager = TenantManager::new(2);
+
+        let result = manager.add_tenant("acme", TenantQuota::new());
+        assert!(result.is_ok());
+        let result = manager.add_tenant("acme", TenantQuota::new());
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Tenant(TenantError::Duplicate("acme".into()))
+        );
+
+        // run calls on the workers
+        let calls: Vec<_> = (0..4)
+            .map(|i| manager.call("acme", &module, "spin", params!(i * 10)))
+            .collect();
+        for (i, call) in calls.into_iter().enumerate() {
+            assert!(call.is_ok());
+            let result = call.unwrap().wait();
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap()[0].to_i32(), i as i32 * 10);
+        }
+
+        let usage = manager.usage("acme").unwrap();
+        assert_eq!(usage.calls, 4);
+        assert_eq!(usage.failed_calls, 0);
+        assert_eq!(usage.active_calls, 0);
+        assert!(usage.fuel_consumed > 0);
+        assert!(usage.peak_concurrent_calls >= 1);
+
+        // unknown tenant
+        let result = manager.call("unknown", &module, "spin", params!(1));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Tenant(TenantError::NotFound("unknown".into()))
+        );
+
+        let result = manager.remove_tenant("acme");
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), usage);
+        assert!(manager.usage_report().is_empty());
+    }
+
+    #[test]
+    fn test_tenant_manager_quota() {
+        let module = module();
+        let manager = TenantManager::new(1);
+
+        // the call is aborted once it uses up the fuel
+        let result = manager.add_tenant("small", TenantQuota::new().with_fuel_per_sec(100));
+        assert!(result.is_ok());
+        let result = manager
+            .call("small", &module, "spin", params!(1_000))
+            .and_then(|call| call.wait());
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Core(CoreError::Common(CoreCommonError::CostLimitExceeded))
+        );
+        let usage = manager.usage("small").unwrap();
+        assert_eq!(usage.failed_calls, 1);
+        assert!(usage.fuel_consumed > 0);
+
+        // the calls beyond the concurrency limit are rejected
+        let result = manager.add_tenant("serial", TenantQuota::new().with_max_concurrent_calls(1));
+        assert!(result.is_ok());
+        let result = manager.call("serial", &module, "spin", params!(1_000_000));
+        assert!(result.is_ok());
+        let running = result.unwrap();
+        let result = manager.call("serial", &module, "spin", params!(1));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Tenant(TenantError::ConcurrencyLimitExceeded("serial".into()))
+        );
+        assert!(running.wait().is_ok());
+
+        let usage = manager.usage("serial").unwrap();
+        assert_eq!(usage.calls, 1);
+        assert_eq!(usage.rejected_calls, 1);
+        assert_eq!(usage.peak_concurrent_calls, 1);
+
+        // the concurrent calls of a tenant are all admitted, and charged once they finish
+        let result = manager.call("serial", &module, "spin", params!(5_000_000));
+        assert!(result.is_ok());
+        let blocker = result.unwrap();
+        let quota = TenantQuota::new()
+            .with_fuel_per_sec(1_000_000)
+            .with_max_concurrent_calls(2);
+        let result = manager.add_tenant("shared", quota);
+        assert!(result.is_ok());
+        let result = manager.call("shared", &module, "spin", params!(1_000));
+        assert!(result.is_ok());
+        let first = result.unwrap();
+        let result = manager.call("shared", &module, "spin", params!(1_000));
+        assert!(result.is_ok());
+        let second = result.unwrap();
+        assert!(blocker.wait().is_ok());
+        assert!(first.wait().is_ok());
+        assert!(second.wait().is_ok());
+        let usage = manager.usage("shared").unwrap();
+        assert_eq!(usage.calls, 2);
+        assert_eq!(usage.failed_calls, 0);
+        assert_eq!(usage.active_calls, 0);
+        assert!(usage.fuel_consumed > 0);
+
+        // a tenant in debt is rejected until the debt is paid off
+        let result = manager.call("serial", &module, "spin", params!(5_000_000));
+        assert!(result.is_ok());
+        let blocker = result.unwrap();
+        let result = manager.add_tenant("debtor", TenantQuota::new().with_fuel_per_sec(1_000));
+        assert!(result.is_ok());
+        let result = manager.call("debtor", &module, "spin", params!(50));
+        assert!(result.is_ok());
+        let first = result.unwrap();
+        let result = manager.call("debtor", &module, "spin", params!(1_000));
+        assert!(result.is_ok());
+        let second = result.unwrap();
+        assert!(blocker.wait().is_ok());
+        assert!(first.wait().is_ok());
+        assert!(second.wait().is_err());
+        let result = manager.call("debtor", &module, "spin", params!(1));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Tenant(TenantError::FuelExhausted("debtor".into()))
+        );
+    }
+
+    #[test]
+    fn test_tenant_manager_imports() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (import "host" "double" (func $double (param i32) (result i32)))
+              (func (export "run") (param i32) (result i32)
+                local.get 0
+                call $double)
+            )
+            "#,
+        )
+        .unwrap();
+        let module = Module::from_bytes(None, wasm_bytes).unwrap();
+        let manager = TenantManager::new(1);
+        assert!(manager.add_tenant("acme", TenantQuota::new()).is_ok());
+
+        // the module can not be instantiated without its imports
+        let result = manager
+            .call("acme", &module, "run", params!(21))
+            .and_then(|call| call.wait());
+        assert!(result.is_err());
+
+        let result = manager.set_imports("acme", || {
+            let import = ImportObjectBuilder::new()
+                .with_func::<i32, i32, NeverType>(
+                    "double",
+                    |_frame, args, _data| Ok(vec![WasmValue::from_i32(args[0].to_i32() * 2)]),
+                    None,
+                )?
+                .build::<NeverType>("host", None)?;
+            Ok(vec![import])
+        });
+        assert!(result.is_ok());
+        let result = manager
+            .call("acme", &module, "run", params!(21))
+            .and_then(|call| call.wait());
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap()[0].to_i32(), 42);
+
+        let result = manager.set_imports("unknown", || Ok(Vec::new()));
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Tenant(TenantError::NotFound("unknown".into()))
+        );
+    }
+}
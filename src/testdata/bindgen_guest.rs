@@ -0,0 +1,71 @@
+/// Typed bindings of the exported functions of a wasm module, generated by `bitbang::bindgen`.
+pub struct Guest {
+    executor: ::bitbang::Executor,
+    instance: ::bitbang::Instance,
+    add: ::bitbang::Func,
+    div_rem: ::bitbang::Func,
+    type_: ::bitbang::Func,
+    reset: ::bitbang::Func,
+}
+#[allow(clippy::too_many_arguments)]
+impl Guest {
+    /// Binds the exported functions of the given module instance, and checks their types.
+    pub fn new(
+        executor: ::bitbang::Executor,
+        instance: ::bitbang::Instance,
+    ) -> ::bitbang::WasmEdgeResult<Self> {
+        Ok(Self {
+            add: ::bitbang::bindgen::bind_func(
+                &instance,
+                "add",
+                &[::bitbang::ValType::I32, ::bitbang::ValType::I32],
+                &[::bitbang::ValType::I32],
+            )?,
+            div_rem: ::bitbang::bindgen::bind_func(
+                &instance,
+                "divRem",
+                &[::bitbang::ValType::I64, ::bitbang::ValType::I64],
+                &[::bitbang::ValType::I64, ::bitbang::ValType::I64],
+            )?,
+            type_: ::bitbang::bindgen::bind_func(
+                &instance,
+                "type",
+                &[::bitbang::ValType::ExternRef],
+                &[],
+            )?,
+            reset: ::bitbang::bindgen::bind_func(
+                &instance,
+                "reset",
+                &[],
+                &[],
+            )?,
+            executor,
+            instance,
+        })
+    }
+
+    /// Returns the module instance.
+    pub fn instance(&self) -> &::bitbang::Instance {
+        &self.instance
+    }
+
+    /// Calls the exported function `add`.
+    pub fn add(&self, lhs: i32, rhs: i32) -> ::bitbang::WasmEdgeResult<i32> {
+        self.executor.run_func_typed::<i32>(&self.add, [::bitbang::WasmValue::from_i32(lhs), ::bitbang::WasmValue::from_i32(rhs)])
+    }
+
+    /// Calls the exported function `divRem`.
+    pub fn div_rem(&self, arg0: i64, arg1: i64) -> ::bitbang::WasmEdgeResult<(i64, i64)> {
+        self.executor.run_func_typed::<(i64, i64)>(&self.div_rem, [::bitbang::WasmValue::from_i64(arg0), ::bitbang::WasmValue::from_i64(arg1)])
+    }
+
+    /// Calls the exported function `type`.
+    pub fn type_(&self, arg0: ::bitbang::WasmValue) -> ::bitbang::WasmEdgeResult<()> {
+        self.executor.run_func_typed::<()>(&self.type_, [arg0])
+    }
+
+    /// Calls the exported function `reset`.
+    pub fn reset(&self) -> ::bitbang::WasmEdgeResult<()> {
+        self.executor.run_func_typed::<()>(&self.reset, [])
+    }
+}
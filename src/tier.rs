@@ -0,0 +1,103 @@
+//! Defines the background tier-up compilation of the modules loaded with [Module::from_bytes_tiered](crate::Module::from_bytes_tiered).
+
+use crate::{aot::PrivateDir, config::Config, Compiler, Module, WasmEdgeResult};
+use bit_sys as sys;
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    thread::JoinHandle,
+};
+
+/// The compilation state of a [module](crate::Module) loaded with [Module::from_bytes_tiered](crate::Module::from_bytes_tiered).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompilationState {
+    /// The module is being compiled in the background. The module instances run interpreted.
+    Compiling,
+    /// The module is compiled. The module instances created from now on run the compiled code.
+    Compiled,
+    /// The compilation failed with the given error. The module instances keep running interpreted.
+    Failed(String),
+}
+
+/// The compiled code of a module, which is produced on a background thread.
+///
+/// The thread holds no reference to the tier, and is joined when the tier is dropped, so dropping the last clone of the module waits for the compilation to finish.
+#[derive(Debug)]
+pub(crate) struct Tier {
+    state: Mutex<CompilationState>,
+    compiled: OnceLock<sys::Module>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+impl Tier {
+    /// Starts compiling the given module bytes on a background thread.
+    pub(crate) fn spawn(config: Option<&Config>, bytes: Vec<u8>) -> Arc<Self> {
+        let tier = Arc::new(Self {
+            state: Mutex::new(CompilationState::Compiling),
+            compiled: OnceLock::new(),
+            thread: Mutex::new(None),
+        });
+        let config = config.cloned();
+        let compiling = Arc::downgrade(&tier);
+        let thread = std::thread::spawn(move || {
+            let result = compile(config.as_ref(), &bytes);
+            // the module is dropped while compiling
+            let tier = match compiling.upgrade() {
+                Some(tier) => tier,
+                None => return,
+            };
+            let state = match result {
+                Ok(compiled) => {
+                    let _ = tier.compiled.set(compiled);
+                    CompilationState::Compiled
+                }
+                Err(err) => CompilationState::Failed(err.to_string()),
+            };
+            *tier
+                .state
+                .lock()
+                .expect("[bitbang] the compilation state is poisoned") = state;
+        });
+        *tier
+            .thread
+            .lock()
+            .expect("[bitbang] the compilation thread is poisoned") = Some(thread);
+        tier
+    }
+
+    /// Returns the current compilation state.
+    pub(crate) fn state(&self) -> CompilationState {
+        self.state
+            .lock()
+            .expect("[bitbang] the compilation state is poisoned")
+            .clone()
+    }
+
+    /// Returns the compiled code, if the compilation is done.
+    pub(crate) fn compiled(&self) -> Option<&sys::Module> {
+        self.compiled.get()
+    }
+}
+
+impl Drop for Tier {
+    fn drop(&mut self) {
+        let thread = self
+            .thread
+            .get_mut()
+            .expect("[bitbang] the compilation thread is poisoned")
+            .take();
+        // the compilation thread itself drops the tier if the module is dropped while the thread stores the result
+        if let Some(thread) = thread {
+            if thread.thread().id() != std::thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// Compiles the given module bytes, and loads the compiled code.
+///
+/// The artifact is written to and loaded from a directory which only the current user can access, and which is removed once the code is loaded, so no other user can replace it before it is loaded.
+fn compile(config: Option<&Config>, bytes: &[u8]) -> WasmEdgeResult<sys::Module> {
+    let dir = PrivateDir::new()?;
+    let artifact = Compiler::new(config)?.compile_from_bytes(bytes, "module", dir.path())?;
+    Module::from_file(config, artifact).map(|module| module.inner)
+}
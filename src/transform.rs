@@ -0,0 +1,222 @@
+//! Defines the transformations applied to the module bytes before they are loaded with [Module::from_bytes_with](crate::Module::from_bytes_with).
+//!
+//! A [ModuleTransform] rewrites a wasm binary, for example to inject metering, coverage counters or tracing imports. The transforms are applied in order, each to the output of the previous one:
+//!
+//! ```ignore
+//! let module = Module::from_bytes_with(None, &wasm_bytes, &[&Metering::new(costs), &Coverage::new()])?;
+//! ```
+//!
+//! The transformed bytes are cached per process, keyed by the input bytes and the [ids](crate::transform::ModuleTransform::id) of the transforms in order, so loading the same module through the same pipeline again skips the transforms. The cache holds the most recently transformed binaries, and is emptied with [clear_cache].
+
+use crate::WasmEdgeResult;
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+/// The number of the transformed binaries kept in the cache.
+const CACHE_CAPACITY: usize = 32;
+
+static CACHE: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug)]
+struct Entry {
+    hash: u64,
+    ids: Vec<String>,
+    input: Arc<[u8]>,
+    output: Arc<[u8]>,
+}
+
+/// Defines a transformation of the module bytes applied before the module is loaded.
+pub trait ModuleTransform: Send + Sync {
+    /// Returns the identity of the transform, which is part of the cache key of the transformed bytes.
+    ///
+    /// Two transforms with the same id must produce the same output from the same input, so the id should cover the name, the version and the parameters of the transform, such as `metering/1?cost=table-a`.
+    fn id(&self) -> String;
+
+    /// Transforms the module bytes.
+    ///
+    /// # Argument
+    ///
+    /// * `bytes` - The wasm binary, which is the output of the previous transform, if any.
+    ///
+    /// # Error
+    ///
+    /// If the binary can not be transformed, then an error is returned, and the module is not loaded.
+    fn transform(&self, bytes: &[u8]) -> WasmEdgeResult<Vec<u8>>;
+
+    /// Returns whether the output of the transform can be cached. A transform whose output depends on anything besides its input and its id, such as the time, returns `false`. Defaults to `true`.
+    fn is_cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// Empties the cache of the transformed module bytes.
+pub fn clear_cache() {
+    lock_cache().clear();
+}
+
+/// Applies the transforms in order, and returns the transformed bytes, from the cache if all the transforms are cacheable.
+pub(crate) fn apply<'a>(
+    bytes: &'a [u8],
+    transforms: &[&dyn ModuleTransform],
+) -> WasmEdgeResult<Cow<'a, [u8]>> {
+    if transforms.is_empty() {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    if !transforms.iter().all(|transform| transform.is_cacheable()) {
+        return run(bytes, transforms).map(Cow::Owned);
+    }
+
+    let ids: Vec<String> = transforms.iter().map(|transform| transform.id()).collect();
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    ids.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    {
+        let mut cache = lock_cache();
+        let hit = cache
+            .iter()
+            .position(|entry| entry.hash == hash && entry.ids == ids && *entry.input == *bytes)
+            .and_then(|index| cache.remove(index));
+        if let Some(entry) = hit {
+            // keep the most recently used entry at the back
+            let output = Arc::clone(&entry.output);
+            cache.push_back(entry);
+            return Ok(Cow::Owned(output.to_vec()));
+        }
+    }
+
+    // the transforms run without holding the cache, so they can load modules themselves
+    let output = run(bytes, transforms)?;
+
+    let mut cache = lock_cache();
+    if cache.len() >= CACHE_CAPACITY {
+        cache.pop_front();
+    }
+    cache.push_back(Entry {
+        hash,
+        ids,
+        input: Arc::from(bytes),
+        output: Arc::from(output.as_slice()),
+    });
+    Ok(Cow::Owned(output))
+}
+
+fn run(bytes: &[u8], transforms: &[&dyn ModuleTransform]) -> WasmEdgeResult<Vec<u8>> {
+    let mut output = transforms[0].transform(bytes)?;
+    for transform in transforms[1..].iter() {
+        output = transform.transform(&output)?;
+    }
+    Ok(output)
+}
+
+fn lock_cache() -> std::sync::MutexGuard<'static, VecDeque<Entry>> {
+    CACHE
+        .lock()
+        .expect("[bitbang] the module transform cache is poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::WasmEdgeError, params, wat2wasm, Executor, Module, Store, WasmVal};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Increments the constant returned by `answer`, and counts its runs.
+    struct Increment {
+        runs: AtomicUsize,
+        cacheable: bool,
+    }
+    impl ModuleTransform for Increment {
+        fn id(&self) -> String {
+            "increment/1".to_string()
+        }
+
+        fn transform(&self, bytes: &[u8]) -> WasmEdgeResult<Vec<u8>> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            let mut bytes = bytes.to_vec();
+            // i32.const <n> end
+            match bytes.windows(3).position(|w| w[0] == 0x41 && w[2] == 0x0b) {
+                Some(pos) => {
+                    bytes[pos + 1] += 1;
+                    Ok(bytes)
+                }
+                None => Err(Box::new(WasmEdgeError::Operation(
+                    "no constant to increment".to_string(),
+                ))),
+            }
+        }
+
+        fn is_cacheable(&self) -> bool {
+            self.cacheable
+        }
+    }
+
+    fn answer(module: &Module) -> i32 {
+        let mut executor = Executor::new(None, None).unwrap();
+        let mut store = Store::new().unwrap();
+        let instance = store.register_active_module(&mut executor, module).unwrap();
+        let result = executor.run_func(&instance.func("answer").unwrap(), params!());
+        assert!(result.is_ok());
+        result.unwrap()[0].to_i32()
+    }
+
+    #[test]
+    fn test_module_transform() {
+        let wasm_bytes = wat2wasm(
+            br#"
+            (module
+              (func (export "answer") (result i32)
+                i32.const 40))
+            "#,
+        )
+        .unwrap();
+
+        let increment = Increment {
+            runs: AtomicUsize::new(0),
+            cacheable: true,
+        };
+
+        // the transforms are applied in order
+        let result = Module::from_bytes_with(None, &wasm_bytes, &[&increment, &increment]);
+        assert!(result.is_ok());
+        assert_eq!(answer(&result.unwrap()), 42);
+        assert_eq!(increment.runs.load(Ordering::SeqCst), 2);
+
+        // the same pipeline hits the cache
+        let result = Module::from_bytes_with(None, &wasm_bytes, &[&increment, &increment]);
+        assert!(result.is_ok());
+        assert_eq!(answer(&result.unwrap()), 42);
+        assert_eq!(increment.runs.load(Ordering::SeqCst), 2);
+
+        // a different pipeline does not
+        let result = Module::from_bytes_with(None, &wasm_bytes, &[&increment]);
+        assert!(result.is_ok());
+        assert_eq!(answer(&result.unwrap()), 41);
+        assert_eq!(increment.runs.load(Ordering::SeqCst), 3);
+
+        // the transforms which are not cacheable always run
+        let uncached = Increment {
+            runs: AtomicUsize::new(0),
+            cacheable: false,
+        };
+        for _ in 0..2 {
+            let result = Module::from_bytes_with(None, &wasm_bytes, &[&uncached]);
+            assert!(result.is_ok());
+        }
+        assert_eq!(uncached.runs.load(Ordering::SeqCst), 2);
+
+        // the errors of the transforms are returned
+        let result = Module::from_bytes_with(None, [0u8, 97, 115, 109, 1, 0, 0, 0], &[&uncached]);
+        assert!(result.is_err());
+
+        clear_cache();
+        let result = Module::from_bytes_with(None, &wasm_bytes, &[&increment]);
+        assert!(result.is_ok());
+        assert_eq!(increment.runs.load(Ordering::SeqCst), 4);
+    }
+}
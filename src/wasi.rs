@@ -1,5 +1,11 @@
 //! Defines wasi module instance.
 
+use crate::{
+    error::{WasiError, WasmEdgeError},
+    WasmEdgeResult,
+};
+use std::path::{Path, PathBuf};
+
 /// Represents a wasi module instance.
 #[derive(Debug, Clone)]
 pub struct WasiInstance {
@@ -31,6 +37,37 @@ impl WasiInstance {
         self.inner.init_wasi(args, envs, preopens);
     }
 
+    /// Initializes the WASI host module with the given parameters and [preopened directories](crate::wasi::Preopen).
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The commandline arguments. The first argument is the program name.
+    ///
+    /// * `envs` - The environment variables in the format `ENV_VAR_NAME=VALUE`.
+    ///
+    /// * `preopens` - The directories to pre-open.
+    ///
+    /// # Error
+    ///
+    /// If a preopen is invalid, then an error is returned, and the WASI host module is left uninitialized.
+    pub fn initialize_with_preopens(
+        &mut self,
+        args: Option<Vec<&str>>,
+        envs: Option<Vec<&str>>,
+        preopens: impl IntoIterator<Item = Preopen>,
+    ) -> WasmEdgeResult<()> {
+        let preopens = preopens
+            .into_iter()
+            .map(|preopen| preopen.encode())
+            .collect::<WasmEdgeResult<Vec<_>>>()?;
+        self.initialize(
+            args,
+            envs,
+            Some(preopens.iter().map(String::as_str).collect()),
+        );
+        Ok(())
+    }
+
     /// Returns the WASI exit code.
     ///
     /// The WASI exit code can be accessed after running the "_start" function of a `wasm32-wasi` program.
@@ -51,3 +88,129 @@ impl WasiInstance {
         self.inner.get_native_handler(fd)
     }
 }
+
+/// Defines a host directory pre-opened for a WASI guest.
+///
+/// The directory is visible to the guest under its guest path, which is the host path itself unless it is remapped with [Preopen::with_guest_path], like the `--dir GUEST:HOST` option of the `wasmedge` CLI.
+///
+/// ```ignore
+/// wasi.initialize_with_preopens(
+///     Some(vec!["app"]),
+///     None,
+///     [
+///         Preopen::new("/srv/dataset").with_guest_path("/data"),
+///         Preopen::new("/var/tmp/app-1").with_guest_path("/tmp"),
+///     ],
+/// )?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preopen {
+    host_path: PathBuf,
+    guest_path: Option<String>,
+}
+impl Preopen {
+    /// Creates a new [Preopen] of the given host directory, which is visible to the guest under the same path.
+    ///
+    /// # Argument
+    ///
+    /// * `host_path` - The directory on the host.
+    pub fn new(host_path: impl AsRef<Path>) -> Self {
+        Self {
+            host_path: host_path.as_ref().to_path_buf(),
+            guest_path: None,
+        }
+    }
+
+    /// Sets the path under which the directory is visible to the guest.
+    ///
+    /// # Argument
+    ///
+    /// * `guest_path` - The path inside the guest, for example `/data`.
+    pub fn with_guest_path(self, guest_path: impl Into<String>) -> Self {
+        Self {
+            guest_path: Some(guest_path.into()),
+            ..self
+        }
+    }
+
+    /// Returns the directory on the host.
+    pub fn host_path(&self) -> &Path {
+        &self.host_path
+    }
+
+    /// Returns the path under which the directory is visible to the guest.
+    pub fn guest_path(&self) -> String {
+        match &self.guest_path {
+            Some(guest_path) => guest_path.clone(),
+            None => self.host_path.to_string_lossy().into_owned(),
+        }
+    }
+
+    /// Encodes the preopen in the `GUEST:HOST` format of the WasmEdge library.
+    fn encode(&self) -> WasmEdgeResult<String> {
+        let host_path = self.host_path.to_string_lossy();
+        if host_path.is_empty() || host_path.contains(':') {
+            return Err(Box::new(WasmEdgeError::Wasi(WasiError::InvalidHostPath(
+                host_path.into_owned(),
+            ))));
+        }
+
+        let guest_path = self.guest_path();
+        if guest_path.is_empty() || guest_path.contains(':') {
+            return Err(Box::new(WasmEdgeError::Wasi(WasiError::InvalidGuestPath(
+                guest_path,
+            ))));
+        }
+
+        Ok(format!("{guest_path}:{host_path}"))
+    }
+}
+
+/// The names of the import modules of the WASI functions.
+pub(crate) const WASI_MODULE_NAMES: [&str; 2] = ["wasi_snapshot_preview1", "wasi_unstable"];
+
+/// Defines the execution models of WASI modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiKind {
+    /// A command module, which exports `_start` as its entry point. A command runs once from `_start`, and is not expected to be called afterwards.
+    Command,
+    /// A reactor module, which exports `_initialize`. A reactor is initialized once, and then its other exported functions are called any number of times.
+    Reactor,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasi_preopen() {
+        let preopen = Preopen::new("/srv/dataset");
+        assert_eq!(preopen.guest_path(), "/srv/dataset");
+        let result = preopen.encode();
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "/srv/dataset:/srv/dataset");
+
+        // remap the guest path
+        let preopen = Preopen::new("/var/tmp/app-1").with_guest_path("/tmp");
+        assert_eq!(preopen.host_path(), Path::new("/var/tmp/app-1"));
+        assert_eq!(preopen.encode().unwrap(), "/tmp:/var/tmp/app-1");
+
+        // invalid guest paths
+        let result = Preopen::new("/srv").with_guest_path("a:b").encode();
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Wasi(WasiError::InvalidGuestPath("a:b".into()))
+        );
+        assert!(Preopen::new("/srv").with_guest_path("").encode().is_err());
+
+        // invalid host paths, which would be split at ':'
+        let result = Preopen::new("/srv/a:b").with_guest_path("/data").encode();
+        assert!(result.is_err());
+        assert_eq!(
+            *result.unwrap_err(),
+            WasmEdgeError::Wasi(WasiError::InvalidHostPath("/srv/a:b".into()))
+        );
+        assert!(Preopen::new("").with_guest_path("/data").encode().is_err());
+    }
+}